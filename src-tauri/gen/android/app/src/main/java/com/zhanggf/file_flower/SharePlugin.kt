@@ -0,0 +1,47 @@
+package com.zhanggf.file_flower
+
+import android.app.Activity
+import android.content.Intent
+import androidx.core.content.FileProvider
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.Plugin
+import java.io.File
+
+@InvokeArg
+class ShareArgs {
+    lateinit var path: String
+    var mimeType: String = "image/*"
+    var title: String? = null
+}
+
+// 调用系统分享面板分享导出的图片
+@TauriPlugin
+class SharePlugin(private val activity: Activity) : Plugin(activity) {
+    @Command
+    fun shareFile(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareArgs::class.java)
+        val file = File(args.path)
+        if (!file.exists()) {
+            invoke.reject("File not found: ${args.path}")
+            return
+        }
+
+        // 不在 res/xml/file_paths.xml 列出的目录中的文件无法分享
+        val uri = try {
+            FileProvider.getUriForFile(activity, "${activity.packageName}.fileprovider", file)
+        } catch (e: IllegalArgumentException) {
+            invoke.reject("File cannot be shared: ${args.path}")
+            return
+        }
+        val intent = Intent(Intent.ACTION_SEND).apply {
+            type = args.mimeType
+            putExtra(Intent.EXTRA_STREAM, uri)
+            addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+        }
+        activity.startActivity(Intent.createChooser(intent, args.title))
+        invoke.resolve()
+    }
+}
@@ -0,0 +1,34 @@
+import Tauri
+import UIKit
+import WebKit
+
+class ShareArgs: Decodable {
+  let path: String
+  let title: String?
+}
+
+// 调用系统分享面板分享导出的图片
+class SharePlugin: Plugin {
+  @objc public func shareFile(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(ShareArgs.self)
+    let url = URL(fileURLWithPath: args.path)
+
+    DispatchQueue.main.async {
+      let controller = UIActivityViewController(activityItems: [url], applicationActivities: nil)
+      // iPad 上必须指定弹出位置
+      if let popover = controller.popoverPresentationController,
+        let view = self.manager.viewController?.view
+      {
+        popover.sourceView = view
+        popover.sourceRect = CGRect(x: view.bounds.midX, y: view.bounds.midY, width: 0, height: 0)
+      }
+      self.manager.viewController?.present(controller, animated: true)
+      invoke.resolve()
+    }
+  }
+}
+
+@_cdecl("init_plugin_share")
+func initPlugin() -> Plugin {
+  return SharePlugin()
+}
@@ -0,0 +1,184 @@
+// 基本调整：亮度、对比度、饱和度、色相、模糊、锐化、灰度、反相、旋转和翻转
+// 一次请求中的多个调整按顺序在同一张解码后的图片上执行，只解码、编码一次
+// 亮度、对比度、饱和度和模糊通过滤镜执行（可用时在 GPU 上）
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+
+use image::io::Reader as ImageReader;
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{Request, Response};
+
+use crate::error::AppError;
+use crate::filters::{self, Backend, Filter};
+use crate::history::{self, EditOperation};
+use crate::storage::{encode_to_vec, save_image_from};
+use crate::{open_image, paths, run_blocking, scope, transfer};
+
+// 一次请求最多的调整数
+const MAX_ADJUSTMENTS: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Adjustment {
+    // -1~1，0 不变
+    Brightness { amount: f32 },
+    // 倍数，1 不变
+    Contrast { amount: f32 },
+    // 倍数，0 为灰度，1 不变
+    Saturation { amount: f32 },
+    // 色相旋转的角度
+    Hue { degrees: i32 },
+    // 高斯模糊
+    Blur { sigma: f32 },
+    // USM 锐化：与模糊结果相差超过 threshold 的像素才锐化
+    Sharpen {
+        sigma: f32,
+        #[serde(default)]
+        threshold: i32,
+    },
+    Grayscale,
+    Invert,
+    // 顺时针旋转 90、180 或 270 度
+    Rotate { degrees: u32 },
+    FlipHorizontal,
+    FlipVertical,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Adjustment {
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            Adjustment::Brightness { amount } if !(-1.0..=1.0).contains(amount) => Err(AppError::InvalidArgument(
+                format!("Brightness must be between -1 and 1: {}", amount),
+            )),
+            Adjustment::Contrast { amount } | Adjustment::Saturation { amount } if !(0.0..=5.0).contains(amount) => {
+                Err(AppError::InvalidArgument(format!(
+                    "Contrast and saturation must be between 0 and 5: {}",
+                    amount
+                )))
+            }
+            Adjustment::Blur { sigma } | Adjustment::Sharpen { sigma, .. } if !(*sigma > 0.0 && *sigma <= 100.0) => {
+                Err(AppError::InvalidArgument(format!("Sigma must be between 0 and 100: {}", sigma)))
+            }
+            Adjustment::Sharpen { threshold, .. } if !(0..=255).contains(threshold) => Err(AppError::InvalidArgument(
+                format!("Sharpen threshold must be between 0 and 255: {}", threshold),
+            )),
+            Adjustment::Rotate { degrees } if ![90, 180, 270].contains(degrees) => Err(AppError::InvalidArgument(
+                format!("Rotation must be 90, 180 or 270 degrees: {}", degrees),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check(adjustments: &[Adjustment]) -> Result<(), AppError> {
+    if adjustments.is_empty() || adjustments.len() > MAX_ADJUSTMENTS {
+        return Err(AppError::InvalidArgument(format!(
+            "Expected 1 to {} adjustments, got {}",
+            MAX_ADJUSTMENTS,
+            adjustments.len()
+        )));
+    }
+    adjustments.iter().try_for_each(Adjustment::validate)
+}
+
+fn filter(img: &DynamicImage, filter: Filter) -> Result<DynamicImage, AppError> {
+    Ok(filters::apply(img, &filter, Backend::Auto)?.0)
+}
+
+pub(crate) fn apply(img: DynamicImage, adjustment: &Adjustment) -> Result<DynamicImage, AppError> {
+    adjustment.validate()?;
+    let adjust = |brightness, contrast, saturation| Filter::Adjust {
+        brightness,
+        contrast,
+        saturation,
+        gamma: 1.0,
+    };
+    Ok(match adjustment {
+        Adjustment::Brightness { amount } => filter(&img, adjust(*amount, 1.0, 1.0))?,
+        Adjustment::Contrast { amount } => filter(&img, adjust(0.0, *amount, 1.0))?,
+        Adjustment::Saturation { amount } => filter(&img, adjust(0.0, 1.0, *amount))?,
+        Adjustment::Hue { degrees } => img.huerotate(*degrees),
+        Adjustment::Blur { sigma } => filter(&img, Filter::Blur { sigma: *sigma })?,
+        Adjustment::Sharpen { sigma, threshold } => img.unsharpen(*sigma, *threshold),
+        // 保留透明通道
+        Adjustment::Grayscale if img.color().has_alpha() => DynamicImage::ImageLumaA8(img.to_luma_alpha8()),
+        Adjustment::Grayscale => DynamicImage::ImageLuma8(img.to_luma8()),
+        Adjustment::Invert => {
+            let mut img = img;
+            img.invert();
+            img
+        }
+        Adjustment::Rotate { degrees: 90 } => img.rotate90(),
+        Adjustment::Rotate { degrees: 180 } => img.rotate180(),
+        Adjustment::Rotate { .. } => img.rotate270(),
+        Adjustment::FlipHorizontal => img.fliph(),
+        Adjustment::FlipVertical => img.flipv(),
+    })
+}
+
+fn apply_all(img: DynamicImage, adjustments: &[Adjustment]) -> Result<DynamicImage, AppError> {
+    adjustments.iter().try_fold(img, apply)
+}
+
+// 按顺序应用调整，output 为空时覆盖原图；每个调整都记入编辑历史
+#[tauri::command]
+pub async fn adjust_image(
+    path: String,
+    adjustments: Vec<Adjustment>,
+    output: Option<String>,
+) -> Result<AdjustResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    check(&adjustments)?;
+
+    run_blocking("adjust_image", move || {
+        let img = apply_all(Arc::unwrap_or_clone(open_image(&path)?), &adjustments)?;
+        let output = output.unwrap_or_else(|| path.clone());
+        save_image_from(&img, Path::new(&path), Path::new(&output))?;
+        history::record_all(
+            Path::new(&path),
+            Path::new(&output),
+            adjustments.into_iter().map(|adjustment| EditOperation::Adjust { adjustment }),
+        )?;
+        let (width, height) = img.dimensions();
+        Ok(AdjustResult {
+            output: paths::display(Path::new(&output)),
+            width,
+            height,
+        })
+    })
+    .await
+}
+
+// 图片数据以原始请求体（或分块上传的 transfer-id）传入，调整列表以 JSON 放在请求头 adjustments 中
+// 返回请求头 format 指定格式（默认 PNG）的原始字节
+#[tauri::command]
+pub async fn adjust_image_from_data(request: Request<'_>) -> Result<Response, AppError> {
+    let adjustments: Vec<Adjustment> = transfer::header_json(&request, "adjustments")?;
+    check(&adjustments)?;
+    let (format, options) = transfer::output_encoding(&request)?;
+    let data = transfer::request_data(&request)?;
+
+    let encoded = run_blocking("adjust_image_from_data", move || {
+        let img = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(AppError::from)?
+            .decode()
+            .map_err(AppError::from)?;
+        encode_to_vec(&apply_all(img, &adjustments)?, format, &options)
+    })
+    .await?;
+    Ok(Response::new(encoded))
+}
@@ -0,0 +1,200 @@
+// 字符画导出：按亮度把图片的每个小块映射为字符集中的一个字符
+// 可以导出为文本文件，也可以用字体绘制成图片（可按原图颜色给每个字符着色，即字符马赛克）
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::storage::{save_image, write_bytes};
+use crate::text::{draw_text, line_height, load_font, measure, parse_color};
+use crate::{open_image, paths, run_blocking, scope};
+
+const MAX_COLUMNS: u32 = 1000;
+// 等宽字体的字符高度约为宽度的两倍，文本输出时行数按此比例压缩
+const TEXT_CELL_ASPECT: f32 = 0.5;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AsciiFormat {
+    #[default]
+    Text,
+    Image,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AsciiOptions {
+    pub format: AsciiFormat,
+    // 每行字符数
+    pub columns: u32,
+    // 从浅到深排列的字符，可以使用任意字符（如中文）
+    pub charset: String,
+    // 反转亮度，用于深色背景上显示
+    pub invert: bool,
+    // 以下仅用于图片输出
+    // 使用原图颜色绘制每个字符，为 false 时使用 foreground
+    pub color: bool,
+    pub font: Option<String>,
+    pub font_size: f32,
+    pub foreground: String,
+    pub background: String,
+    // 为空时在原图旁边生成 name_ascii.txt 或 name_ascii.png
+    pub output: Option<String>,
+}
+
+impl Default for AsciiOptions {
+    fn default() -> Self {
+        Self {
+            format: AsciiFormat::Text,
+            columns: 100,
+            charset: " .:-=+*#%@".to_string(),
+            invert: false,
+            color: false,
+            font: None,
+            font_size: 12.0,
+            foreground: "#000000".to_string(),
+            background: "#ffffff".to_string(),
+            output: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AsciiResult {
+    pub output: String,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+// 把图片缩小为每个字符一个像素；透明部分按白色背景计算亮度
+fn sample(img: &DynamicImage, columns: u32, cell_aspect: f32) -> RgbaImage {
+    let (width, height) = img.dimensions();
+    let rows = ((height as f32 / width as f32) * columns as f32 * cell_aspect).round().max(1.0) as u32;
+    let mut cells = img.resize_exact(columns, rows, FilterType::Triangle).to_rgba8();
+    for pixel in cells.pixels_mut() {
+        let alpha = pixel[3] as f32 / 255.0;
+        for c in 0..3 {
+            pixel[c] = (pixel[c] as f32 * alpha + 255.0 * (1.0 - alpha)).round() as u8;
+        }
+        pixel[3] = 255;
+    }
+    cells
+}
+
+fn character(pixel: &Rgba<u8>, charset: &[char], invert: bool) -> char {
+    let luma = (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32) / 255.0;
+    // 越暗使用越靠后（越“重”）的字符
+    let darkness = if invert { luma } else { 1.0 - luma };
+    let index = (darkness * (charset.len() - 1) as f32).round() as usize;
+    charset[index.min(charset.len() - 1)]
+}
+
+fn to_lines(cells: &RgbaImage, charset: &[char], invert: bool) -> Vec<String> {
+    (0..cells.height())
+        .map(|y| (0..cells.width()).map(|x| character(cells.get_pixel(x, y), charset, invert)).collect())
+        .collect()
+}
+
+fn render(img: &DynamicImage, charset: &[char], options: &AsciiOptions) -> Result<(RgbaImage, u32, u32), AppError> {
+    let font = load_font(options.font.as_deref())?;
+    let foreground = parse_color(&options.foreground)?;
+    let background = parse_color(&options.background)?;
+
+    // 以字符集中最宽的字符作为格子宽度，中文等全角字符也不会重叠
+    let cell_width = charset
+        .iter()
+        .map(|c| measure(&font, options.font_size, &c.to_string()).0)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let cell_height = line_height(&font, options.font_size).max(1);
+    let cells = sample(img, options.columns, cell_width as f32 / cell_height as f32);
+
+    let mut canvas = RgbaImage::from_pixel(cells.width() * cell_width, cells.height() * cell_height, background);
+    for (x, y, pixel) in cells.enumerate_pixels() {
+        let c = character(pixel, charset, options.invert);
+        if c.is_whitespace() {
+            continue;
+        }
+        let color = if options.color { *pixel } else { foreground };
+        draw_text(
+            &mut canvas,
+            &font,
+            options.font_size,
+            (x * cell_width) as i32,
+            (y * cell_height) as i32,
+            &c.to_string(),
+            color,
+        );
+    }
+    Ok((canvas, cells.width(), cells.height()))
+}
+
+fn output_path(path: &Path, format: AsciiFormat, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = if format == AsciiFormat::Text { "txt" } else { "png" };
+            path.with_file_name(format!("{}_ascii.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 把图片导出为字符画
+#[tauri::command]
+pub async fn export_ascii_art(path: String, options: Option<AsciiOptions>) -> Result<AsciiResult, AppError> {
+    scope::check(&path)?;
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    if let Some(font) = &options.font {
+        scope::check(font)?;
+    }
+    if options.columns == 0 || options.columns > MAX_COLUMNS {
+        return Err(AppError::InvalidArgument(format!(
+            "Columns must be between 1 and {}: {}",
+            MAX_COLUMNS, options.columns
+        )));
+    }
+    let charset: Vec<char> = options.charset.chars().filter(|c| !c.is_control()).collect();
+    if charset.len() < 2 {
+        return Err(AppError::InvalidArgument("Charset must contain at least 2 characters".to_string()));
+    }
+    if options.format == AsciiFormat::Image && !(options.font_size >= 4.0 && options.font_size <= 72.0) {
+        return Err(AppError::InvalidArgument(format!(
+            "Font size must be between 4 and 72: {}",
+            options.font_size
+        )));
+    }
+
+    run_blocking("export_ascii_art", move || {
+        let path = Path::new(&path);
+        let img = open_image(path)?;
+        let output = output_path(path, options.format, options.output.clone());
+
+        let (columns, rows) = match options.format {
+            AsciiFormat::Text => {
+                let cells = sample(&img, options.columns, TEXT_CELL_ASPECT);
+                let mut text = to_lines(&cells, &charset, options.invert).join("\n");
+                text.push('\n');
+                write_bytes(&output, text.as_bytes())?;
+                (cells.width(), cells.height())
+            }
+            AsciiFormat::Image => {
+                let (canvas, columns, rows) = render(&img, &charset, &options)?;
+                save_image(&DynamicImage::ImageRgba8(canvas), &output)?;
+                (columns, rows)
+            }
+        };
+        Ok(AsciiResult {
+            output: paths::display(&output),
+            columns,
+            rows,
+        })
+    })
+    .await
+}
@@ -0,0 +1,251 @@
+// 本地 HTTP 自动化接口：可选开启，仅监听 127.0.0.1，并要求 Bearer token 认证
+//
+//   GET  /health
+//   POST /info     {"path"}
+//   POST /resize   {"path", "width", "height"}
+//   POST /crop     {"path", "x", "y", "width", "height"}  （坐标为 0~1 的比例）
+//   POST /convert  {"path", "output", "overwrite", "autoRename", "palette", "options"}
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+
+use parking_lot::Mutex;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::async_runtime::block_on;
+use tauri::{AppHandle, Emitter, State};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::error::AppError;
+use crate::palette::PaletteOptions;
+use crate::storage::EncodeOptions;
+use crate::{run_blocking, scope};
+
+const TOKEN_LENGTH: usize = 32;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AutomationServerInfo {
+    pub port: u16,
+    pub token: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AutomationRequestEvent {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+}
+
+struct RunningServer {
+    server: Arc<Server>,
+    info: AutomationServerInfo,
+}
+
+#[derive(Default)]
+pub struct AutomationState {
+    running: Mutex<Option<RunningServer>>,
+}
+
+#[derive(Deserialize)]
+struct PathRequest {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct ResizeRequest {
+    path: String,
+    width: u32,
+    height: u32,
+    output: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CropRequest {
+    path: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    output: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConvertRequest {
+    path: String,
+    output: String,
+    #[serde(default)]
+    overwrite: bool,
+    #[serde(default)]
+    auto_rename: bool,
+    // GIF/PNG 保存为索引色时的颜色数和抖动算法
+    palette: Option<PaletteOptions>,
+    // 质量、PNG 压缩级别等编码选项，与 save_as 相同
+    #[serde(default)]
+    options: EncodeOptions,
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, (u16, AppError)> {
+    serde_json::from_str(body).map_err(|e| (400, AppError::from(e)))
+}
+
+// 根据错误类型选择 HTTP 状态码
+fn status_of(error: AppError) -> (u16, AppError) {
+    let status = match error {
+        AppError::NotFound(_) => 404,
+        AppError::InvalidArgument(_) | AppError::Unsupported(_) => 400,
+        AppError::PermissionDenied(_) => 403,
+        AppError::TooLarge(_) => 413,
+        AppError::Timeout(_) => 504,
+        _ => 422,
+    };
+    (status, error)
+}
+
+// 分发请求到对应的图片操作
+fn dispatch(method: &Method, url: &str, body: &str) -> Result<Value, (u16, AppError)> {
+    let route = url.split('?').next().unwrap_or("");
+    match (method, route) {
+        (Method::Get, "/health") => Ok(json!({ "ok": true })),
+        (Method::Post, "/info") => {
+            let request: PathRequest = parse(body)?;
+            block_on(crate::get_image_info(request.path))
+                .map(|info| json!(info))
+                .map_err(status_of)
+        }
+        (Method::Post, "/resize") => {
+            let request: ResizeRequest = parse(body)?;
+            block_on(crate::resize_image(request.path, request.width, request.height, request.output))
+                .map(|ok| json!({ "ok": ok }))
+                .map_err(status_of)
+        }
+        (Method::Post, "/crop") => {
+            let request: CropRequest = parse(body)?;
+            block_on(crate::crop_image(
+                request.path,
+                request.x,
+                request.y,
+                request.width,
+                request.height,
+                request.output,
+            ))
+            .map(|ok| json!({ "ok": ok }))
+            .map_err(status_of)
+        }
+        (Method::Post, "/convert") => {
+            let request: ConvertRequest = parse(body)?;
+            scope::check(&request.path).map_err(status_of)?;
+            scope::check(&request.output).map_err(status_of)?;
+            request.options.validate().map_err(status_of)?;
+            block_on(run_blocking("save_as", move || {
+                crate::convert_file(
+                    &request.path,
+                    &request.output,
+                    request.overwrite,
+                    request.auto_rename,
+                    request.palette,
+                    &request.options,
+                )
+            }))
+            .map(|output| json!({ "output": output }))
+            .map_err(status_of)
+        }
+        _ => Err((404, AppError::NotFound(route.to_string()))),
+    }
+}
+
+fn handle(app: &AppHandle, token: &str, mut request: Request) {
+    let authorized = request.headers().iter().any(|h| {
+        h.field.equiv("Authorization") && h.value.as_str() == format!("Bearer {}", token)
+    });
+
+    let (status, body) = if !authorized {
+        (401, json!({ "error": AppError::PermissionDenied("Invalid automation token".to_string()) }))
+    } else {
+        let mut body = String::new();
+        match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => match dispatch(request.method(), request.url(), &body) {
+                Ok(value) => (200, value),
+                Err((status, error)) => (status, json!({ "error": error })),
+            },
+            Err(e) => (400, json!({ "error": AppError::from(e) })),
+        }
+    };
+
+    let _ = app.emit(
+        "automation-request",
+        AutomationRequestEvent {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            status,
+        },
+    );
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("valid header");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    let _ = request.respond(response);
+}
+
+// 启动自动化接口，port 为 0 时由系统分配端口
+#[tauri::command]
+pub fn start_automation_server(
+    app: AppHandle,
+    state: State<'_, AutomationState>,
+    port: u16,
+) -> Result<AutomationServerInfo, AppError> {
+    let mut running = state.running.lock();
+    if let Some(server) = running.as_ref() {
+        return Ok(server.info.clone());
+    }
+
+    let server = Server::http(("127.0.0.1", port)).map_err(|e| AppError::Network(format!("Failed to start server: {}", e)))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or_else(|| AppError::Internal("Failed to resolve server address".to_string()))?;
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+
+    let server = Arc::new(server);
+    let worker = server.clone();
+    let worker_token = token.clone();
+    thread::spawn(move || {
+        // server.unblock() 后 incoming_requests 结束循环
+        for request in worker.incoming_requests() {
+            handle(&app, &worker_token, request);
+        }
+    });
+
+    let info = AutomationServerInfo { port, token };
+    *running = Some(RunningServer {
+        server,
+        info: info.clone(),
+    });
+    Ok(info)
+}
+
+// 停止自动化接口
+#[tauri::command]
+pub fn stop_automation_server(state: State<'_, AutomationState>) -> bool {
+    match state.running.lock().take() {
+        Some(running) => {
+            running.server.unblock();
+            true
+        }
+        None => false,
+    }
+}
+
+// 查询自动化接口状态，未启动时返回 None
+#[tauri::command]
+pub fn get_automation_server(state: State<'_, AutomationState>) -> Option<AutomationServerInfo> {
+    state.running.lock().as_ref().map(|r| r.info.clone())
+}
@@ -0,0 +1,172 @@
+// 自动保存：定期把打开的编辑会话写入应用数据目录，崩溃后下次启动时可以恢复未保存的修改
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::history::EditOperation;
+use crate::storage::write_app_file;
+
+// 有修改时每 15 秒写入一次
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(15);
+
+lazy_static::lazy_static! {
+    // 本次运行中打开的会话，按会话 id 索引
+    static ref SESSIONS: RwLock<HashMap<String, SessionSnapshot>> = RwLock::new(HashMap::new());
+    // 上次运行遗留的未保存会话，等待用户选择恢复或丢弃
+    static ref RECOVERED: RwLock<HashMap<String, SessionSnapshot>> = RwLock::new(HashMap::new());
+    // 自动保存文件，init 之前为 None（此时不写入）
+    static ref AUTOSAVE_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+// 自上次写入后会话是否有变化
+static DIRTY: AtomicBool = AtomicBool::new(false);
+
+// 编辑会话的快照，operations 为前端的操作列表或后端会话的 EditOperation，自动保存时不解析其内容
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    pub id: String,
+    pub source: String,
+    #[serde(default)]
+    pub operations: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub unsaved: bool,
+    // 最后更新时间（Unix 秒），由后端填写
+    #[serde(default)]
+    pub updated: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// 把当前会话和尚未处理的遗留会话写入磁盘；只保留有未保存修改的会话
+pub(crate) fn flush() -> Result<(), AppError> {
+    let Some(file) = AUTOSAVE_FILE.read().clone() else {
+        return Ok(());
+    };
+    let sessions: Vec<SessionSnapshot> = RECOVERED
+        .read()
+        .values()
+        .chain(SESSIONS.read().values())
+        .filter(|session| session.unsaved)
+        .cloned()
+        .collect();
+    write_app_file(&file, &serde_json::to_vec(&sessions)?)
+}
+
+// 启动时读取上次运行遗留的会话，并开始定期自动保存
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let file = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("autosave.json");
+
+    let saved: Vec<SessionSnapshot> = fs::read_to_string(&file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    *RECOVERED.write() = saved
+        .into_iter()
+        .filter(|session| session.unsaved)
+        .map(|session| (session.id.clone(), session))
+        .collect();
+    *AUTOSAVE_FILE.write() = Some(file);
+
+    tauri::async_runtime::spawn(async {
+        let mut ticker = tokio::time::interval(AUTOSAVE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if !DIRTY.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+            match tauri::async_runtime::spawn_blocking(flush).await {
+                Ok(Err(e)) => tracing::warn!(error = %e, "autosave failed"),
+                Err(e) => tracing::warn!(error = %e, "autosave task failed"),
+                Ok(Ok(())) => {}
+            }
+        }
+    });
+    Ok(())
+}
+
+// 前端在会话变化时调用，更新内存中的快照，由定时任务写入磁盘
+#[tauri::command]
+pub fn update_session(mut session: SessionSnapshot) -> SessionSnapshot {
+    session.updated = now();
+    SESSIONS.write().insert(session.id.clone(), session.clone());
+    DIRTY.store(true, Ordering::SeqCst);
+    session
+}
+
+// 后端编辑会话（session）修改或保存后调用：有未保存的操作时更新快照，否则移除快照
+pub(crate) fn snapshot(id: &str, source: &str, operations: &[EditOperation]) {
+    if operations.is_empty() {
+        discard(id);
+        return;
+    }
+    let operations = operations
+        .iter()
+        .filter_map(|operation| serde_json::to_value(operation).ok())
+        .collect();
+    update_session(SessionSnapshot {
+        id: id.to_string(),
+        source: source.to_string(),
+        operations,
+        unsaved: true,
+        updated: 0,
+    });
+}
+
+fn discard(id: &str) -> bool {
+    let removed = SESSIONS.write().remove(id).is_some();
+    if removed {
+        DIRTY.store(true, Ordering::SeqCst);
+    }
+    removed
+}
+
+// 放弃会话的修改，不再自动保存
+#[tauri::command]
+pub fn discard_snapshot(id: &str) -> bool {
+    discard(id)
+}
+
+// 上次运行遗留的未保存会话，按更新时间从新到旧排列
+#[tauri::command]
+pub fn get_recoverable_sessions() -> Vec<SessionSnapshot> {
+    let mut sessions: Vec<SessionSnapshot> = RECOVERED.read().values().cloned().collect();
+    sessions.sort_by(|a, b| b.updated.cmp(&a.updated));
+    sessions
+}
+
+// 恢复遗留会话：转为本次运行的会话并返回其快照
+#[tauri::command]
+pub fn restore_session(id: &str) -> Result<SessionSnapshot, AppError> {
+    let session = RECOVERED
+        .write()
+        .remove(id)
+        .ok_or_else(|| AppError::NotFound(format!("No recoverable session: {}", id)))?;
+    SESSIONS.write().insert(session.id.clone(), session.clone());
+    DIRTY.store(true, Ordering::SeqCst);
+    Ok(session)
+}
+
+// 丢弃所有遗留会话，立即写入磁盘
+#[tauri::command]
+pub fn discard_recovered_sessions() -> Result<bool, AppError> {
+    RECOVERED.write().clear();
+    flush()?;
+    Ok(true)
+}
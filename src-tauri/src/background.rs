@@ -0,0 +1,86 @@
+// 背景移除：使用 U²-Net 显著性模型生成前景蒙版，输出透明背景的 PNG
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, GrayImage, Luma};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::ml::{load_session, resolve_model, to_tensor, IMAGENET_MEAN, IMAGENET_STD};
+use crate::open_image;
+use crate::paths;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::save_image_with_format;
+
+const MODEL_FILE: &str = "u2netp.onnx";
+// 模型输入尺寸
+const MODEL_SIZE: u32 = 320;
+
+// 默认输出到原图旁边：name_nobg.png
+fn default_output(path: &Path) -> PathBuf {
+    let stem = paths::file_stem(path, "image");
+    path.with_file_name(format!("{}_nobg.png", stem))
+}
+
+// 运行模型，返回与原图同尺寸的前景蒙版
+fn predict_mask(app: &AppHandle, img: &DynamicImage) -> Result<GrayImage, AppError> {
+    let session = load_session(&resolve_model(app, MODEL_FILE)?)?;
+
+    let (width, height) = img.dimensions();
+    let input = img
+        .resize_exact(MODEL_SIZE, MODEL_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    let tensor = to_tensor(&input, Some((IMAGENET_MEAN, IMAGENET_STD)));
+
+    let inputs = ort::inputs![tensor].map_err(|e| AppError::ProcessingFailed(format!("Failed to prepare model input: {}", e)))?;
+    let outputs = session
+        .run(inputs)
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to run model: {}", e)))?;
+    let saliency = outputs[0]
+        .try_extract_tensor::<f32>()
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to read model output: {}", e)))?;
+
+    // 输出形状为 [1, 1, H, W]，先做最小-最大归一化
+    let min = saliency.iter().cloned().fold(f32::MAX, f32::min);
+    let max = saliency.iter().cloned().fold(f32::MIN, f32::max);
+    let range = (max - min).max(f32::EPSILON);
+
+    let mask = GrayImage::from_fn(MODEL_SIZE, MODEL_SIZE, |x, y| {
+        let value = saliency[[0, 0, y as usize, x as usize]];
+        Luma([(((value - min) / range) * 255.0).round() as u8])
+    });
+
+    Ok(image::imageops::resize(&mask, width, height, FilterType::Triangle))
+}
+
+// 移除图片背景，返回输出文件路径
+#[tauri::command]
+pub async fn remove_background(app: AppHandle, path: String, output: Option<String>) -> Result<String, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("remove_background", move || {
+        let img = open_image(&path)?;
+
+        let mask = predict_mask(&app, &img)?;
+
+        // 以蒙版作为透明通道，与原有透明度相乘
+        let mut rgba = img.to_rgba8();
+        for (pixel, alpha) in rgba.pixels_mut().zip(mask.pixels()) {
+            pixel[3] = ((pixel[3] as u16 * alpha[0] as u16) / 255) as u8;
+        }
+
+        let output_path = match output {
+            Some(output) => PathBuf::from(output),
+            None => default_output(Path::new(&path)),
+        };
+
+        save_image_with_format(&DynamicImage::ImageRgba8(rgba), &output_path, image::ImageFormat::Png)?;
+
+        Ok(output_path.to_string_lossy().to_string())
+    })
+    .await
+}
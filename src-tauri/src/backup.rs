@@ -0,0 +1,174 @@
+// 原图备份：覆盖文件前把原图复制到应用数据目录下的 backups 中，可以随时恢复
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::history;
+use crate::paths;
+use crate::scope;
+use crate::settings;
+use crate::storage::write_atomic;
+
+lazy_static::lazy_static! {
+    // 备份目录，在 init 之前为 None（此时不做备份）
+    static ref BACKUP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+// 备份的元数据，与备份文件同名，扩展名为 .json
+#[derive(Serialize, Deserialize, Debug)]
+struct BackupMeta {
+    original: String,
+    created: u64,
+    size: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// 以规范化路径的 FNV-1a 哈希作为备份文件名，跨版本保持稳定
+fn backup_key(path: &Path) -> String {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in path.to_string_lossy().as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn backup_paths(dir: &Path, path: &Path) -> (PathBuf, PathBuf) {
+    let key = backup_key(path);
+    (dir.join(format!("{}.bak", key)), dir.join(format!("{}.json", key)))
+}
+
+fn read_meta(path: &Path) -> Option<BackupMeta> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+// 删除超过保留天数的备份，并把总大小控制在上限以内（超出时从最早的备份开始删除）
+fn prune(dir: &Path) {
+    let settings = settings::current();
+    let max_age = settings.backup_retention_days * 24 * 60 * 60;
+    let max_total_bytes = settings.backup_max_mb * 1024 * 1024;
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut backups: Vec<(PathBuf, BackupMeta)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+        .filter_map(|p| read_meta(&p).map(|meta| (p, meta)))
+        .collect();
+    backups.sort_by_key(|(_, meta)| meta.created);
+
+    let expired_before = now().saturating_sub(max_age);
+    let mut total: u64 = backups.iter().map(|(_, meta)| meta.size).sum();
+    for (meta_path, meta) in backups {
+        if meta.created >= expired_before && total <= max_total_bytes {
+            break;
+        }
+        let _ = fs::remove_file(meta_path.with_extension("bak"));
+        let _ = fs::remove_file(&meta_path);
+        total = total.saturating_sub(meta.size);
+    }
+}
+
+// 启动时确定备份目录并清理过期备份
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("backups");
+    fs::create_dir_all(&dir).map_err(AppError::from)?;
+
+    prune(&dir);
+    *BACKUP_DIR.write() = Some(dir);
+    Ok(())
+}
+
+// 覆盖文件前调用：同一文件只保留第一次修改前的原图
+pub(crate) fn backup_original(path: &Path) -> Result<(), AppError> {
+    let Some(dir) = BACKUP_DIR.read().clone() else {
+        return Ok(());
+    };
+    let (backup, meta_path) = backup_paths(&dir, path);
+    if backup.is_file() && meta_path.is_file() {
+        return Ok(());
+    }
+
+    let size = fs::copy(paths::extended(path), &backup).map_err(|e| AppError::from(e).at(path))?;
+    let meta = BackupMeta {
+        original: fs::canonicalize(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .to_string(),
+        created: now(),
+        size,
+    };
+    fs::write(&meta_path, serde_json::to_vec(&meta)?).map_err(AppError::from)?;
+
+    prune(&dir);
+    Ok(())
+}
+
+// 文件的原图备份（不存在时为 None），备份文件的扩展名是 .bak，读取时需要按内容识别格式
+pub(crate) fn original_file(path: &Path) -> Option<PathBuf> {
+    let dir = BACKUP_DIR.read().clone()?;
+    let (backup, _) = backup_paths(&dir, path);
+    backup.is_file().then_some(backup)
+}
+
+// 文件是否有可以恢复的原图备份
+#[tauri::command]
+pub fn has_original(path: &str) -> bool {
+    if scope::check(path).is_err() {
+        return false;
+    }
+
+    match BACKUP_DIR.read().as_ref() {
+        Some(dir) => backup_paths(dir, Path::new(path)).0.is_file(),
+        None => false,
+    }
+}
+
+// 用备份的原图覆盖当前文件，恢复后删除该备份
+#[tauri::command]
+pub fn restore_original(path: &str) -> Result<bool, AppError> {
+    scope::check(path)?;
+
+    let dir = BACKUP_DIR
+        .read()
+        .clone()
+        .ok_or_else(|| AppError::Internal("Backups are not initialized".to_string()))?;
+    let target = Path::new(path);
+    let (backup, meta_path) = backup_paths(&dir, target);
+    if !backup.is_file() {
+        return Err(AppError::NotFound(format!("No backup for {}", path)));
+    }
+
+    write_atomic(target, |writer| {
+        let mut source = fs::File::open(&backup).map_err(AppError::from)?;
+        io::copy(&mut source, writer).map_err(AppError::from)?;
+        Ok(())
+    })?;
+
+    let _ = fs::remove_file(&backup);
+    let _ = fs::remove_file(&meta_path);
+    // 恢复原图后编辑历史不再适用
+    history::clear(target)?;
+    Ok(true)
+}
@@ -0,0 +1,258 @@
+// 二维码与条形码的识别和生成
+use std::io::Cursor;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage};
+use qrcode::{Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::open_image;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::{save_image, write_bytes};
+
+#[derive(Serialize, Debug)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DecodedCode {
+    pub format: String,
+    pub text: String,
+    pub bounds: BoundingBox,
+}
+
+// 根据识别结果的定位点计算外接矩形
+fn bounding_box(points: &[rxing::Point], width: u32, height: u32) -> BoundingBox {
+    let mut min_x = f32::MAX;
+    let mut min_y = f32::MAX;
+    let mut max_x = 0.0f32;
+    let mut max_y = 0.0f32;
+    for point in points {
+        min_x = min_x.min(point.x);
+        min_y = min_y.min(point.y);
+        max_x = max_x.max(point.x);
+        max_y = max_y.max(point.y);
+    }
+    if points.is_empty() {
+        return BoundingBox { x: 0, y: 0, width: 0, height: 0 };
+    }
+
+    let x = (min_x.max(0.0) as u32).min(width);
+    let y = (min_y.max(0.0) as u32).min(height);
+    BoundingBox {
+        x,
+        y,
+        width: (max_x.round() as u32).min(width).saturating_sub(x),
+        height: (max_y.round() as u32).min(height).saturating_sub(y),
+    }
+}
+
+// 识别图片中的所有二维码和条形码
+#[tauri::command]
+pub async fn decode_qr(path: String) -> Result<Vec<DecodedCode>, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("decode_qr", move || {
+        let img = open_image(&path)?;
+
+        let (width, height) = img.dimensions();
+        let luma = img.to_luma8().into_raw();
+
+        // 没有识别到任何码时返回空列表而不是错误
+        let results = match rxing::helpers::detect_multiple_in_luma(luma, width, height) {
+            Ok(results) => results,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        Ok(results
+            .iter()
+            .map(|r| DecodedCode {
+                format: r.getBarcodeFormat().to_string(),
+                text: r.getText().to_string(),
+                bounds: bounding_box(r.getPoints(), width, height),
+            })
+            .collect())
+    })
+    .await
+}
+
+// 二维码容错等级
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum ErrorCorrection {
+    L,
+    M,
+    Q,
+    H,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QrOptions {
+    pub text: String,
+    // 输出图片的边长（像素）
+    pub size: u32,
+    pub error_correction: Option<ErrorCorrection>,
+    // 颜色使用 #rrggbb 或 #rrggbbaa
+    pub dark_color: Option<String>,
+    pub light_color: Option<String>,
+    // 居中叠加的 logo 图片
+    pub logo: Option<String>,
+}
+
+// 二维码四周保留的空白模块数
+const QUIET_ZONE: usize = 4;
+// logo 占二维码边长的比例
+const LOGO_RATIO: f32 = 0.2;
+// 输出边长上限（RGBA 约 256 MB）
+const MAX_QR_SIZE: u32 = 8192;
+
+fn parse_color(value: &str) -> Result<Rgba<u8>, AppError> {
+    let hex = value.trim_start_matches('#');
+    if !hex.is_ascii() {
+        return Err(AppError::InvalidArgument(format!("Invalid color: {}", value)));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| AppError::InvalidArgument(format!("Invalid color: {}", value)))
+    };
+    match hex.len() {
+        6 => Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, 255])),
+        8 => Ok(Rgba([channel(0)?, channel(2)?, channel(4)?, channel(6)?])),
+        _ => Err(AppError::InvalidArgument(format!("Invalid color: {}", value))),
+    }
+}
+
+fn color_to_svg(color: Rgba<u8>) -> String {
+    format!(
+        "rgba({},{},{},{:.3})",
+        color[0],
+        color[1],
+        color[2],
+        color[3] as f32 / 255.0
+    )
+}
+
+// 生成二维码并保存为 PNG/SVG（其他扩展名按对应的位图格式保存）
+#[tauri::command]
+pub async fn generate_qr(options: QrOptions, output: String) -> Result<bool, AppError> {
+    scope::check(&output)?;
+    if let Some(logo) = &options.logo {
+        scope::check(logo)?;
+    }
+
+    run_blocking("generate_qr", move || {
+        if options.size == 0 {
+            return Err(AppError::InvalidArgument("QR code size must be greater than 0".to_string()));
+        }
+        if options.size > MAX_QR_SIZE {
+            return Err(AppError::TooLarge(format!(
+                "QR code size must be at most {}: {}",
+                MAX_QR_SIZE, options.size
+            )));
+        }
+
+        // 带 logo 时默认使用最高容错等级，避免遮挡导致无法识别
+        let level = match options.error_correction {
+            Some(ErrorCorrection::L) => EcLevel::L,
+            Some(ErrorCorrection::M) => EcLevel::M,
+            Some(ErrorCorrection::Q) => EcLevel::Q,
+            Some(ErrorCorrection::H) => EcLevel::H,
+            None if options.logo.is_some() => EcLevel::H,
+            None => EcLevel::M,
+        };
+        let code = QrCode::with_error_correction_level(options.text.as_bytes(), level)
+            .map_err(|e| AppError::InvalidArgument(format!("Failed to generate QR code: {}", e)))?;
+
+        let dark = parse_color(options.dark_color.as_deref().unwrap_or("#000000"))?;
+        let light = parse_color(options.light_color.as_deref().unwrap_or("#ffffff"))?;
+
+        let modules = code.width();
+        let colors = code.to_colors();
+        let total = modules + QUIET_ZONE * 2;
+
+        let logo = match &options.logo {
+            Some(path) => Some(open_image(path)?),
+            None => None,
+        };
+
+        let ext = Path::new(&output)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        if ext == "svg" {
+            let mut svg = format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {total} {total}\" shape-rendering=\"crispEdges\">\n<rect width=\"{total}\" height=\"{total}\" fill=\"{light}\"/>\n",
+                size = options.size,
+                total = total,
+                light = color_to_svg(light),
+            );
+            for (index, color) in colors.iter().enumerate() {
+                if *color == Color::Dark {
+                    svg.push_str(&format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>\n",
+                        index % modules + QUIET_ZONE,
+                        index / modules + QUIET_ZONE,
+                        color_to_svg(dark)
+                    ));
+                }
+            }
+            if let Some(logo) = logo {
+                let mut buffer = Cursor::new(Vec::new());
+                logo.write_to(&mut buffer, ImageFormat::Png)
+                    .map_err(AppError::from)?;
+                let logo_size = total as f32 * LOGO_RATIO;
+                let offset = (total as f32 - logo_size) / 2.0;
+                svg.push_str(&format!(
+                    "<image x=\"{offset}\" y=\"{offset}\" width=\"{logo_size}\" height=\"{logo_size}\" href=\"data:image/png;base64,{data}\"/>\n",
+                    offset = offset,
+                    logo_size = logo_size,
+                    data = STANDARD.encode(buffer.into_inner()),
+                ));
+            }
+            svg.push_str("</svg>\n");
+            write_bytes(Path::new(&output), svg.as_bytes())?;
+            return Ok(true);
+        }
+
+        // 位图输出：按像素映射到模块
+        let size = options.size;
+        let mut canvas = RgbaImage::from_fn(size, size, |x, y| {
+            let mx = (x as usize * total / size as usize).checked_sub(QUIET_ZONE);
+            let my = (y as usize * total / size as usize).checked_sub(QUIET_ZONE);
+            match (mx, my) {
+                (Some(mx), Some(my)) if mx < modules && my < modules => {
+                    if colors[my * modules + mx] == Color::Dark {
+                        dark
+                    } else {
+                        light
+                    }
+                }
+                _ => light,
+            }
+        });
+
+        if let Some(logo) = logo {
+            let logo_size = ((size as f32 * LOGO_RATIO) as u32).max(1);
+            let logo = logo
+                .resize(logo_size, logo_size, image::imageops::FilterType::Triangle)
+                .to_rgba8();
+            let x = (size - logo.width()) / 2;
+            let y = (size - logo.height()) / 2;
+            image::imageops::overlay(&mut canvas, &logo, x as i64, y as i64);
+        }
+
+        save_image(&DynamicImage::ImageRgba8(canvas), Path::new(&output))?;
+
+        Ok(true)
+    })
+    .await
+}
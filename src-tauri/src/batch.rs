@@ -0,0 +1,563 @@
+// 批量任务：按预设依次处理一组图片，每处理完一个文件就把进度写入应用数据目录
+// 中途失败（休眠、崩溃、磁盘已满）后可以用 resume_job 从上次完成的文件继续
+// 每次取一组文件在线程池中并行处理，整组完成后再保存进度；单个文件出错不影响其他文件，结束时一并报告
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::history::EditOperation;
+use crate::hotfolder::{self, HotFolderPreset};
+use crate::paths;
+use crate::scope;
+use crate::storage::{output_format, write_app_file};
+
+lazy_static::lazy_static! {
+    // 任务进度文件所在目录，init 之前为 None
+    static ref JOBS_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+    // 正在执行的任务，值为请求的停止状态（Paused 或 Cancelled）
+    static ref RUNNING: Mutex<HashMap<String, Arc<Mutex<Option<JobStatus>>>>> = Mutex::new(HashMap::new());
+}
+
+// 同时处理的文件数上限，同时解码多张大图会占用较多内存
+const MAX_PARALLEL: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Running,
+    Paused,
+    // 应用在任务执行期间退出，等待恢复
+    Interrupted,
+    Completed,
+    // 已取消，不能再恢复
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFailure {
+    pub path: String,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJob {
+    pub id: String,
+    pub inputs: Vec<String>,
+    // batch_process 创建的任务 format 可以为空，此时沿用原图的格式
+    pub preset: HotFolderPreset,
+    // 输出文件名模板（不含扩展名），{name} 为原文件名，{index} 为从 1 开始的序号；为空时沿用原文件名
+    #[serde(default)]
+    pub rename: Option<String>,
+    pub status: JobStatus,
+    // 已处理（成功或失败）的文件数，恢复时从 inputs[completed] 继续
+    pub completed: usize,
+    #[serde(default)]
+    pub failures: Vec<JobFailure>,
+    // 创建和最后更新时间（Unix 秒）
+    pub created: u64,
+    pub updated: u64,
+}
+
+// batch_process 的处理步骤：Resize 和 Edit 按顺序应用到图片上，Convert 和 Rename 决定输出文件
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BatchStep {
+    // 缩放到不超过 width x height（保持宽高比）
+    Resize { width: u32, height: u32 },
+    // 其他编辑操作（裁剪、滤镜、调色、插件），与编辑历史中的操作相同
+    Edit { operation: EditOperation },
+    // 输出格式（扩展名）；没有该步骤时沿用原图的格式
+    Convert { format: String },
+    // 输出文件名模板，与 start_batch_job 的 rename 相同
+    Rename { pattern: String },
+}
+
+// 把处理步骤拆分为预设和重命名模板；Convert、Rename 最多各一个
+fn pipeline_preset(pipeline: Vec<BatchStep>, output_dir: String) -> Result<(HotFolderPreset, Option<String>), AppError> {
+    if pipeline.is_empty() {
+        return Err(AppError::InvalidArgument("Batch pipeline is empty".to_string()));
+    }
+    let mut preset = HotFolderPreset {
+        operations: Vec::new(),
+        format: String::new(),
+        output_dir: Some(output_dir),
+    };
+    let mut rename = None;
+    for step in pipeline {
+        match step {
+            BatchStep::Resize { width, height } => {
+                if width == 0 || height == 0 {
+                    return Err(AppError::InvalidArgument(format!("Invalid resize size: {}x{}", width, height)));
+                }
+                preset.operations.push(EditOperation::Resize { width, height });
+            }
+            BatchStep::Edit { operation } => preset.operations.push(operation),
+            BatchStep::Convert { format } => {
+                if !preset.format.is_empty() {
+                    return Err(AppError::InvalidArgument("Batch pipeline has more than one convert step".to_string()));
+                }
+                output_format(&format.to_lowercase())?;
+                preset.format = format.to_lowercase();
+            }
+            BatchStep::Rename { pattern } => {
+                if rename.replace(pattern).is_some() {
+                    return Err(AppError::InvalidArgument("Batch pipeline has more than one rename step".to_string()));
+                }
+            }
+        }
+    }
+    Ok((preset, rename))
+}
+
+// 每处理完一个文件发送一次 batch-progress 事件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchProgress {
+    pub job_id: String,
+    pub path: String,
+    pub output: Option<String>,
+    pub error: Option<AppError>,
+    pub done: usize,
+    pub total: usize,
+}
+
+// 任务结束（完成、暂停或取消）时发送 batch-finished 事件，附带全部失败的文件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchFinished {
+    pub job: BatchJob,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn jobs_dir() -> Result<PathBuf, AppError> {
+    JOBS_DIR
+        .read()
+        .clone()
+        .ok_or_else(|| AppError::Internal("Batch jobs are not initialized".to_string()))
+}
+
+fn job_file(id: &str) -> Result<PathBuf, AppError> {
+    // id 由后端生成，只包含十六进制字符；拒绝其他输入以免访问任务目录以外的文件
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::InvalidArgument(format!("Invalid job id: {}", id)));
+    }
+    Ok(jobs_dir()?.join(format!("{}.json", id)))
+}
+
+fn save_job(job: &BatchJob) -> Result<(), AppError> {
+    write_app_file(&job_file(&job.id)?, &serde_json::to_vec_pretty(job)?)
+}
+
+fn load_job(id: &str) -> Result<BatchJob, AppError> {
+    let file = job_file(id)?;
+    let content = fs::read_to_string(&file).map_err(|_| AppError::NotFound(format!("Batch job {}", id)))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+// 启动时读取任务目录；上次运行中未完成的任务标记为 Interrupted
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("jobs");
+    fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+    *JOBS_DIR.write() = Some(dir);
+
+    for mut job in read_jobs()? {
+        if job.status == JobStatus::Running {
+            job.status = JobStatus::Interrupted;
+            save_job(&job)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_jobs() -> Result<Vec<BatchJob>, AppError> {
+    let dir = jobs_dir()?;
+    let entries = fs::read_dir(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+    let mut jobs: Vec<BatchJob> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().map(|e| e == "json").unwrap_or(false))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    jobs.sort_by_key(|job| job.created);
+    Ok(jobs)
+}
+
+fn output_dir(preset: &HotFolderPreset) -> Result<PathBuf, AppError> {
+    preset
+        .output_dir
+        .as_ref()
+        .map(PathBuf::from)
+        .ok_or_else(|| AppError::InvalidArgument("Batch jobs need an output directory".to_string()))
+}
+
+// inputs[index] 的输出扩展名：预设的格式，未指定时（batch_process 没有 Convert 步骤）沿用原图的扩展名
+fn output_extension(job: &BatchJob, index: usize) -> String {
+    if !job.preset.format.is_empty() {
+        return job.preset.format.clone();
+    }
+    Path::new(&job.inputs[index])
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "png".to_string())
+}
+
+// inputs[index] 的输出文件名（不含扩展名）
+fn output_name(job: &BatchJob, index: usize) -> String {
+    let stem = paths::file_stem(Path::new(&job.inputs[index]), "image");
+    match &job.rename {
+        Some(template) => {
+            let digits = job.inputs.len().to_string().len();
+            template
+                .replace("{name}", &stem)
+                .replace("{index}", &format!("{:0width$}", index + 1, width = digits))
+        }
+        None => stem,
+    }
+}
+
+// 重命名后的文件名必须合法且互不相同，否则后处理的文件会覆盖先处理的
+fn check_rename(job: &BatchJob) -> Result<(), AppError> {
+    if job.rename.is_none() {
+        return Ok(());
+    }
+    let mut names = HashSet::new();
+    for index in 0..job.inputs.len() {
+        let name = output_name(job, index);
+        if name.trim().is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(AppError::InvalidArgument(format!("Invalid output file name: {}", name)));
+        }
+        // 不区分大小写的文件系统上只有大小写不同的文件名也会冲突
+        if !names.insert(name.to_lowercase()) {
+            return Err(AppError::InvalidArgument(format!(
+                "Rename pattern produces duplicate file name {}; include {{index}} or {{name}}",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+// 在后台线程中从 job.completed 开始处理，每组文件处理后保存进度
+fn spawn_job(app: AppHandle, mut job: BatchJob) -> Result<(), AppError> {
+    let output_dir = output_dir(&job.preset)?;
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(MAX_PARALLEL);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to create batch thread pool: {}", e)))?;
+    let stop = Arc::new(Mutex::new(None));
+    {
+        let mut running = RUNNING.lock();
+        if running.contains_key(&job.id) {
+            return Err(AppError::InvalidArgument(format!("Batch job {} is already running", job.id)));
+        }
+        running.insert(job.id.clone(), stop.clone());
+    }
+
+    job.status = JobStatus::Running;
+    job.updated = now();
+    if let Err(e) = save_job(&job) {
+        RUNNING.lock().remove(&job.id);
+        return Err(e);
+    }
+
+    thread::spawn(move || {
+        let total = job.inputs.len();
+        let done = AtomicUsize::new(job.completed);
+        while job.completed < total {
+            if let Some(status) = *stop.lock() {
+                job.status = status;
+                break;
+            }
+
+            let end = (job.completed + threads).min(total);
+            let failures: Vec<JobFailure> = pool.install(|| {
+                (job.completed..end)
+                    .into_par_iter()
+                    .filter_map(|index| {
+                        let path = PathBuf::from(&job.inputs[index]);
+                        let output = output_dir.join(format!("{}.{}", output_name(&job, index), output_extension(&job, index)));
+                        let (output, error) = match hotfolder::process_to(&app, &path, &job.preset, &output) {
+                            Ok(output) => (Some(output), None),
+                            Err(error) => {
+                                tracing::warn!(job = %job.id, path = %path.display(), error = %error, "batch job file failed");
+                                (None, Some(error))
+                            }
+                        };
+                        let failure = error.as_ref().map(|error| JobFailure {
+                            path: paths::display(&path),
+                            code: error.code().to_string(),
+                            message: error.to_string(),
+                        });
+                        let _ = app.emit(
+                            "batch-progress",
+                            BatchProgress {
+                                job_id: job.id.clone(),
+                                path: paths::display(&path),
+                                output,
+                                error,
+                                done: done.fetch_add(1, Ordering::Relaxed) + 1,
+                                total,
+                            },
+                        );
+                        failure
+                    })
+                    .collect()
+            });
+            job.failures.extend(failures);
+            job.completed = end;
+            job.updated = now();
+            if let Err(e) = save_job(&job) {
+                tracing::warn!(job = %job.id, error = %e, "failed to save batch job progress");
+            }
+        }
+
+        if job.completed >= total && job.status == JobStatus::Running {
+            job.status = JobStatus::Completed;
+        }
+        job.updated = now();
+        if let Err(e) = save_job(&job) {
+            tracing::warn!(job = %job.id, error = %e, "failed to save batch job progress");
+        }
+        RUNNING.lock().remove(&job.id);
+        tracing::info!(job = %job.id, status = ?job.status, done = job.completed, total, "batch job stopped");
+        let _ = app.emit("batch-finished", BatchFinished { job });
+    });
+    Ok(())
+}
+
+// 创建并开始批量任务，返回任务 id；preset.output_dir 必须指定
+#[tauri::command]
+pub fn start_batch_job(
+    app: AppHandle,
+    inputs: Vec<String>,
+    preset: HotFolderPreset,
+    rename: Option<String>,
+) -> Result<String, AppError> {
+    output_format(&preset.format.to_lowercase())?;
+    create_job(app, inputs, preset, rename)
+}
+
+// 按处理步骤批量处理 inputs，结果写入 output_dir，返回任务 id
+// 与 start_batch_job 相同：进度通过 batch-progress 事件发送，可以用 pause_job、cancel_job 暂停或取消，单个文件的错误在 batch-finished 中一并报告
+#[tauri::command]
+pub fn batch_process(
+    app: AppHandle,
+    inputs: Vec<String>,
+    output_dir: String,
+    pipeline: Vec<BatchStep>,
+) -> Result<String, AppError> {
+    let (preset, rename) = pipeline_preset(pipeline, output_dir)?;
+    create_job(app, inputs, preset, rename)
+}
+
+fn create_job(app: AppHandle, inputs: Vec<String>, preset: HotFolderPreset, rename: Option<String>) -> Result<String, AppError> {
+    for input in &inputs {
+        scope::check(input)?;
+    }
+    let dir = output_dir(&preset)?;
+    scope::check(&dir)?;
+    fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+
+    let created = now();
+    let job = BatchJob {
+        id: format!("{:016x}", rand::random::<u64>()),
+        inputs,
+        preset,
+        rename,
+        status: JobStatus::Running,
+        completed: 0,
+        failures: Vec::new(),
+        created,
+        updated: created,
+    };
+    check_rename(&job)?;
+    let id = job.id.clone();
+    spawn_job(app, job)?;
+    Ok(id)
+}
+
+// 从上次完成的文件继续执行暂停或中断的任务；已完成的任务直接返回
+#[tauri::command]
+pub fn resume_job(app: AppHandle, job_id: &str) -> Result<BatchJob, AppError> {
+    let job = load_job(job_id)?;
+    match job.status {
+        JobStatus::Completed => return Ok(job),
+        JobStatus::Cancelled => {
+            return Err(AppError::InvalidArgument(format!("Batch job {} was cancelled", job_id)));
+        }
+        _ => {}
+    }
+    // 恢复前重新检查，任务创建后访问范围可能已改变
+    for input in &job.inputs[job.completed..] {
+        scope::check(input)?;
+    }
+    let dir = output_dir(&job.preset)?;
+    scope::check(&dir)?;
+    fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+
+    spawn_job(app, job.clone())?;
+    Ok(BatchJob {
+        status: JobStatus::Running,
+        ..job
+    })
+}
+
+// 暂停任务：正在处理的文件处理完后停止，进度保留
+#[tauri::command]
+pub fn pause_job(job_id: &str) -> bool {
+    match RUNNING.lock().get(job_id) {
+        Some(stop) => {
+            stop.lock().get_or_insert(JobStatus::Paused);
+            true
+        }
+        None => false,
+    }
+}
+
+// 取消任务：正在执行时处理完当前文件后停止；已处理的文件保留，任务不能再恢复
+#[tauri::command]
+pub fn cancel_job(job_id: &str) -> Result<bool, AppError> {
+    if let Some(stop) = RUNNING.lock().get(job_id) {
+        *stop.lock() = Some(JobStatus::Cancelled);
+        return Ok(true);
+    }
+    let mut job = load_job(job_id)?;
+    if matches!(job.status, JobStatus::Completed | JobStatus::Cancelled) {
+        return Ok(false);
+    }
+    job.status = JobStatus::Cancelled;
+    job.updated = now();
+    save_job(&job)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn list_jobs() -> Result<Vec<BatchJob>, AppError> {
+    read_jobs()
+}
+
+#[tauri::command]
+pub fn get_job(job_id: &str) -> Result<BatchJob, AppError> {
+    load_job(job_id)
+}
+
+// 删除任务记录；正在执行的任务需要先暂停
+#[tauri::command]
+pub fn delete_job(job_id: &str) -> Result<bool, AppError> {
+    if RUNNING.lock().contains_key(job_id) {
+        return Err(AppError::InvalidArgument(format!("Batch job {} is still running", job_id)));
+    }
+    let file = job_file(job_id)?;
+    if !file.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&file).map_err(|e| AppError::from(e).at(&file))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(inputs: &[&str], rename: Option<&str>) -> BatchJob {
+        BatchJob {
+            id: "0".to_string(),
+            inputs: inputs.iter().map(|input| input.to_string()).collect(),
+            preset: HotFolderPreset {
+                operations: Vec::new(),
+                format: "png".to_string(),
+                output_dir: Some("/out".to_string()),
+            },
+            rename: rename.map(str::to_string),
+            status: JobStatus::Running,
+            completed: 0,
+            failures: Vec::new(),
+            created: 0,
+            updated: 0,
+        }
+    }
+
+    #[test]
+    fn output_name_fills_template() {
+        let inputs: Vec<String> = (1..=12).map(|i| format!("/photos/IMG_{}.jpg", i)).collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let job = job(&inputs, Some("trip_{index}_{name}"));
+        // 序号补零到与总数相同的位数
+        assert_eq!(output_name(&job, 0), "trip_01_IMG_1");
+        assert_eq!(output_name(&job, 11), "trip_12_IMG_12");
+        assert!(check_rename(&job).is_ok());
+    }
+
+    #[test]
+    fn output_name_without_template_keeps_stem() {
+        let job = job(&["/photos/beach.jpeg"], None);
+        assert_eq!(output_name(&job, 0), "beach");
+        assert_eq!(output_extension(&job, 0), "png");
+    }
+
+    #[test]
+    fn output_extension_defaults_to_source_format() {
+        let mut job = job(&["/photos/beach.JPEG"], None);
+        job.preset.format = String::new();
+        assert_eq!(output_extension(&job, 0), "jpeg");
+    }
+
+    #[test]
+    fn check_rename_rejects_duplicates() {
+        assert!(check_rename(&job(&["/a/one.jpg", "/a/two.jpg"], Some("photo"))).is_err());
+        // 不区分大小写的文件系统上 A 和 a 是同一个文件
+        assert!(check_rename(&job(&["/a/A.jpg", "/b/a.jpg"], Some("{name}"))).is_err());
+    }
+
+    #[test]
+    fn check_rename_rejects_invalid_names() {
+        assert!(check_rename(&job(&["/a/one.jpg"], Some("sub/{name}"))).is_err());
+        assert!(check_rename(&job(&["/a/one.jpg"], Some("..\\{name}"))).is_err());
+        assert!(check_rename(&job(&["/a/one.jpg"], Some(".."))).is_err());
+        assert!(check_rename(&job(&["/a/one.jpg"], Some("  "))).is_err());
+    }
+
+    #[test]
+    fn pipeline_splits_steps() {
+        let pipeline = vec![
+            BatchStep::Resize { width: 800, height: 600 },
+            BatchStep::Convert { format: "PNG".to_string() },
+            BatchStep::Rename { pattern: "{index}".to_string() },
+        ];
+        let (preset, rename) = pipeline_preset(pipeline, "/out".to_string()).unwrap();
+        assert!(matches!(preset.operations[..], [EditOperation::Resize { width: 800, height: 600 }]));
+        assert_eq!(preset.format, "png");
+        assert_eq!(rename.as_deref(), Some("{index}"));
+    }
+
+    #[test]
+    fn pipeline_rejects_repeated_steps() {
+        let convert = || BatchStep::Convert { format: "png".to_string() };
+        assert!(pipeline_preset(vec![convert(), convert()], "/out".to_string()).is_err());
+        assert!(pipeline_preset(Vec::new(), "/out".to_string()).is_err());
+    }
+}
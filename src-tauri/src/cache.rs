@@ -0,0 +1,143 @@
+// 解码图片缓存：同一张图片查看信息、预览、编辑时只解码一次
+// 以路径为键，文件修改时间或大小变化时失效；总大小超过设置中的上限时淘汰最久未使用的图片
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use image::DynamicImage;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::settings;
+
+struct Entry {
+    modified: SystemTime,
+    len: u64,
+    image: Arc<DynamicImage>,
+    bytes: usize,
+    // 最近一次使用的序号，越小越久未使用
+    used: u64,
+}
+
+#[derive(Default)]
+struct ImageCache {
+    entries: HashMap<PathBuf, Entry>,
+    total_bytes: usize,
+    clock: u64,
+}
+
+impl ImageCache {
+    fn remove(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.total_bytes -= entry.bytes;
+        }
+    }
+
+    // 淘汰最久未使用的图片，直到总大小不超过 limit
+    fn shrink_to(&mut self, limit: usize) {
+        while self.total_bytes > limit {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    pub entries: usize,
+    pub bytes: u64,
+    pub limit_bytes: u64,
+}
+
+lazy_static::lazy_static! {
+    static ref CACHE: Mutex<ImageCache> = Mutex::new(ImageCache::default());
+}
+
+fn limit() -> usize {
+    (settings::current().cache_max_mb as usize).saturating_mul(1024 * 1024)
+}
+
+// 从缓存中取出图片，未命中或已过期时调用 decode 解码并放入缓存
+// 缓存和调用方共用同一份图片，命中时不复制像素
+pub(crate) fn get_or_decode<F>(path: &Path, decode: F) -> Result<Arc<DynamicImage>, AppError>
+where
+    F: FnOnce() -> Result<DynamicImage, AppError>,
+{
+    // 取不到文件信息时不使用缓存，由 decode 报告具体错误
+    let Ok((modified, len)) = fs::metadata(path).and_then(|m| Ok((m.modified()?, m.len()))) else {
+        return decode();
+    };
+
+    {
+        let mut cache = CACHE.lock();
+        cache.clock += 1;
+        let clock = cache.clock;
+        match cache.entries.get_mut(path) {
+            Some(entry) if entry.modified == modified && entry.len == len => {
+                entry.used = clock;
+                return Ok(entry.image.clone());
+            }
+            Some(_) => cache.remove(path),
+            None => {}
+        }
+    }
+
+    // 解码期间不持有锁，其他图片可以同时读取缓存
+    let image = Arc::new(decode()?);
+    let bytes = image.as_bytes().len();
+    let limit = limit();
+    if bytes <= limit {
+        let mut cache = CACHE.lock();
+        cache.remove(path);
+        cache.shrink_to(limit - bytes);
+        cache.clock += 1;
+        let used = cache.clock;
+        cache.total_bytes += bytes;
+        cache.entries.insert(
+            path.to_path_buf(),
+            Entry {
+                modified,
+                len,
+                image: image.clone(),
+                bytes,
+                used,
+            },
+        );
+    }
+    Ok(image)
+}
+
+pub(crate) fn usage() -> CacheUsage {
+    let cache = CACHE.lock();
+    CacheUsage {
+        entries: cache.entries.len(),
+        bytes: cache.total_bytes as u64,
+        limit_bytes: limit() as u64,
+    }
+}
+
+// 文件被写入后调用，丢弃缓存中的旧图片
+pub(crate) fn invalidate(path: &Path) {
+    CACHE.lock().remove(path);
+}
+
+// 清空缓存，返回释放的字节数
+#[tauri::command]
+pub fn clear_image_cache() -> u64 {
+    let mut cache = CACHE.lock();
+    let freed = cache.total_bytes as u64;
+    cache.entries.clear();
+    cache.total_bytes = 0;
+    freed
+}
@@ -0,0 +1,181 @@
+// 屏幕截图：支持整个显示器、单个窗口或显示器上的指定区域
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use xcap::{Monitor, Window};
+
+use crate::error::AppError;
+use crate::ImageInfo;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::save_image;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub id: u32,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub is_primary: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub id: u32,
+    pub title: String,
+    pub app_name: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CaptureTargets {
+    pub monitors: Vec<MonitorInfo>,
+    pub windows: Vec<WindowInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CaptureTarget {
+    // 整个显示器，id 为空时使用主显示器
+    Monitor { id: Option<u32> },
+    Window { id: u32 },
+    // 显示器上的区域，坐标相对于该显示器
+    Region {
+        monitor: Option<u32>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+fn find_monitor(id: Option<u32>) -> Result<Monitor, AppError> {
+    let monitors = Monitor::all().map_err(|e| AppError::Device(format!("Failed to list monitors: {}", e)))?;
+    monitors
+        .into_iter()
+        .find(|m| match id {
+            Some(id) => m.id() == id,
+            None => m.is_primary(),
+        })
+        .ok_or_else(|| AppError::Device("Monitor not found".to_string()))
+}
+
+// xcap 返回的缓冲区转换为本 crate 使用的图片类型
+fn to_image(width: u32, height: u32, raw: Vec<u8>) -> Result<RgbaImage, AppError> {
+    RgbaImage::from_raw(width, height, raw).ok_or_else(|| AppError::Device("Invalid capture buffer".to_string()))
+}
+
+// 默认保存到应用缓存目录下的 captures 文件夹
+fn default_output(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = scope::captures_dir(app)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    Ok(dir.join(format!("capture-{}.png", timestamp)))
+}
+
+// 列出可以截图的显示器和窗口
+#[tauri::command]
+pub fn list_capture_targets() -> Result<CaptureTargets, AppError> {
+    let monitors = Monitor::all()
+        .map_err(|e| AppError::Device(format!("Failed to list monitors: {}", e)))?
+        .iter()
+        .map(|m| MonitorInfo {
+            id: m.id(),
+            name: m.name().to_string(),
+            x: m.x(),
+            y: m.y(),
+            width: m.width(),
+            height: m.height(),
+            is_primary: m.is_primary(),
+        })
+        .collect();
+
+    let windows = Window::all()
+        .map_err(|e| AppError::Device(format!("Failed to list windows: {}", e)))?
+        .iter()
+        .filter(|w| !w.is_minimized())
+        .map(|w| WindowInfo {
+            id: w.id(),
+            title: w.title().to_string(),
+            app_name: w.app_name().to_string(),
+        })
+        .collect();
+
+    Ok(CaptureTargets { monitors, windows })
+}
+
+// 截图并保存为图片文件，返回图片信息供编辑器打开
+#[tauri::command]
+pub async fn capture_screen(app: AppHandle, target: CaptureTarget, output: Option<String>) -> Result<ImageInfo, AppError> {
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("capture_screen", move || {
+        let captured = match target {
+            CaptureTarget::Monitor { id } => {
+                let shot = find_monitor(id)?
+                    .capture_image()
+                    .map_err(|e| AppError::Device(format!("Failed to capture screen: {}", e)))?;
+                to_image(shot.width(), shot.height(), shot.into_raw())?
+            }
+            CaptureTarget::Window { id } => {
+                let window = Window::all()
+                    .map_err(|e| AppError::Device(format!("Failed to list windows: {}", e)))?
+                    .into_iter()
+                    .find(|w| w.id() == id)
+                    .ok_or_else(|| AppError::NotFound(format!("Window {}", id)))?;
+                let shot = window
+                    .capture_image()
+                    .map_err(|e| AppError::Device(format!("Failed to capture window: {}", e)))?;
+                to_image(shot.width(), shot.height(), shot.into_raw())?
+            }
+            CaptureTarget::Region { monitor, x, y, width, height } => {
+                let shot = find_monitor(monitor)?
+                    .capture_image()
+                    .map_err(|e| AppError::Device(format!("Failed to capture screen: {}", e)))?;
+                let full = to_image(shot.width(), shot.height(), shot.into_raw())?;
+                if x >= full.width() || y >= full.height() || width == 0 || height == 0 {
+                    return Err(AppError::InvalidArgument("Capture region is outside the monitor".to_string()));
+                }
+                let width = width.min(full.width() - x);
+                let height = height.min(full.height() - y);
+                image::imageops::crop_imm(&full, x, y, width, height).to_image()
+            }
+        };
+
+        let output_path = match output {
+            Some(output) => PathBuf::from(output),
+            None => default_output(&app)?,
+        };
+
+        let (width, height) = captured.dimensions();
+        save_image(&DynamicImage::ImageRgba8(captured), &output_path)?;
+
+        let size = fs::metadata(&output_path)
+            .map_err(AppError::from)?
+            .len();
+
+        Ok(ImageInfo {
+            path: output_path.to_string_lossy().to_string(),
+            name: output_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            width,
+            height,
+            size,
+            poster: None,
+        })
+    })
+    .await
+}
@@ -0,0 +1,133 @@
+// 取色：读取图片中某个像素（或周围区域平均）的颜色，前端取色器不需要把整张图片下载到 canvas 中
+use std::sync::Arc;
+
+use image::{DynamicImage, GenericImageView};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::{open_image, run_blocking, scope, session};
+
+// 平均半径的上限（101x101 区域）
+const MAX_RADIUS: u32 = 50;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PixelColor {
+    pub x: u32,
+    pub y: u32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+    // #rrggbb，不透明时不含透明度，否则为 #rrggbbaa
+    pub hex: String,
+    // 0~1 的 sRGB 值和线性值（RGBA，透明度不做转换）
+    pub srgb: [f32; 4],
+    pub linear: [f32; 4],
+    // 参与平均的像素数，靠近边缘时少于 (2 * radius + 1)²
+    pub samples: u32,
+}
+
+// source 为打开的编辑会话 id 时读取会话中（可能尚未保存）的图片，否则作为文件路径解码
+pub(crate) fn source_image(app: &AppHandle, source: &str) -> Result<Arc<DynamicImage>, AppError> {
+    if let Some(image) = session::current_image(app, source) {
+        return Ok(image);
+    }
+    scope::check(source)?;
+    open_image(source)
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// 以 (x, y) 为中心、边长 2 * radius + 1 的区域在线性空间中的平均颜色（超出图片的部分不计）
+fn average_linear(img: &DynamicImage, x: u32, y: u32, radius: u32) -> ([f32; 4], u32) {
+    let (width, height) = img.dimensions();
+    let x_range = x.saturating_sub(radius)..=(x + radius).min(width - 1);
+    let y_range = y.saturating_sub(radius)..=(y + radius).min(height - 1);
+
+    let mut sum = [0.0f64; 4];
+    let mut samples = 0;
+    for py in y_range {
+        for px in x_range.clone() {
+            let pixel = img.get_pixel(px, py).0;
+            for c in 0..3 {
+                sum[c] += srgb_to_linear(pixel[c] as f32 / 255.0) as f64;
+            }
+            sum[3] += pixel[3] as f64 / 255.0;
+            samples += 1;
+        }
+    }
+    (sum.map(|v| (v / samples as f64) as f32), samples)
+}
+
+fn hex(rgba: [u8; 4]) -> String {
+    let [r, g, b, a] = rgba;
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+// 读取 (x, y) 处的 RGBA 颜色，坐标为像素坐标
+// radius 为平均半径：0（默认）只取一个像素，1 为 3x3，2 为 5x5，以此类推；平均在线性空间中进行
+#[tauri::command]
+pub async fn get_pixel_color(
+    app: AppHandle,
+    source: String,
+    x: u32,
+    y: u32,
+    radius: Option<u32>,
+) -> Result<PixelColor, AppError> {
+    let radius = radius.unwrap_or(0);
+    if radius > MAX_RADIUS {
+        return Err(AppError::InvalidArgument(format!("Radius must be at most {}", MAX_RADIUS)));
+    }
+
+    run_blocking("get_pixel_color", move || {
+        let img = source_image(&app, &source)?;
+        let (width, height) = img.dimensions();
+        if x >= width || y >= height {
+            return Err(AppError::InvalidArgument(format!(
+                "Pixel ({}, {}) is outside the {}x{} image",
+                x, y, width, height
+            )));
+        }
+        let (linear, samples) = average_linear(&img, x, y, radius);
+        let srgb = [
+            linear_to_srgb(linear[0]),
+            linear_to_srgb(linear[1]),
+            linear_to_srgb(linear[2]),
+            linear[3],
+        ];
+        let rgba = srgb.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+        Ok(PixelColor {
+            x,
+            y,
+            r: rgba[0],
+            g: rgba[1],
+            b: rgba[2],
+            a: rgba[3],
+            hex: hex(rgba),
+            srgb,
+            linear,
+            samples,
+        })
+    })
+    .await
+}
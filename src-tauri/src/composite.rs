@@ -0,0 +1,358 @@
+// 叠加水印：把图片（带透明通道的 logo 等）或文字按锚点位置叠加到照片上，可设置不透明度、缩放、边距，也可以平铺满整张图片
+// 文件版本写入新文件；内存版本返回编码后的字节，供前端在保存前预览
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::io::Reader as ImageReader;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{Request, Response};
+
+use crate::error::AppError;
+use crate::storage::{encode_to_vec, save_image_from};
+use crate::text::{blend, draw_text, load_font, measure, parse_color};
+use crate::{open_image, paths, run_blocking, scope, transfer};
+
+const MAX_OVERLAYS: usize = 20;
+// 平铺时最多绘制的次数，避免叠加层很小时耗时过长
+const MAX_TILES: usize = 10_000;
+
+fn one() -> f32 {
+    1.0
+}
+
+fn default_margin() -> f32 {
+    2.0
+}
+
+fn default_spacing() -> f32 {
+    0.5
+}
+
+fn default_font_size() -> f32 {
+    4.0
+}
+
+fn default_color() -> String {
+    "#ffffff".to_string()
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OverlayContent {
+    Image {
+        path: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Text {
+        text: String,
+        // 字体文件，为空时使用系统字体
+        font: Option<String>,
+        // 字号，占底图短边的百分比
+        #[serde(default = "default_font_size")]
+        font_size: f32,
+        #[serde(default = "default_color")]
+        color: String,
+        // 在文字下方绘制半透明阴影，浅色背景上也能看清
+        #[serde(default)]
+        shadow: bool,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Overlay {
+    #[serde(flatten)]
+    pub content: OverlayContent,
+    #[serde(default)]
+    pub anchor: Anchor,
+    // 0~1
+    #[serde(default = "one")]
+    pub opacity: f32,
+    // 图片叠加层的宽度，占底图宽度的百分比；为空时使用原尺寸
+    pub scale: Option<f32>,
+    // 与底图边缘的距离，占底图短边的百分比
+    #[serde(default = "default_margin")]
+    pub margin: f32,
+    // 平铺满整张底图（忽略锚点）
+    #[serde(default)]
+    pub tile: bool,
+    // 平铺时的间距，为叠加层尺寸的倍数
+    #[serde(default = "default_spacing")]
+    pub spacing: f32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompositeResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn check(overlays: &[Overlay]) -> Result<(), AppError> {
+    if overlays.is_empty() || overlays.len() > MAX_OVERLAYS {
+        return Err(AppError::InvalidArgument(format!(
+            "Expected 1 to {} overlays, got {}",
+            MAX_OVERLAYS,
+            overlays.len()
+        )));
+    }
+    for overlay in overlays {
+        if !(0.0..=1.0).contains(&overlay.opacity) {
+            return Err(AppError::InvalidArgument(format!(
+                "Opacity must be between 0 and 1: {}",
+                overlay.opacity
+            )));
+        }
+        if let Some(scale) = overlay.scale {
+            if !(scale > 0.0 && scale <= 100.0) {
+                return Err(AppError::InvalidArgument(format!("Scale must be between 0 and 100%: {}", scale)));
+            }
+        }
+        if !(0.0..=50.0).contains(&overlay.margin) {
+            return Err(AppError::InvalidArgument(format!(
+                "Margin must be between 0 and 50%: {}",
+                overlay.margin
+            )));
+        }
+        if !(0.0..=10.0).contains(&overlay.spacing) {
+            return Err(AppError::InvalidArgument(format!(
+                "Spacing must be between 0 and 10: {}",
+                overlay.spacing
+            )));
+        }
+        match &overlay.content {
+            OverlayContent::Image { path } => {
+                scope::check(path)?;
+            }
+            OverlayContent::Text {
+                text,
+                font,
+                font_size,
+                color,
+                ..
+            } => {
+                if let Some(font) = font {
+                    scope::check(font)?;
+                }
+                if text.trim().is_empty() {
+                    return Err(AppError::InvalidArgument("Overlay text is empty".to_string()));
+                }
+                if !(*font_size > 0.0 && *font_size <= 50.0) {
+                    return Err(AppError::InvalidArgument(format!(
+                        "Font size must be between 0 and 50%: {}",
+                        font_size
+                    )));
+                }
+                parse_color(color)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 渲染叠加层（透明背景）
+fn render(overlay: &Overlay, width: u32, height: u32) -> Result<RgbaImage, AppError> {
+    match &overlay.content {
+        OverlayContent::Image { path } => {
+            let layer = open_image(path)?;
+            Ok(match overlay.scale {
+                Some(scale) => {
+                    let target = ((width as f32 * scale / 100.0).round() as u32).max(1);
+                    let target_height = ((layer.height() as f32 * target as f32 / layer.width() as f32).round() as u32).max(1);
+                    layer.resize_exact(target, target_height, FilterType::Lanczos3).to_rgba8()
+                }
+                None => layer.to_rgba8(),
+            })
+        }
+        OverlayContent::Text {
+            text,
+            font,
+            font_size,
+            color,
+            shadow,
+        } => {
+            let font = load_font(font.as_deref())?;
+            let size = (width.min(height) as f32 * font_size / 100.0).max(8.0);
+            let (text_width, text_height) = measure(&font, size, text);
+            let offset = if *shadow { ((size / 16.0).round() as i32).max(1) } else { 0 };
+            let mut layer = RgbaImage::new(text_width.max(1) + offset as u32, text_height.max(1) + offset as u32);
+            if *shadow {
+                draw_text(&mut layer, &font, size, offset, offset, text, Rgba([0, 0, 0, 160]));
+            }
+            draw_text(&mut layer, &font, size, 0, 0, text, parse_color(color)?);
+            Ok(layer)
+        }
+    }
+}
+
+// 叠加层左上角的位置
+fn position(anchor: Anchor, base: (u32, u32), layer: (u32, u32), margin: i32) -> (i32, i32) {
+    let (free_x, free_y) = (base.0 as i32 - layer.0 as i32, base.1 as i32 - layer.1 as i32);
+    let x = match anchor {
+        Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => margin,
+        Anchor::Top | Anchor::Center | Anchor::Bottom => free_x / 2,
+        Anchor::TopRight | Anchor::Right | Anchor::BottomRight => free_x - margin,
+    };
+    let y = match anchor {
+        Anchor::TopLeft | Anchor::Top | Anchor::TopRight => margin,
+        Anchor::Left | Anchor::Center | Anchor::Right => free_y / 2,
+        Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => free_y - margin,
+    };
+    (x, y)
+}
+
+// 以 opacity 把 layer 叠加到 canvas 的 (x, y) 处，超出画布的部分忽略
+fn draw_layer(canvas: &mut RgbaImage, layer: &RgbaImage, x: i32, y: i32, opacity: f32) {
+    let (width, height) = canvas.dimensions();
+    for (lx, ly, pixel) in layer.enumerate_pixels() {
+        let (px, py) = (x + lx as i32, y + ly as i32);
+        if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+            continue;
+        }
+        blend(canvas.get_pixel_mut(px as u32, py as u32), *pixel, opacity);
+    }
+}
+
+pub(crate) fn composite(img: &DynamicImage, overlays: &[Overlay]) -> Result<DynamicImage, AppError> {
+    let mut canvas = img.to_rgba8();
+    let (width, height) = canvas.dimensions();
+    for overlay in overlays {
+        let layer = render(overlay, width, height)?;
+        let margin = (width.min(height) as f32 * overlay.margin / 100.0) as i32;
+        if overlay.tile {
+            let step_x = ((layer.width() as f32 * (1.0 + overlay.spacing)).round() as i32).max(1);
+            let step_y = ((layer.height() as f32 * (1.0 + overlay.spacing)).round() as i32).max(1);
+            let columns = ((width as i32 - margin) / step_x + 1).max(1) as usize;
+            let rows = ((height as i32 - margin) / step_y + 1).max(1) as usize;
+            if columns * rows > MAX_TILES {
+                return Err(AppError::InvalidArgument(format!(
+                    "Overlay is too small to tile ({} copies, at most {})",
+                    columns * rows,
+                    MAX_TILES
+                )));
+            }
+            for row in 0..rows {
+                for column in 0..columns {
+                    let (x, y) = (margin + column as i32 * step_x, margin + row as i32 * step_y);
+                    draw_layer(&mut canvas, &layer, x, y, overlay.opacity);
+                }
+            }
+        } else {
+            let (x, y) = position(overlay.anchor, (width, height), layer.dimensions(), margin);
+            draw_layer(&mut canvas, &layer, x, y, overlay.opacity);
+        }
+    }
+    Ok(if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(canvas)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(canvas).to_rgb8())
+    })
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "png".to_string());
+            path.with_file_name(format!("{}_watermarked.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 按顺序叠加各层；output 为空时在原图旁边生成 name_watermarked.ext
+#[tauri::command]
+pub async fn composite_image(
+    path: String,
+    overlays: Vec<Overlay>,
+    output: Option<String>,
+) -> Result<CompositeResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    check(&overlays)?;
+
+    run_blocking("composite_image", move || {
+        let path = Path::new(&path);
+        let result = composite(&open_image(path)?, &overlays)?;
+        let output = output_path(path, output);
+        save_image_from(&result, path, &output)?;
+        let (width, height) = result.dimensions();
+        Ok(CompositeResult {
+            output: paths::display(&output),
+            width,
+            height,
+        })
+    })
+    .await
+}
+
+// 图片数据以原始请求体（或分块上传的 transfer-id）传入，叠加层列表以 JSON 放在请求头 overlays 中
+// 返回请求头 format 指定格式（默认 PNG）的原始字节
+#[tauri::command]
+pub async fn composite_image_from_data(request: Request<'_>) -> Result<Response, AppError> {
+    let overlays: Vec<Overlay> = transfer::header_json(&request, "overlays")?;
+    check(&overlays)?;
+    let (format, options) = transfer::output_encoding(&request)?;
+    let data = transfer::request_data(&request)?;
+
+    let encoded = run_blocking("composite_image_from_data", move || {
+        let img = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(AppError::from)?
+            .decode()
+            .map_err(AppError::from)?;
+        encode_to_vec(&composite(&img, &overlays)?, format, &options)
+    })
+    .await?;
+    Ok(Response::new(encoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_follows_anchor() {
+        let (base, layer, margin) = ((100, 80), (20, 10), 5);
+        let cases = [
+            (Anchor::TopLeft, (5, 5)),
+            (Anchor::Top, (40, 5)),
+            (Anchor::TopRight, (75, 5)),
+            (Anchor::Left, (5, 35)),
+            (Anchor::Center, (40, 35)),
+            (Anchor::Right, (75, 35)),
+            (Anchor::BottomLeft, (5, 65)),
+            (Anchor::Bottom, (40, 65)),
+            (Anchor::BottomRight, (75, 65)),
+        ];
+        for (anchor, expected) in cases {
+            assert_eq!(position(anchor, base, layer, margin), expected, "{:?}", anchor);
+        }
+    }
+
+    #[test]
+    fn position_of_larger_layer_is_negative() {
+        // 叠加层比底图大时超出的部分由 draw_layer 忽略
+        assert_eq!(position(Anchor::Center, (10, 10), (30, 20), 0), (-10, -5));
+        assert_eq!(position(Anchor::BottomRight, (10, 10), (30, 20), 2), (-22, -12));
+    }
+}
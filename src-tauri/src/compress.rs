@@ -0,0 +1,216 @@
+// 压缩到指定大小：上传表单常有文件大小上限，自动寻找不超过上限的最高质量，必要时缩小图片
+//   JPEG：二分查找编码质量
+//   PNG：依次尝试无损压缩和颜色逐渐减少的索引色
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilter, PngEncoder};
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::palette::{self, Dither, PaletteOptions};
+use crate::storage::write_bytes;
+use crate::{open_image, paths, run_blocking, scope};
+
+const MAX_JPEG_QUALITY: u8 = 95;
+// 索引色 PNG 依次尝试的颜色数
+const PNG_COLORS: [u16; 4] = [256, 128, 64, 32];
+// 缩小后的最长边不低于该值
+const MIN_DIMENSION: u32 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Jpeg,
+    Png,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompressOptions {
+    // 为空时在原图旁边生成 name_compressed.ext
+    pub output: Option<String>,
+    // JPEG 质量的下限，降到该值仍超过大小时缩小图片
+    pub min_quality: u8,
+    // 是否允许缩小图片
+    pub allow_resize: bool,
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        Self {
+            output: None,
+            min_quality: 40,
+            allow_resize: true,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressResult {
+    pub output: String,
+    pub size: u64,
+    pub width: u32,
+    pub height: u32,
+    // JPEG 为编码质量，PNG 为调色板颜色数（无损时为空）
+    pub quality: Option<u16>,
+    pub resized: bool,
+}
+
+enum Fit {
+    Found(Vec<u8>, Option<u16>),
+    // 最小设置下的编码大小
+    TooLarge(usize),
+}
+
+fn encode_jpeg(img: &DynamicImage, quality: u8) -> Result<Vec<u8>, AppError> {
+    let mut buffer = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buffer, quality).encode_image(&img.to_rgb8())?;
+    Ok(buffer.into_inner())
+}
+
+fn encode_png(img: &DynamicImage) -> Result<Vec<u8>, AppError> {
+    let mut buffer = Vec::new();
+    let rgba = img.to_rgba8();
+    PngEncoder::new_with_quality(&mut buffer, CompressionType::Best, PngFilter::Adaptive).write_image(
+        rgba.as_raw(),
+        rgba.width(),
+        rgba.height(),
+        image::ColorType::Rgba8,
+    )?;
+    Ok(buffer)
+}
+
+// 不超过 budget 的最高质量 JPEG
+fn fit_jpeg(img: &DynamicImage, budget: usize, min_quality: u8) -> Result<Fit, AppError> {
+    let lowest = encode_jpeg(img, min_quality)?;
+    if lowest.len() > budget {
+        return Ok(Fit::TooLarge(lowest.len()));
+    }
+    let (mut best, mut best_quality) = (lowest, min_quality);
+    let (mut low, mut high) = (min_quality + 1, MAX_JPEG_QUALITY);
+    while low <= high {
+        let quality = low + (high - low) / 2;
+        let data = encode_jpeg(img, quality)?;
+        if data.len() <= budget {
+            best = data;
+            best_quality = quality;
+            low = quality + 1;
+        } else {
+            high = quality - 1;
+        }
+    }
+    Ok(Fit::Found(best, Some(best_quality as u16)))
+}
+
+// 先尝试无损 PNG，再依次减少调色板颜色
+fn fit_png(img: &DynamicImage, budget: usize) -> Result<Fit, AppError> {
+    let lossless = encode_png(img)?;
+    if lossless.len() <= budget {
+        return Ok(Fit::Found(lossless, None));
+    }
+    let mut smallest = lossless.len();
+    for colors in PNG_COLORS {
+        let options = PaletteOptions {
+            colors,
+            dither: Dither::FloydSteinberg,
+        };
+        let data = palette::encode_png(&palette::quantize(img, &options)?)?;
+        if data.len() <= budget {
+            return Ok(Fit::Found(data, Some(colors)));
+        }
+        smallest = smallest.min(data.len());
+    }
+    Ok(Fit::TooLarge(smallest))
+}
+
+fn output_path(path: &Path, target: Target, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = if target == Target::Jpeg { "jpg" } else { "png" };
+            path.with_file_name(format!("{}_compressed.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 把图片压缩到不超过 max_kb KB，format 为 jpg 或 png
+#[tauri::command]
+pub async fn compress_to_size(
+    path: String,
+    max_kb: u32,
+    format: String,
+    options: Option<CompressOptions>,
+) -> Result<CompressResult, AppError> {
+    scope::check(&path)?;
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    if max_kb == 0 {
+        return Err(AppError::InvalidArgument("Target size must be positive".to_string()));
+    }
+    if !(1..=MAX_JPEG_QUALITY).contains(&options.min_quality) {
+        return Err(AppError::InvalidArgument(format!(
+            "Minimum quality must be between 1 and {}: {}",
+            MAX_JPEG_QUALITY, options.min_quality
+        )));
+    }
+    let target = match format.to_lowercase().as_str() {
+        "jpg" | "jpeg" => Target::Jpeg,
+        "png" => Target::Png,
+        _ => return Err(AppError::Unsupported(format!("Compression target format {}", format))),
+    };
+
+    run_blocking("compress_to_size", move || {
+        let path = Path::new(&path);
+        let budget = max_kb as usize * 1024;
+        let original = open_image(path)?;
+        let (width, height) = original.dimensions();
+        let mut img = original.clone();
+
+        loop {
+            let fit = match target {
+                Target::Jpeg => fit_jpeg(&img, budget, options.min_quality)?,
+                Target::Png => fit_png(&img, budget)?,
+            };
+            let smallest = match fit {
+                Fit::Found(data, quality) => {
+                    let output = output_path(path, target, options.output);
+                    write_bytes(&output, &data)?;
+                    let (out_width, out_height) = img.dimensions();
+                    return Ok(CompressResult {
+                        output: paths::display(&output),
+                        size: data.len() as u64,
+                        width: out_width,
+                        height: out_height,
+                        quality,
+                        resized: (out_width, out_height) != (width, height),
+                    });
+                }
+                Fit::TooLarge(smallest) => smallest,
+            };
+
+            // 文件大小大致与像素数成正比，按面积比例估算缩放倍数，留一些余量
+            let (current_width, current_height) = img.dimensions();
+            let factor = ((budget as f64 / smallest as f64).sqrt() * 0.9).clamp(0.5, 0.95);
+            let next_width = (current_width as f64 * factor).round() as u32;
+            let next_height = (current_height as f64 * factor).round() as u32;
+            if !options.allow_resize || next_width.max(next_height) < MIN_DIMENSION {
+                return Err(AppError::TooLarge(format!(
+                    "Cannot compress below {} KB (smallest result is {} KB)",
+                    max_kb,
+                    smallest.div_ceil(1024)
+                ))
+                .at(path));
+            }
+            // 每次都从原图缩小，避免多次重采样累积模糊
+            img = Arc::new(original.resize_exact(next_width.max(1), next_height.max(1), FilterType::Lanczos3));
+        }
+    })
+    .await
+}
@@ -0,0 +1,354 @@
+// 联系表：把一组图片（或文件夹中的图片）的缩略图按网格排到页面上，每张下方标注文件名和/或拍摄日期
+// 输出为 PDF（多页）或图片（多页时每页一个文件：name-1.png、name-2.png……）
+use std::path::{Path, PathBuf};
+
+use ab_glyph::FontVec;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use printpdf::{Image, ImageTransform, Mm, PdfDocument};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::listing::{list_files, SymlinkPolicy};
+use crate::print::PaperSize;
+use crate::storage::{save_image_with_format, write_atomic};
+use crate::text::{draw_text, line_height, load_font, measure, parse_color};
+use crate::{metadata, open_image, paths, run_blocking, scope};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SheetLabel {
+    None,
+    #[default]
+    FileName,
+    Date,
+    FileNameAndDate,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContactSheetOptions {
+    pub columns: u32,
+    pub rows: u32,
+    pub paper: PaperSize,
+    pub landscape: bool,
+    // 页面分辨率，决定输出图片的像素尺寸
+    pub dpi: f32,
+    // 页边距和格子间距（毫米）
+    pub margin: f32,
+    pub spacing: f32,
+    pub label: SheetLabel,
+    // 标签字号（磅）
+    pub font_size: f32,
+    // 字体文件，为空时使用系统字体
+    pub font: Option<String>,
+    pub background: String,
+    pub text_color: String,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        Self {
+            columns: 4,
+            rows: 5,
+            paper: PaperSize::A4,
+            landscape: false,
+            dpi: 150.0,
+            margin: 10.0,
+            spacing: 4.0,
+            label: SheetLabel::FileName,
+            font_size: 8.0,
+            font: None,
+            background: "#ffffff".to_string(),
+            text_color: "#000000".to_string(),
+        }
+    }
+}
+
+// 每排完一页发送一次 contact-sheet-progress 事件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheetProgress {
+    pub page: usize,
+    pub pages: usize,
+}
+
+impl SheetLabel {
+    fn lines(self) -> u32 {
+        match self {
+            SheetLabel::None => 0,
+            SheetLabel::FileName | SheetLabel::Date => 1,
+            SheetLabel::FileNameAndDate => 2,
+        }
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 展开输入：文件夹替换为其中的图片（按文件名排序，不递归）
+fn expand_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, AppError> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            let mut listing: Vec<PathBuf> = list_files(path, SymlinkPolicy::Skip, false)?
+                .files
+                .into_iter()
+                .filter(|p| is_image(p))
+                .collect();
+            listing.sort();
+            files.extend(listing);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+fn label_lines(path: &Path, label: SheetLabel) -> Vec<String> {
+    let date = || {
+        metadata::read_exif(path)
+            .and_then(|exif| metadata::date_taken(&exif))
+            .map(|d| format!("{:04}-{:02}-{:02} {:02}:{:02}", d.year, d.month, d.day, d.hour, d.minute))
+            .unwrap_or_default()
+    };
+    match label {
+        SheetLabel::None => Vec::new(),
+        SheetLabel::FileName => vec![paths::file_name(path)],
+        SheetLabel::Date => vec![date()],
+        SheetLabel::FileNameAndDate => vec![paths::file_name(path), date()],
+    }
+}
+
+// 文字超出宽度时截断并加省略号
+fn fit_text(font: &FontVec, size: f32, text: &str, max_width: u32) -> String {
+    if measure(font, size, text).0 <= max_width {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate: String = chars.iter().collect::<String>() + "…";
+        if measure(font, size, &candidate).0 <= max_width {
+            return candidate;
+        }
+    }
+    String::new()
+}
+
+struct Layout {
+    page_width: u32,
+    page_height: u32,
+    margin: u32,
+    spacing: u32,
+    cell_width: u32,
+    cell_height: u32,
+    // 缩略图区域的高度（格子高度减去标签）
+    thumb_height: u32,
+    font_px: f32,
+    line_height: u32,
+}
+
+impl ContactSheetOptions {
+    fn layout(&self, line_height: u32, lines: u32) -> Result<Layout, AppError> {
+        if self.columns == 0 || self.rows == 0 {
+            return Err(AppError::InvalidArgument("Columns and rows must be at least 1".to_string()));
+        }
+        if !(36.0..=600.0).contains(&self.dpi) {
+            return Err(AppError::InvalidArgument(format!("DPI must be between 36 and 600: {}", self.dpi)));
+        }
+        let px = |mm: f32| (mm / 25.4 * self.dpi).round() as u32;
+        let (mut width, mut height) = self.paper.dimensions();
+        if self.landscape {
+            std::mem::swap(&mut width, &mut height);
+        }
+        let (page_width, page_height) = (px(width), px(height));
+        let (margin, spacing) = (px(self.margin), px(self.spacing));
+
+        let usable_width = page_width as i64 - 2 * margin as i64 - (self.columns as i64 - 1) * spacing as i64;
+        let usable_height = page_height as i64 - 2 * margin as i64 - (self.rows as i64 - 1) * spacing as i64;
+        let cell_width = usable_width / self.columns as i64;
+        let cell_height = usable_height / self.rows as i64;
+        let label_height = (line_height * lines) as i64 + if lines > 0 { spacing as i64 / 2 } else { 0 };
+        if cell_width < 8 || cell_height - label_height < 8 {
+            return Err(AppError::InvalidArgument("Too many cells for the selected page".to_string()));
+        }
+
+        Ok(Layout {
+            page_width,
+            page_height,
+            margin,
+            spacing,
+            cell_width: cell_width as u32,
+            cell_height: cell_height as u32,
+            thumb_height: (cell_height - label_height) as u32,
+            font_px: self.font_size * self.dpi / 72.0,
+            line_height,
+        })
+    }
+}
+
+fn render_page(
+    paths: &[PathBuf],
+    options: &ContactSheetOptions,
+    layout: &Layout,
+    font: Option<&FontVec>,
+) -> Result<RgbaImage, AppError> {
+    let background = parse_color(&options.background)?;
+    let text_color = parse_color(&options.text_color)?;
+    let mut page = RgbaImage::from_pixel(layout.page_width, layout.page_height, background);
+
+    for (slot, path) in paths.iter().enumerate() {
+        let column = slot as u32 % options.columns;
+        let row = slot as u32 / options.columns;
+        let left = layout.margin + column * (layout.cell_width + layout.spacing);
+        let top = layout.margin + row * (layout.cell_height + layout.spacing);
+
+        // 无法解码的图片留空，只显示标签
+        match open_image(path) {
+            Ok(img) => {
+                let thumb = img.thumbnail(layout.cell_width, layout.thumb_height).to_rgba8();
+                let x = left + (layout.cell_width - thumb.width()) / 2;
+                let y = top + (layout.thumb_height - thumb.height()) / 2;
+                image::imageops::overlay(&mut page, &thumb, x as i64, y as i64);
+            }
+            Err(e) => tracing::warn!(path = %path.display(), error = %e, "contact sheet image skipped"),
+        }
+
+        if let Some(font) = font {
+            let lines = label_lines(path, options.label);
+            let mut y = top + layout.cell_height - layout.line_height * lines.len() as u32;
+            for line in lines {
+                let line = fit_text(font, layout.font_px, &line, layout.cell_width);
+                let width = measure(font, layout.font_px, &line).0;
+                let x = left + layout.cell_width.saturating_sub(width) / 2;
+                draw_text(&mut page, font, layout.font_px, x as i32, y as i32, &line, text_color);
+                y += layout.line_height;
+            }
+        }
+    }
+    Ok(page)
+}
+
+// 多页图片输出时的文件名：name-1.png、name-2.png……
+fn page_path(output: &Path, page: usize, pages: usize) -> PathBuf {
+    if pages == 1 {
+        return output.to_path_buf();
+    }
+    let stem = paths::file_stem(output, "contact-sheet");
+    let ext = output.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    output.with_file_name(format!("{}-{}.{}", stem, page + 1, ext))
+}
+
+// 生成联系表，返回写入的文件；output 的扩展名决定输出格式（pdf 或图片格式）
+#[tauri::command]
+pub async fn generate_contact_sheet(
+    app: AppHandle,
+    inputs: Vec<String>,
+    output: String,
+    options: Option<ContactSheetOptions>,
+) -> Result<Vec<String>, AppError> {
+    for input in &inputs {
+        scope::check(input)?;
+    }
+    scope::check(&output)?;
+    let options = options.unwrap_or_default();
+    if let Some(font) = &options.font {
+        scope::check(font)?;
+    }
+
+    run_blocking("generate_contact_sheet", move || {
+        let output = PathBuf::from(&output);
+        let is_pdf = output
+            .extension()
+            .map(|e| e.eq_ignore_ascii_case("pdf"))
+            .unwrap_or(false);
+        let format = if is_pdf {
+            None
+        } else {
+            let format = ImageFormat::from_path(&output).map_err(|e| AppError::from(e).at(&output))?;
+            if !format.writing_enabled() {
+                return Err(AppError::Unsupported(format!("Writing {:?} images", format)));
+            }
+            Some(format)
+        };
+
+        let files = expand_inputs(&inputs)?;
+        if files.is_empty() {
+            return Err(AppError::InvalidArgument("No images for the contact sheet".to_string()));
+        }
+
+        let lines = options.label.lines();
+        let font = if lines > 0 { Some(load_font(options.font.as_deref())?) } else { None };
+        let font_px = options.font_size * options.dpi / 72.0;
+        let line_height = font.as_ref().map(|font| line_height(font, font_px)).unwrap_or(0);
+        let layout = options.layout(line_height, lines)?;
+
+        let per_page = (options.columns * options.rows) as usize;
+        let pages: Vec<&[PathBuf]> = files.chunks(per_page).collect();
+        let mut written = Vec::new();
+
+        if is_pdf {
+            let page_width = Mm(layout.page_width as f32 / options.dpi * 25.4);
+            let page_height = Mm(layout.page_height as f32 / options.dpi * 25.4);
+            let (doc, first_page, first_layer) = PdfDocument::new("Contact sheet", page_width, page_height, "Layer 1");
+            for (index, chunk) in pages.iter().enumerate() {
+                let layer = if index == 0 {
+                    doc.get_page(first_page).get_layer(first_layer)
+                } else {
+                    let (page, layer) = doc.add_page(page_width, page_height, "Layer 1");
+                    doc.get_page(page).get_layer(layer)
+                };
+                let page = render_page(chunk, &options, &layout, font.as_deref())?;
+                // PDF 中的图片不使用透明通道
+                let page = DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(page).to_rgb8());
+                Image::from_dynamic_image(&page).add_to_layer(
+                    layer,
+                    ImageTransform {
+                        dpi: Some(options.dpi),
+                        ..Default::default()
+                    },
+                );
+                let _ = app.emit(
+                    "contact-sheet-progress",
+                    ContactSheetProgress {
+                        page: index + 1,
+                        pages: pages.len(),
+                    },
+                );
+            }
+            write_atomic(&output, |writer| {
+                doc.save(writer)
+                    .map_err(|e| AppError::EncodeFailed(format!("Failed to save PDF: {}", e)))
+            })?;
+            written.push(paths::display(&output));
+        } else if let Some(format) = format {
+            for (index, chunk) in pages.iter().enumerate() {
+                let page = DynamicImage::ImageRgba8(render_page(chunk, &options, &layout, font.as_deref())?);
+                let page = if format == ImageFormat::Jpeg {
+                    DynamicImage::ImageRgb8(page.to_rgb8())
+                } else {
+                    page
+                };
+                let path = page_path(&output, index, pages.len());
+                save_image_with_format(&page, &path, format)?;
+                written.push(paths::display(&path));
+                let _ = app.emit(
+                    "contact-sheet-progress",
+                    ContactSheetProgress {
+                        page: index + 1,
+                        pages: pages.len(),
+                    },
+                );
+            }
+        }
+        Ok(written)
+    })
+    .await
+}
@@ -0,0 +1,318 @@
+// 瓦片金字塔导出：把大图切成 DZI（Deep Zoom）或 IIIF（level 0 静态瓦片）目录结构，
+// 供 OpenSeadragon 等查看器直接读取
+//
+//   DZI:  <name>.dzi 和 <name>_files/<level>/<col>_<row>.<ext>
+//   IIIF: <name>/info.json 和 <name>/<x>,<y>,<w>,<h>/<w'>,<h'>/0/default.<ext>
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::storage::{write_app_file, write_bytes};
+use crate::tiles::{encode_tile, level_size, max_level, render_region, Region, TileFormat};
+use crate::{open_image, paths, run_blocking, scope};
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PyramidLayout {
+    #[default]
+    Dzi,
+    Iiif,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeepZoomOptions {
+    pub layout: PyramidLayout,
+    pub tile_size: u32,
+    // 相邻瓦片重叠的像素（仅 DZI）
+    pub overlap: u32,
+    // jpg 或 png
+    pub format: String,
+    // 输出文件名（不含扩展名），为空时使用原图文件名
+    pub name: Option<String>,
+    // IIIF info.json 中的 id，为空时使用 name
+    pub base_url: Option<String>,
+}
+
+impl Default for DeepZoomOptions {
+    fn default() -> Self {
+        Self {
+            layout: PyramidLayout::Dzi,
+            tile_size: 254,
+            overlap: 1,
+            format: "jpg".to_string(),
+            name: None,
+            base_url: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepZoomResult {
+    // .dzi 文件或 info.json 的路径
+    pub descriptor: String,
+    pub levels: u32,
+    pub tiles: u32,
+}
+
+// 每生成完一级发送一次 deep-zoom-progress 事件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepZoomProgress {
+    pub path: String,
+    pub done: u32,
+    pub total: u32,
+}
+
+impl DeepZoomOptions {
+    fn validate(&self) -> Result<TileFormat, AppError> {
+        if !(16..=4096).contains(&self.tile_size) {
+            return Err(AppError::InvalidArgument(format!(
+                "Tile size must be between 16 and 4096: {}",
+                self.tile_size
+            )));
+        }
+        if self.overlap * 2 >= self.tile_size {
+            return Err(AppError::InvalidArgument("Tile overlap is too large".to_string()));
+        }
+        match self.format.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Ok(TileFormat::Jpeg),
+            "png" => Ok(TileFormat::Png),
+            _ => Err(AppError::Unsupported(format!("Tile format {}", self.format))),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        if self.format.eq_ignore_ascii_case("png") {
+            "png"
+        } else {
+            "jpg"
+        }
+    }
+}
+
+// 从原图开始逐级缩小一半，依次对每一级调用 visit（最高级在前）；visit 返回 false 时停止
+fn for_each_level<F>(img: &DynamicImage, mut visit: F) -> Result<(), AppError>
+where
+    F: FnMut(u32, &DynamicImage) -> Result<bool, AppError>,
+{
+    let (width, height) = img.dimensions();
+    let top = max_level(width, height);
+    if !visit(top, img)? {
+        return Ok(());
+    }
+    let mut current: Option<DynamicImage> = None;
+    for level in (0..top).rev() {
+        let (level_width, level_height) = level_size(width, height, level);
+        let next = current
+            .as_ref()
+            .unwrap_or(img)
+            .resize_exact(level_width, level_height, FilterType::Triangle);
+        if !visit(level, &next)? {
+            break;
+        }
+        current = Some(next);
+    }
+    Ok(())
+}
+
+// 瓦片是新生成的导出文件，重新导出时直接覆盖，不为每个瓦片备份
+fn write_tile(path: &Path, img: &DynamicImage, format: TileFormat) -> Result<(), AppError> {
+    write_app_file(path, &encode_tile(img, format)?)
+}
+
+fn export_dzi(
+    app: &AppHandle,
+    img: &DynamicImage,
+    source: &str,
+    output_dir: &Path,
+    name: &str,
+    options: &DeepZoomOptions,
+    format: TileFormat,
+) -> Result<DeepZoomResult, AppError> {
+    let (width, height) = img.dimensions();
+    let tile_size = options.tile_size;
+    let overlap = options.overlap;
+    let files_dir = output_dir.join(format!("{}_files", name));
+    let total = max_level(width, height) + 1;
+    let mut tiles = 0;
+
+    for_each_level(img, |level, level_img| {
+        let (level_width, level_height) = level_img.dimensions();
+        let level_dir = files_dir.join(level.to_string());
+        fs::create_dir_all(&level_dir).map_err(|e| AppError::from(e).at(&level_dir))?;
+
+        for row in 0..level_height.div_ceil(tile_size) {
+            for col in 0..level_width.div_ceil(tile_size) {
+                // 除第一行/列外向左上扩展 overlap，除最后一行/列外向右下扩展 overlap
+                let x = (col * tile_size).saturating_sub(if col > 0 { overlap } else { 0 });
+                let y = (row * tile_size).saturating_sub(if row > 0 { overlap } else { 0 });
+                let right = ((col + 1) * tile_size + overlap).min(level_width);
+                let bottom = ((row + 1) * tile_size + overlap).min(level_height);
+                let region = Region {
+                    x,
+                    y,
+                    width: right - x,
+                    height: bottom - y,
+                };
+                let tile = render_region(level_img, region, region.width, region.height);
+                let file = level_dir.join(format!("{}_{}.{}", col, row, options.extension()));
+                write_tile(&file, &tile, format)?;
+                tiles += 1;
+            }
+        }
+
+        let _ = app.emit(
+            "deep-zoom-progress",
+            DeepZoomProgress {
+                path: source.to_string(),
+                done: total - level,
+                total,
+            },
+        );
+        Ok(true)
+    })?;
+
+    let descriptor = output_dir.join(format!("{}.dzi", name));
+    let xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" Format=\"{}\" Overlap=\"{}\" TileSize=\"{}\">\n  \
+         <Size Width=\"{}\" Height=\"{}\"/>\n\
+         </Image>\n",
+        options.extension(),
+        overlap,
+        tile_size,
+        width,
+        height
+    );
+    write_bytes(&descriptor, xml.as_bytes())?;
+
+    Ok(DeepZoomResult {
+        descriptor: paths::display(&descriptor),
+        levels: total,
+        tiles,
+    })
+}
+
+// IIIF Image API 3.0 level 0：按 scaleFactors 预先生成所有瓦片，查看器只按 info.json 中声明的瓦片请求
+fn export_iiif(
+    app: &AppHandle,
+    img: &DynamicImage,
+    source: &str,
+    output_dir: &Path,
+    name: &str,
+    options: &DeepZoomOptions,
+    format: TileFormat,
+) -> Result<DeepZoomResult, AppError> {
+    let (width, height) = img.dimensions();
+    let tile_size = options.tile_size;
+    let root = output_dir.join(name);
+    let top = max_level(width, height);
+    // 缩小到一个瓦片能放下整张图片为止
+    let last_level = (0..=top)
+        .rev()
+        .find(|level| {
+            let (w, h) = level_size(width, height, *level);
+            w <= tile_size && h <= tile_size
+        })
+        .unwrap_or(top);
+    let total = top - last_level + 1;
+    let mut scale_factors = Vec::new();
+    let mut tiles = 0;
+
+    for_each_level(img, |level, level_img| {
+        let scale = 1u32 << (top - level);
+        scale_factors.push(scale);
+        let (level_width, level_height) = level_img.dimensions();
+        // 瓦片在原图中覆盖 tile_size * scale 像素
+        let step = tile_size * scale;
+
+        for row in 0..height.div_ceil(step) {
+            for col in 0..width.div_ceil(step) {
+                let (x, y) = (col * step, row * step);
+                let (w, h) = (step.min(width - x), step.min(height - y));
+                let region = Region {
+                    x: x / scale,
+                    y: y / scale,
+                    width: w.div_ceil(scale).min(level_width - x / scale),
+                    height: h.div_ceil(scale).min(level_height - y / scale),
+                };
+                let tile = render_region(level_img, region, region.width, region.height);
+                let dir = root
+                    .join(format!("{},{},{},{}", x, y, w, h))
+                    .join(format!("{},{}", region.width, region.height))
+                    .join("0");
+                fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+                write_tile(&dir.join(format!("default.{}", options.extension())), &tile, format)?;
+                tiles += 1;
+            }
+        }
+
+        let _ = app.emit(
+            "deep-zoom-progress",
+            DeepZoomProgress {
+                path: source.to_string(),
+                done: top - level + 1,
+                total,
+            },
+        );
+        Ok(level > last_level)
+    })?;
+
+    let info = json!({
+        "@context": "http://iiif.io/api/image/3/context.json",
+        "id": options.base_url.clone().unwrap_or_else(|| name.to_string()),
+        "type": "ImageService3",
+        "protocol": "http://iiif.io/api/image",
+        "profile": "level0",
+        "width": width,
+        "height": height,
+        "tiles": [{ "width": tile_size, "height": tile_size, "scaleFactors": scale_factors }],
+        "preferredFormats": [options.extension()],
+    });
+    let descriptor = root.join("info.json");
+    write_bytes(&descriptor, &serde_json::to_vec_pretty(&info)?)?;
+
+    Ok(DeepZoomResult {
+        descriptor: paths::display(&descriptor),
+        levels: total,
+        tiles,
+    })
+}
+
+// 把图片导出为瓦片金字塔，output_dir 不存在时自动创建
+#[tauri::command]
+pub async fn export_deep_zoom(
+    app: AppHandle,
+    path: String,
+    output_dir: String,
+    options: Option<DeepZoomOptions>,
+) -> Result<DeepZoomResult, AppError> {
+    scope::check(&path)?;
+    scope::check(&output_dir)?;
+    let options = options.unwrap_or_default();
+    let format = options.validate()?;
+
+    run_blocking("export_deep_zoom", move || {
+        let output_dir = PathBuf::from(&output_dir);
+        fs::create_dir_all(&output_dir).map_err(|e| AppError::from(e).at(&output_dir))?;
+        let name = match &options.name {
+            Some(name) => paths::file_name(Path::new(name)),
+            None => paths::file_stem(Path::new(&path), "image"),
+        };
+
+        let img = open_image(&path)?;
+        match options.layout {
+            PyramidLayout::Dzi => export_dzi(&app, &img, &path, &output_dir, &name, &options, format),
+            PyramidLayout::Iiif => export_iiif(&app, &img, &path, &output_dir, &name, &options, format),
+        }
+    })
+    .await
+}
@@ -0,0 +1,328 @@
+// 磁盘占用：列出各磁盘的容量，并统计文件夹树中每个文件夹的大小和图片数量，找出图片最多的文件夹
+// 每个文件夹只缓存自身直接包含的文件，按文件夹修改时间判断是否需要重新读取，再次扫描时只读取有变化的文件夹
+// 修改时间只在增删、重命名文件时改变，原地改写文件不会触发重新读取
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sysinfo::{DiskKind, Disks};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::{paths, run_blocking, scope};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+// 每扫描这么多文件夹发送一次 folder-scan-progress 事件
+const PROGRESS_INTERVAL: u64 = 50;
+// 结果中最多返回的文件夹数（按图片大小排序）
+const MAX_FOLDERS: usize = 200;
+// 最多缓存的文件夹数，超出时丢弃本次扫描范围以外的文件夹
+const MAX_CACHED_FOLDERS: usize = 100_000;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskInfo {
+    pub drive: String,
+    // ssd、hdd、removable 或 unknown
+    pub drive_type: String,
+    pub total_space: u64,
+    pub free_space: u64,
+    pub used_space: u64,
+    // 该磁盘上扫描过的文件夹中的图片总大小，未扫描过时为空
+    pub image_size: Option<u64>,
+}
+
+// 每个扫描根目录最近一次的扫描结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSizeInfo {
+    pub drive: String,
+    pub file_size: u64,
+    pub image_size: u64,
+    pub last_modified: u64,
+    pub last_scanned: u64,
+}
+
+// 单个文件夹直接包含的内容（不含子文件夹）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedFolderInfo {
+    pub size: u64,
+    pub image_count: u64,
+    pub image_size: u64,
+    pub subfolders: Vec<PathBuf>,
+    pub last_modified: u64,
+    pub last_scanned: u64,
+}
+
+// 包含所有子文件夹的统计
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSize {
+    pub path: String,
+    pub size: u64,
+    pub image_count: u64,
+    pub image_size: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanResult {
+    pub total: FolderSize,
+    // 图片大小最大的文件夹，从大到小
+    pub folders: Vec<FolderSize>,
+    pub scanned: u64,
+    // 需要重新读取的文件夹数，其余使用缓存
+    pub changed: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanProgress {
+    pub root: String,
+    pub current: String,
+    pub scanned: u64,
+}
+
+lazy_static::lazy_static! {
+    static ref DISK_SIZE_CACHE: RwLock<HashMap<String, DiskSizeInfo>> = RwLock::new(HashMap::new());
+    static ref FOLDER_CACHE: RwLock<HashMap<PathBuf, CachedFolderInfo>> = RwLock::new(HashMap::new());
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 读取文件夹自身的内容；符号链接和无法读取的条目跳过
+fn read_folder(dir: &Path, last_modified: u64) -> Result<CachedFolderInfo, AppError> {
+    let mut info = CachedFolderInfo {
+        size: 0,
+        image_count: 0,
+        image_size: 0,
+        subfolders: Vec::new(),
+        last_modified,
+        last_scanned: now(),
+    };
+    for entry in fs::read_dir(dir).map_err(|e| AppError::from(e).at(dir))?.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            info.subfolders.push(path);
+        } else if file_type.is_file() {
+            let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+            info.size += size;
+            if is_image(&path) {
+                info.image_count += 1;
+                info.image_size += size;
+            }
+        }
+    }
+    Ok(info)
+}
+
+struct Scan<'a> {
+    app: &'a AppHandle,
+    root: String,
+    scanned: AtomicU64,
+    changed: AtomicU64,
+    folders: Mutex<Vec<FolderSize>>,
+}
+
+impl Scan<'_> {
+    // 递归统计，子文件夹并行扫描；无法读取的子文件夹计为空
+    fn folder(&self, dir: &Path) -> Result<FolderSize, AppError> {
+        let metadata = fs::metadata(dir).map_err(|e| AppError::from(e).at(dir))?;
+        let last_modified = modified_secs(&metadata);
+        let cached = FOLDER_CACHE
+            .read()
+            .get(dir)
+            .filter(|info| info.last_modified == last_modified)
+            .cloned();
+        let info = match cached {
+            Some(info) => info,
+            None => {
+                let info = read_folder(dir, last_modified)?;
+                FOLDER_CACHE.write().insert(dir.to_path_buf(), info.clone());
+                self.changed.fetch_add(1, Ordering::Relaxed);
+                info
+            }
+        };
+
+        let scanned = self.scanned.fetch_add(1, Ordering::Relaxed) + 1;
+        if scanned % PROGRESS_INTERVAL == 0 {
+            let _ = self.app.emit(
+                "folder-scan-progress",
+                FolderScanProgress {
+                    root: self.root.clone(),
+                    current: paths::display(dir),
+                    scanned,
+                },
+            );
+        }
+
+        let mut total = info
+            .subfolders
+            .par_iter()
+            .filter_map(|subfolder| self.folder(subfolder).ok())
+            .reduce(FolderSize::default, |a, b| FolderSize {
+                path: String::new(),
+                size: a.size + b.size,
+                image_count: a.image_count + b.image_count,
+                image_size: a.image_size + b.image_size,
+            });
+        total.path = paths::display(dir);
+        total.size += info.size;
+        total.image_count += info.image_count;
+        total.image_size += info.image_size;
+        if total.image_count > 0 {
+            self.folders.lock().push(total.clone());
+        }
+        Ok(total)
+    }
+}
+
+// 扫描过的文件夹所在的磁盘：挂载点是文件夹路径前缀中最长的那个（例如 /home 单独挂载时不算作 /）
+fn mount_point_of<'a>(root: &Path, mount_points: &[&'a Path]) -> Option<&'a Path> {
+    mount_points
+        .iter()
+        .filter(|mount_point| root.starts_with(mount_point))
+        .max_by_key(|mount_point| mount_point.components().count())
+        .copied()
+}
+
+// 按挂载点汇总扫描结果；嵌套的扫描根目录只计算最外层的，避免重复计算
+fn image_sizes(mount_points: &[&Path]) -> HashMap<PathBuf, u64> {
+    let scanned = DISK_SIZE_CACHE.read();
+    let roots: Vec<(PathBuf, u64)> = scanned
+        .values()
+        .map(|info| (PathBuf::from(&info.drive), info.image_size))
+        .collect();
+    let mut sizes = HashMap::new();
+    for (root, image_size) in &roots {
+        if roots.iter().any(|(other, _)| other != root && root.starts_with(other)) {
+            continue;
+        }
+        if let Some(mount_point) = mount_point_of(root, mount_points) {
+            *sizes.entry(mount_point.to_path_buf()).or_insert(0) += image_size;
+        }
+    }
+    sizes
+}
+
+// 缓存的文件夹数超过上限时，先丢弃本次扫描范围以外的文件夹，仍然超出时全部清空
+fn trim_folder_cache(root: &Path) {
+    let mut cache = FOLDER_CACHE.write();
+    if cache.len() <= MAX_CACHED_FOLDERS {
+        return;
+    }
+    cache.retain(|dir, _| dir.starts_with(root));
+    if cache.len() > MAX_CACHED_FOLDERS {
+        cache.clear();
+    }
+}
+
+// 列出所有磁盘（挂载点）的容量，并附上该磁盘上扫描过的文件夹的图片总大小
+#[tauri::command]
+pub async fn list_drives() -> Result<Vec<DiskInfo>, AppError> {
+    run_blocking("list_drives", || {
+        let disks = Disks::new_with_refreshed_list();
+        let mount_points: Vec<&Path> = disks.list().iter().map(|disk| disk.mount_point()).collect();
+        let scanned = image_sizes(&mount_points);
+        Ok(disks
+            .list()
+            .iter()
+            .map(|disk| {
+                let drive = paths::display(disk.mount_point());
+                let drive_type = if disk.is_removable() {
+                    "removable"
+                } else {
+                    match disk.kind() {
+                        DiskKind::SSD => "ssd",
+                        DiskKind::HDD => "hdd",
+                        DiskKind::Unknown(_) => "unknown",
+                    }
+                };
+                DiskInfo {
+                    image_size: scanned.get(disk.mount_point()).copied(),
+                    drive,
+                    drive_type: drive_type.to_string(),
+                    total_space: disk.total_space(),
+                    free_space: disk.available_space(),
+                    used_space: disk.total_space().saturating_sub(disk.available_space()),
+                }
+            })
+            .collect())
+    })
+    .await
+}
+
+// 统计文件夹树的大小和图片数量，扫描过程中发送 folder-scan-progress 事件
+#[tauri::command]
+pub async fn scan_folder_sizes(app: AppHandle, path: String) -> Result<FolderScanResult, AppError> {
+    let root = scope::check(&path)?;
+    if !root.is_dir() {
+        return Err(AppError::NotFound("Folder".to_string()).at(&root));
+    }
+
+    // 耗时与文件夹数成正比，不使用单个操作的超时
+    tauri::async_runtime::spawn_blocking(move || {
+        let scan = Scan {
+            app: &app,
+            root: paths::display(&root),
+            scanned: AtomicU64::new(0),
+            changed: AtomicU64::new(0),
+            folders: Mutex::new(Vec::new()),
+        };
+        let total = scan.folder(&root)?;
+        trim_folder_cache(&root);
+
+        let last_modified = fs::metadata(&root).map(|metadata| modified_secs(&metadata)).unwrap_or(0);
+        DISK_SIZE_CACHE.write().insert(
+            scan.root.clone(),
+            DiskSizeInfo {
+                drive: scan.root.clone(),
+                file_size: total.size,
+                image_size: total.image_size,
+                last_modified,
+                last_scanned: now(),
+            },
+        );
+
+        let mut folders = scan.folders.into_inner();
+        folders.sort_by(|a, b| b.image_size.cmp(&a.image_size));
+        folders.truncate(MAX_FOLDERS);
+        Ok(FolderScanResult {
+            total,
+            folders,
+            scanned: scan.scanned.into_inner(),
+            changed: scan.changed.into_inner(),
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Folder scan task failed: {}", e)))?
+}
@@ -0,0 +1,325 @@
+// 文档模式：把手机拍摄的文档照片处理成类似扫描件的效果
+//   1. 背景变白：估计不均匀的光照背景并除去，阴影和纸张底色变为白色
+//   2. 自动纠斜：按投影轮廓找出文字行的倾斜角度并旋转校正
+//   3. 自适应二值化（Sauvola）：按局部均值和方差选择阈值，光照不均时文字也完整
+//   4. 去噪点：去掉面积很小的黑色斑点
+//   5. 裁边：去掉内容四周多余的空白，保留指定的边距
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, Luma};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::storage::save_image;
+use crate::{open_image, paths, run_blocking, scope};
+
+// 纠斜的搜索范围和步长，度
+const MAX_SKEW: f32 = 15.0;
+const COARSE_STEP: f32 = 0.5;
+const FINE_STEP: f32 = 0.1;
+// 估计倾斜角度时使用的缩小尺寸
+const ANALYSIS_SIZE: u32 = 1000;
+// 低于该亮度的像素视为内容（纠斜和裁边时）
+const INK_LEVEL: u8 = 160;
+// Sauvola 的参数：标准差的动态范围和灵敏度
+const SAUVOLA_RANGE: f64 = 128.0;
+const SAUVOLA_K: f64 = 0.2;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DocumentOptions {
+    pub whiten: bool,
+    pub deskew: bool,
+    // 为 false 时输出灰度图
+    pub binarize: bool,
+    // Sauvola 的窗口大小，像素；为 0 时按图片尺寸自动选择
+    pub window: u32,
+    // 面积不超过该值（像素）的黑色斑点被去掉，0 表示不去噪点（仅在二值化时使用）
+    pub despeckle: u32,
+    pub trim: bool,
+    // 裁边后保留的边距，像素
+    pub margin: u32,
+    // 为空时在原图旁边生成 name_scan.png
+    pub output: Option<String>,
+}
+
+impl Default for DocumentOptions {
+    fn default() -> Self {
+        Self {
+            whiten: true,
+            deskew: true,
+            binarize: true,
+            window: 0,
+            despeckle: 4,
+            trim: true,
+            margin: 20,
+            output: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentResult {
+    pub output: String,
+    // 检测到的倾斜角度（度，顺时针为正），未纠斜时为 0
+    pub skew: f32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// 用缩小后做最大值滤波（去掉深色的文字）再模糊的结果作为背景，原图除以背景
+fn whiten(gray: &GrayImage) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let small_width = (width / 8).max(1);
+    let small_height = (height / 8).max(1);
+    let small = imageops::resize(gray, small_width, small_height, FilterType::Triangle);
+
+    // 半径约为短边的 2%，足以盖住一般大小的文字
+    let radius = (small_width.min(small_height) / 50).max(2) as i32;
+    let dilate = |img: &GrayImage, dx: i32, dy: i32| {
+        GrayImage::from_fn(img.width(), img.height(), |x, y| {
+            let max = (-radius..=radius)
+                .filter_map(|i| {
+                    let sx = x as i32 + dx * i;
+                    let sy = y as i32 + dy * i;
+                    (sx >= 0 && sy >= 0 && (sx as u32) < img.width() && (sy as u32) < img.height())
+                        .then(|| img.get_pixel(sx as u32, sy as u32)[0])
+                })
+                .max()
+                .unwrap_or(255);
+            Luma([max])
+        })
+    };
+    let background = dilate(&dilate(&small, 1, 0), 0, 1);
+    let background = imageops::blur(&background, 2.0);
+    let background = imageops::resize(&background, width, height, FilterType::Triangle);
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let value = gray.get_pixel(x, y)[0] as u32;
+        let base = (background.get_pixel(x, y)[0] as u32).max(1);
+        Luma([(value * 255 / base).min(255) as u8])
+    })
+}
+
+// 投影轮廓法：文字行与投影方向一致时，各行的深色像素数差别最大（平方和最大）
+fn detect_skew(gray: &GrayImage) -> f32 {
+    let small = if gray.width().max(gray.height()) > ANALYSIS_SIZE {
+        DynamicImage::ImageLuma8(gray.clone()).thumbnail(ANALYSIS_SIZE, ANALYSIS_SIZE).to_luma8()
+    } else {
+        gray.clone()
+    };
+    let ink: Vec<(f32, f32)> = small
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel[0] < INK_LEVEL)
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect();
+    if ink.is_empty() {
+        return 0.0;
+    }
+
+    let diagonal = (small.width() as f32).hypot(small.height() as f32).ceil() as usize;
+    let score = |angle: f32| {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut rows = vec![0u64; diagonal * 2 + 1];
+        for (x, y) in &ink {
+            let row = (y * cos - x * sin).round() as i64 + diagonal as i64;
+            rows[row.clamp(0, rows.len() as i64 - 1) as usize] += 1;
+        }
+        rows.iter().map(|n| n * n).sum::<u64>()
+    };
+    let best = |from: f32, to: f32, step: f32| {
+        let steps = ((to - from) / step).round() as i32;
+        (0..=steps)
+            .map(|i| from + i as f32 * step)
+            .max_by_key(|angle| score(*angle))
+            .unwrap_or(0.0)
+    };
+    let coarse = best(-MAX_SKEW, MAX_SKEW, COARSE_STEP);
+    best(coarse - COARSE_STEP, coarse + COARSE_STEP, FINE_STEP)
+}
+
+// 绕中心旋转（双线性插值），使倾斜 angle 度的行变为水平；尺寸不变，空出的部分为白色
+fn rotate(gray: &GrayImage, angle: f32) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let sample = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x as u32 >= width || y as u32 >= height {
+            255.0
+        } else {
+            gray.get_pixel(x as u32, y as u32)[0] as f32
+        }
+    };
+    GrayImage::from_fn(width, height, |x, y| {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        let sx = dx * cos - dy * sin + cx - 0.5;
+        let sy = dx * sin + dy * cos + cy - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (tx, ty) = (sx - x0, sy - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = sample(x0, y0) * (1.0 - tx) + sample(x0 + 1, y0) * tx;
+        let bottom = sample(x0, y0 + 1) * (1.0 - tx) + sample(x0 + 1, y0 + 1) * tx;
+        Luma([(top * (1.0 - ty) + bottom * ty).round() as u8])
+    })
+}
+
+// Sauvola 自适应二值化，用积分图计算窗口内的均值和方差
+fn binarize(gray: &GrayImage, window: u32) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let stride = width as usize + 1;
+    let mut sum = vec![0f64; stride * (height as usize + 1)];
+    let mut squares = vec![0f64; sum.len()];
+    for y in 0..height as usize {
+        let (mut row_sum, mut row_squares) = (0.0, 0.0);
+        for x in 0..width as usize {
+            let value = gray.get_pixel(x as u32, y as u32)[0] as f64;
+            row_sum += value;
+            row_squares += value * value;
+            sum[(y + 1) * stride + x + 1] = sum[y * stride + x + 1] + row_sum;
+            squares[(y + 1) * stride + x + 1] = squares[y * stride + x + 1] + row_squares;
+        }
+    }
+
+    let half = (window / 2) as i64;
+    GrayImage::from_fn(width, height, |x, y| {
+        let x0 = (x as i64 - half).max(0) as usize;
+        let y0 = (y as i64 - half).max(0) as usize;
+        let x1 = (x as i64 + half + 1).min(width as i64) as usize;
+        let y1 = (y as i64 + half + 1).min(height as i64) as usize;
+        let area = ((x1 - x0) * (y1 - y0)) as f64;
+        let total = |table: &[f64]| {
+            table[y1 * stride + x1] - table[y0 * stride + x1] - table[y1 * stride + x0] + table[y0 * stride + x0]
+        };
+        let mean = total(&sum) / area;
+        let deviation = (total(&squares) / area - mean * mean).max(0.0).sqrt();
+        let threshold = mean * (1.0 + SAUVOLA_K * (deviation / SAUVOLA_RANGE - 1.0));
+        Luma([if (gray.get_pixel(x, y)[0] as f64) <= threshold { 0 } else { 255 }])
+    })
+}
+
+// 去掉面积不超过 max_area 的黑色连通区域（8 邻域）
+fn despeckle(binary: &mut GrayImage, max_area: u32) {
+    let (width, height) = binary.dimensions();
+    let mut visited = vec![false; (width * height) as usize];
+    let mut queue = VecDeque::new();
+    let mut component = Vec::new();
+    for start in 0..(width * height) {
+        if visited[start as usize] || binary.get_pixel(start % width, start / width)[0] != 0 {
+            continue;
+        }
+        visited[start as usize] = true;
+        queue.push_back(start);
+        component.clear();
+        while let Some(index) = queue.pop_front() {
+            component.push(index);
+            let (x, y) = ((index % width) as i64, (index / width) as i64);
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                    continue;
+                }
+                let neighbor = (ny as u32) * width + nx as u32;
+                if !visited[neighbor as usize] && binary.get_pixel(nx as u32, ny as u32)[0] == 0 {
+                    visited[neighbor as usize] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        if component.len() as u32 <= max_area {
+            for index in &component {
+                binary.put_pixel(index % width, index / width, Luma([255]));
+            }
+        }
+    }
+}
+
+// 内容的外接矩形加上边距，没有内容时返回 None
+fn content_bounds(gray: &GrayImage, margin: u32) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = gray.dimensions();
+    let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+    for (x, y, pixel) in gray.enumerate_pixels() {
+        if pixel[0] < INK_LEVEL {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+    }
+    if left > right {
+        return None;
+    }
+    let x = left.saturating_sub(margin);
+    let y = top.saturating_sub(margin);
+    let x1 = (right + 1 + margin).min(width);
+    let y1 = (bottom + 1 + margin).min(height);
+    Some((x, y, x1 - x, y1 - y))
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => path.with_file_name(format!("{}_scan.png", paths::file_stem(path, "document"))),
+    }
+}
+
+// 把文档照片处理成扫描件效果
+#[tauri::command]
+pub async fn clean_document(path: String, options: Option<DocumentOptions>) -> Result<DocumentResult, AppError> {
+    scope::check(&path)?;
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    if options.window > 1000 {
+        return Err(AppError::InvalidArgument(format!(
+            "Window must be at most 1000 pixels: {}",
+            options.window
+        )));
+    }
+
+    run_blocking("clean_document", move || {
+        let path = Path::new(&path);
+        let mut gray = open_image(path)?.to_luma8();
+
+        if options.whiten {
+            gray = whiten(&gray);
+        }
+        let mut skew = 0.0;
+        if options.deskew {
+            skew = detect_skew(&gray);
+            if skew.abs() >= FINE_STEP {
+                gray = rotate(&gray, skew);
+            }
+        }
+        if options.binarize {
+            // 默认窗口约为短边的 1/30，能覆盖一般文字的笔画和间距
+            let window = match options.window {
+                0 => (gray.width().min(gray.height()) / 30).clamp(15, 101) | 1,
+                window => window,
+            };
+            gray = binarize(&gray, window);
+            if options.despeckle > 0 {
+                despeckle(&mut gray, options.despeckle);
+            }
+        }
+        if options.trim {
+            if let Some((x, y, width, height)) = content_bounds(&gray, options.margin) {
+                gray = imageops::crop_imm(&gray, x, y, width, height).to_image();
+            }
+        }
+
+        let output = output_path(path, options.output);
+        let (width, height) = gray.dimensions();
+        save_image(&DynamicImage::ImageLuma8(gray), &output)?;
+        Ok(DocumentResult {
+            output: paths::display(&output),
+            skew,
+            width,
+            height,
+        })
+    })
+    .await
+}
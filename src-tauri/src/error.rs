@@ -0,0 +1,168 @@
+// 统一的错误类型：序列化为 { code, message, context, localizedMessage }，前端可以根据 code 分支处理
+// localizedMessage 是按界面语言给用户看的说明，message 和 context 面向开发者
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use image::ImageError;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::i18n;
+
+#[derive(Debug, Clone)]
+pub enum AppError {
+    NotFound(String),
+    Unsupported(String),
+    DecodeFailed(String),
+    EncodeFailed(String),
+    Io(String),
+    PermissionDenied(String),
+    TooLarge(String),
+    InvalidArgument(String),
+    // 远程服务（S3、WebDAV、SFTP 等）出错
+    Network(String),
+    // 摄像头、截图等设备出错
+    Device(String),
+    // 模型推理、插件、脚本等处理过程出错
+    ProcessingFailed(String),
+    Timeout(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Unsupported(_) => "UNSUPPORTED",
+            AppError::DecodeFailed(_) => "DECODE_FAILED",
+            AppError::EncodeFailed(_) => "ENCODE_FAILED",
+            AppError::Io(_) => "IO",
+            AppError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AppError::TooLarge(_) => "TOO_LARGE",
+            AppError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            AppError::Network(_) => "NETWORK",
+            AppError::Device(_) => "DEVICE",
+            AppError::ProcessingFailed(_) => "PROCESSING_FAILED",
+            AppError::Timeout(_) => "TIMEOUT",
+            AppError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "Not found",
+            AppError::Unsupported(_) => "Unsupported",
+            AppError::DecodeFailed(_) => "Failed to decode image",
+            AppError::EncodeFailed(_) => "Failed to encode image",
+            AppError::Io(_) => "File system error",
+            AppError::PermissionDenied(_) => "Permission denied",
+            AppError::TooLarge(_) => "Too large",
+            AppError::InvalidArgument(_) => "Invalid argument",
+            AppError::Network(_) => "Remote service error",
+            AppError::Device(_) => "Device error",
+            AppError::ProcessingFailed(_) => "Processing failed",
+            AppError::Timeout(_) => "Operation timed out",
+            AppError::Internal(_) => "Internal error",
+        }
+    }
+
+    pub fn context(&self) -> &str {
+        match self {
+            AppError::NotFound(c)
+            | AppError::Unsupported(c)
+            | AppError::DecodeFailed(c)
+            | AppError::EncodeFailed(c)
+            | AppError::Io(c)
+            | AppError::PermissionDenied(c)
+            | AppError::TooLarge(c)
+            | AppError::InvalidArgument(c)
+            | AppError::Network(c)
+            | AppError::Device(c)
+            | AppError::ProcessingFailed(c)
+            | AppError::Timeout(c)
+            | AppError::Internal(c) => c,
+        }
+    }
+
+    fn context_mut(&mut self) -> &mut String {
+        match self {
+            AppError::NotFound(c)
+            | AppError::Unsupported(c)
+            | AppError::DecodeFailed(c)
+            | AppError::EncodeFailed(c)
+            | AppError::Io(c)
+            | AppError::PermissionDenied(c)
+            | AppError::TooLarge(c)
+            | AppError::InvalidArgument(c)
+            | AppError::Network(c)
+            | AppError::Device(c)
+            | AppError::ProcessingFailed(c)
+            | AppError::Timeout(c)
+            | AppError::Internal(c) => c,
+        }
+    }
+
+    // 在上下文前加上出错的文件路径
+    pub fn at(mut self, path: impl AsRef<Path>) -> Self {
+        let context = self.context_mut();
+        *context = if context.is_empty() {
+            path.as_ref().display().to_string()
+        } else {
+            format!("{}: {}", path.as_ref().display(), context)
+        };
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.context().is_empty() {
+            write!(f, "{}", self.message())
+        } else {
+            write!(f, "{}: {}", self.message(), self.context())
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("context", self.context())?;
+        state.serialize_field("localizedMessage", i18n::localize(self))?;
+        state.end()
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => AppError::NotFound(e.to_string()),
+            io::ErrorKind::PermissionDenied => AppError::PermissionDenied(e.to_string()),
+            _ => AppError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<ImageError> for AppError {
+    fn from(e: ImageError) -> Self {
+        match e {
+            ImageError::Decoding(_) => AppError::DecodeFailed(e.to_string()),
+            ImageError::Encoding(_) => AppError::EncodeFailed(e.to_string()),
+            ImageError::Unsupported(_) => AppError::Unsupported(e.to_string()),
+            ImageError::Limits(_) => AppError::TooLarge(e.to_string()),
+            ImageError::Parameter(_) => AppError::InvalidArgument(e.to_string()),
+            ImageError::IoError(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::InvalidArgument(e.to_string())
+    }
+}
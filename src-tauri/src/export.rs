@@ -0,0 +1,313 @@
+// 导出目标：除本地磁盘外，支持直接写入 WebDAV 与 SFTP 服务器
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use ssh2::{CheckResult, KnownHostFileKind, KnownHosts, Session};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::error::AppError;
+use crate::open_image;
+use crate::prepare_for_format;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::{encode_from, output_format, write_app_file, write_bytes, EncodeOptions};
+
+// SFTP 服务器的主机密钥记录
+struct KnownHostFiles {
+    // 应用首次连接服务器时记录的密钥
+    app: PathBuf,
+    // 用户自己的 ~/.ssh/known_hosts，只读取不修改
+    user: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    // init 之前为 None；检查和记录密钥期间持有锁，同时连接新服务器时不会互相覆盖
+    static ref KNOWN_HOSTS: Mutex<Option<KnownHostFiles>> = Mutex::new(None);
+}
+
+// 导出目标需要实现的接口：把编码后的数据写到目标位置
+pub trait ExportTarget {
+    fn write(&self, remote_path: &str, data: &[u8]) -> Result<(), AppError>;
+}
+
+// SFTP 认证方式
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SftpAuth {
+    Password { password: String },
+    #[serde(rename_all = "camelCase")]
+    KeyFile { private_key: String, passphrase: Option<String> },
+}
+
+// 前端传入的导出目标配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ExportTargetConfig {
+    Local,
+    #[serde(rename_all = "camelCase")]
+    WebDav {
+        base_url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Sftp {
+        host: String,
+        port: Option<u16>,
+        username: String,
+        auth: SftpAuth,
+    },
+}
+
+pub struct LocalTarget;
+
+impl ExportTarget for LocalTarget {
+    fn write(&self, remote_path: &str, data: &[u8]) -> Result<(), AppError> {
+        write_bytes(Path::new(remote_path), data)
+    }
+}
+
+pub struct WebDavTarget {
+    base_url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl ExportTarget for WebDavTarget {
+    fn write(&self, remote_path: &str, data: &[u8]) -> Result<(), AppError> {
+        let url = format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            remote_path.trim_start_matches('/')
+        );
+
+        let client = reqwest::blocking::Client::new();
+        let mut request = client.put(&url).body(data.to_vec());
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+
+        let response = request
+            .send()
+            .map_err(|e| AppError::Network(format!("Failed to upload to WebDAV: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Network(format!("WebDAV server returned {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+pub struct SftpTarget {
+    host: String,
+    port: u16,
+    username: String,
+    auth: SftpAuth,
+}
+
+impl SftpTarget {
+    fn connect(&self) -> Result<Session, AppError> {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port))
+            .map_err(|e| AppError::Network(format!("Failed to connect to {}: {}", self.host, e)))?;
+        let mut session = Session::new().map_err(|e| AppError::Network(format!("Failed to create SSH session: {}", e)))?;
+        session.set_tcp_stream(tcp);
+        session
+            .handshake()
+            .map_err(|e| AppError::Network(format!("SSH handshake failed: {}", e)))?;
+        // 认证前先确认服务器身份，避免把密码发给冒充的服务器
+        verify_host_key(&session, &self.host, self.port)?;
+
+        match &self.auth {
+            SftpAuth::Password { password } => session
+                .userauth_password(&self.username, password)
+                .map_err(|e| AppError::Network(format!("SSH authentication failed: {}", e)))?,
+            SftpAuth::KeyFile { private_key, passphrase } => session
+                .userauth_pubkey_file(
+                    &self.username,
+                    None,
+                    Path::new(private_key),
+                    passphrase.as_deref(),
+                )
+                .map_err(|e| AppError::Network(format!("SSH authentication failed: {}", e)))?,
+        }
+
+        Ok(session)
+    }
+}
+
+// 主机密钥与记录比对：一致时为 true，没有记录时为 false，不一致时返回错误
+fn check_host_key(known: &KnownHosts, host: &str, port: u16, key: &[u8]) -> Result<bool, AppError> {
+    match known.check_port(host, port, key) {
+        CheckResult::Match => Ok(true),
+        CheckResult::NotFound => Ok(false),
+        CheckResult::Mismatch => Err(AppError::PermissionDenied(format!(
+            "Host key of {} does not match the known key; refusing to connect",
+            host
+        ))),
+        CheckResult::Failure => Err(AppError::Network(format!("Failed to check the host key of {}", host))),
+    }
+}
+
+// 校验服务器的主机密钥：先查用户的 known_hosts，再查应用记录的密钥；都没有记录时信任并记录本次的密钥
+fn verify_host_key(session: &Session, host: &str, port: u16) -> Result<(), AppError> {
+    let (key, key_type) = session
+        .host_key()
+        .ok_or_else(|| AppError::Network(format!("{} did not send a host key", host)))?;
+    let files = KNOWN_HOSTS.lock();
+    let files = files
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("Known hosts are not loaded".to_string()))?;
+    let new_known_hosts = || {
+        session
+            .known_hosts()
+            .map_err(|e| AppError::Internal(format!("Failed to create known hosts: {}", e)))
+    };
+
+    // 用户的文件可能包含 libssh2 不支持的条目，读取失败时忽略
+    if let Some(user) = files.user.as_deref().filter(|file| file.is_file()) {
+        let mut known = new_known_hosts()?;
+        if known.read_file(user, KnownHostFileKind::OpenSSH).is_ok() && check_host_key(&known, host, port, key)? {
+            return Ok(());
+        }
+    }
+
+    let mut known = new_known_hosts()?;
+    if files.app.is_file() {
+        known
+            .read_file(&files.app, KnownHostFileKind::OpenSSH)
+            .map_err(|e| AppError::Io(format!("Failed to read known hosts: {}", e)).at(&files.app))?;
+    }
+    if check_host_key(&known, host, port, key)? {
+        return Ok(());
+    }
+
+    let entry = if port == 22 { host.to_string() } else { format!("[{}]:{}", host, port) };
+    known
+        .add(&entry, key, "", key_type.into())
+        .map_err(|e| AppError::Internal(format!("Failed to add host key: {}", e)))?;
+    let hosts = known
+        .hosts()
+        .map_err(|e| AppError::Internal(format!("Failed to list known hosts: {}", e)))?;
+    let mut content = String::new();
+    for known_host in &hosts {
+        let line = known
+            .write_string(known_host, KnownHostFileKind::OpenSSH)
+            .map_err(|e| AppError::Internal(format!("Failed to write known hosts: {}", e)))?;
+        content.push_str(line.trim_end());
+        content.push('\n');
+    }
+    write_app_file(&files.app, content.as_bytes())?;
+    tracing::info!(host = %entry, "recorded SFTP host key");
+    Ok(())
+}
+
+impl ExportTarget for SftpTarget {
+    fn write(&self, remote_path: &str, data: &[u8]) -> Result<(), AppError> {
+        let session = self.connect()?;
+        let sftp = session
+            .sftp()
+            .map_err(|e| AppError::Network(format!("Failed to start SFTP: {}", e)))?;
+        let mut file = sftp
+            .create(Path::new(remote_path))
+            .map_err(|e| AppError::Network(format!("Failed to create remote file: {}", e)))?;
+        file.write_all(data)
+            .map_err(|e| AppError::Network(format!("Failed to write remote file: {}", e)))?;
+        Ok(())
+    }
+}
+
+impl ExportTargetConfig {
+    pub fn build(self) -> Box<dyn ExportTarget> {
+        match self {
+            ExportTargetConfig::Local => Box::new(LocalTarget),
+            ExportTargetConfig::WebDav { base_url, username, password } => Box::new(WebDavTarget {
+                base_url,
+                username,
+                password,
+            }),
+            ExportTargetConfig::Sftp { host, port, username, auth } => Box::new(SftpTarget {
+                host,
+                port: port.unwrap_or(22),
+                username,
+                auth,
+            }),
+        }
+    }
+}
+
+// 启动时确定主机密钥记录的位置
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let file = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("known_hosts");
+    let user = app.path().home_dir().ok().map(|home| home.join(".ssh").join("known_hosts"));
+    *KNOWN_HOSTS.lock() = Some(KnownHostFiles { app: file, user });
+    Ok(())
+}
+
+// 通过系统对话框选择 SFTP 认证用的私钥，选中的文件自动授权；取消选择时为 None
+#[tauri::command]
+pub async fn pick_ssh_key(app: AppHandle) -> Option<String> {
+    let path = app
+        .dialog()
+        .file()
+        .set_title("SSH private key")
+        .blocking_pick_file()?
+        .as_path()?
+        .to_path_buf();
+    scope::allow(&path);
+    Some(path.to_string_lossy().to_string())
+}
+
+// 将图片编码为目标格式并写入导出目标；options 为质量、PNG 压缩级别等编码选项，与 save_as 相同
+#[tauri::command]
+pub async fn export_to_target(
+    path: String,
+    target: ExportTargetConfig,
+    remote_path: String,
+    options: Option<EncodeOptions>,
+) -> Result<bool, AppError> {
+    scope::check(&path)?;
+    match &target {
+        ExportTargetConfig::Local => {
+            scope::check(&remote_path)?;
+        }
+        // 私钥只能是用户通过 pick_ssh_key 选择的文件
+        ExportTargetConfig::Sftp {
+            auth: SftpAuth::KeyFile { private_key, .. },
+            ..
+        } => {
+            scope::check(private_key)?;
+        }
+        _ => {}
+    }
+    let options = options.unwrap_or_default();
+    options.validate()?;
+
+    run_blocking("export_to_target", move || {
+        let img = Arc::unwrap_or_clone(open_image(&path)?);
+
+        // 根据远程路径的扩展名确定输出格式
+        let ext = Path::new(&remote_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let format = output_format(&ext)?;
+
+        let processed_img = prepare_for_format(img, &ext);
+
+        // 与保存到本地相同：按格式转换颜色类型（JPEG 去掉透明通道），并保留原图的 EXIF
+        let data = encode_from(&processed_img, Path::new(&path), format, &options)?;
+        target.build().write(&remote_path, &data)?;
+
+        Ok(true)
+    })
+    .await
+}
@@ -0,0 +1,207 @@
+// 在外部程序中打开图片，并在外部程序修改文件后通知前端重新加载
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::AppError;
+use crate::storage::write_app_file;
+use crate::{run_blocking, scope};
+
+// 轮询文件修改时间的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+lazy_static::lazy_static! {
+    // 用户通过系统对话框选择过的外部编辑器，只有这些程序可以被启动
+    static ref EDITORS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+    // 持久化编辑器列表的文件，init 之前为 None
+    static ref EDITORS_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ExternalChange {
+    pub path: String,
+    pub modified_time: u64,
+}
+
+// 正在监视的文件及其停止标志
+#[derive(Default)]
+pub struct ExternalEditState {
+    watchers: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// 后台线程：文件修改时间变化时发送 external-file-changed 事件
+fn spawn_watcher(app: AppHandle, path: String, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let file = Path::new(&path);
+        let mut last = modified_time(file);
+
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(POLL_INTERVAL);
+
+            let current = modified_time(file);
+            if current.is_some() && current != last {
+                last = current;
+                let modified_time = current
+                    .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let _ = app.emit(
+                    "external-file-changed",
+                    ExternalChange {
+                        path: path.clone(),
+                        modified_time,
+                    },
+                );
+            }
+        }
+    });
+}
+
+fn save_editors(editors: &[PathBuf]) -> Result<(), AppError> {
+    if let Some(file) = EDITORS_FILE.read().as_ref() {
+        let editors: Vec<String> = editors.iter().map(|e| e.to_string_lossy().to_string()).collect();
+        write_app_file(file, &serde_json::to_vec_pretty(&editors)?)?;
+    }
+    Ok(())
+}
+
+// 启动时加载已选择的外部编辑器
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let file = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("editors.json");
+    let saved: Vec<PathBuf> = fs::read_to_string(&file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    *EDITORS.write() = saved;
+    *EDITORS_FILE.write() = Some(file);
+    Ok(())
+}
+
+// 已选择的编辑器中与 editor 相同的那个
+fn trusted_editor(editor: &str) -> Result<PathBuf, AppError> {
+    let resolved = fs::canonicalize(editor).map_err(|e| AppError::from(e).at(Path::new(editor)))?;
+    EDITORS
+        .read()
+        .iter()
+        .find(|trusted| **trusted == resolved)
+        .cloned()
+        .ok_or_else(|| AppError::PermissionDenied(format!("Editor was not chosen with pick_external_editor: {}", editor)))
+}
+
+// 通过系统对话框选择外部编辑器并记住，返回其路径；取消选择时为 None
+#[tauri::command]
+pub async fn pick_external_editor(app: AppHandle) -> Result<Option<String>, AppError> {
+    let Some(path) = app
+        .dialog()
+        .file()
+        .blocking_pick_file()
+        .and_then(|file| file.as_path().map(Path::to_path_buf))
+    else {
+        return Ok(None);
+    };
+    let resolved = fs::canonicalize(&path).map_err(|e| AppError::from(e).at(&path))?;
+
+    // 写文件会阻塞，先复制列表并释放锁，再在阻塞线程中保存
+    let editors = {
+        let mut editors = EDITORS.write();
+        if editors.contains(&resolved) {
+            None
+        } else {
+            editors.push(resolved.clone());
+            Some(editors.clone())
+        }
+    };
+    if let Some(editors) = editors {
+        run_blocking("pick_external_editor", move || save_editors(&editors)).await?;
+    }
+    Ok(Some(resolved.to_string_lossy().to_string()))
+}
+
+// 已选择的外部编辑器
+#[tauri::command]
+pub fn list_external_editors() -> Vec<String> {
+    EDITORS.read().iter().map(|e| e.to_string_lossy().to_string()).collect()
+}
+
+// 移除已选择的外部编辑器
+#[tauri::command]
+pub fn remove_external_editor(path: &str) -> Result<bool, AppError> {
+    let mut editors = EDITORS.write();
+    let before = editors.len();
+    editors.retain(|editor| editor != Path::new(path));
+    let removed = editors.len() != before;
+    if removed {
+        save_editors(&editors)?;
+    }
+    Ok(removed)
+}
+
+// 用系统默认程序或通过 pick_external_editor 选择的外部编辑器打开图片
+#[tauri::command]
+pub fn open_external(
+    app: AppHandle,
+    state: State<'_, ExternalEditState>,
+    path: &str,
+    editor: Option<String>,
+) -> Result<bool, AppError> {
+    scope::check(path)?;
+
+    if !Path::new(path).is_file() {
+        return Err(AppError::NotFound(path.to_string()));
+    }
+
+    match editor {
+        Some(editor) if !editor.trim().is_empty() => {
+            Command::new(trusted_editor(&editor)?)
+                .arg(path)
+                .spawn()
+                .map_err(|e| AppError::Io(format!("Failed to launch {}: {}", editor, e)))?;
+        }
+        _ => {
+            app.opener()
+                .open_path(path, None::<&str>)
+                .map_err(|e| AppError::Internal(format!("Failed to open file: {}", e)))?;
+        }
+    }
+
+    // 已在监视中则不重复创建线程
+    let mut watchers = state.watchers.lock();
+    if !watchers.contains_key(path) {
+        let stop = Arc::new(AtomicBool::new(false));
+        watchers.insert(path.to_string(), stop.clone());
+        spawn_watcher(app, path.to_string(), stop);
+    }
+
+    Ok(true)
+}
+
+// 停止监视外部程序对文件的修改
+#[tauri::command]
+pub fn stop_external_watch(state: State<'_, ExternalEditState>, path: &str) -> bool {
+    match state.watchers.lock().remove(path) {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,127 @@
+// 人脸检测：使用 UltraFace (RFB-320) 模型，返回归一化的人脸框
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::ml::{load_session, resolve_model, to_tensor};
+use crate::open_image;
+use crate::run_blocking;
+use crate::scope;
+
+const MODEL_FILE: &str = "ultraface-rfb-320.onnx";
+const MODEL_WIDTH: u32 = 320;
+const MODEL_HEIGHT: u32 = 240;
+pub(crate) const DEFAULT_THRESHOLD: f32 = 0.7;
+// 非极大值抑制的重叠阈值
+const NMS_IOU: f32 = 0.3;
+
+// 人脸框，坐标和尺寸均为相对图片宽高的比例（0~1）
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct FaceBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub score: f32,
+}
+
+impl FaceBox {
+    fn iou(&self, other: &FaceBox) -> f32 {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        let intersection = (right - left).max(0.0) * (bottom - top).max(0.0);
+        let union = self.width * self.height + other.width * other.height - intersection;
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+}
+
+// 按分数从高到低保留互不重叠的框
+fn non_max_suppression(mut boxes: Vec<FaceBox>) -> Vec<FaceBox> {
+    boxes.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut kept: Vec<FaceBox> = Vec::new();
+    for candidate in boxes {
+        if kept.iter().all(|k| k.iou(&candidate) < NMS_IOU) {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+// 运行 SSD 结构的检测模型（输入 320x240，输出 scores/boxes），车牌检测模型也使用同样的接口
+pub(crate) fn detect_with_model(
+    app: &AppHandle,
+    model_file: &str,
+    img: &DynamicImage,
+    threshold: f32,
+) -> Result<Vec<FaceBox>, AppError> {
+    let session = load_session(&resolve_model(app, model_file)?)?;
+
+    let input = img
+        .resize_exact(MODEL_WIDTH, MODEL_HEIGHT, FilterType::Triangle)
+        .to_rgb8();
+    // 模型要求 (x - 127) / 128 的归一化
+    let tensor = to_tensor(&input, Some(([127.0 / 255.0; 3], [128.0 / 255.0; 3])));
+
+    let inputs = ort::inputs![tensor].map_err(|e| AppError::ProcessingFailed(format!("Failed to prepare model input: {}", e)))?;
+    let outputs = session
+        .run(inputs)
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to run model: {}", e)))?;
+    // 输出：scores [1, N, 2]，boxes [1, N, 4]（左上右下，已归一化）
+    let scores = outputs[0]
+        .try_extract_tensor::<f32>()
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to read model output: {}", e)))?;
+    let boxes = outputs[1]
+        .try_extract_tensor::<f32>()
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to read model output: {}", e)))?;
+
+    let count = scores.shape()[1];
+    let mut candidates = Vec::new();
+    for i in 0..count {
+        let score = scores[[0, i, 1]];
+        if score < threshold {
+            continue;
+        }
+        let left = boxes[[0, i, 0]].clamp(0.0, 1.0);
+        let top = boxes[[0, i, 1]].clamp(0.0, 1.0);
+        let right = boxes[[0, i, 2]].clamp(0.0, 1.0);
+        let bottom = boxes[[0, i, 3]].clamp(0.0, 1.0);
+        if right <= left || bottom <= top {
+            continue;
+        }
+        candidates.push(FaceBox {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+            score,
+        });
+    }
+
+    Ok(non_max_suppression(candidates))
+}
+
+// 检测图片中的人脸，供其他模块（打码、智能裁剪等）复用
+pub(crate) fn find_faces(app: &AppHandle, img: &DynamicImage, threshold: f32) -> Result<Vec<FaceBox>, AppError> {
+    detect_with_model(app, MODEL_FILE, img, threshold)
+}
+
+// 检测人脸并返回归一化的人脸框
+#[tauri::command]
+pub async fn detect_faces(app: AppHandle, path: String, threshold: Option<f32>) -> Result<Vec<FaceBox>, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("detect_faces", move || {
+        let img = open_image(&path)?;
+
+        find_faces(&app, &img, threshold.unwrap_or(DEFAULT_THRESHOLD))
+    })
+    .await
+}
@@ -0,0 +1,132 @@
+// ffmpeg 集成（可选）：生成视频、读取视频帧等功能调用系统中的 ffmpeg，没有安装时这些功能返回 Unsupported
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, process};
+
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::error::AppError;
+use crate::{run_blocking, settings};
+
+// stderr 中保留在错误信息里的最后几行
+const ERROR_LINES: usize = 5;
+
+// 临时工作文件夹（存放交给 ffmpeg 的帧、ffmpeg 的输出等），离开作用域时删除
+pub(crate) struct WorkDir(PathBuf);
+
+impl WorkDir {
+    pub(crate) fn new(prefix: &str) -> Result<Self, AppError> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let dir = env::temp_dir().join(format!("file_flower_{}_{}_{}", prefix, process::id(), nanos));
+        fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+        Ok(Self(dir))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// 在 PATH 中查找可执行文件
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let names: Vec<String> = if cfg!(windows) {
+        vec![format!("{}.exe", name)]
+    } else {
+        vec![name.to_string()]
+    };
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+// 设置中指定的 ffmpeg，没有指定时在 PATH 中查找
+pub(crate) fn binary() -> Result<PathBuf, AppError> {
+    if let Some(path) = settings::current().ffmpeg_path {
+        return Ok(PathBuf::from(path));
+    }
+    find_in_path("ffmpeg")
+        .ok_or_else(|| AppError::Unsupported("ffmpeg is not installed (set its path in settings)".to_string()))
+}
+
+fn command(program: PathBuf) -> Command {
+    let mut command = Command::new(program);
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    // Windows 上不弹出控制台窗口
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+// 执行 ffmpeg 并等待结束，失败时错误信息中附带 stderr 的最后几行；返回 stdout
+pub(crate) fn run<I, S>(args: I) -> Result<Vec<u8>, AppError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = command(binary()?)
+        .args(["-hide_banner", "-loglevel", "error", "-nostdin"])
+        .args(args)
+        .output()
+        .map_err(|e| AppError::Io(format!("Failed to run ffmpeg: {}", e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines: Vec<&str> = stderr.lines().collect();
+        let tail = lines[lines.len().saturating_sub(ERROR_LINES)..].join("\n");
+        return Err(AppError::ProcessingFailed(format!("ffmpeg failed ({}): {}", output.status, tail)));
+    }
+    Ok(output.stdout)
+}
+
+// -version 的第一行
+fn version(program: PathBuf) -> Option<String> {
+    let output = command(program).arg("-version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.to_string())
+}
+
+// ffmpeg 的版本信息（-version 的第一行），没有安装时为 None
+#[tauri::command]
+pub async fn get_ffmpeg_version() -> Option<String> {
+    tauri::async_runtime::spawn_blocking(|| version(binary().ok()?))
+        .await
+        .ok()
+        .flatten()
+}
+
+// 通过系统对话框选择 ffmpeg，确认是 ffmpeg 后保存到设置并返回版本信息；取消选择时为 None
+#[tauri::command]
+pub async fn pick_ffmpeg(app: AppHandle) -> Result<Option<String>, AppError> {
+    let Some(path) = app
+        .dialog()
+        .file()
+        .set_title("ffmpeg")
+        .blocking_pick_file()
+        .and_then(|file| file.as_path().map(Path::to_path_buf))
+    else {
+        return Ok(None);
+    };
+
+    // 检查版本和保存设置都会阻塞，一起在阻塞线程中执行
+    run_blocking("pick_ffmpeg", move || {
+        let line = version(path.clone())
+            .filter(|line| line.starts_with("ffmpeg version"))
+            .ok_or_else(|| AppError::InvalidArgument(format!("Not an ffmpeg executable: {}", path.display())))?;
+        settings::set_ffmpeg_path(Some(path.to_string_lossy().to_string()))?;
+        Ok(Some(line))
+    })
+    .await
+}
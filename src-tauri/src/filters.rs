@@ -0,0 +1,375 @@
+// 滤镜：模糊、缩放、LUT、调色和色盲模拟；启用 gpu 特性且显卡可用时在 GPU 上执行，否则使用 CPU
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::history::{self, EditOperation};
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::save_image_from;
+use crate::{open_image, paths};
+
+// 缩放结果的最大边长
+const MAX_DIMENSION: u32 = 16384;
+
+fn one() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Filter {
+    // 高斯模糊
+    Blur { sigma: f32 },
+    // 双线性缩放到指定尺寸（不保持宽高比）
+    Resize { width: u32, height: u32 },
+    // 每个通道 256 项的查找表
+    Lut { red: Vec<u8>, green: Vec<u8>, blue: Vec<u8> },
+    // 亮度 -1~1（0 不变），对比度、饱和度、gamma 为倍数（1 不变）
+    #[serde(rename_all = "camelCase")]
+    Adjust {
+        #[serde(default)]
+        brightness: f32,
+        #[serde(default = "one")]
+        contrast: f32,
+        #[serde(default = "one")]
+        saturation: f32,
+        #[serde(default = "one")]
+        gamma: f32,
+    },
+    // 模拟色觉缺陷者看到的效果，severity 0~1（1 为完全缺失该类视锥细胞）
+    #[serde(rename_all = "camelCase")]
+    ColorBlind {
+        kind: ColorBlindness,
+        #[serde(default = "one")]
+        severity: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorBlindness {
+    // 红色盲
+    Protanopia,
+    // 绿色盲
+    Deuteranopia,
+    // 蓝色盲
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    // Machado 等（2009）的模拟矩阵（严重程度 1.0），作用于线性 RGB
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindness::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorBlindness::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorBlindness::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+// 按严重程度在单位矩阵和完全缺失的矩阵之间插值，按行展开为 9 项
+fn color_blind_matrix(kind: ColorBlindness, severity: f32) -> [f32; 9] {
+    let matrix = kind.matrix();
+    let mut values = [0.0; 9];
+    for (row, line) in matrix.iter().enumerate() {
+        for (col, value) in line.iter().enumerate() {
+            let identity = if row == col { 1.0 } else { 0.0 };
+            values[row * 3 + col] = identity + (value - identity) * severity;
+        }
+    }
+    values
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// 执行滤镜的后端；Auto 在 GPU 可用且图片放得下时使用 GPU
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Backend {
+    #[default]
+    Auto,
+    Cpu,
+    Gpu,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuInfo {
+    pub name: String,
+    pub backend: String,
+    pub device_type: String,
+    // 单张图片（RGBA）能使用的最大缓冲区，字节
+    pub max_buffer_size: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterResult {
+    pub output: String,
+    // 实际使用的后端：cpu 或 gpu
+    pub backend: Backend,
+}
+
+impl Filter {
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            Filter::Blur { sigma } if !(*sigma > 0.0 && *sigma <= 100.0) => Err(AppError::InvalidArgument(format!(
+                "Blur sigma must be between 0 and 100: {}",
+                sigma
+            ))),
+            Filter::Resize { width, height } if *width == 0 || *height == 0 => {
+                Err(AppError::InvalidArgument("Resize dimensions must be positive".to_string()))
+            }
+            Filter::Resize { width, height } if *width > MAX_DIMENSION || *height > MAX_DIMENSION => {
+                Err(AppError::InvalidArgument(format!(
+                    "Resize dimensions must be at most {}: {}x{}",
+                    MAX_DIMENSION, width, height
+                )))
+            }
+            Filter::Lut { red, green, blue } if red.len() != 256 || green.len() != 256 || blue.len() != 256 => Err(
+                AppError::InvalidArgument("LUT tables must have 256 entries per channel".to_string()),
+            ),
+            Filter::Adjust { gamma, .. } if *gamma <= 0.0 => {
+                Err(AppError::InvalidArgument(format!("Gamma must be positive: {}", gamma)))
+            }
+            Filter::ColorBlind { severity, .. } if !(0.0..=1.0).contains(severity) => Err(AppError::InvalidArgument(
+                format!("Color blindness severity must be between 0 and 1: {}", severity),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// 高斯核的一半（含中心），已归一化
+#[cfg(feature = "gpu")]
+fn gaussian_weights(sigma: f32) -> Vec<f32> {
+    let radius = (sigma * 3.0).ceil() as usize;
+    let weights: Vec<f32> = (0..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+fn adjust_pixel(value: [f32; 3], brightness: f32, contrast: f32, saturation: f32, gamma: f32) -> [f32; 3] {
+    let rgb = value.map(|v| (v + brightness - 0.5) * contrast + 0.5);
+    let luma = 0.299 * rgb[0] + 0.587 * rgb[1] + 0.114 * rgb[2];
+    rgb.map(|v| (luma + (v - luma) * saturation).max(0.0).powf(1.0 / gamma))
+}
+
+fn apply_cpu(img: &RgbaImage, filter: &Filter) -> RgbaImage {
+    match filter {
+        Filter::Blur { sigma } => image::imageops::blur(img, *sigma),
+        Filter::Resize { width, height } => image::imageops::resize(img, *width, *height, FilterType::Triangle),
+        Filter::Lut { red, green, blue } => {
+            let mut output = img.clone();
+            for pixel in output.pixels_mut() {
+                pixel.0 = [
+                    red[pixel[0] as usize],
+                    green[pixel[1] as usize],
+                    blue[pixel[2] as usize],
+                    pixel[3],
+                ];
+            }
+            output
+        }
+        Filter::Adjust {
+            brightness,
+            contrast,
+            saturation,
+            gamma,
+        } => {
+            let mut output = img.clone();
+            for pixel in output.pixels_mut() {
+                let rgb = [pixel[0], pixel[1], pixel[2]].map(|v| v as f32 / 255.0);
+                let [r, g, b] = adjust_pixel(rgb, *brightness, *contrast, *saturation, *gamma)
+                    .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+                pixel.0 = [r, g, b, pixel[3]];
+            }
+            output
+        }
+        Filter::ColorBlind { kind, severity } => {
+            let m = color_blind_matrix(*kind, *severity);
+            // 8 位 sRGB 只有 256 个取值，预先计算线性值
+            let linear: Vec<f32> = (0..=255).map(|v| srgb_to_linear(v as f32 / 255.0)).collect();
+            let mut output = img.clone();
+            for pixel in output.pixels_mut() {
+                let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|v| linear[v as usize]);
+                let [r, g, b] = [
+                    m[0] * r + m[1] * g + m[2] * b,
+                    m[3] * r + m[4] * g + m[5] * b,
+                    m[6] * r + m[7] * g + m[8] * b,
+                ]
+                .map(|v| (linear_to_srgb(v.clamp(0.0, 1.0)) * 255.0).round() as u8);
+                pixel.0 = [r, g, b, pixel[3]];
+            }
+            output
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+fn apply_gpu(gpu: &crate::gpu::Gpu, img: &RgbaImage, filter: &Filter) -> Result<RgbaImage, AppError> {
+    use crate::gpu::Params;
+
+    let (width, height) = img.dimensions();
+    match filter {
+        Filter::Blur { sigma } => {
+            let weights = gaussian_weights(*sigma);
+            let mut params = Params {
+                radius: weights.len() as i32 - 1,
+                horizontal: 1,
+                ..Default::default()
+            };
+            let horizontal = gpu.run("blur", img, width, height, params, &weights)?;
+            params.horizontal = 0;
+            gpu.run("blur", &horizontal, width, height, params, &weights)
+        }
+        Filter::Resize {
+            width: dst_width,
+            height: dst_height,
+        } => gpu.run("resize", img, *dst_width, *dst_height, Params::default(), &[]),
+        Filter::Lut { red, green, blue } => {
+            let table: Vec<f32> = red.iter().chain(green).chain(blue).map(|v| *v as f32).collect();
+            gpu.run("lut", img, width, height, Params::default(), &table)
+        }
+        Filter::Adjust {
+            brightness,
+            contrast,
+            saturation,
+            gamma,
+        } => {
+            let params = Params {
+                values: [*brightness, *contrast, *saturation, *gamma],
+                ..Default::default()
+            };
+            gpu.run("adjust", img, width, height, params, &[])
+        }
+        Filter::ColorBlind { kind, severity } => {
+            gpu.run("color_matrix", img, width, height, Params::default(), &color_blind_matrix(*kind, *severity))
+        }
+    }
+}
+
+// 原图和结果能否都放进单个存储缓冲区（缩放的结果可能比原图大）
+#[cfg(feature = "gpu")]
+fn fits(gpu: &crate::gpu::Gpu, img: &RgbaImage, filter: &Filter) -> bool {
+    let output_fits = match filter {
+        Filter::Resize { width, height } => gpu.fits(*width, *height),
+        _ => true,
+    };
+    output_fits && gpu.fits(img.width(), img.height())
+}
+
+// 按 backend 选择 GPU 或 CPU；GPU 执行失败时自动回退到 CPU
+#[cfg(feature = "gpu")]
+fn apply_rgba(img: &RgbaImage, filter: &Filter, backend: Backend) -> Result<(RgbaImage, Backend), AppError> {
+    if backend != Backend::Cpu {
+        match crate::gpu::get() {
+            Some(gpu) if fits(gpu, img, filter) => match apply_gpu(gpu, img, filter) {
+                Ok(output) => return Ok((output, Backend::Gpu)),
+                Err(e) => tracing::warn!(error = %e, "GPU filter failed, falling back to CPU"),
+            },
+            _ if backend == Backend::Gpu => {
+                return Err(AppError::Unsupported("No GPU available for this image".to_string()))
+            }
+            _ => {}
+        }
+    }
+    Ok((apply_cpu(img, filter), Backend::Cpu))
+}
+
+#[cfg(not(feature = "gpu"))]
+fn apply_rgba(img: &RgbaImage, filter: &Filter, backend: Backend) -> Result<(RgbaImage, Backend), AppError> {
+    if backend == Backend::Gpu {
+        return Err(AppError::Unsupported("GPU filters are not enabled in this build".to_string()));
+    }
+    Ok((apply_cpu(img, filter), Backend::Cpu))
+}
+
+// 执行滤镜；原图没有透明通道时按 RGB 返回
+pub(crate) fn apply(img: &DynamicImage, filter: &Filter, backend: Backend) -> Result<(DynamicImage, Backend), AppError> {
+    filter.validate()?;
+    let (output, used) = apply_rgba(&img.to_rgba8(), filter, backend)?;
+    let output = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(output)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(output).to_rgb8())
+    };
+    Ok((output, used))
+}
+
+// 对图片应用滤镜，output 为空时覆盖原图
+#[tauri::command]
+pub async fn apply_filter(
+    path: String,
+    filter: Filter,
+    output: Option<String>,
+    backend: Option<Backend>,
+) -> Result<FilterResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("apply_filter", move || {
+        let img = open_image(&path)?;
+        let (result, backend) = apply(&img, &filter, backend.unwrap_or_default())?;
+
+        let output = output.unwrap_or_else(|| path.clone());
+        save_image_from(&result, Path::new(&path), Path::new(&output))?;
+        history::record(Path::new(&path), Path::new(&output), EditOperation::Filter { filter })?;
+
+        Ok(FilterResult {
+            output: paths::display(Path::new(&output)),
+            backend,
+        })
+    })
+    .await
+}
+
+// 当前使用的显卡信息，没有可用的显卡或未启用 gpu 特性时为 None
+#[tauri::command]
+pub async fn get_gpu_info() -> Option<GpuInfo> {
+    #[cfg(feature = "gpu")]
+    {
+        tauri::async_runtime::spawn_blocking(|| crate::gpu::get().map(|gpu| gpu.info().clone()))
+            .await
+            .ok()
+            .flatten()
+    }
+    #[cfg(not(feature = "gpu"))]
+    {
+        None
+    }
+}
@@ -0,0 +1,180 @@
+// 景深合成：把同一场景、对焦在不同距离的多张照片合成为一张处处清晰的照片
+// 按局部对比度（拉普拉斯响应）给每张照片的每个像素加权，清晰的照片权重高，再用金字塔融合
+// 手持拍摄时先用 MTB 按中间一张对齐（只校正平移），合成后裁掉对齐时空出的边缘
+use std::path::{Path, PathBuf};
+
+use image::{imageops, DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::fusion::{self, Blender, Planar};
+use crate::storage::save_image;
+use crate::{open_image, paths, run_blocking, scope};
+
+const MIN_IMAGES: usize = 2;
+const MAX_IMAGES: usize = 20;
+const ALIGN_LEVELS: u32 = 6;
+// 对比度在该半径内取平均，避免权重在像素之间跳变
+const CONTRAST_RADIUS: u32 = 3;
+const DEFAULT_SHARPNESS: f32 = 4.0;
+
+fn yes() -> bool {
+    true
+}
+
+fn default_sharpness() -> f32 {
+    DEFAULT_SHARPNESS
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusStackOptions {
+    #[serde(default = "yes")]
+    pub align: bool,
+    // 对比度权重的指数，越大越倾向于只取最清晰的一张，越小过渡越柔和
+    #[serde(default = "default_sharpness")]
+    pub sharpness: f32,
+    // 为空时在第一张照片旁边生成 name_stacked.ext
+    pub output: Option<String>,
+}
+
+impl Default for FocusStackOptions {
+    fn default() -> Self {
+        Self {
+            align: true,
+            sharpness: DEFAULT_SHARPNESS,
+            output: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusStackResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    // 每张照片对齐时的平移量（像素）
+    pub offsets: Vec<(i32, i32)>,
+}
+
+// 盒式模糊（积分图）
+fn box_blur(values: &[f32], width: u32, height: u32, radius: u32) -> Vec<f32> {
+    let (w, h) = (width as usize, height as usize);
+    let mut integral = vec![0f64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row = 0f64;
+        for x in 0..w {
+            row += values[y * w + x] as f64;
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row;
+        }
+    }
+    let r = radius as usize;
+    let mut output = vec![0f32; w * h];
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(r), (y + r + 1).min(h));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(r), (x + r + 1).min(w));
+            let sum = integral[y1 * (w + 1) + x1] - integral[y0 * (w + 1) + x1] - integral[y1 * (w + 1) + x0]
+                + integral[y0 * (w + 1) + x0];
+            output[y * w + x] = (sum / ((x1 - x0) * (y1 - y0)) as f64) as f32;
+        }
+    }
+    output
+}
+
+fn weights(img: &Planar, sharpness: f32) -> Planar {
+    let contrast = fusion::contrast(&fusion::luminance(img), img.width, img.height);
+    let smoothed = box_blur(&contrast, img.width, img.height, CONTRAST_RADIUS);
+    let mut weight = Planar::new(img.width, img.height, 1);
+    for (value, contrast) in weight.data.iter_mut().zip(smoothed) {
+        *value = contrast.powf(sharpness) + 1e-12;
+    }
+    weight
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "jpg".to_string());
+            path.with_file_name(format!("{}_stacked.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 合成对焦在不同位置的照片，所有照片的尺寸必须相同
+#[tauri::command]
+pub async fn focus_stack(paths: Vec<String>, options: Option<FocusStackOptions>) -> Result<FocusStackResult, AppError> {
+    if !(MIN_IMAGES..=MAX_IMAGES).contains(&paths.len()) {
+        return Err(AppError::InvalidArgument(format!(
+            "Focus stacking needs {} to {} images, got {}",
+            MIN_IMAGES,
+            MAX_IMAGES,
+            paths.len()
+        )));
+    }
+    for path in &paths {
+        scope::check(path)?;
+    }
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    if !(0.5..=10.0).contains(&options.sharpness) {
+        return Err(AppError::InvalidArgument(format!(
+            "Sharpness must be between 0.5 and 10: {}",
+            options.sharpness
+        )));
+    }
+
+    run_blocking("focus_stack", move || {
+        let reference = open_image(&paths[paths.len() / 2])?;
+        let (width, height) = reference.dimensions();
+        let reference_gray = reference.to_luma8();
+
+        let mut offsets = Vec::with_capacity(paths.len());
+        let mut blender = Blender::new(width, height);
+        for path in &paths {
+            let img = open_image(path)?;
+            if img.dimensions() != (width, height) {
+                return Err(AppError::InvalidArgument(format!(
+                    "All images must have the same size ({}x{})",
+                    width, height
+                ))
+                .at(path));
+            }
+            let offset = if options.align {
+                fusion::mtb_offset(&reference_gray, &img.to_luma8(), ALIGN_LEVELS)
+            } else {
+                (0, 0)
+            };
+            let planar = Planar::from_rgb(&fusion::shift(&img.to_rgb8(), offset.0, offset.1));
+            blender.add(&planar, &weights(&planar, options.sharpness));
+            offsets.push(offset);
+        }
+        let merged = blender.finish().to_rgb();
+
+        // 裁掉平移后由边缘像素填充的部分
+        let left = offsets.iter().map(|o| o.0.max(0)).max().unwrap_or(0) as u32;
+        let right = offsets.iter().map(|o| (-o.0).max(0)).max().unwrap_or(0) as u32;
+        let top = offsets.iter().map(|o| o.1.max(0)).max().unwrap_or(0) as u32;
+        let bottom = offsets.iter().map(|o| (-o.1).max(0)).max().unwrap_or(0) as u32;
+        let merged = if left + right < width && top + bottom < height {
+            imageops::crop_imm(&merged, left, top, width - left - right, height - top - bottom).to_image()
+        } else {
+            merged
+        };
+
+        let output = output_path(Path::new(&paths[0]), options.output);
+        let (out_width, out_height) = merged.dimensions();
+        save_image(&DynamicImage::ImageRgb8(merged), &output)?;
+        Ok(FocusStackResult {
+            output: paths::display(&output),
+            width: out_width,
+            height: out_height,
+            offsets,
+        })
+    })
+    .await
+}
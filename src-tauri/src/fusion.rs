@@ -0,0 +1,330 @@
+// 多张图片合成共用的工具：
+//   按权重做拉普拉斯金字塔融合（HDR 曝光融合、景深合成），各层分别加权，接缝和过渡处不会出现光晕
+//   中值阈值位图（MTB）对齐：对曝光不同的同一场景也能估计出平移量
+use image::{GrayImage, RgbImage};
+
+// 金字塔最高一层的最短边不小于该值
+const MIN_LEVEL_SIZE: u32 = 8;
+const MAX_LEVELS: usize = 10;
+// MTB 中与中值相差不超过该值的像素不参与比较（容易受噪点影响）
+const MTB_NOISE: i32 = 4;
+// 权重之和的下限，避免除以零
+const EPSILON: f32 = 1e-12;
+
+// 按通道交错存储的浮点图片，取值 0~1
+#[derive(Clone)]
+pub(crate) struct Planar {
+    pub width: u32,
+    pub height: u32,
+    pub channels: usize,
+    pub data: Vec<f32>,
+}
+
+impl Planar {
+    pub(crate) fn new(width: u32, height: u32, channels: usize) -> Self {
+        Self {
+            width,
+            height,
+            channels,
+            data: vec![0.0; width as usize * height as usize * channels],
+        }
+    }
+
+    pub(crate) fn from_rgb(img: &RgbImage) -> Self {
+        Self {
+            width: img.width(),
+            height: img.height(),
+            channels: 3,
+            data: img.as_raw().iter().map(|v| *v as f32 / 255.0).collect(),
+        }
+    }
+
+    pub(crate) fn to_rgb(&self) -> RgbImage {
+        let data = self.data.iter().map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8).collect();
+        RgbImage::from_raw(self.width, self.height, data).unwrap_or_else(|| RgbImage::new(self.width, self.height))
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * self.channels
+    }
+
+    // 取像素，坐标超出范围时取最近的边缘像素
+    fn clamped(&self, x: i64, y: i64, c: usize) -> f32 {
+        let x = x.clamp(0, self.width as i64 - 1) as u32;
+        let y = y.clamp(0, self.height as i64 - 1) as u32;
+        self.data[self.index(x, y) + c]
+    }
+
+    // 5 抽头高斯模糊后隔行隔列取样，尺寸减半
+    fn downsample(&self) -> Planar {
+        const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+        let width = self.width.div_ceil(2);
+        let mut horizontal = Planar::new(width, self.height, self.channels);
+        for y in 0..self.height {
+            for x in 0..width {
+                let base = horizontal.index(x, y);
+                for c in 0..self.channels {
+                    horizontal.data[base + c] = KERNEL
+                        .iter()
+                        .enumerate()
+                        .map(|(i, k)| k * self.clamped(x as i64 * 2 + i as i64 - 2, y as i64, c))
+                        .sum();
+                }
+            }
+        }
+        let height = self.height.div_ceil(2);
+        let mut output = Planar::new(width, height, self.channels);
+        for y in 0..height {
+            for x in 0..width {
+                let base = output.index(x, y);
+                for c in 0..self.channels {
+                    output.data[base + c] = KERNEL
+                        .iter()
+                        .enumerate()
+                        .map(|(i, k)| k * horizontal.clamped(x as i64, y as i64 * 2 + i as i64 - 2, c))
+                        .sum();
+                }
+            }
+        }
+        output
+    }
+
+    // 双线性插值放大到 width x height
+    fn upsample(&self, width: u32, height: u32) -> Planar {
+        let mut output = Planar::new(width, height, self.channels);
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+        for y in 0..height {
+            let sy = ((y as f32 + 0.5) * scale_y - 0.5).max(0.0);
+            let (y0, ty) = (sy.floor() as i64, sy.fract());
+            for x in 0..width {
+                let sx = ((x as f32 + 0.5) * scale_x - 0.5).max(0.0);
+                let (x0, tx) = (sx.floor() as i64, sx.fract());
+                let base = output.index(x, y);
+                for c in 0..self.channels {
+                    let top = self.clamped(x0, y0, c) * (1.0 - tx) + self.clamped(x0 + 1, y0, c) * tx;
+                    let bottom = self.clamped(x0, y0 + 1, c) * (1.0 - tx) + self.clamped(x0 + 1, y0 + 1, c) * tx;
+                    output.data[base + c] = top * (1.0 - ty) + bottom * ty;
+                }
+            }
+        }
+        output
+    }
+}
+
+// 高斯金字塔，第 0 层为原图
+fn gaussian_pyramid(img: &Planar, levels: usize) -> Vec<Planar> {
+    let mut pyramid = vec![img.clone()];
+    for _ in 1..levels {
+        let next = pyramid[pyramid.len() - 1].downsample();
+        pyramid.push(next);
+    }
+    pyramid
+}
+
+// 拉普拉斯金字塔：每层为该层与上一层放大后之差，最高一层保留高斯金字塔的结果
+fn laplacian_pyramid(img: &Planar, levels: usize) -> Vec<Planar> {
+    let mut pyramid = gaussian_pyramid(img, levels);
+    for i in 0..pyramid.len() - 1 {
+        let expanded = pyramid[i + 1].upsample(pyramid[i].width, pyramid[i].height);
+        for (value, base) in pyramid[i].data.iter_mut().zip(&expanded.data) {
+            *value -= base;
+        }
+    }
+    pyramid
+}
+
+// 按图片尺寸选择金字塔层数
+pub(crate) fn pyramid_levels(width: u32, height: u32) -> usize {
+    let mut levels = 1;
+    let mut size = width.min(height);
+    while size / 2 >= MIN_LEVEL_SIZE && levels < MAX_LEVELS {
+        size /= 2;
+        levels += 1;
+    }
+    levels
+}
+
+// 金字塔融合：逐张加入图片和对应的权重图（单通道），各层按模糊后的权重加权平均
+// 每次只保存一张图片的金字塔，内存占用与图片数量无关
+pub(crate) struct Blender {
+    levels: usize,
+    sum: Vec<Planar>,
+    weights: Vec<Planar>,
+}
+
+impl Blender {
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        let levels = pyramid_levels(width, height);
+        let sum = gaussian_pyramid(&Planar::new(width, height, 3), levels);
+        let weights = gaussian_pyramid(&Planar::new(width, height, 1), levels);
+        Self { levels, sum, weights }
+    }
+
+    pub(crate) fn add(&mut self, img: &Planar, weight: &Planar) {
+        let laplacian = laplacian_pyramid(img, self.levels);
+        let weight = gaussian_pyramid(weight, self.levels);
+        for level in 0..self.levels {
+            let (sum, total) = (&mut self.sum[level], &mut self.weights[level]);
+            for (i, w) in weight[level].data.iter().enumerate() {
+                total.data[i] += w;
+                for c in 0..3 {
+                    sum.data[i * 3 + c] += w * laplacian[level].data[i * 3 + c];
+                }
+            }
+        }
+    }
+
+    // 各层除以权重之和后从最高一层逐层放大相加
+    pub(crate) fn finish(mut self) -> Planar {
+        for level in 0..self.levels {
+            let (sum, total) = (&mut self.sum[level], &self.weights[level]);
+            for (i, w) in total.data.iter().enumerate() {
+                for c in 0..3 {
+                    sum.data[i * 3 + c] /= w.max(EPSILON);
+                }
+            }
+        }
+        let mut result = self.sum.pop().unwrap_or_else(|| Planar::new(1, 1, 3));
+        while let Some(level) = self.sum.pop() {
+            let mut expanded = result.upsample(level.width, level.height);
+            for (value, detail) in expanded.data.iter_mut().zip(&level.data) {
+                *value += detail;
+            }
+            result = expanded;
+        }
+        result
+    }
+}
+
+// 3x3 拉普拉斯算子的绝对值，衡量局部对比度（清晰程度）
+pub(crate) fn contrast(gray: &[f32], width: u32, height: u32) -> Vec<f32> {
+    let (w, h) = (width as i64, height as i64);
+    let at = |x: i64, y: i64| gray[(y.clamp(0, h - 1) * w + x.clamp(0, w - 1)) as usize];
+    let mut output = Vec::with_capacity(gray.len());
+    for y in 0..h {
+        for x in 0..w {
+            let laplacian = at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1) - 4.0 * at(x, y);
+            output.push(laplacian.abs());
+        }
+    }
+    output
+}
+
+// 灰度（0~1）
+pub(crate) fn luminance(img: &Planar) -> Vec<f32> {
+    img.data
+        .chunks_exact(3)
+        .map(|p| 0.299 * p[0] + 0.587 * p[1] + 0.114 * p[2])
+        .collect()
+}
+
+// 平移图片（dx、dy 为正时内容向右、向下移动），空出的部分取最近的边缘像素
+pub(crate) fn shift(img: &RgbImage, dx: i32, dy: i32) -> RgbImage {
+    if dx == 0 && dy == 0 {
+        return img.clone();
+    }
+    let (width, height) = img.dimensions();
+    RgbImage::from_fn(width, height, |x, y| {
+        let sx = (x as i64 - dx as i64).clamp(0, width as i64 - 1) as u32;
+        let sy = (y as i64 - dy as i64).clamp(0, height as i64 - 1) as u32;
+        *img.get_pixel(sx, sy)
+    })
+}
+
+// 中值阈值位图和排除位图（与中值接近的像素为 false）
+fn threshold_bitmaps(gray: &GrayImage) -> (Vec<bool>, Vec<bool>) {
+    let mut histogram = [0usize; 256];
+    for pixel in gray.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+    let half = (gray.width() as usize * gray.height() as usize) / 2;
+    let mut count = 0;
+    let median = histogram
+        .iter()
+        .position(|n| {
+            count += n;
+            count > half
+        })
+        .unwrap_or(128) as i32;
+    let threshold = gray.pixels().map(|p| p[0] as i32 > median).collect();
+    let exclusion = gray.pixels().map(|p| (p[0] as i32 - median).abs() > MTB_NOISE).collect();
+    (threshold, exclusion)
+}
+
+fn halve(gray: &GrayImage) -> GrayImage {
+    let width = (gray.width() / 2).max(1);
+    let height = (gray.height() / 2).max(1);
+    GrayImage::from_fn(width, height, |x, y| {
+        let mut sum = 0u32;
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let sx = (x * 2 + dx).min(gray.width() - 1);
+            let sy = (y * 2 + dy).min(gray.height() - 1);
+            sum += gray.get_pixel(sx, sy)[0] as u32;
+        }
+        image::Luma([(sum / 4) as u8])
+    })
+}
+
+// 两个位图在 image 平移 (dx, dy) 后不一致的像素数
+fn mismatch(
+    reference: &(Vec<bool>, Vec<bool>),
+    image: &(Vec<bool>, Vec<bool>),
+    width: u32,
+    height: u32,
+    dx: i32,
+    dy: i32,
+) -> usize {
+    let mut count = 0;
+    for y in 0..height as i32 {
+        let sy = y - dy;
+        if sy < 0 || sy >= height as i32 {
+            continue;
+        }
+        for x in 0..width as i32 {
+            let sx = x - dx;
+            if sx < 0 || sx >= width as i32 {
+                continue;
+            }
+            let a = (y * width as i32 + x) as usize;
+            let b = (sy * width as i32 + sx) as usize;
+            if reference.0[a] != image.0[b] && reference.1[a] && image.1[b] {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// MTB 对齐：从缩小的图片开始，每层在上一层结果附近 ±1 像素内搜索，返回 image 需要平移的量
+// 最大可检测的平移约为 2^levels 像素
+pub(crate) fn mtb_offset(reference: &GrayImage, image: &GrayImage, levels: u32) -> (i32, i32) {
+    if reference.dimensions() != image.dimensions() {
+        return (0, 0);
+    }
+    if levels > 0 && reference.width().min(reference.height()) >= 32 {
+        let (dx, dy) = mtb_offset(&halve(reference), &halve(image), levels - 1);
+        let (dx, dy) = (dx * 2, dy * 2);
+        refine(reference, image, dx, dy)
+    } else {
+        refine(reference, image, 0, 0)
+    }
+}
+
+fn refine(reference: &GrayImage, image: &GrayImage, dx: i32, dy: i32) -> (i32, i32) {
+    let (width, height) = reference.dimensions();
+    let reference = threshold_bitmaps(reference);
+    let image = threshold_bitmaps(image);
+    let mut best = (dx, dy);
+    let mut best_count = usize::MAX;
+    for oy in -1..=1 {
+        for ox in -1..=1 {
+            let count = mismatch(&reference, &image, width, height, dx + ox, dy + oy);
+            if count < best_count {
+                best_count = count;
+                best = (dx + ox, dy + oy);
+            }
+        }
+    }
+    best
+}
@@ -0,0 +1,131 @@
+// 离线反向地理编码：根据 EXIF 中的 GPS 坐标查找最近的城市
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use exif::{In, Tag, Value};
+use reverse_geocoder::ReverseGeocoder;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::listing::{list_files, report_symlinks, SymlinkPolicy};
+use crate::paths;
+use crate::run_blocking;
+use crate::scope;
+
+lazy_static::lazy_static! {
+    // 内置的 GeoNames 城市数据，首次使用时构建索引
+    static ref GEOCODER: ReverseGeocoder = ReverseGeocoder::new();
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub city: String,
+    pub region: String,
+    pub country_code: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationGroup {
+    pub city: String,
+    pub region: String,
+    pub country_code: String,
+    pub images: Vec<String>,
+}
+
+// 将度/分/秒形式的有理数转换为十进制度数
+fn to_degrees(value: &Value) -> Option<f64> {
+    match value {
+        Value::Rational(parts) if !parts.is_empty() => {
+            let mut degrees = 0.0;
+            for (part, divisor) in parts.iter().zip([1.0, 60.0, 3600.0]) {
+                degrees += part.to_f64() / divisor;
+            }
+            Some(degrees)
+        }
+        _ => None,
+    }
+}
+
+// 读取图片 EXIF 中的 GPS 坐标（纬度，经度）
+pub fn read_gps(path: &Path) -> Option<(f64, f64)> {
+    let file = File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+
+    let coordinate = |tag: Tag, ref_tag: Tag, negative: &str| {
+        let value = to_degrees(&exif.get_field(tag, In::PRIMARY)?.value)?;
+        let reference = exif
+            .get_field(ref_tag, In::PRIMARY)
+            .map(|f| f.display_value().to_string())
+            .unwrap_or_default();
+        Some(if reference.eq_ignore_ascii_case(negative) { -value } else { value })
+    };
+
+    let latitude = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?;
+    let longitude = coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?;
+    Some((latitude, longitude))
+}
+
+fn lookup(latitude: f64, longitude: f64) -> Location {
+    let record = GEOCODER.search((latitude, longitude)).record;
+    Location {
+        latitude,
+        longitude,
+        city: record.name.clone(),
+        region: record.admin1.clone(),
+        country_code: record.cc.clone(),
+    }
+}
+
+// 获取图片拍摄地点，没有 GPS 信息时返回 None
+#[tauri::command]
+pub fn get_image_location(path: &str) -> Option<Location> {
+    scope::check(path).ok()?;
+
+    read_gps(Path::new(path)).map(|(latitude, longitude)| lookup(latitude, longitude))
+}
+
+// 按城市对目录中的图片分组，没有 GPS 信息的图片不参与分组
+#[tauri::command]
+pub async fn group_images_by_location(
+    app: AppHandle,
+    path: String,
+    symlinks: Option<SymlinkPolicy>,
+) -> Result<Vec<LocationGroup>, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("group_images_by_location", move || {
+        let listing = list_files(Path::new(&path), symlinks.unwrap_or_default(), false)?;
+        report_symlinks(&app, Path::new(&path), &listing);
+
+        let mut groups: BTreeMap<(String, String, String), Vec<String>> = BTreeMap::new();
+        for file in listing.files {
+            if let Some((latitude, longitude)) = read_gps(&file) {
+                let location = lookup(latitude, longitude);
+                groups
+                    .entry((location.country_code, location.region, location.city))
+                    .or_default()
+                    .push(paths::display(&file));
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|((country_code, region, city), images)| LocationGroup {
+                city,
+                region,
+                country_code,
+                images,
+            })
+            .collect())
+    })
+    .await
+}
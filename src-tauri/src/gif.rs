@@ -0,0 +1,314 @@
+// GIF 动画：列出各帧（帧数、延时和预览图）、导出单帧，以及对所有帧统一缩放、裁剪后重新编码
+// 解码得到的每一帧都已合成为完整画面，重新编码时保留每帧的延时和循环次数
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::{AnimationDecoder, DynamicImage, Frame, ImageDecoder, ImageFormat};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::storage::{save_image, write_app_file, write_atomic};
+use crate::{paths, run_blocking, scope, settings};
+
+// 所有帧合计最多的像素数（每帧都解码为完整画面，RGBA 约 1 GB），避免超长或超大的动画占满内存
+const MAX_TOTAL_PIXELS: u64 = 256 * 1024 * 1024;
+// 记录循环次数的应用扩展
+const LOOP_EXTENSIONS: [&[u8]; 2] = [b"NETSCAPE2.0", b"ANIMEXTS1.0"];
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GifFrame {
+    pub index: usize,
+    // 毫秒
+    pub delay: u32,
+    // 缓存中的 PNG 预览图（缩略图尺寸）
+    pub preview: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GifInfo {
+    pub width: u32,
+    pub height: u32,
+    // 0 为无限循环；为空时只播放一次
+    pub loop_count: Option<u16>,
+    // 总时长（毫秒）
+    pub duration: u64,
+    pub frames: Vec<GifFrame>,
+}
+
+struct Animation {
+    width: u32,
+    height: u32,
+    loop_count: Option<u16>,
+    frames: Vec<Frame>,
+}
+
+pub(crate) fn is_gif(path: &Path) -> bool {
+    path.extension().map(|ext| ext.eq_ignore_ascii_case("gif")).unwrap_or(false)
+}
+
+fn delay_ms(frame: &Frame) -> u32 {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    numer / denom.max(1)
+}
+
+// 颜色表的字节数，packed 为逻辑屏幕描述符或图像描述符中的标志字节
+fn color_table_size(packed: u8) -> usize {
+    if packed & 0x80 == 0 {
+        0
+    } else {
+        3 << ((packed & 7) + 1)
+    }
+}
+
+// 跳过从 pos 开始的一串数据子块（以长度为 0 的子块结束），返回其后的位置
+fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let size = *data.get(pos)? as usize;
+        pos += 1 + size;
+        if size == 0 {
+            return Some(pos);
+        }
+    }
+}
+
+// 应用扩展中的循环次数；没有该扩展时只播放一次
+// 按 GIF 的块结构逐块跳过，只查看应用扩展，图像数据中恰好出现的标识不会被误认
+fn loop_count(data: &[u8]) -> Option<u16> {
+    if !data.starts_with(b"GIF") {
+        return None;
+    }
+    // 文件头 6 字节、逻辑屏幕描述符 7 字节，之后是全局颜色表
+    let mut pos = 13 + color_table_size(*data.get(10)?);
+    loop {
+        match *data.get(pos)? {
+            0x21 => {
+                let label = *data.get(pos + 1)?;
+                pos += 2;
+                if label == 0xFF && *data.get(pos)? == 11 && LOOP_EXTENSIONS.contains(&data.get(pos + 1..pos + 12)?) {
+                    if let [3, 1, low, high] = data.get(pos + 12..pos + 16)? {
+                        return Some(u16::from_le_bytes([*low, *high]));
+                    }
+                }
+                pos = skip_sub_blocks(data, pos)?;
+            }
+            0x2C => {
+                // 图像描述符 10 字节和局部颜色表，之后是 LZW 最小码长和图像数据
+                let table = color_table_size(*data.get(pos + 9)?);
+                pos = skip_sub_blocks(data, pos + 10 + table + 1)?;
+            }
+            // 文件结束（0x3B）或数据损坏
+            _ => return None,
+        }
+    }
+}
+
+fn decode(path: &Path) -> Result<Animation, AppError> {
+    let data = fs::read(path).map_err(|e| AppError::from(e).at(path))?;
+    let decoder = GifDecoder::new(Cursor::new(&data)).map_err(|e| AppError::from(e).at(path))?;
+    let (width, height) = decoder.dimensions();
+    let max_frames = (MAX_TOTAL_PIXELS / (width as u64 * height as u64).max(1)) as usize;
+    let mut frames = Vec::new();
+    for frame in decoder.into_frames() {
+        if frames.len() >= max_frames {
+            return Err(AppError::TooLarge(format!(
+                "GIF has too many frames for its size (at most {} frames of {}x{})",
+                max_frames, width, height
+            ))
+            .at(path));
+        }
+        frames.push(frame.map_err(|e| AppError::from(e).at(path))?);
+    }
+    if frames.is_empty() {
+        return Err(AppError::DecodeFailed("GIF has no frames".to_string()).at(path));
+    }
+    Ok(Animation {
+        width,
+        height,
+        loop_count: loop_count(&data),
+        frames,
+    })
+}
+
+fn encode(path: &Path, frames: Vec<Frame>, loop_count: Option<u16>) -> Result<(), AppError> {
+    write_atomic(path, |writer| {
+        let mut encoder = GifEncoder::new_with_speed(writer, 10);
+        if let Some(count) = loop_count {
+            encoder.set_repeat(if count == 0 { Repeat::Infinite } else { Repeat::Finite(count) })?;
+        }
+        encoder.encode_frames(frames)?;
+        Ok(())
+    })
+}
+
+// 对每一帧执行相同的编辑后重新编码为 GIF 动画，返回输出尺寸
+pub(crate) fn edit_frames<F>(source: &Path, output: &Path, edit: F) -> Result<(u32, u32), AppError>
+where
+    F: Fn(DynamicImage) -> DynamicImage,
+{
+    let animation = decode(source)?;
+    let frames: Vec<Frame> = animation
+        .frames
+        .into_iter()
+        .map(|frame| {
+            let delay = frame.delay();
+            let edited = edit(DynamicImage::ImageRgba8(frame.into_buffer()));
+            Frame::from_parts(edited.to_rgba8(), 0, 0, delay)
+        })
+        .collect();
+    let dimensions = frames[0].buffer().dimensions();
+    encode(output, frames, animation.loop_count)?;
+    Ok(dimensions)
+}
+
+// 预览图的缓存文件夹，以路径、修改时间、大小和缩略图尺寸为键
+fn preview_dir(app: &AppHandle, path: &Path) -> Result<PathBuf, AppError> {
+    let metadata = fs::metadata(path).map_err(|e| AppError::from(e).at(path))?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    settings::current().thumbnail_size.hash(&mut hasher);
+
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve cache directory: {}", e)))?
+        .join("gif-frames")
+        .join(format!("{:016x}", hasher.finish()));
+    fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+    Ok(dir)
+}
+
+// 列出 GIF 的各帧，预览图已缓存时不再重新生成
+#[tauri::command]
+pub async fn get_gif_frames(app: AppHandle, path: String) -> Result<GifInfo, AppError> {
+    let path = scope::check(&path)?;
+
+    run_blocking("get_gif_frames", move || {
+        let animation = decode(&path)?;
+        let dir = preview_dir(&app, &path)?;
+        let size = settings::current().thumbnail_size;
+        let mut frames = Vec::with_capacity(animation.frames.len());
+        for (index, frame) in animation.frames.iter().enumerate() {
+            let file = dir.join(format!("frame_{:05}.png", index));
+            if !file.is_file() {
+                let preview = DynamicImage::ImageRgba8(frame.buffer().clone()).thumbnail(size, size);
+                let mut buffer = Cursor::new(Vec::new());
+                preview.write_to(&mut buffer, ImageFormat::Png)?;
+                write_app_file(&file, &buffer.into_inner())?;
+            }
+            frames.push(GifFrame {
+                index,
+                delay: delay_ms(frame),
+                preview: paths::display(&file),
+            });
+        }
+        Ok(GifInfo {
+            width: animation.width,
+            height: animation.height,
+            loop_count: animation.loop_count,
+            duration: frames.iter().map(|frame| frame.delay as u64).sum(),
+            frames,
+        })
+    })
+    .await
+}
+
+// 把第 index 帧（从 0 开始）保存为图片；output 为空时在原图旁边生成 name_frameN.png
+#[tauri::command]
+pub async fn extract_gif_frame(path: String, index: usize, output: Option<String>) -> Result<String, AppError> {
+    let path = scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("extract_gif_frame", move || {
+        let animation = decode(&path)?;
+        let count = animation.frames.len();
+        let frame = animation.frames.into_iter().nth(index).ok_or_else(|| {
+            AppError::InvalidArgument(format!("Frame {} is out of range (GIF has {} frames)", index, count))
+        })?;
+        let output = match output {
+            Some(output) => PathBuf::from(output),
+            None => path.with_file_name(format!("{}_frame{}.png", paths::file_stem(&path, "image"), index)),
+        };
+        save_image(&DynamicImage::ImageRgba8(frame.into_buffer()), &output)?;
+        Ok(paths::display(&output))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Delay, Rgba, RgbaImage};
+
+    use super::*;
+
+    // 2 色全局颜色表的 1x1 GIF，blocks 放在第一个图像之前
+    fn gif_with(blocks: &[u8]) -> Vec<u8> {
+        let mut data = b"GIF89a".to_vec();
+        data.extend_from_slice(&[1, 0, 1, 0, 0x80, 0, 0]);
+        data.extend_from_slice(&[0, 0, 0, 255, 255, 255]);
+        data.extend_from_slice(blocks);
+        // 图像描述符、LZW 最小码长和一个数据子块
+        data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x44, 0x01, 0]);
+        data.push(0x3B);
+        data
+    }
+
+    fn loop_extension(identifier: &[u8], count: u16) -> Vec<u8> {
+        let mut block = vec![0x21, 0xFF, 11];
+        block.extend_from_slice(identifier);
+        block.extend_from_slice(&[3, 1]);
+        block.extend_from_slice(&count.to_le_bytes());
+        block.push(0);
+        block
+    }
+
+    #[test]
+    fn loop_count_reads_application_extension() {
+        assert_eq!(loop_count(&gif_with(&loop_extension(b"NETSCAPE2.0", 0))), Some(0));
+        assert_eq!(loop_count(&gif_with(&loop_extension(b"ANIMEXTS1.0", 7))), Some(7));
+    }
+
+    #[test]
+    fn loop_count_skips_other_extensions() {
+        // 图形控制扩展和注释扩展之后才是循环次数
+        let mut blocks = vec![0x21, 0xF9, 4, 0, 10, 0, 0, 0];
+        blocks.extend_from_slice(&[0x21, 0xFE, 5]);
+        blocks.extend_from_slice(b"hello");
+        blocks.push(0);
+        blocks.extend_from_slice(&loop_extension(b"NETSCAPE2.0", 3));
+        assert_eq!(loop_count(&gif_with(&blocks)), Some(3));
+    }
+
+    #[test]
+    fn loop_count_ignores_marker_inside_other_blocks() {
+        // 注释中出现的标识不是应用扩展
+        let mut comment = vec![0x21, 0xFE, 15];
+        comment.extend_from_slice(b"NETSCAPE2.0");
+        comment.extend_from_slice(&[3, 1, 5, 0, 0]);
+        assert_eq!(loop_count(&gif_with(&comment)), None);
+        assert_eq!(loop_count(&gif_with(&[])), None);
+        assert_eq!(loop_count(b"not a gif"), None);
+    }
+
+    #[test]
+    fn loop_count_matches_encoder() {
+        let frame = Frame::from_parts(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255])), 0, 0, Delay::from_numer_denom_ms(100, 1));
+        let mut data = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut data);
+            encoder.set_repeat(Repeat::Finite(5)).unwrap();
+            encoder.encode_frames(vec![frame.clone(), frame]).unwrap();
+        }
+        assert_eq!(loop_count(&data), Some(5));
+    }
+}
@@ -0,0 +1,269 @@
+// GPU 计算后端（wgpu）：在显卡上执行模糊、缩放、LUT 和调色，不可用时由 filters 回退到 CPU
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+use bytemuck::{Pod, Zeroable};
+use image::RgbaImage;
+use parking_lot::Mutex;
+use wgpu::util::DeviceExt;
+
+use crate::error::AppError;
+use crate::filters::GpuInfo;
+
+const SHADER: &str = include_str!("gpu.wgsl");
+// 与着色器中的 @workgroup_size 一致
+const WORKGROUP_SIZE: u32 = 16;
+const ENTRY_POINTS: [&str; 5] = ["adjust", "lut", "blur", "resize", "color_matrix"];
+
+// 与着色器中的 Params 布局一致
+#[repr(C)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+pub(crate) struct Params {
+    src_size: [u32; 2],
+    dst_size: [u32; 2],
+    pub values: [f32; 4],
+    pub radius: i32,
+    pub horizontal: u32,
+    _pad: [u32; 2],
+}
+
+pub(crate) struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    pipelines: HashMap<&'static str, wgpu::ComputePipeline>,
+    info: GpuInfo,
+    // 错误范围属于整个设备，同时执行多个计算时错误会记到别的计算上，因此逐个执行
+    running: Mutex<()>,
+}
+
+lazy_static::lazy_static! {
+    // 第一次使用时初始化；没有可用的显卡（或只有软件渲染）时为 None
+    static ref GPU: Option<Gpu> = Gpu::new();
+}
+
+pub(crate) fn get() -> Option<&'static Gpu> {
+    GPU.as_ref()
+}
+
+fn buffer_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))?;
+        let adapter_info = adapter.get_info();
+        // 软件渲染比 CPU 路径更慢
+        if adapter_info.device_type == wgpu::DeviceType::Cpu {
+            tracing::info!(adapter = %adapter_info.name, "skipping software GPU adapter");
+            return None;
+        }
+
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("filters"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits.clone(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(|e| tracing::warn!(error = %e, "failed to open GPU device"))
+        .ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("filters"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("filters"),
+            entries: &[
+                buffer_entry(0, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer_entry(1, wgpu::BufferBindingType::Storage { read_only: false }),
+                buffer_entry(2, wgpu::BufferBindingType::Uniform),
+                buffer_entry(3, wgpu::BufferBindingType::Storage { read_only: true }),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("filters"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipelines = ENTRY_POINTS
+            .iter()
+            .map(|entry| {
+                let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(entry),
+                    layout: Some(&pipeline_layout),
+                    module: &module,
+                    entry_point: entry,
+                    compilation_options: Default::default(),
+                    cache: None,
+                });
+                (*entry, pipeline)
+            })
+            .collect();
+
+        let info = GpuInfo {
+            name: adapter_info.name,
+            backend: format!("{:?}", adapter_info.backend),
+            device_type: format!("{:?}", adapter_info.device_type),
+            max_buffer_size: (limits.max_storage_buffer_binding_size as u64).min(limits.max_buffer_size),
+        };
+        tracing::info!(adapter = %info.name, backend = %info.backend, "GPU filters available");
+        Some(Gpu {
+            device,
+            queue,
+            layout,
+            pipelines,
+            info,
+            running: Mutex::new(()),
+        })
+    }
+
+    pub(crate) fn info(&self) -> &GpuInfo {
+        &self.info
+    }
+
+    // 图片能否放进单个存储缓冲区，放不下时使用 CPU
+    pub(crate) fn fits(&self, width: u32, height: u32) -> bool {
+        width as u64 * height as u64 * 4 <= self.info.max_buffer_size
+    }
+
+    // 执行一次计算：entry 为着色器入口，结果尺寸为 dst_width x dst_height
+    // wgpu 的校验错误和显存不足在错误范围内捕获并转换为错误返回，不会触发默认的 panic
+    pub(crate) fn run(
+        &self,
+        entry: &str,
+        img: &RgbaImage,
+        dst_width: u32,
+        dst_height: u32,
+        params: Params,
+        table: &[f32],
+    ) -> Result<RgbaImage, AppError> {
+        if !self.fits(img.width(), img.height()) || !self.fits(dst_width, dst_height) {
+            return Err(AppError::TooLarge(format!(
+                "Image does not fit in a GPU buffer ({}x{} -> {}x{})",
+                img.width(),
+                img.height(),
+                dst_width,
+                dst_height
+            )));
+        }
+
+        let _running = self.running.lock();
+        self.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let result = self.dispatch(entry, img, dst_width, dst_height, params, table);
+        let validation = pollster::block_on(self.device.pop_error_scope());
+        let out_of_memory = pollster::block_on(self.device.pop_error_scope());
+        match validation.or(out_of_memory) {
+            Some(error) => Err(AppError::ProcessingFailed(format!("GPU filter failed: {}", error))),
+            None => result,
+        }
+    }
+
+    fn dispatch(
+        &self,
+        entry: &str,
+        img: &RgbaImage,
+        dst_width: u32,
+        dst_height: u32,
+        mut params: Params,
+        table: &[f32],
+    ) -> Result<RgbaImage, AppError> {
+        let pipeline = self
+            .pipelines
+            .get(entry)
+            .ok_or_else(|| AppError::Internal(format!("Unknown GPU filter: {}", entry)))?;
+        params.src_size = [img.width(), img.height()];
+        params.dst_size = [dst_width, dst_height];
+        // 存储缓冲区不能为空
+        let table = if table.is_empty() { &[0.0][..] } else { table };
+        let dst_size = dst_width as u64 * dst_height as u64 * 4;
+
+        let src = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("src"),
+            contents: img.as_raw(),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let dst = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("dst"),
+            size: dst_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let uniform = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let table = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("table"),
+            contents: bytemuck::cast_slice(table),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: dst_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(entry),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: src.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: dst.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: uniform.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: table.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(entry) });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some(entry),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(dst_width.div_ceil(WORKGROUP_SIZE), dst_height.div_ceil(WORKGROUP_SIZE), 1);
+        }
+        encoder.copy_buffer_to_buffer(&dst, 0, &readback, 0, dst_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        // 等待计算完成并读回结果
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| AppError::ProcessingFailed(format!("GPU readback failed: {}", e)))?
+            .map_err(|e| AppError::ProcessingFailed(format!("GPU readback failed: {}", e)))?;
+        let pixels = slice.get_mapped_range().to_vec();
+        readback.unmap();
+
+        RgbaImage::from_raw(dst_width, dst_height, pixels)
+            .ok_or_else(|| AppError::Internal("GPU output has an unexpected size".to_string()))
+    }
+}
@@ -0,0 +1,114 @@
+// 滤镜计算着色器：像素以 RGBA8 打包为 u32，与 image::RgbaImage 的内存布局一致
+
+struct Params {
+    src_size: vec2<u32>,
+    dst_size: vec2<u32>,
+    // adjust：亮度、对比度、饱和度、gamma
+    values: vec4<f32>,
+    // blur：卷积半径和方向
+    radius: i32,
+    horizontal: u32,
+    _pad: vec2<u32>,
+}
+
+@group(0) @binding(0) var<storage, read> src: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+@group(0) @binding(2) var<uniform> params: Params;
+// lut：768 项（R、G、B 各 256 项）；blur：radius + 1 个权重；color_matrix：按行展开的 3x3 矩阵
+@group(0) @binding(3) var<storage, read> table: array<f32>;
+
+// 读取像素，超出边界时取最近的边缘像素
+fn load(x: i32, y: i32) -> vec4<f32> {
+    let cx = clamp(x, 0, i32(params.src_size.x) - 1);
+    let cy = clamp(y, 0, i32(params.src_size.y) - 1);
+    return unpack4x8unorm(src[u32(cy) * params.src_size.x + u32(cx)]);
+}
+
+fn store(id: vec2<u32>, color: vec4<f32>) {
+    dst[id.y * params.dst_size.x + id.x] = pack4x8unorm(clamp(color, vec4<f32>(0.0), vec4<f32>(1.0)));
+}
+
+fn outside(id: vec3<u32>) -> bool {
+    return id.x >= params.dst_size.x || id.y >= params.dst_size.y;
+}
+
+@compute @workgroup_size(16, 16)
+fn adjust(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (outside(id)) {
+        return;
+    }
+    let color = load(i32(id.x), i32(id.y));
+    var rgb = color.rgb + vec3<f32>(params.values.x);
+    rgb = (rgb - vec3<f32>(0.5)) * params.values.y + vec3<f32>(0.5);
+    let luma = dot(rgb, vec3<f32>(0.299, 0.587, 0.114));
+    rgb = mix(vec3<f32>(luma), rgb, params.values.z);
+    rgb = pow(max(rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / params.values.w));
+    store(id.xy, vec4<f32>(rgb, color.a));
+}
+
+@compute @workgroup_size(16, 16)
+fn lut(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (outside(id)) {
+        return;
+    }
+    let color = load(i32(id.x), i32(id.y));
+    let index = vec3<u32>(round(color.rgb * 255.0));
+    let rgb = vec3<f32>(table[index.r], table[256u + index.g], table[512u + index.b]) / 255.0;
+    store(id.xy, vec4<f32>(rgb, color.a));
+}
+
+// 可分离的高斯模糊，水平和垂直各执行一次
+@compute @workgroup_size(16, 16)
+fn blur(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (outside(id)) {
+        return;
+    }
+    let step = select(vec2<i32>(0, 1), vec2<i32>(1, 0), params.horizontal == 1u);
+    var sum = vec4<f32>(0.0);
+    for (var i = -params.radius; i <= params.radius; i++) {
+        let offset = step * i;
+        sum += table[abs(i)] * load(i32(id.x) + offset.x, i32(id.y) + offset.y);
+    }
+    store(id.xy, sum);
+}
+
+// 双线性插值缩放
+@compute @workgroup_size(16, 16)
+fn resize(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (outside(id)) {
+        return;
+    }
+    let scale = vec2<f32>(params.src_size) / vec2<f32>(params.dst_size);
+    let position = (vec2<f32>(id.xy) + 0.5) * scale - 0.5;
+    let base = floor(position);
+    let t = position - base;
+    let x = i32(base.x);
+    let y = i32(base.y);
+    let top = mix(load(x, y), load(x + 1, y), t.x);
+    let bottom = mix(load(x, y + 1), load(x + 1, y + 1), t.x);
+    store(id.xy, mix(top, bottom, t.y));
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + vec3<f32>(0.055)) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - vec3<f32>(0.055), c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+// 在线性 RGB 中乘以 3x3 颜色矩阵（色盲模拟）
+@compute @workgroup_size(16, 16)
+fn color_matrix(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (outside(id)) {
+        return;
+    }
+    let color = load(i32(id.x), i32(id.y));
+    let rgb = srgb_to_linear(color.rgb);
+    let mixed = vec3<f32>(
+        dot(vec3<f32>(table[0], table[1], table[2]), rgb),
+        dot(vec3<f32>(table[3], table[4], table[5]), rgb),
+        dot(vec3<f32>(table[6], table[7], table[8]), rgb),
+    );
+    store(id.xy, vec4<f32>(linear_to_srgb(clamp(mixed, vec3<f32>(0.0), vec3<f32>(1.0))), color.a));
+}
@@ -0,0 +1,174 @@
+// HDR 合成：把同一场景的 2~5 张包围曝光照片合成为一张（Mertens 曝光融合）
+// 不需要曝光时间和相机响应曲线，按对比度、饱和度和曝光适中程度给每张照片的每个像素加权，
+// 用金字塔融合直接得到可以显示的结果（相当于已经过色调映射）
+// 手持拍摄时先用 MTB 按中间一张对齐（只校正平移），合成后裁掉对齐时空出的边缘
+use std::path::{Path, PathBuf};
+
+use image::{imageops, DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::fusion::{self, Blender, Planar};
+use crate::storage::save_image;
+use crate::{open_image, paths, run_blocking, scope};
+
+const MIN_EXPOSURES: usize = 2;
+const MAX_EXPOSURES: usize = 5;
+// MTB 的层数，最大可校正约 64 像素的平移
+const ALIGN_LEVELS: u32 = 6;
+// 曝光适中程度的高斯宽度
+const EXPOSURE_SIGMA: f32 = 0.2;
+
+fn one() -> f32 {
+    1.0
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HdrOptions {
+    #[serde(default = "yes")]
+    pub align: bool,
+    // 对比度、饱和度和曝光适中程度权重的指数，0 表示不考虑该项
+    #[serde(default = "one")]
+    pub contrast: f32,
+    #[serde(default = "one")]
+    pub saturation: f32,
+    #[serde(default = "one")]
+    pub exposure: f32,
+    // 为空时在第一张照片旁边生成 name_hdr.ext
+    pub output: Option<String>,
+}
+
+impl Default for HdrOptions {
+    fn default() -> Self {
+        Self {
+            align: true,
+            contrast: 1.0,
+            saturation: 1.0,
+            exposure: 1.0,
+            output: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HdrResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    // 每张照片对齐时的平移量（像素）
+    pub offsets: Vec<(i32, i32)>,
+}
+
+// Mertens 权重：对比度^wc × 饱和度^ws × 曝光适中程度^we
+fn weights(img: &Planar, options: &HdrOptions) -> Planar {
+    let luminance = fusion::luminance(img);
+    let contrast = fusion::contrast(&luminance, img.width, img.height);
+    let mut weight = Planar::new(img.width, img.height, 1);
+    for (i, pixel) in img.data.chunks_exact(3).enumerate() {
+        let mean = (pixel[0] + pixel[1] + pixel[2]) / 3.0;
+        let saturation = (pixel.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / 3.0).sqrt();
+        let exposure: f32 = pixel
+            .iter()
+            .map(|v| (-(v - 0.5).powi(2) / (2.0 * EXPOSURE_SIGMA * EXPOSURE_SIGMA)).exp())
+            .product();
+        weight.data[i] = contrast[i].powf(options.contrast)
+            * saturation.powf(options.saturation)
+            * exposure.powf(options.exposure)
+            + 1e-6;
+    }
+    weight
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "jpg".to_string());
+            path.with_file_name(format!("{}_hdr.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 合成包围曝光照片，所有照片的尺寸必须相同
+#[tauri::command]
+pub async fn merge_hdr(paths: Vec<String>, options: Option<HdrOptions>) -> Result<HdrResult, AppError> {
+    if !(MIN_EXPOSURES..=MAX_EXPOSURES).contains(&paths.len()) {
+        return Err(AppError::InvalidArgument(format!(
+            "HDR merge needs {} to {} exposures, got {}",
+            MIN_EXPOSURES,
+            MAX_EXPOSURES,
+            paths.len()
+        )));
+    }
+    for path in &paths {
+        scope::check(path)?;
+    }
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    for value in [options.contrast, options.saturation, options.exposure] {
+        if !(0.0..=5.0).contains(&value) {
+            return Err(AppError::InvalidArgument(format!(
+                "Weight exponents must be between 0 and 5: {}",
+                value
+            )));
+        }
+    }
+
+    run_blocking("merge_hdr", move || {
+        let reference = open_image(&paths[paths.len() / 2])?;
+        let (width, height) = reference.dimensions();
+        let reference_gray = reference.to_luma8();
+
+        let mut offsets = Vec::with_capacity(paths.len());
+        let mut blender = Blender::new(width, height);
+        for path in &paths {
+            let img = open_image(path)?;
+            if img.dimensions() != (width, height) {
+                return Err(AppError::InvalidArgument(format!(
+                    "All exposures must have the same size ({}x{})",
+                    width, height
+                ))
+                .at(path));
+            }
+            let offset = if options.align {
+                fusion::mtb_offset(&reference_gray, &img.to_luma8(), ALIGN_LEVELS)
+            } else {
+                (0, 0)
+            };
+            let planar = Planar::from_rgb(&fusion::shift(&img.to_rgb8(), offset.0, offset.1));
+            blender.add(&planar, &weights(&planar, &options));
+            offsets.push(offset);
+        }
+        let merged = blender.finish().to_rgb();
+
+        // 裁掉平移后由边缘像素填充的部分
+        let left = offsets.iter().map(|o| o.0.max(0)).max().unwrap_or(0) as u32;
+        let right = offsets.iter().map(|o| (-o.0).max(0)).max().unwrap_or(0) as u32;
+        let top = offsets.iter().map(|o| o.1.max(0)).max().unwrap_or(0) as u32;
+        let bottom = offsets.iter().map(|o| (-o.1).max(0)).max().unwrap_or(0) as u32;
+        let merged = if left + right < width && top + bottom < height {
+            imageops::crop_imm(&merged, left, top, width - left - right, height - top - bottom).to_image()
+        } else {
+            merged
+        };
+
+        let output = output_path(Path::new(&paths[0]), options.output);
+        let (out_width, out_height) = merged.dimensions();
+        save_image(&DynamicImage::ImageRgb8(merged), &output)?;
+        Ok(HdrResult {
+            output: paths::display(&output),
+            width: out_width,
+            height: out_height,
+            offsets,
+        })
+    })
+    .await
+}
@@ -0,0 +1,152 @@
+// 编辑历史：每次修改图片后把操作及参数追加到旁车文件 photo.jpg.history.json
+// 可以在原图备份上重新应用整段历史，或恢复原图并清空历史
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::io::Reader as ImageReader;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::adjust::{self, Adjustment};
+use crate::backup;
+use crate::error::AppError;
+use crate::filters::{self, Backend, Filter};
+use crate::plugins::PluginRegistry;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::{save_image, write_app_file};
+use crate::{crop_relative, paths};
+
+// 可以重新应用的编辑操作
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EditOperation {
+    Resize { width: u32, height: u32 },
+    // 相对坐标（0-1）
+    Crop { x: f32, y: f32, width: f32, height: f32 },
+    Plugin { name: String, params: serde_json::Value },
+    Filter { filter: Filter },
+    Adjust { adjustment: Adjustment },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EditRecord {
+    pub operation: EditOperation,
+    // Unix 秒
+    pub timestamp: u64,
+    // 操作读取的源文件，输出到其他文件时与记录所在的图片不同
+    pub source: String,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// 图片对应的历史文件：photo.jpg -> photo.jpg.history.json（保留扩展名，避免同名不同格式的图片冲突）
+pub fn history_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".history.json");
+    path.with_file_name(name)
+}
+
+fn read(path: &Path) -> Vec<EditRecord> {
+    fs::read_to_string(history_path(path))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write(path: &Path, records: &[EditRecord]) -> Result<(), AppError> {
+    write_app_file(&history_path(path), &serde_json::to_vec_pretty(records)?)
+}
+
+// 保存修改后调用：输出图片的历史为源文件的历史加上本次操作
+pub(crate) fn record(source: &Path, output: &Path, operation: EditOperation) -> Result<(), AppError> {
+    record_all(source, output, [operation])
+}
+
+// 一次保存中依次应用了多个操作时使用
+pub(crate) fn record_all(
+    source: &Path,
+    output: &Path,
+    operations: impl IntoIterator<Item = EditOperation>,
+) -> Result<(), AppError> {
+    let timestamp = now();
+    let mut records = read(source);
+    records.extend(operations.into_iter().map(|operation| EditRecord {
+        operation,
+        timestamp,
+        source: paths::display(source),
+    }));
+    write(output, &records)
+}
+
+// 删除图片的编辑历史
+pub(crate) fn clear(path: &Path) -> Result<(), AppError> {
+    let file = history_path(path);
+    match fs::remove_file(&file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(AppError::from(e).at(&file)),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn apply(app: &AppHandle, img: image::DynamicImage, operation: &EditOperation) -> Result<image::DynamicImage, AppError> {
+    Ok(match operation {
+        EditOperation::Resize { width, height } => img.resize(*width, *height, image::imageops::FilterType::Triangle),
+        EditOperation::Crop { x, y, width, height } => crop_relative(&img, *x, *y, *width, *height),
+        EditOperation::Plugin { name, params } => app.state::<PluginRegistry>().apply(name, &img, params)?,
+        EditOperation::Filter { filter } => filters::apply(&img, filter, Backend::Auto)?.0,
+        EditOperation::Adjust { adjustment } => adjust::apply(img, adjustment)?,
+    })
+}
+
+#[tauri::command]
+pub fn get_edit_history(path: &str) -> Result<Vec<EditRecord>, AppError> {
+    scope::check(path)?;
+    Ok(read(Path::new(path)))
+}
+
+// 恢复原图并清空编辑历史
+#[tauri::command]
+pub fn revert_edit_history(path: &str) -> Result<bool, AppError> {
+    backup::restore_original(path)
+}
+
+// 在原图备份上依次重新应用编辑历史，结果写入 output（为空时覆盖当前图片）
+#[tauri::command]
+pub async fn reapply_edit_history(app: AppHandle, path: String, output: Option<String>) -> Result<bool, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("reapply_edit_history", move || {
+        let source = Path::new(&path);
+        let original = backup::original_file(source)
+            .ok_or_else(|| AppError::NotFound(format!("No original backup for {}", path)))?;
+        let records = read(source);
+
+        let mut img = ImageReader::open(&original)
+            .map_err(|e| AppError::from(e).at(&original))?
+            .with_guessed_format()
+            .map_err(|e| AppError::from(e).at(&original))?
+            .decode()
+            .map_err(|e| AppError::from(e).at(&original))?;
+        for record in &records {
+            img = apply(&app, img, &record.operation)?;
+        }
+
+        let output = output.map(PathBuf::from).unwrap_or_else(|| source.to_path_buf());
+        save_image(&img, &output)?;
+        if output != source {
+            write(&output, &records)?;
+        }
+        Ok(true)
+    })
+    .await
+}
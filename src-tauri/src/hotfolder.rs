@@ -0,0 +1,210 @@
+// 监视文件夹：新放入文件夹的图片自动按预设处理（例如相机导入后统一缩放并转换格式）
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use image::{DynamicImage, ImageFormat};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::error::AppError;
+use crate::history::{self, EditOperation};
+use crate::listing::{list_files, SymlinkPolicy};
+use crate::storage::{output_format, save_image_with_format};
+use crate::{open_image, paths, prepare_for_format, scope};
+
+// 轮询文件夹的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(2000);
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+
+// 处理预设：依次应用 operations，再以 format 格式保存到 output_dir
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HotFolderPreset {
+    #[serde(default)]
+    pub operations: Vec<EditOperation>,
+    // 输出格式（扩展名）
+    pub format: String,
+    // 为空时输出到监视文件夹下的 processed 子文件夹
+    pub output_dir: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HotFolderInfo {
+    pub path: String,
+    pub preset: HotFolderPreset,
+}
+
+// 每处理完一个文件发送一次 hot-folder-file 事件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HotFolderEvent {
+    pub folder: String,
+    pub path: String,
+    pub output: Option<String>,
+    pub error: Option<AppError>,
+}
+
+struct HotFolder {
+    preset: HotFolderPreset,
+    stop: Arc<AtomicBool>,
+}
+
+// 正在监视的文件夹
+#[derive(Default)]
+pub struct HotFolderState {
+    folders: Mutex<HashMap<String, HotFolder>>,
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn images_in(dir: &Path) -> Vec<PathBuf> {
+    list_files(dir, SymlinkPolicy::Skip, false)
+        .map(|listing| listing.files.into_iter().filter(|p| is_image(p)).collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn process(app: &AppHandle, path: &Path, preset: &HotFolderPreset, output_dir: &Path) -> Result<String, AppError> {
+    let output = output_dir.join(format!("{}.{}", paths::file_stem(path, "image"), preset.format));
+    process_to(app, path, preset, &output)
+}
+
+// 按预设的操作处理并写入 output，输出格式由 output 的扩展名决定
+pub(crate) fn process_to(app: &AppHandle, path: &Path, preset: &HotFolderPreset, output: &Path) -> Result<String, AppError> {
+    let ext = output.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let format = output_format(&ext)?;
+
+    let mut img = Arc::unwrap_or_clone(open_image(path)?);
+    for operation in &preset.operations {
+        img = history::apply(app, img, operation)?;
+    }
+    let mut img = prepare_for_format(img, &ext);
+    // JPEG 不支持透明通道
+    if format == ImageFormat::Jpeg {
+        img = DynamicImage::ImageRgb8(img.to_rgb8());
+    }
+
+    save_image_with_format(&img, output, format)?;
+    history::record_all(path, output, preset.operations.iter().cloned())?;
+    Ok(paths::display(output))
+}
+
+// 后台线程：发现新文件后等待其大小不再变化（仍在复制中的文件不处理），再按预设处理
+fn spawn_watcher(app: AppHandle, folder: String, preset: HotFolderPreset, output_dir: PathBuf, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let dir = Path::new(&folder);
+        // 开始监视前已有的文件不处理
+        let mut seen: HashSet<PathBuf> = images_in(dir).into_iter().collect();
+        let mut pending: HashMap<PathBuf, u64> = HashMap::new();
+
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(POLL_INTERVAL);
+
+            for path in images_in(dir) {
+                if seen.contains(&path) {
+                    continue;
+                }
+                let Ok(size) = fs::metadata(&path).map(|m| m.len()) else {
+                    continue;
+                };
+                if pending.insert(path.clone(), size) != Some(size) {
+                    continue;
+                }
+
+                pending.remove(&path);
+                seen.insert(path.clone());
+                let (output, error) = match process(&app, &path, &preset, &output_dir) {
+                    Ok(output) => (Some(output), None),
+                    Err(error) => {
+                        tracing::warn!(path = %path.display(), error = %error, "hot folder processing failed");
+                        (None, Some(error))
+                    }
+                };
+                let _ = app.emit(
+                    "hot-folder-file",
+                    HotFolderEvent {
+                        folder: folder.clone(),
+                        path: paths::display(&path),
+                        output,
+                        error,
+                    },
+                );
+            }
+        }
+    });
+}
+
+// 开始监视文件夹，已在监视时替换为新的预设
+#[tauri::command]
+pub fn start_hot_folder(
+    app: AppHandle,
+    state: State<'_, HotFolderState>,
+    path: &str,
+    preset: HotFolderPreset,
+) -> Result<bool, AppError> {
+    scope::check(path)?;
+    if !Path::new(path).is_dir() {
+        return Err(AppError::NotFound(path.to_string()));
+    }
+    let format = ImageFormat::from_extension(&preset.format)
+        .ok_or_else(|| AppError::Unsupported(format!("Output format {}", preset.format)))?;
+    if !format.writing_enabled() {
+        return Err(AppError::Unsupported(format!("Writing {} images", preset.format)));
+    }
+
+    let output_dir = match &preset.output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(path).join("processed"),
+    };
+    scope::check(&output_dir)?;
+    fs::create_dir_all(&output_dir).map_err(|e| AppError::from(e).at(&output_dir))?;
+
+    let mut folders = state.folders.lock();
+    if let Some(previous) = folders.remove(path) {
+        previous.stop.store(true, Ordering::Relaxed);
+    }
+    let stop = Arc::new(AtomicBool::new(false));
+    folders.insert(
+        path.to_string(),
+        HotFolder {
+            preset: preset.clone(),
+            stop: stop.clone(),
+        },
+    );
+    spawn_watcher(app, path.to_string(), preset, output_dir, stop);
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn stop_hot_folder(state: State<'_, HotFolderState>, path: &str) -> bool {
+    match state.folders.lock().remove(path) {
+        Some(folder) => {
+            folder.stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn list_hot_folders(state: State<'_, HotFolderState>) -> Vec<HotFolderInfo> {
+    state
+        .folders
+        .lock()
+        .iter()
+        .map(|(path, folder)| HotFolderInfo {
+            path: path.clone(),
+            preset: folder.preset.clone(),
+        })
+        .collect()
+}
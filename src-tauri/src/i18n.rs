@@ -0,0 +1,66 @@
+// 错误信息目录：按错误码提供面向用户的本地化说明，目前支持 en 和 zh-CN
+use std::collections::HashMap;
+
+use crate::error::AppError;
+use crate::settings;
+
+pub const SUPPORTED_LOCALES: [&str; 2] = ["en", "zh-CN"];
+
+// (错误码, 英文, 简体中文)
+const CATALOG: [(&str, &str, &str); 13] = [
+    ("NOT_FOUND", "The file or folder could not be found.", "找不到指定的文件或文件夹。"),
+    ("UNSUPPORTED", "This file type or operation is not supported.", "不支持该文件类型或操作。"),
+    ("DECODE_FAILED", "The image could not be read. It may be damaged.", "无法读取图片，文件可能已损坏。"),
+    ("ENCODE_FAILED", "The image could not be saved in this format.", "无法以该格式保存图片。"),
+    ("IO", "The file could not be read or written.", "读写文件时出错。"),
+    ("PERMISSION_DENIED", "Access to this location was denied. Choose it again in the file dialog.", "没有访问该位置的权限，请通过文件对话框重新选择。"),
+    ("TOO_LARGE", "The image is too large to process.", "图片过大，无法处理。"),
+    ("INVALID_ARGUMENT", "Some of the options are invalid.", "部分参数无效。"),
+    ("NETWORK", "The remote service could not be reached.", "无法连接远程服务。"),
+    ("DEVICE", "The device is unavailable.", "设备不可用。"),
+    ("PROCESSING_FAILED", "The image could not be processed.", "处理图片时出错。"),
+    ("TIMEOUT", "The operation took too long and was stopped.", "操作超时，已停止。"),
+    ("INTERNAL", "Something went wrong inside the app.", "应用内部出错。"),
+];
+
+// 把 zh、zh_CN、zh-Hans 等写法统一为支持的语言，其他语言返回 None
+pub(crate) fn normalize(locale: &str) -> Option<&'static str> {
+    let locale = locale.to_lowercase().replace('_', "-");
+    if locale.starts_with("zh") {
+        Some("zh-CN")
+    } else if locale == "en" || locale.starts_with("en-") {
+        Some("en")
+    } else {
+        None
+    }
+}
+
+// 错误码对应的说明，未知语言回退到英文，未知错误码使用 INTERNAL 的说明
+pub(crate) fn error_message(code: &str, locale: &str) -> &'static str {
+    let (_, en, zh) = CATALOG
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .unwrap_or(&CATALOG[CATALOG.len() - 1]);
+    match normalize(locale) {
+        Some("zh-CN") => zh,
+        _ => en,
+    }
+}
+
+// 按设置中的界面语言返回错误说明
+pub(crate) fn localize(error: &AppError) -> &'static str {
+    error_message(error.code(), &settings::current().language)
+}
+
+// 返回某种语言的完整错误信息目录，locale 为空时使用设置中的界面语言
+#[tauri::command]
+pub fn get_error_messages(locale: Option<String>) -> Result<HashMap<String, String>, AppError> {
+    let locale = locale.unwrap_or_else(|| settings::current().language);
+    if normalize(&locale).is_none() {
+        return Err(AppError::InvalidArgument(format!("Unsupported locale: {}", locale)));
+    }
+    Ok(CATALOG
+        .iter()
+        .map(|(code, _, _)| (code.to_string(), error_message(code, &locale).to_string()))
+        .collect())
+}
@@ -0,0 +1,446 @@
+// 图标生成：从一张源图生成
+//   网站 favicon 全套文件（favicon.ico、apple-touch-icon、android-chrome 和 site.webmanifest）
+//   应用图标：iOS 的 AppIcon.appiconset、Android 的 mipmap-*（含自适应图标）、Windows 的 .ico 和 macOS 的 .icns
+// 非正方形的源图按比例缩放后居中放在正方形画布上
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use image::codecs::ico::{IcoEncoder, IcoFrame};
+use image::imageops::{self, FilterType};
+use image::{ColorType, DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::AppError;
+use crate::storage::{save_image, write_bytes};
+use crate::text::parse_color;
+use crate::{open_image, paths, run_blocking, scope};
+
+// 多尺寸 ICO 包含的尺寸（不超过源图的尺寸）
+pub(crate) const ICO_SIZES: [u32; 6] = [16, 24, 32, 48, 64, 256];
+const FAVICON_ICO_SIZES: [u32; 3] = [16, 32, 48];
+// 应用图标建议使用的源图尺寸
+const MASTER_SIZE: u32 = 1024;
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+// iOS 图标：(idiom, 点数, 倍数)
+const IOS_ICONS: [(&str, f32, u32); 18] = [
+    ("iphone", 20.0, 2),
+    ("iphone", 20.0, 3),
+    ("iphone", 29.0, 2),
+    ("iphone", 29.0, 3),
+    ("iphone", 40.0, 2),
+    ("iphone", 40.0, 3),
+    ("iphone", 60.0, 2),
+    ("iphone", 60.0, 3),
+    ("ipad", 20.0, 1),
+    ("ipad", 20.0, 2),
+    ("ipad", 29.0, 1),
+    ("ipad", 29.0, 2),
+    ("ipad", 40.0, 1),
+    ("ipad", 40.0, 2),
+    ("ipad", 76.0, 1),
+    ("ipad", 76.0, 2),
+    ("ipad", 83.5, 2),
+    ("ios-marketing", 1024.0, 1),
+];
+// Android 各密度目录与启动图标的尺寸（48dp）
+const ANDROID_DENSITIES: [(&str, u32); 5] =
+    [("mdpi", 48), ("hdpi", 72), ("xhdpi", 96), ("xxhdpi", 144), ("xxxhdpi", 192)];
+// 自适应图标的前景为 108dp，只有中间 72dp 保证可见
+const ADAPTIVE_SCALE: f32 = 108.0 / 48.0;
+const ADAPTIVE_PADDING: f32 = (108.0 - 72.0) / 2.0 / 108.0 * 100.0;
+// macOS .icns 中以 PNG 存储的图标类型
+const ICNS_TYPES: [(&[u8; 4], u32); 10] = [
+    (b"icp4", 16),
+    (b"icp5", 32),
+    (b"ic11", 32),
+    (b"ic12", 64),
+    (b"ic07", 128),
+    (b"ic13", 256),
+    (b"ic08", 256),
+    (b"ic14", 512),
+    (b"ic09", 512),
+    (b"ic10", 1024),
+];
+const ADAPTIVE_ICON_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<adaptive-icon xmlns:android="http://schemas.android.com/apk/res/android">
+    <background android:drawable="@color/ic_launcher_background"/>
+    <foreground android:drawable="@mipmap/ic_launcher_foreground"/>
+</adaptive-icon>
+"#;
+const BACKGROUND_COLOR_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<resources>
+    <color name="ic_launcher_background">{color}</color>
+</resources>
+"#;
+// Linux 桌面等使用的 PNG 尺寸
+const DESKTOP_PNG_SIZES: [u32; 5] = [32, 64, 128, 256, 512];
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FaviconOptions {
+    // 图标四周留白，占边长的百分比
+    pub padding: f32,
+    // 背景颜色，为空时保持透明（apple-touch-icon 不支持透明，为空时使用白色）
+    pub background: Option<String>,
+    // 网站名称，写入 site.webmanifest
+    pub name: String,
+    pub short_name: Option<String>,
+    pub theme_color: String,
+    // 图标在网站上的路径前缀
+    pub path_prefix: String,
+}
+
+impl Default for FaviconOptions {
+    fn default() -> Self {
+        Self {
+            padding: 0.0,
+            background: None,
+            name: String::new(),
+            short_name: None,
+            theme_color: "#ffffff".to_string(),
+            path_prefix: "/".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FaviconResult {
+    pub files: Vec<String>,
+    // 放在 <head> 中的 link 标签
+    pub html: String,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IconPlatform {
+    Ios,
+    Android,
+    Windows,
+    Macos,
+    Desktop,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppIconOptions {
+    pub platforms: Vec<IconPlatform>,
+    // 图标四周留白，占边长的百分比（Android 自适应图标另外留出安全区）
+    pub padding: f32,
+    // 背景颜色，为空时保持透明；iOS 图标和 Android 自适应图标的背景不能透明，为空时使用白色
+    pub background: Option<String>,
+}
+
+impl Default for AppIconOptions {
+    fn default() -> Self {
+        Self {
+            platforms: vec![
+                IconPlatform::Ios,
+                IconPlatform::Android,
+                IconPlatform::Windows,
+                IconPlatform::Macos,
+                IconPlatform::Desktop,
+            ],
+            padding: 0.0,
+            background: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppIconResult {
+    pub files: Vec<String>,
+    // 源图不是 1024px 以上的正方形等问题
+    pub warnings: Vec<String>,
+}
+
+fn check_padding(padding: f32) -> Result<(), AppError> {
+    if !(0.0..50.0).contains(&padding) {
+        return Err(AppError::InvalidArgument(format!("Padding must be between 0 and 50%: {}", padding)));
+    }
+    Ok(())
+}
+
+// 把图片按比例缩放到 size x size 的正方形画布中央，四周留出 padding（百分比）
+pub(crate) fn square_icon(img: &DynamicImage, size: u32, padding: f32, background: Option<Rgba<u8>>) -> RgbaImage {
+    let inner = ((size as f32 * (1.0 - padding * 2.0 / 100.0)).round() as u32).clamp(1, size);
+    let (width, height) = img.dimensions();
+    let scale = inner as f32 / width.max(height) as f32;
+    let scaled_width = ((width as f32 * scale).round() as u32).max(1);
+    let scaled_height = ((height as f32 * scale).round() as u32).max(1);
+    let scaled = img.resize_exact(scaled_width, scaled_height, FilterType::Lanczos3).to_rgba8();
+
+    let mut canvas = RgbaImage::from_pixel(size, size, background.unwrap_or(Rgba([0, 0, 0, 0])));
+    let x = (size - scaled_width) / 2;
+    let y = (size - scaled_height) / 2;
+    imageops::overlay(&mut canvas, &scaled, x as i64, y as i64);
+    canvas
+}
+
+// 编码包含多个尺寸的 ICO，每个尺寸以 PNG 存储；只保留不超过源图最长边的尺寸
+pub(crate) fn encode_ico(
+    img: &DynamicImage,
+    sizes: &[u32],
+    padding: f32,
+    background: Option<Rgba<u8>>,
+) -> Result<Vec<u8>, AppError> {
+    let longest = img.width().max(img.height());
+    let mut sizes: Vec<u32> = sizes.iter().copied().filter(|size| *size <= longest).collect();
+    if sizes.is_empty() {
+        sizes.push(longest.min(256));
+    }
+    let icons: Vec<RgbaImage> = sizes.iter().map(|size| square_icon(img, *size, padding, background)).collect();
+    let frames = icons
+        .iter()
+        .map(|icon| IcoFrame::as_png(icon.as_raw(), icon.width(), icon.height(), ColorType::Rgba8))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut data = Cursor::new(Vec::new());
+    IcoEncoder::new(&mut data).encode_images(&frames)?;
+    Ok(data.into_inner())
+}
+
+// 从 path 生成 favicon 全套文件，写入 output_dir
+#[tauri::command]
+pub async fn generate_favicons(
+    path: String,
+    output_dir: String,
+    options: Option<FaviconOptions>,
+) -> Result<FaviconResult, AppError> {
+    scope::check(&path)?;
+    scope::check(&output_dir)?;
+    let options = options.unwrap_or_default();
+    check_padding(options.padding)?;
+    let background = options.background.as_deref().map(parse_color).transpose()?;
+    let theme_color = parse_color(&options.theme_color)?;
+
+    run_blocking("generate_favicons", move || {
+        let img = open_image(&path)?;
+        let dir = Path::new(&output_dir);
+        fs::create_dir_all(dir).map_err(|e| AppError::from(e).at(dir))?;
+        let mut files = Vec::new();
+
+        let ico = dir.join("favicon.ico");
+        write_bytes(&ico, &encode_ico(&img, &FAVICON_ICO_SIZES, options.padding, background)?)?;
+        files.push(paths::display(&ico));
+
+        // iOS 把透明部分显示为黑色，apple-touch-icon 总是填充背景
+        let pngs = [
+            ("favicon-16x16.png", 16, background),
+            ("favicon-32x32.png", 32, background),
+            ("apple-touch-icon.png", 180, Some(background.unwrap_or(WHITE))),
+            ("android-chrome-192x192.png", 192, background),
+            ("android-chrome-512x512.png", 512, background),
+        ];
+        for (name, size, background) in pngs {
+            let icon = square_icon(&img, size, options.padding, background);
+            let output = dir.join(name);
+            save_image(&DynamicImage::ImageRgba8(icon), &output)?;
+            files.push(paths::display(&output));
+        }
+
+        let prefix = options.path_prefix.trim_end_matches('/');
+        let hex = |color: Rgba<u8>| format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2]);
+        let manifest = json!({
+            "name": options.name,
+            "short_name": options.short_name.clone().unwrap_or_else(|| options.name.clone()),
+            "icons": [
+                { "src": format!("{}/android-chrome-192x192.png", prefix), "sizes": "192x192", "type": "image/png" },
+                { "src": format!("{}/android-chrome-512x512.png", prefix), "sizes": "512x512", "type": "image/png" },
+            ],
+            "theme_color": hex(theme_color),
+            "background_color": hex(background.unwrap_or(WHITE)),
+            "display": "standalone",
+        });
+        let webmanifest = dir.join("site.webmanifest");
+        write_bytes(&webmanifest, &serde_json::to_vec_pretty(&manifest)?)?;
+        files.push(paths::display(&webmanifest));
+
+        let html = [
+            format!("<link rel=\"icon\" href=\"{}/favicon.ico\" sizes=\"any\">", prefix),
+            format!("<link rel=\"icon\" type=\"image/png\" sizes=\"32x32\" href=\"{}/favicon-32x32.png\">", prefix),
+            format!("<link rel=\"icon\" type=\"image/png\" sizes=\"16x16\" href=\"{}/favicon-16x16.png\">", prefix),
+            format!("<link rel=\"apple-touch-icon\" sizes=\"180x180\" href=\"{}/apple-touch-icon.png\">", prefix),
+            format!("<link rel=\"manifest\" href=\"{}/site.webmanifest\">", prefix),
+            format!("<meta name=\"theme-color\" content=\"{}\">", hex(theme_color)),
+        ]
+        .join("\n");
+
+        Ok(FaviconResult { files, html })
+    })
+    .await
+}
+
+// 圆形图标：圆外透明，边缘抗锯齿
+fn round_icon(mut icon: RgbaImage) -> RgbaImage {
+    let radius = icon.width() as f32 / 2.0;
+    for (x, y, pixel) in icon.enumerate_pixels_mut() {
+        let distance = (x as f32 + 0.5 - radius).hypot(y as f32 + 0.5 - radius);
+        let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+        pixel[3] = (pixel[3] as f32 * coverage).round() as u8;
+    }
+    icon
+}
+
+// 编码 macOS 的 .icns：每个条目为 4 字节类型、4 字节长度（含条目头）和 PNG 数据
+fn encode_icns(img: &DynamicImage, padding: f32, background: Option<Rgba<u8>>) -> Result<Vec<u8>, AppError> {
+    let mut entries = Vec::new();
+    for (kind, size) in ICNS_TYPES {
+        let icon = square_icon(img, size, padding, background);
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(icon).write_to(&mut png, ImageFormat::Png)?;
+        let png = png.into_inner();
+        entries.extend_from_slice(kind);
+        entries.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+        entries.extend_from_slice(&png);
+    }
+    let mut data = Vec::with_capacity(entries.len() + 8);
+    data.extend_from_slice(b"icns");
+    data.extend_from_slice(&(entries.len() as u32 + 8).to_be_bytes());
+    data.extend_from_slice(&entries);
+    Ok(data)
+}
+
+fn save_png(icon: RgbaImage, path: &Path, files: &mut Vec<String>) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::from(e).at(parent))?;
+    }
+    save_image(&DynamicImage::ImageRgba8(icon), path)?;
+    files.push(paths::display(path));
+    Ok(())
+}
+
+fn save_file(data: &[u8], path: &Path, files: &mut Vec<String>) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::from(e).at(parent))?;
+    }
+    write_bytes(path, data)?;
+    files.push(paths::display(path));
+    Ok(())
+}
+
+// iOS 图标不能有透明部分
+fn ios_icons(
+    img: &DynamicImage,
+    dir: &Path,
+    padding: f32,
+    background: Rgba<u8>,
+    files: &mut Vec<String>,
+) -> Result<(), AppError> {
+    let dir = dir.join("ios").join("AppIcon.appiconset");
+    let mut images = Vec::new();
+    let mut written = Vec::new();
+    for (idiom, points, scale) in IOS_ICONS {
+        let size = (points * scale as f32).round() as u32;
+        let points = if points.fract() == 0.0 { format!("{}", points as u32) } else { format!("{}", points) };
+        let filename = format!("Icon-{}@{}x.png", points, scale);
+        // iPhone 和 iPad 中相同尺寸的图标共用一个文件
+        if !written.contains(&filename) {
+            save_png(square_icon(img, size, padding, Some(background)), &dir.join(&filename), files)?;
+            written.push(filename.clone());
+        }
+        images.push(json!({
+            "filename": filename,
+            "idiom": idiom,
+            "scale": format!("{}x", scale),
+            "size": format!("{}x{}", points, points),
+        }));
+    }
+    let contents = json!({ "images": images, "info": { "author": "xcode", "version": 1 } });
+    save_file(&serde_json::to_vec_pretty(&contents)?, &dir.join("Contents.json"), files)
+}
+
+// Android 启动图标：传统图标、圆形图标和自适应图标（API 26+）
+fn android_icons(
+    img: &DynamicImage,
+    dir: &Path,
+    padding: f32,
+    background: Option<Rgba<u8>>,
+    files: &mut Vec<String>,
+) -> Result<(), AppError> {
+    let res = dir.join("android").join("res");
+    for (density, size) in ANDROID_DENSITIES {
+        let mipmap = res.join(format!("mipmap-{}", density));
+        save_png(square_icon(img, size, padding, background), &mipmap.join("ic_launcher.png"), files)?;
+        let round = round_icon(square_icon(img, size, padding, Some(background.unwrap_or(WHITE))));
+        save_png(round, &mipmap.join("ic_launcher_round.png"), files)?;
+        // 前景透明，背景由 ic_launcher_background 颜色提供；内容缩进到安全区内
+        let foreground_size = (size as f32 * ADAPTIVE_SCALE).round() as u32;
+        let foreground_padding = ADAPTIVE_PADDING + padding * (1.0 - ADAPTIVE_PADDING * 2.0 / 100.0);
+        let foreground = square_icon(img, foreground_size, foreground_padding, None);
+        save_png(foreground, &mipmap.join("ic_launcher_foreground.png"), files)?;
+    }
+
+    let anydpi = res.join("mipmap-anydpi-v26");
+    save_file(ADAPTIVE_ICON_XML.as_bytes(), &anydpi.join("ic_launcher.xml"), files)?;
+    save_file(ADAPTIVE_ICON_XML.as_bytes(), &anydpi.join("ic_launcher_round.xml"), files)?;
+
+    let color = background.unwrap_or(WHITE);
+    let values = BACKGROUND_COLOR_XML.replace("{color}", &format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2]));
+    save_file(values.as_bytes(), &res.join("values").join("ic_launcher_background.xml"), files)?;
+
+    // Google Play 商店图标
+    let playstore = square_icon(img, 512, padding, Some(background.unwrap_or(WHITE)));
+    save_png(playstore, &dir.join("android").join("ic_launcher-playstore.png"), files)
+}
+
+// 从一张源图（建议 1024px 以上的正方形）生成各平台的应用图标，写入 output_dir 下的各平台目录
+#[tauri::command]
+pub async fn generate_app_icons(
+    path: String,
+    output_dir: String,
+    options: Option<AppIconOptions>,
+) -> Result<AppIconResult, AppError> {
+    scope::check(&path)?;
+    scope::check(&output_dir)?;
+    let options = options.unwrap_or_default();
+    check_padding(options.padding)?;
+    if options.platforms.is_empty() {
+        return Err(AppError::InvalidArgument("No icon platforms selected".to_string()));
+    }
+    let background = options.background.as_deref().map(parse_color).transpose()?;
+
+    run_blocking("generate_app_icons", move || {
+        let img = open_image(&path)?;
+        let dir = Path::new(&output_dir);
+        let (width, height) = img.dimensions();
+        let mut warnings = Vec::new();
+        if width != height {
+            warnings.push(format!("Source is not square ({}x{}); it is centered on a square canvas", width, height));
+        }
+        if width.min(height) < MASTER_SIZE {
+            warnings.push(format!(
+                "Source is smaller than {}px ({}x{}); large icons are upscaled",
+                MASTER_SIZE, width, height
+            ));
+        }
+
+        let mut files = Vec::new();
+        let padding = options.padding;
+        for platform in &options.platforms {
+            match platform {
+                IconPlatform::Ios => ios_icons(&img, dir, padding, background.unwrap_or(WHITE), &mut files)?,
+                IconPlatform::Android => android_icons(&img, dir, padding, background, &mut files)?,
+                IconPlatform::Windows => {
+                    let data = encode_ico(&img, &ICO_SIZES, padding, background)?;
+                    save_file(&data, &dir.join("windows").join("app.ico"), &mut files)?;
+                }
+                IconPlatform::Macos => {
+                    let data = encode_icns(&img, padding, background)?;
+                    save_file(&data, &dir.join("macos").join("app.icns"), &mut files)?;
+                }
+                IconPlatform::Desktop => {
+                    for size in DESKTOP_PNG_SIZES {
+                        let icon = square_icon(&img, size, padding, background);
+                        save_png(icon, &dir.join("desktop").join(format!("{}x{}.png", size, size)), &mut files)?;
+                    }
+                }
+            }
+        }
+        Ok(AppIconResult { files, warnings })
+    })
+    .await
+}
@@ -0,0 +1,115 @@
+// 外部打开请求：处理 imageeditor://open?path=... 链接、文件关联以及命令行参数
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use url::Url;
+
+use crate::ImageInfo;
+use crate::error::AppError;
+use crate::paths;
+use crate::scope;
+
+pub const URL_SCHEME: &str = "imageeditor";
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+
+// 前端尚未就绪时收到的打开请求，由前端启动后主动获取
+#[derive(Default)]
+pub struct LaunchState {
+    pending: Mutex<Vec<ImageInfo>>,
+    // 前端获取过待处理请求后，后续请求只通过事件通知
+    ready: AtomicBool,
+}
+
+// 校验并读取要打开的图片：必须是存在的图片文件，且文件头可以解析
+fn validate(path: &Path) -> Result<ImageInfo, AppError> {
+    let path = fs::canonicalize(path).map_err(|e| AppError::from(e).at(path))?;
+    if !path.is_file() {
+        return Err(AppError::NotFound(path.display().to_string()));
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if !IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(AppError::Unsupported(path.display().to_string()));
+    }
+
+    let (width, height) =
+        image::image_dimensions(&path).map_err(|e| AppError::from(e).at(&path))?;
+    let size = fs::metadata(&path)
+        .map_err(AppError::from)?
+        .len();
+
+    Ok(ImageInfo {
+        path: paths::display(&path),
+        name: paths::file_name(&path),
+        width,
+        height,
+        size,
+        poster: None,
+    })
+}
+
+// 从链接中解析文件路径，支持 imageeditor://open?path=... 和 file:// 链接
+fn path_from_url(url: &Url) -> Option<PathBuf> {
+    match url.scheme() {
+        URL_SCHEME if url.host_str() == Some("open") || url.path().trim_matches('/') == "open" => url
+            .query_pairs()
+            .find(|(key, _)| key == "path")
+            .map(|(_, value)| PathBuf::from(value.into_owned())),
+        "file" => url.to_file_path().ok(),
+        _ => None,
+    }
+}
+
+// 处理一个打开请求：通知前端并加入待处理列表
+pub fn handle_path(app: &AppHandle, path: &Path) {
+    match validate(path) {
+        Ok(info) => {
+            // 由系统转交的文件视为用户授权，只授权该文件本身
+            scope::allow(&info.path);
+            let state = app.state::<LaunchState>();
+            if !state.ready.load(Ordering::SeqCst) {
+                state.pending.lock().push(info.clone());
+            }
+            let _ = app.emit("open-image", info);
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_focus();
+            }
+        }
+        Err(error) => {
+            let _ = app.emit("open-image-error", error);
+        }
+    }
+}
+
+pub fn handle_url(app: &AppHandle, url: &Url) {
+    if let Some(path) = path_from_url(url) {
+        handle_path(app, &path);
+    }
+}
+
+// 处理启动参数：既可能是文件路径（Windows/Linux 的文件关联），也可能是链接
+pub fn handle_args<I: IntoIterator<Item = String>>(app: &AppHandle, args: I) {
+    for arg in args {
+        if arg.starts_with('-') {
+            continue;
+        }
+        match Url::parse(&arg) {
+            Ok(url) if url.scheme() == URL_SCHEME || url.scheme() == "file" => handle_url(app, &url),
+            _ => handle_path(app, Path::new(&arg)),
+        }
+    }
+}
+
+// 取出所有待处理的打开请求
+#[tauri::command]
+pub fn take_pending_opens(state: State<'_, LaunchState>) -> Vec<ImageInfo> {
+    state.ready.store(true, Ordering::SeqCst);
+    std::mem::take(&mut *state.pending.lock())
+}
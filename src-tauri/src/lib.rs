@@ -1,172 +1,389 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use std::collections::HashMap;
 use std::fs;
 use std::io::Cursor;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Instant;
 use serde::{Deserialize, Serialize};
 
+mod adjust;
+mod ascii;
+mod automation;
+mod autosave;
+mod backup;
+#[cfg(feature = "ml")]
+mod background;
+mod barcode;
+mod batch;
+mod cache;
+#[cfg(desktop)]
+mod capture;
+mod color;
+mod composite;
+mod compress;
+mod contact;
+mod deepzoom;
+mod disk;
+mod document;
+mod error;
+mod export;
+mod external;
+#[cfg(feature = "ml")]
+mod face;
+mod ffmpeg;
+mod filters;
+mod focus;
+mod fusion;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod geo;
+mod gif;
+mod hdr;
+mod history;
+mod hotfolder;
+mod i18n;
+mod icons;
+mod launch;
+mod listing;
+mod logging;
+mod lossless;
+mod metadata;
+#[cfg(feature = "ml")]
+mod ml;
+#[cfg(feature = "ocr")]
+mod ocr;
+mod orientation;
+mod palette;
+mod panorama;
+mod paths;
+mod perf;
+mod plugins;
+mod presets;
+mod preview;
+mod print;
+#[cfg(feature = "ml")]
+mod privacy;
+mod proxy;
+mod redeye;
+mod resources;
+mod scope;
+mod script;
+mod session;
+mod settings;
+mod share;
+mod stamp;
+mod storage;
+#[cfg(feature = "ml")]
+mod tagging;
+mod text;
+mod thumbnails;
+mod tiles;
+mod timelapse;
+mod timeouts;
+mod transfer;
+mod upload;
+#[cfg(feature = "ml")]
+mod upscale;
+mod vectorize;
+mod video;
+mod watch;
+#[cfg(desktop)]
+mod webcam;
+mod xmp;
+
 // 导入图片处理库
 use image::io::Reader as ImageReader;
 use image::{ GenericImageView };
 
+use error::AppError;
+
 // 定义图片信息结构体
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImageInfo {
     pub path: String,
     pub name: String,
     pub width: u32,
     pub height: u32,
     pub size: u64,
+    // 视频文件的封面帧（缓存中的 JPEG 文件），图片为空
+    #[serde(default)]
+    pub poster: Option<String>,
+}
+
+// 打开并解码图片，错误中附带文件路径；文件未修改时直接使用缓存中已解码的图片
+// 按 EXIF 方向旋转，竖拍的照片以正确的方向显示和处理
+// 返回的图片与缓存共用，需要修改时用 Arc::unwrap_or_clone 取得自己的副本
+pub(crate) fn open_image(path: impl AsRef<Path>) -> Result<Arc<image::DynamicImage>, AppError> {
+    let path = path.as_ref();
+    cache::get_or_decode(path, || {
+        perf::measure(perf::Stage::Decode, || {
+            let img = ImageReader::open(paths::extended(path))
+                .map_err(|e| AppError::from(e).at(path))?
+                .decode()
+                .map_err(|e| AppError::from(e).at(path))?;
+            let orientation = metadata::read_exif(path).map(|exif| metadata::orientation(&exif)).unwrap_or(1);
+            Ok(metadata::apply_orientation(img, orientation))
+        })
+    })
+}
+
+// 在阻塞线程池中执行耗时的解码/编码操作，避免阻塞命令线程和界面
+// 超过该命令的超时时间后返回超时错误；阻塞线程无法强制终止，但之后不会再写入文件
+// 超时时已经开始写入文件的操作不取消，等待其完成并返回实际结果
+// 每次操作的耗时和错误都会写入日志，解码/处理/编码各阶段的耗时计入性能统计
+pub(crate) async fn run_blocking<T, F>(command: &str, task: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    let started = Instant::now();
+    let cancel = timeouts::Cancel::default();
+    let task_cancel = cancel.clone();
+    let mut handle = tauri::async_runtime::spawn_blocking(move || timeouts::with_cancel(task_cancel, || perf::track(task)));
+    let joined = match timeouts::timeout_for(command) {
+        Some(limit) => match tokio::time::timeout(limit, &mut handle).await {
+            Ok(joined) => joined.map_err(|e| AppError::Internal(format!("Background task failed: {}", e))),
+            Err(_) if !cancel.cancel() => handle
+                .await
+                .map_err(|e| AppError::Internal(format!("Background task failed: {}", e))),
+            Err(_) => Err(AppError::Timeout(format!("{} exceeded {} ms", command, limit.as_millis()))),
+        },
+        None => handle
+            .await
+            .map_err(|e| AppError::Internal(format!("Background task failed: {}", e))),
+    };
+    let (result, stages) = match joined {
+        Ok((result, stages)) => (result, stages),
+        Err(e) => (Err(e), perf::StageTimes::default()),
+    };
+
+    let elapsed = started.elapsed();
+    perf::record(command, elapsed, stages, result.is_ok());
+    let elapsed_ms = elapsed.as_millis() as u64;
+    match &result {
+        Ok(_) => tracing::info!(command, elapsed_ms, "operation finished"),
+        Err(e) => tracing::warn!(command, elapsed_ms, code = e.code(), error = %e, "operation failed"),
+    }
+    result
+}
+
+// 列表中显示的图片或视频的信息；不是图片或视频、或读取失败时为 None
+// 只读取文件头获取尺寸，不解码整张图片
+pub(crate) fn image_info(app: &tauri::AppHandle, path: &Path) -> Result<Option<ImageInfo>, AppError> {
+    // 获取文件扩展名
+    let Some(ext) = path.extension() else {
+        return Ok(None);
+    };
+    let ext = ext.to_string_lossy();
+    // 检查是否是图片文件
+    if ["jpg", "jpeg", "png", "gif", "bmp"].contains(&ext.to_lowercase().as_str()) {
+        // 获取文件元数据
+        let metadata = fs::metadata(paths::extended(path)).map_err(|e| AppError::from(e).at(path))?;
+        let size = metadata.len();
+
+        // 从文件头读取图片尺寸，读取失败时跳过该文件
+        let dimensions = ImageReader::open(paths::extended(path)).ok().and_then(|reader| reader.into_dimensions().ok());
+        let Some((mut width, mut height)) = dimensions else {
+            return Ok(None);
+        };
+        // EXIF 方向为 5~8 时显示的宽高与存储的相反
+        if metadata::read_exif(path).map(|exif| metadata::orientation(&exif) >= 5).unwrap_or(false) {
+            std::mem::swap(&mut width, &mut height);
+        }
+        Ok(Some(ImageInfo {
+            path: paths::display(path),
+            name: paths::file_name(path),
+            width,
+            height,
+            size,
+            poster: None,
+        }))
+    } else if video::is_video(path) {
+        // 视频以封面帧显示；没有安装 ffmpeg 或提取失败时跳过
+        let Ok((poster, width, height)) = video::poster(app, path) else {
+            return Ok(None);
+        };
+        Ok(Some(ImageInfo {
+            path: paths::display(path),
+            name: paths::file_name(path),
+            width,
+            height,
+            size: fs::metadata(paths::extended(path)).map_err(|e| AppError::from(e).at(path))?.len(),
+            poster: Some(paths::display(&poster)),
+        }))
+    } else {
+        Ok(None)
+    }
 }
 
+// symlinks 指定符号链接的处理方式，默认忽略
+// 缩略图在后台生成，每生成一张发送一次 thumbnail-ready 事件
 #[tauri::command]
-fn list_images(path: &str) -> Result<Vec<ImageInfo>, String> {
-    let path = Path::new(path);
-    let mut images = Vec::new();
+async fn list_images(app: tauri::AppHandle, path: String, symlinks: Option<listing::SymlinkPolicy>) -> Result<Vec<ImageInfo>, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("list_images", move || {
+        let path = Path::new(&path);
+        let mut images = Vec::new();
+        let mut files = Vec::new();
     
-    // 读取目录
-    let entries = fs::read_dir(path).map_err(|e| format!("Failed to read directory: {}", e))?;
+        // 读取目录
+        let listing = listing::list_files(path, symlinks.unwrap_or_default(), false)?;
+        listing::report_symlinks(&app, path, &listing);
     
-    // 遍历目录内容
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        
-        // 检查是否是文件
-        if path.is_file() {
-            // 获取文件扩展名
-            if let Some(ext) = path.extension() {
-                let ext = ext.to_str().unwrap_or("");
-                // 检查是否是图片文件
-                if ["jpg", "jpeg", "png", "gif", "bmp"].contains(&ext.to_lowercase().as_str()) {
-                    // 获取文件元数据
-                    let metadata = fs::metadata(&path).map_err(|e| format!("Failed to get metadata: {}", e))?;
-                    let size = metadata.len();
-                    
-                    // 尝试读取图片尺寸
-                    match ImageReader::open(&path) {
-                        Ok(reader) => {
-                            match reader.decode() {
-                                Ok(image) => {
-                                    let (width, height) = image.dimensions();
-                                    let name = path.file_name().unwrap().to_str().unwrap().to_string();
-                                    
-                                    images.push(ImageInfo {
-                                        path: path.to_str().unwrap().to_string(),
-                                        name,
-                                        width,
-                                        height,
-                                        size,
-                                    });
-                                },
-                                Err(_) => continue, // 解码失败，跳过该文件
-                            }
-                        },
-                        Err(_) => continue, // 打开失败，跳过该文件
-                    }
-                }
+        // 遍历目录内容
+        for path in listing.files {
+            if let Some(info) = image_info(&app, &path)? {
+                images.push(info);
+                files.push(path);
             }
         }
-    }
-    
-    Ok(images)
+
+        thumbnails::generate_in_background(app.clone(), files);
+        Ok(images)
+    })
+    .await
 }
 
+// output 为空时覆盖原图，否则写入新文件
 #[tauri::command]
-fn resize_image(path: &str, width: u32, height: u32) -> Result<bool, String> {
-    // 打开图片
-    let img = ImageReader::open(path)
-        .map_err(|e| format!("Failed to open image: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
-    
-    // 调整图片大小
-    let resized = img.resize(width, height, image::imageops::FilterType::Triangle);
-    
-    // 保存图片
-    resized.save(path)
-        .map_err(|e| format!("Failed to save image: {}", e))?;
+async fn resize_image(path: String, width: u32, height: u32, output: Option<String>) -> Result<bool, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("resize_image", move || {
+        let output = output.unwrap_or_else(|| path.to_string());
+        if gif::is_gif(Path::new(&path)) && gif::is_gif(Path::new(&output)) {
+            // GIF 动画的每一帧都缩放，保留动画
+            gif::edit_frames(Path::new(&path), Path::new(&output), |frame| {
+                frame.resize(width, height, image::imageops::FilterType::Triangle)
+            })?;
+        } else {
+            // 打开图片
+            let img = open_image(&path)?;
+
+            // 调整图片大小
+            let resized = img.resize(width, height, image::imageops::FilterType::Triangle);
+
+            // 保存图片
+            storage::save_image_from(&resized, Path::new(&path), Path::new(&output))?;
+        }
+        history::record(Path::new(&path), Path::new(&output), history::EditOperation::Resize { width, height })?;
     
-    Ok(true)
+        Ok(true)
+    })
+    .await
 }
 
+// 图片数据以原始请求体（或分块上传的 transfer-id）传入，宽高放在请求头 width、height 中
+// 返回请求头 format 指定格式（默认 PNG）的原始字节（前端收到 ArrayBuffer），编码选项放在请求头 options 中
 #[tauri::command]
-fn resize_image_from_data(data: Vec<u8>, width: u32, height: u32) -> Result<Vec<u8>, String> {
-    // 从数据中创建Cursor以模拟读取器
-    let cursor = Cursor::new(data);
-    
-    // 打开图片
-    let img = ImageReader::new(cursor)
-        .with_guessed_format()
-        .map_err(|e| format!("Failed to create image reader: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
-    
-    // 调整图片大小
-    let resized = img.resize(width, height, image::imageops::FilterType::Triangle);
+async fn resize_image_from_data(request: tauri::ipc::Request<'_>) -> Result<tauri::ipc::Response, AppError> {
+    let width = transfer::header_u32(&request, "width")?;
+    let height = transfer::header_u32(&request, "height")?;
+    let (format, options) = transfer::output_encoding(&request)?;
+    let data = transfer::request_data(&request)?;
+
+    let encoded = run_blocking("resize_image_from_data", move || {
+        // 从数据中创建Cursor以模拟读取器
+        let cursor = Cursor::new(data);
     
-    // 创建一个缓冲区来保存PNG数据
-    let mut buffer = Cursor::new(Vec::new());
+        // 打开图片
+        let img = ImageReader::new(cursor)
+            .with_guessed_format()
+            .map_err(AppError::from)?
+            .decode()
+            .map_err(AppError::from)?;
     
-    // 将调整大小后的图片保存为PNG格式
-    resized.write_to(&mut buffer, image::ImageFormat::Png)
-        .map_err(|e| format!("Failed to encode image: {}", e))?;
+        // 调整图片大小
+        let resized = img.resize(width, height, image::imageops::FilterType::Triangle);
     
-    // 返回编码后的PNG数据
-    Ok(buffer.into_inner())
+        // 按请求的格式编码
+        storage::encode_to_vec(&resized, format, &options)
+    })
+    .await?;
+    Ok(tauri::ipc::Response::new(encoded))
 }
 
 // 获取图片信息
 #[tauri::command]
-fn get_image_info(path: &str) -> Result<ImageInfo, String> {
-    let path_obj = Path::new(path);
-    let file_name = path_obj.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+async fn get_image_info(path: String) -> Result<ImageInfo, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("get_image_info", move || {
+        let path_obj = Path::new(&path);
+        let file_name = paths::file_name(path_obj);
     
-    // 打开图片
-    let img = ImageReader::open(path)
-        .map_err(|e| format!("Failed to open image: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
+        // 打开图片
+        let img = open_image(&path)?;
     
-    let (width, height) = img.dimensions();
-    let metadata = fs::metadata(path)
-        .map_err(|e| format!("Failed to get metadata: {}", e))?;
-    let size = metadata.len();
+        let (width, height) = img.dimensions();
+        let metadata = fs::metadata(&path)
+            .map_err(AppError::from)?;
+        let size = metadata.len();
     
-    Ok(ImageInfo {
-        path: path.to_string(),
-        name: file_name,
-        width,
-        height,
-        size,
+        Ok(ImageInfo {
+            path: path.to_string(),
+            name: file_name,
+            width,
+            height,
+            size,
+            poster: None,
+        })
     })
+    .await
 }
 
 
 
-// 裁剪图片
+// 裁剪图片，output 为空时覆盖原图
 #[tauri::command]
-fn crop_image(path: &str, x: f32, y: f32, width: f32, height: f32) -> Result<bool, String> {
-    // 打开图片
-    let img = ImageReader::open(path)
-        .map_err(|e| format!("Failed to open image: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
+async fn crop_image(path: String, x: f32, y: f32, width: f32, height: f32, output: Option<String>) -> Result<bool, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("crop_image", move || {
+        let output = output.unwrap_or_else(|| path.to_string());
+        if gif::is_gif(Path::new(&path)) && gif::is_gif(Path::new(&output)) {
+            // GIF 动画的每一帧都裁剪，保留动画
+            gif::edit_frames(Path::new(&path), Path::new(&output), |frame| {
+                crop_relative(&frame, x, y, width, height)
+            })?;
+        } else {
+            // 打开图片
+            let img = open_image(&path)?;
+
+            // 裁剪图片
+            let cropped = crop_relative(&img, x, y, width, height);
+
+            // 保存图片
+            storage::save_image_from(&cropped, Path::new(&path), Path::new(&output))?;
+        }
+        history::record(Path::new(&path), Path::new(&output), history::EditOperation::Crop { x, y, width, height })?;
     
+        Ok(true)
+    })
+    .await
+}
+
+// 按相对坐标（0-1）裁剪图片
+pub(crate) fn crop_relative(img: &image::DynamicImage, x: f32, y: f32, width: f32, height: f32) -> image::DynamicImage {
     let (original_width, original_height) = img.dimensions();
-    
+
     // 计算实际裁剪坐标和尺寸（使用四舍五入确保更准确的裁剪范围）
     let crop_x = (x * original_width as f32 + 0.5) as u32;
     let crop_y = (y * original_height as f32 + 0.5) as u32;
     let crop_width = (width * original_width as f32 + 0.5) as u32;
     let crop_height = (height * original_height as f32 + 0.5) as u32;
-    
+
     // 计算裁剪区域的右下角坐标
     let crop_right = crop_x + crop_width;
     let crop_bottom = crop_y + crop_height;
-    
+
     // 确保裁剪区域在图片范围内（调整宽度和高度而不是坐标）
     let final_crop_width = if crop_right > original_width {
         original_width - crop_x
@@ -178,34 +395,13 @@ fn crop_image(path: &str, x: f32, y: f32, width: f32, height: f32) -> Result<boo
     } else {
         crop_height
     };
-    
-    // 裁剪图片
-    let cropped = img.crop_imm(crop_x, crop_y, final_crop_width, final_crop_height);
-    
-    // 保存图片
-    cropped.save(path)
-        .map_err(|e| format!("Failed to save image: {}", e))?;
-    
-    Ok(true)
+
+    img.crop_imm(crop_x, crop_y, final_crop_width, final_crop_height)
 }
-// 保存图片为不同格式
-#[tauri::command]
-fn save_as(path: &str, output: &str) -> Result<bool, String> {
-    // 打开图片
-    let img = ImageReader::open(path)
-        .map_err(|e| format!("Failed to open image: {}", e))?
-        .decode()
-        .map_err(|e| format!("Failed to decode image: {}", e))?;
-    
-    // 获取输出文件的扩展名
-    let output_path = Path::new(output);
-    let ext = output_path.extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    
+// 根据目标格式的限制调整图片（例如ICO的尺寸上限）
+pub(crate) fn prepare_for_format(img: image::DynamicImage, ext: &str) -> image::DynamicImage {
     // 检查是否是ICO格式，如果是则需要调整尺寸
-    let processed_img = if ext == "ico" {
+    if ext == "ico" {
         let (width, height) = img.dimensions();
         
         // ICO格式要求宽度和高度都不超过256像素
@@ -229,44 +425,107 @@ fn save_as(path: &str, output: &str) -> Result<bool, String> {
     } else {
         // 不是ICO格式，直接使用原图
         img
-    };
-    
-    // 保存为目标格式
-    processed_img.save(output)
-        .map_err(|e| format!("Failed to save image: {}", e))?;
-    
-    Ok(true)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct DiskInfo {
-    pub drive: String,
-    pub drive_type: String,
-    pub total_space: u64,
-    pub free_space: u64,
-    pub used_space: u64,
+// 检查文件是否已存在，供前端在保存前提示
+#[tauri::command]
+fn file_exists(path: &str) -> bool {
+    if scope::check(path).is_err() {
+        return false;
+    }
+
+    Path::new(&path).exists()
 }
 
-// 磁盘大小信息结构体
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct DiskSizeInfo {
-    pub drive: String,
-    pub file_size: u64,
-    pub last_modified: u64,
-    pub last_scanned: u64,
+// 读取已授权的图片文件的原始内容，供前端预览；前端不再直接通过 fs 插件读取文件
+#[tauri::command]
+async fn read_image_file(path: String) -> Result<tauri::ipc::Response, AppError> {
+    let path = scope::check(&path)?;
+    let data = run_blocking("read_image_file", move || {
+        fs::read(paths::extended(&path)).map_err(|e| AppError::from(e).at(&path))
+    })
+    .await?;
+    Ok(tauri::ipc::Response::new(data))
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CachedFolderInfo {
-    pub size: u64,
-    pub last_modified: u64,
-    pub last_scanned: u64,
+// 保存图片为不同格式，返回实际写入的路径
+// 目标已存在时：overwrite 为 true 则覆盖，auto_rename 为 true 则改名为 name (1).ext，否则报错
+// 指定 palette 时 GIF/PNG 按其中的颜色数和抖动算法保存为索引色
+// ICO 包含 16~256 中不超过图片尺寸的多个尺寸
+// options 为 JPEG/WebP/AVIF 的质量、PNG 压缩级别和 WebP 无损压缩等编码选项
+#[tauri::command]
+async fn save_as(
+    path: String,
+    output: String,
+    overwrite: Option<bool>,
+    auto_rename: Option<bool>,
+    palette: Option<palette::PaletteOptions>,
+    options: Option<storage::EncodeOptions>,
+) -> Result<String, AppError> {
+    scope::check(&path)?;
+    scope::check(&output)?;
+    let options = options.unwrap_or_default();
+    options.validate()?;
+
+    run_blocking("save_as", move || {
+        convert_file(
+            &path,
+            &output,
+            overwrite.unwrap_or(false),
+            auto_rename.unwrap_or(false),
+            palette,
+            &options,
+        )
+    })
+    .await
 }
 
-// 定义全局缓存
-lazy_static::lazy_static! {
-    static ref DISK_SIZE_CACHE: Arc<RwLock<HashMap<String, DiskSizeInfo>>> = Arc::new(RwLock::new(HashMap::new()));
-    static ref FOLDER_CACHE: Arc<RwLock<HashMap<String, CachedFolderInfo>>> = Arc::new(RwLock::new(HashMap::new()));
+// save_as 和自动化接口 /convert 共用的转换过程，调用前需要校验路径和编码选项
+pub(crate) fn convert_file(
+    path: &str,
+    output: &str,
+    overwrite: bool,
+    auto_rename: bool,
+    palette: Option<palette::PaletteOptions>,
+    options: &storage::EncodeOptions,
+) -> Result<String, AppError> {
+    // 处理已存在的目标文件
+    let mut output_path = Path::new(output).to_path_buf();
+    if output_path.exists() && !overwrite {
+        if auto_rename {
+            output_path = storage::unique_path(&output_path);
+        } else {
+            return Err(AppError::InvalidArgument(format!("File already exists: {}", output)));
+        }
+    }
+
+    // 打开图片
+    let img = Arc::unwrap_or_clone(open_image(path)?);
+
+    // 获取输出文件的扩展名
+    let ext = output_path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    // 按目标格式的限制处理图片
+    let processed_img = prepare_for_format(img, &ext);
+
+    // 保存为目标格式
+    match palette {
+        Some(palette) if ext == "gif" || ext == "png" => {
+            let indexed = palette::quantize(&processed_img, &palette)?;
+            palette::save_indexed(&indexed, &output_path)?;
+        }
+        _ if ext == "ico" => {
+            let data = icons::encode_ico(&processed_img, &icons::ICO_SIZES, 0.0, None)?;
+            storage::write_bytes(&output_path, &data)?;
+        }
+        _ => storage::save_image_as(&processed_img, Path::new(path), &output_path, options)?,
+    }
+
+    Ok(output_path.to_string_lossy().to_string())
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -281,19 +540,280 @@ pub struct FileInfo {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let context = tauri::generate_context!();
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+
+    // 已有实例运行时，把新实例的启动参数（文件路径或链接）转交给已有实例
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        launch::handle_args(app, argv.into_iter().skip(1));
+    }));
+
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init()) 
+        .plugin(share::init())
+        .manage(upload::UploadState::default())
+        .manage(external::ExternalEditState::default())
+        .manage(hotfolder::HotFolderState::default())
+        .manage(automation::AutomationState::default())
+        .manage(launch::LaunchState::default())
+        .manage(session::SessionState::default())
+        .manage(watch::WatchState::default())
+        .setup(|app| {
+            // 最先初始化日志，记录后续初始化中的错误
+            logging::init(app.handle())?;
+            // 加载已授权的路径范围
+            scope::init(app.handle())?;
+            settings::init(app.handle())?;
+            // 加载 WASM 滤镜插件
+            plugins::init(app.handle())?;
+            backup::init(app.handle())?;
+            external::init(app.handle())?;
+            export::init(app.handle())?;
+            // 读取上次运行遗留的未保存会话，开始自动保存
+            autosave::init(app.handle())?;
+            batch::init(app.handle())?;
+            presets::init(app.handle())?;
+
+            // 处理通过链接、文件关联或命令行打开的图片
+            #[cfg(any(windows, target_os = "linux"))]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let _ = app.deep_link().register_all();
+                launch::handle_args(app.handle(), std::env::args().skip(1));
+            }
+            #[cfg(any(target_os = "macos", mobile))]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        launch::handle_url(&handle, &url);
+                    }
+                });
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // 拖放到窗口中的文件视为用户授权
+            if let tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
+                for path in paths {
+                    scope::allow(path);
+                }
+            }
+            if let tauri::WindowEvent::Destroyed = event {
+                session::release_window(tauri::Manager::app_handle(window), window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             list_images, 
             resize_image, 
             resize_image_from_data,
             get_image_info,
             crop_image,
-            save_as
+            save_as,
+            file_exists,
+            read_image_file,
+            adjust::adjust_image,
+            adjust::adjust_image_from_data,
+            ascii::export_ascii_art,
+            automation::start_automation_server,
+            automation::stop_automation_server,
+            automation::get_automation_server,
+            autosave::update_session,
+            autosave::discard_snapshot,
+            autosave::get_recoverable_sessions,
+            autosave::restore_session,
+            autosave::discard_recovered_sessions,
+            #[cfg(feature = "ml")]
+            background::remove_background,
+            backup::has_original,
+            backup::restore_original,
+            barcode::decode_qr,
+            barcode::generate_qr,
+            batch::start_batch_job,
+            batch::batch_process,
+            batch::resume_job,
+            batch::pause_job,
+            batch::cancel_job,
+            batch::list_jobs,
+            batch::get_job,
+            batch::delete_job,
+            cache::clear_image_cache,
+            #[cfg(desktop)]
+            capture::list_capture_targets,
+            #[cfg(desktop)]
+            capture::capture_screen,
+            color::get_pixel_color,
+            composite::composite_image,
+            composite::composite_image_from_data,
+            compress::compress_to_size,
+            contact::generate_contact_sheet,
+            deepzoom::export_deep_zoom,
+            disk::list_drives,
+            disk::scan_folder_sizes,
+            document::clean_document,
+            export::export_to_target,
+            export::pick_ssh_key,
+            external::open_external,
+            external::stop_external_watch,
+            external::pick_external_editor,
+            external::list_external_editors,
+            external::remove_external_editor,
+            #[cfg(feature = "ml")]
+            face::detect_faces,
+            ffmpeg::get_ffmpeg_version,
+            ffmpeg::pick_ffmpeg,
+            filters::apply_filter,
+            filters::get_gpu_info,
+            focus::focus_stack,
+            geo::get_image_location,
+            geo::group_images_by_location,
+            gif::extract_gif_frame,
+            gif::get_gif_frames,
+            hdr::merge_hdr,
+            history::get_edit_history,
+            history::revert_edit_history,
+            history::reapply_edit_history,
+            hotfolder::start_hot_folder,
+            hotfolder::stop_hot_folder,
+            hotfolder::list_hot_folders,
+            i18n::get_error_messages,
+            icons::generate_app_icons,
+            icons::generate_favicons,
+            launch::take_pending_opens,
+            logging::set_log_level,
+            logging::get_log_level,
+            logging::get_recent_logs,
+            lossless::transform_jpeg_lossless,
+            metadata::get_image_metadata,
+            #[cfg(feature = "ocr")]
+            ocr::recognize_text,
+            orientation::normalize_orientation,
+            palette::quantize_image,
+            panorama::stitch_panorama,
+            perf::get_perf_stats,
+            perf::reset_perf_stats,
+            plugins::list_plugins,
+            plugins::reload_plugins,
+            plugins::apply_plugin,
+            presets::list_export_presets,
+            presets::save_export_preset,
+            presets::delete_export_preset,
+            presets::export_with_preset,
+            preview::stream_preview,
+            preview::cancel_preview,
+            print::generate_print_pdf,
+            #[cfg(feature = "ml")]
+            privacy::redact_images,
+            proxy::open_preview_session,
+            proxy::set_preview_operation,
+            proxy::truncate_preview_operations,
+            proxy::get_preview_operations,
+            proxy::close_preview_session,
+            redeye::remove_red_eye,
+            resources::get_resource_usage,
+            scope::pick_image_file,
+            scope::pick_directory,
+            scope::pick_save_path,
+            scope::list_allowed_paths,
+            scope::revoke_path,
+            script::validate_script,
+            script::run_script,
+            session::open_session,
+            session::release_session,
+            session::list_sessions,
+            session::apply_session_operation,
+            session::undo_session,
+            session::redo_session,
+            session::get_session_image,
+            session::save_session,
+            settings::get_settings,
+            settings::set_settings,
+            share::share_image,
+            stamp::stamp_images,
+            #[cfg(feature = "ml")]
+            tagging::classify_image,
+            thumbnails::get_thumbnail,
+            tiles::get_image_region,
+            tiles::get_image_tile,
+            timelapse::create_timelapse,
+            timeouts::get_operation_timeouts,
+            timeouts::set_operation_timeout,
+            transfer::begin_transfer,
+            transfer::append_transfer_chunk,
+            transfer::cancel_transfer,
+            transfer::read_file_chunk,
+            upload::configure_upload,
+            upload::clear_upload_config,
+            upload::upload_image,
+            #[cfg(feature = "ml")]
+            upscale::upscale_image,
+            vectorize::vectorize_image,
+            video::extract_video_frame,
+            watch::unwatch_directory,
+            watch::watch_directory,
+            #[cfg(desktop)]
+            webcam::list_cameras,
+            #[cfg(desktop)]
+            webcam::capture_webcam,
+            xmp::get_image_tags,
+            xmp::set_image_tags,
+            xmp::search_images_by_tag
         ])
-        .run(context)
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // 退出前写入最后一次会话状态
+            if let tauri::RunEvent::Exit = _event {
+                if let Err(e) = autosave::flush() {
+                    tracing::warn!(error = %e, "autosave on exit failed");
+                }
+            }
+
+            // macOS 通过系统事件传递文件关联打开的文件
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                for url in urls.iter().filter(|u| u.scheme() == "file") {
+                    launch::handle_url(_app, url);
+                }
+            }
+        });
 }
 
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    use super::*;
+
+    // 每个像素的值为其坐标，便于检查裁剪的位置
+    fn coordinates(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| Rgb([x as u8, y as u8, 0])))
+    }
+
+    #[test]
+    fn crop_relative_scales_to_pixels() {
+        let cropped = crop_relative(&coordinates(100, 80), 0.25, 0.25, 0.5, 0.5);
+        assert_eq!(cropped.dimensions(), (50, 40));
+        assert_eq!(cropped.to_rgb8().get_pixel(0, 0), &Rgb([25, 20, 0]));
+    }
+
+    #[test]
+    fn crop_relative_rounds_to_nearest_pixel() {
+        // 0.333 x 10 = 3.33 取整为 3，0.666 x 10 = 6.66 取整为 7
+        let cropped = crop_relative(&coordinates(10, 10), 0.333, 0.0, 0.666, 1.0);
+        assert_eq!(cropped.dimensions(), (7, 10));
+        assert_eq!(cropped.to_rgb8().get_pixel(0, 0), &Rgb([3, 0, 0]));
+    }
+
+    #[test]
+    fn crop_relative_clamps_to_image() {
+        let cropped = crop_relative(&coordinates(100, 80), 0.5, 0.5, 0.8, 0.8);
+        assert_eq!(cropped.dimensions(), (50, 40));
+        assert_eq!(cropped.to_rgb8().get_pixel(49, 39), &Rgb([99, 79, 0]));
+    }
+}
@@ -0,0 +1,112 @@
+// 目录遍历：列表和批处理共用，统一处理符号链接（Windows 上的目录联接同样视为符号链接）
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::paths;
+use crate::scope;
+
+// 遇到符号链接时的处理方式
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SymlinkPolicy {
+    // 忽略符号链接
+    #[default]
+    Skip,
+    // 跟随符号链接，递归时按规范路径检测循环
+    Follow,
+    // 不跟随，但把遇到的符号链接返回给前端
+    Report,
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct Listing {
+    pub files: Vec<PathBuf>,
+    pub symlinks: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SymlinkReport {
+    pub dir: String,
+    pub symlinks: Vec<String>,
+}
+
+fn walk(
+    dir: &Path,
+    policy: SymlinkPolicy,
+    recursive: bool,
+    visited: &mut HashSet<PathBuf>,
+    listing: &mut Listing,
+) -> Result<(), AppError> {
+    // 已经访问过的目录（通过符号链接绕回）直接跳过
+    let canonical = fs::canonicalize(dir).map_err(|e| AppError::from(e).at(dir))?;
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(paths::extended(dir)).map_err(|e| AppError::from(e).at(dir))?;
+    let mut dirs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        let is_dir = if file_type.is_symlink() {
+            match policy {
+                SymlinkPolicy::Skip => continue,
+                SymlinkPolicy::Report => {
+                    listing.symlinks.push(path);
+                    continue;
+                }
+                // 跟随链接查看目标类型；目标不存在或指向授权范围之外时忽略
+                SymlinkPolicy::Follow => match fs::metadata(&path) {
+                    Ok(metadata) if scope::check(&path).is_ok() => metadata.is_dir(),
+                    _ => continue,
+                },
+            }
+        } else {
+            file_type.is_dir()
+        };
+
+        if is_dir {
+            dirs.push(path);
+        } else {
+            listing.files.push(path);
+        }
+    }
+
+    if recursive {
+        for dir in dirs {
+            walk(&dir, policy, recursive, visited, listing)?;
+        }
+    }
+    Ok(())
+}
+
+// 列出目录下的文件（不含目录），按文件路径排序
+pub(crate) fn list_files(dir: &Path, policy: SymlinkPolicy, recursive: bool) -> Result<Listing, AppError> {
+    let mut listing = Listing::default();
+    walk(dir, policy, recursive, &mut HashSet::new(), &mut listing)?;
+    listing.files.sort();
+    listing.symlinks.sort();
+    Ok(listing)
+}
+
+// Report 策略下把遇到的符号链接通过 symlinks-found 事件告知前端
+pub(crate) fn report_symlinks(app: &AppHandle, dir: &Path, listing: &Listing) {
+    if listing.symlinks.is_empty() {
+        return;
+    }
+    let _ = app.emit(
+        "symlinks-found",
+        SymlinkReport {
+            dir: paths::display(dir),
+            symlinks: listing.symlinks.iter().map(|p| paths::display(p)).collect(),
+        },
+    );
+}
@@ -0,0 +1,139 @@
+// 日志：基于 tracing 的结构化日志，写入应用数据目录下按天滚动的日志文件，供诊断面板查看
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Registry};
+
+use crate::error::AppError;
+
+// 日志文件名为 file_flower.<日期>.log
+const LOG_PREFIX: &str = "file_flower";
+const LOG_SUFFIX: &str = "log";
+// 最多保留最近 7 天的日志文件
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+// 诊断面板默认读取的行数
+const DEFAULT_RECENT_LINES: usize = 200;
+
+struct Logger {
+    dir: PathBuf,
+    level: reload::Handle<LevelFilter, Registry>,
+    // 后台写入线程的守卫，释放后缓冲中的日志会丢失，因此一直持有
+    _guard: WorkerGuard,
+}
+
+lazy_static::lazy_static! {
+    // init 之前为 None，此时日志直接丢弃
+    static ref LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+}
+
+// 启动时创建日志目录并注册全局日志订阅者，应在其他初始化之前调用
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("logs");
+    fs::create_dir_all(&dir).map_err(AppError::from)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| AppError::Internal(format!("Failed to create log file: {}", e)))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    // 级别过滤放在可重新加载的层中，运行时可以通过 set_log_level 修改
+    let (filter, level) = reload::Layer::new(DEFAULT_LEVEL);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_target(false),
+        )
+        .try_init()
+        .map_err(|e| AppError::Internal(format!("Failed to initialize logging: {}", e)))?;
+
+    *LOGGER.lock() = Some(Logger {
+        dir,
+        level,
+        _guard: guard,
+    });
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "logging started");
+    Ok(())
+}
+
+fn not_initialized() -> AppError {
+    AppError::Internal("Logging is not initialized".to_string())
+}
+
+// 设置日志级别：off、error、warn、info、debug、trace，返回生效后的级别
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<String, AppError> {
+    let filter = LevelFilter::from_str(&level)
+        .map_err(|_| AppError::InvalidArgument(format!("Unknown log level: {}", level)))?;
+
+    let logger = LOGGER.lock();
+    let logger = logger.as_ref().ok_or_else(not_initialized)?;
+    logger
+        .level
+        .modify(|current| *current = filter)
+        .map_err(|e| AppError::Internal(format!("Failed to change log level: {}", e)))?;
+
+    tracing::info!(level = %filter, "log level changed");
+    Ok(filter.to_string().to_lowercase())
+}
+
+#[tauri::command]
+pub fn get_log_level() -> Result<String, AppError> {
+    let logger = LOGGER.lock();
+    let logger = logger.as_ref().ok_or_else(not_initialized)?;
+    let level = logger
+        .level
+        .clone_current()
+        .ok_or_else(|| AppError::Internal("Log level is unavailable".to_string()))?;
+    Ok(level.to_string().to_lowercase())
+}
+
+// 读取最新日志文件的最后 lines 行（默认 200 行）
+#[tauri::command]
+pub fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, AppError> {
+    let dir = LOGGER
+        .lock()
+        .as_ref()
+        .map(|logger| logger.dir.clone())
+        .ok_or_else(not_initialized)?;
+
+    // 文件名中带有日期，按文件名排序后最后一个就是最新的
+    let latest = fs::read_dir(&dir)
+        .map_err(|e| AppError::from(e).at(&dir))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().starts_with(LOG_PREFIX))
+                .unwrap_or(false)
+        })
+        .max();
+    let Some(latest) = latest else {
+        return Ok(Vec::new());
+    };
+
+    let content = fs::read(&latest).map_err(|e| AppError::from(e).at(&latest))?;
+    let content = String::from_utf8_lossy(&content);
+    let all: Vec<&str> = content.lines().collect();
+    let count = lines.unwrap_or(DEFAULT_RECENT_LINES).min(all.len());
+    Ok(all[all.len() - count..].iter().map(|line| line.to_string()).collect())
+}
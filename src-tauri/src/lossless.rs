@@ -0,0 +1,1040 @@
+// 无损 JPEG 变换（类似 jpegtran）：直接重排量化后的 DCT 系数完成 90° 旋转、翻转和按 MCU 对齐的裁剪，
+// 不经过解码和重新编码，画质没有任何损失
+// 支持顺序式霍夫曼编码（基线/扩展）的 JPEG，渐进式和算术编码的 JPEG 返回 Unsupported
+// 翻转方向上不足一个 MCU 的边缘无法无损变换，与 jpegtran -trim 一样裁掉
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::metadata;
+use crate::storage::write_bytes;
+use crate::{paths, run_blocking, scope};
+
+// 之字形顺序中第 k 个系数在 8x8 块（行优先）中的位置
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21,
+    28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61,
+    54, 47, 55, 62, 63,
+];
+
+type Block = [i16; 64];
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LosslessOperation {
+    // 顺时针旋转
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    // 沿左上-右下对角线翻转
+    Transpose,
+    // 左上角向下取整到 MCU 边界（通常为 8 或 16 像素），宽高相应增加
+    Crop { x: u32, y: u32, width: u32, height: u32 },
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LosslessResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    // 是否裁掉了不足一个 MCU 的边缘
+    pub trimmed: bool,
+}
+
+fn corrupt(message: &str) -> AppError {
+    AppError::DecodeFailed(format!("Corrupt JPEG: {}", message))
+}
+
+struct Component {
+    id: u8,
+    h: u32,
+    v: u32,
+    quant: u8,
+    // 自然顺序的系数块，blocks_w x blocks_h，只包含覆盖图片的块
+    blocks_w: usize,
+    blocks_h: usize,
+    blocks: Vec<Block>,
+}
+
+impl Component {
+    fn block(&self, bx: usize, by: usize) -> &Block {
+        // MCU 中超出图片的块用边缘的块填充
+        &self.blocks[by.min(self.blocks_h - 1) * self.blocks_w + bx.min(self.blocks_w - 1)]
+    }
+}
+
+#[derive(Clone, Copy)]
+struct QuantTable {
+    precision: u8,
+    // 自然顺序
+    values: [u16; 64],
+}
+
+struct Jpeg {
+    width: u32,
+    height: u32,
+    // 帧头标记：SOF0（基线）或 SOF1（扩展顺序式），写回时保持不变
+    frame_marker: u8,
+    // APPn/COM 段（含标记和长度），原样写回
+    segments: Vec<Vec<u8>>,
+    quant: [Option<QuantTable>; 4],
+    components: Vec<Component>,
+    trimmed: bool,
+}
+
+impl Jpeg {
+    fn max_factors(&self) -> (u32, u32) {
+        let h = self.components.iter().map(|c| c.h).max().unwrap_or(1);
+        let v = self.components.iter().map(|c| c.v).max().unwrap_or(1);
+        (h, v)
+    }
+
+    fn mcu_size(&self) -> (u32, u32) {
+        let (h, v) = self.max_factors();
+        (8 * h, 8 * v)
+    }
+
+    // 各分量覆盖图片所需的块数
+    fn block_counts(&self, component: &Component) -> (usize, usize) {
+        let (hmax, vmax) = self.max_factors();
+        let width = (self.width * component.h).div_ceil(hmax);
+        let height = (self.height * component.v).div_ceil(vmax);
+        (width.div_ceil(8) as usize, height.div_ceil(8) as usize)
+    }
+
+    // 从 offset（以 MCU 为单位）开始取出覆盖图片所需的块；先更新 width/height 再调用
+    fn fit_blocks(&mut self, offset: (usize, usize)) {
+        let counts: Vec<(usize, usize)> = self.components.iter().map(|c| self.block_counts(c)).collect();
+        for (component, (blocks_w, blocks_h)) in self.components.iter_mut().zip(counts) {
+            let (ox, oy) = (offset.0 * component.h as usize, offset.1 * component.v as usize);
+            let mut blocks = Vec::with_capacity(blocks_w * blocks_h);
+            for by in 0..blocks_h {
+                for bx in 0..blocks_w {
+                    blocks.push(*component.block(ox + bx, oy + by));
+                }
+            }
+            component.blocks = blocks;
+            component.blocks_w = blocks_w;
+            component.blocks_h = blocks_h;
+        }
+    }
+
+    // 把宽度/高度裁到 MCU 的整数倍
+    fn trim(&mut self, width: bool, height: bool) -> Result<(), AppError> {
+        let (mcu_w, mcu_h) = self.mcu_size();
+        let new_width = if width { self.width / mcu_w * mcu_w } else { self.width };
+        let new_height = if height { self.height / mcu_h * mcu_h } else { self.height };
+        if new_width == 0 || new_height == 0 {
+            return Err(AppError::InvalidArgument("Image is smaller than one MCU".to_string()));
+        }
+        if (new_width, new_height) != (self.width, self.height) {
+            self.width = new_width;
+            self.height = new_height;
+            self.trimmed = true;
+            self.fit_blocks((0, 0));
+        }
+        Ok(())
+    }
+
+    fn crop(&mut self, x: u32, y: u32, width: u32, height: u32) -> Result<(), AppError> {
+        if width == 0 || height == 0 || x >= self.width || y >= self.height {
+            return Err(AppError::InvalidArgument(format!(
+                "Crop ({}, {}, {}x{}) is outside the {}x{} image",
+                x, y, width, height, self.width, self.height
+            )));
+        }
+        let (mcu_w, mcu_h) = self.mcu_size();
+        let (x0, y0) = (x / mcu_w * mcu_w, y / mcu_h * mcu_h);
+        self.width = (width + x - x0).min(self.width - x0);
+        self.height = (height + y - y0).min(self.height - y0);
+        self.fit_blocks(((x0 / mcu_w) as usize, (y0 / mcu_h) as usize));
+        Ok(())
+    }
+
+    fn flip_horizontal(&mut self) {
+        for component in &mut self.components {
+            let width = component.blocks_w;
+            for row in component.blocks.chunks_exact_mut(width) {
+                row.reverse();
+                for block in row {
+                    // 奇数列的余弦基函数水平翻转后变号
+                    for (i, value) in block.iter_mut().enumerate() {
+                        if i % 8 % 2 == 1 {
+                            *value = -*value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn flip_vertical(&mut self) {
+        for component in &mut self.components {
+            let width = component.blocks_w;
+            let rows: Vec<Vec<Block>> = component.blocks.chunks_exact(width).rev().map(|row| row.to_vec()).collect();
+            component.blocks = rows.concat();
+            for block in &mut component.blocks {
+                for (i, value) in block.iter_mut().enumerate() {
+                    if i / 8 % 2 == 1 {
+                        *value = -*value;
+                    }
+                }
+            }
+        }
+    }
+
+    fn transpose(&mut self) {
+        for component in &mut self.components {
+            let (width, height) = (component.blocks_w, component.blocks_h);
+            let mut blocks = Vec::with_capacity(width * height);
+            for bx in 0..width {
+                for by in 0..height {
+                    let source = &component.blocks[by * width + bx];
+                    blocks.push(std::array::from_fn(|i| source[i % 8 * 8 + i / 8]));
+                }
+            }
+            component.blocks = blocks;
+            component.blocks_w = height;
+            component.blocks_h = width;
+            std::mem::swap(&mut component.h, &mut component.v);
+        }
+        // 系数转置后量化表也要转置
+        for table in self.quant.iter_mut().flatten() {
+            let values = table.values;
+            table.values = std::array::from_fn(|i| values[i % 8 * 8 + i / 8]);
+        }
+        std::mem::swap(&mut self.width, &mut self.height);
+    }
+
+    fn apply(&mut self, operation: LosslessOperation) -> Result<(), AppError> {
+        match operation {
+            LosslessOperation::FlipHorizontal => {
+                self.trim(true, false)?;
+                self.flip_horizontal();
+            }
+            LosslessOperation::FlipVertical => {
+                self.trim(false, true)?;
+                self.flip_vertical();
+            }
+            LosslessOperation::Transpose => self.transpose(),
+            // 转置后再水平翻转，原来的高度成为被翻转的宽度
+            LosslessOperation::Rotate90 => {
+                self.trim(false, true)?;
+                self.transpose();
+                self.flip_horizontal();
+            }
+            LosslessOperation::Rotate180 => {
+                self.trim(true, true)?;
+                self.flip_horizontal();
+                self.flip_vertical();
+            }
+            LosslessOperation::Rotate270 => {
+                self.trim(true, false)?;
+                self.transpose();
+                self.flip_vertical();
+            }
+            LosslessOperation::Crop { x, y, width, height } => self.crop(x, y, width, height)?,
+        }
+        Ok(())
+    }
+
+    // 按扫描顺序依次访问每个块：多分量时按 MCU 交织，单分量时逐块
+    fn for_each_block<F: FnMut(usize, &Block)>(&self, mut visit: F) {
+        if self.components.len() == 1 {
+            for block in &self.components[0].blocks {
+                visit(0, block);
+            }
+            return;
+        }
+        let (mcu_w, mcu_h) = self.mcu_size();
+        for my in 0..self.height.div_ceil(mcu_h) as usize {
+            for mx in 0..self.width.div_ceil(mcu_w) as usize {
+                for (index, component) in self.components.iter().enumerate() {
+                    let (h, v) = (component.h as usize, component.v as usize);
+                    for by in 0..v {
+                        for bx in 0..h {
+                            visit(index, component.block(mx * h + bx, my * v + by));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ---- 霍夫曼解码 ----
+
+struct Decoder {
+    maxcode: [i32; 18],
+    mincode: [i32; 17],
+    valptr: [i32; 17],
+    values: Vec<u8>,
+}
+
+impl Decoder {
+    fn new(bits: &[u8], values: Vec<u8>) -> Self {
+        let mut decoder = Decoder {
+            maxcode: [-1; 18],
+            mincode: [0; 17],
+            valptr: [0; 17],
+            values,
+        };
+        let (mut code, mut k) = (0i32, 0i32);
+        for length in 1..=16 {
+            let count = bits[length - 1] as i32;
+            decoder.valptr[length] = k;
+            decoder.mincode[length] = code;
+            code += count;
+            k += count;
+            decoder.maxcode[length] = if count > 0 { code - 1 } else { -1 };
+            code <<= 1;
+        }
+        decoder.maxcode[17] = i32::MAX;
+        decoder
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u8, AppError> {
+        let mut code = reader.bit() as i32;
+        let mut length = 1;
+        while code > self.maxcode[length] {
+            code = (code << 1) | reader.bit() as i32;
+            length += 1;
+            if length > 16 {
+                return Err(corrupt("invalid Huffman code"));
+            }
+        }
+        let index = (self.valptr[length] + code - self.mincode[length]) as usize;
+        self.values.get(index).copied().ok_or_else(|| corrupt("invalid Huffman code"))
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+    // 遇到标记后不再读取，之后的位都为 0
+    at_marker: bool,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> u32 {
+        if self.count == 0 {
+            let mut byte = 0;
+            if !self.at_marker && self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xFF {
+                    match self.data.get(self.pos + 1) {
+                        Some(0) => self.pos += 2,
+                        _ => {
+                            self.at_marker = true;
+                            byte = 0;
+                        }
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            self.buffer = byte as u32;
+            self.count = 8;
+        }
+        self.count -= 1;
+        (self.buffer >> self.count) & 1
+    }
+
+    fn receive(&mut self, bits: u8) -> i32 {
+        (0..bits).fold(0, |value, _| (value << 1) | self.bit() as i32)
+    }
+
+    // 跳过 RSTn 标记，从下一个字节开始读取
+    fn restart(&mut self) {
+        self.count = 0;
+        while let Some(pair) = self.data.get(self.pos..self.pos + 2) {
+            if pair[0] == 0xFF && (0xD0..=0xD7).contains(&pair[1]) {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.pos += 2;
+        self.at_marker = false;
+    }
+}
+
+fn extend(value: i32, bits: u8) -> i32 {
+    if bits == 0 || value >= 1 << (bits - 1) {
+        value
+    } else {
+        value - (1 << bits) + 1
+    }
+}
+
+fn decode_block(reader: &mut BitReader, dc: &Decoder, ac: &Decoder, predictor: &mut i32) -> Result<Block, AppError> {
+    let mut block = [0i16; 64];
+    let size = dc.decode(reader)?;
+    if size > 11 {
+        return Err(corrupt("invalid DC coefficient"));
+    }
+    *predictor += extend(reader.receive(size), size);
+    block[0] = *predictor as i16;
+    let mut k = 1;
+    while k < 64 {
+        let symbol = ac.decode(reader)?;
+        let (run, size) = ((symbol >> 4) as usize, symbol & 15);
+        if size == 0 {
+            if run != 15 {
+                break;
+            }
+            k += 16;
+            continue;
+        }
+        k += run;
+        if k > 63 {
+            return Err(corrupt("too many AC coefficients"));
+        }
+        block[ZIGZAG[k]] = extend(reader.receive(size), size) as i16;
+        k += 1;
+    }
+    Ok(block)
+}
+
+// ---- 解析 ----
+
+fn read_u16(data: &[u8], pos: usize) -> Result<usize, AppError> {
+    match data.get(pos..pos + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize),
+        None => Err(corrupt("unexpected end of file")),
+    }
+}
+
+fn parse(data: &[u8]) -> Result<Jpeg, AppError> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(AppError::DecodeFailed("Not a JPEG file".to_string()));
+    }
+    let mut jpeg = Jpeg {
+        width: 0,
+        height: 0,
+        frame_marker: 0xC0,
+        segments: Vec::new(),
+        quant: [None; 4],
+        components: Vec::new(),
+        trimmed: false,
+    };
+    let mut dc_tables: [Option<Decoder>; 4] = Default::default();
+    let mut ac_tables: [Option<Decoder>; 4] = Default::default();
+    let mut restart_interval = 0;
+    let mut pos = 2;
+
+    loop {
+        if data.get(pos) != Some(&0xFF) {
+            return Err(corrupt("expected marker"));
+        }
+        let marker = *data.get(pos + 1).ok_or_else(|| corrupt("unexpected end of file"))?;
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        pos += 2;
+        let length = read_u16(data, pos)?;
+        let body = data.get(pos + 2..pos + length).ok_or_else(|| corrupt("truncated segment"))?;
+
+        match marker {
+            0xC0 | 0xC1 => {
+                if body.len() < 6 || body[0] != 8 {
+                    return Err(AppError::Unsupported("JPEG sample precision other than 8 bits".to_string()));
+                }
+                jpeg.frame_marker = marker;
+                jpeg.height = read_u16(body, 1)? as u32;
+                jpeg.width = read_u16(body, 3)? as u32;
+                let count = body[5] as usize;
+                for c in body.get(6..6 + count * 3).ok_or_else(|| corrupt("truncated frame header"))?.chunks_exact(3) {
+                    let (h, v) = ((c[1] >> 4) as u32, (c[1] & 15) as u32);
+                    if !(1..=4).contains(&h) || !(1..=4).contains(&v) || c[2] > 3 {
+                        return Err(corrupt("invalid component"));
+                    }
+                    jpeg.components.push(Component {
+                        id: c[0],
+                        h,
+                        v,
+                        quant: c[2],
+                        blocks_w: 0,
+                        blocks_h: 0,
+                        blocks: Vec::new(),
+                    });
+                }
+                if jpeg.width == 0 || jpeg.height == 0 || jpeg.components.is_empty() {
+                    return Err(corrupt("invalid frame header"));
+                }
+            }
+            0xC2 | 0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                return Err(AppError::Unsupported(
+                    "Lossless transforms of progressive or arithmetic-coded JPEG".to_string(),
+                ));
+            }
+            0xC4 => {
+                let mut rest = body;
+                while rest.len() >= 17 {
+                    let (class, id) = (rest[0] >> 4, (rest[0] & 15) as usize);
+                    let bits = &rest[1..17];
+                    let total: usize = bits.iter().map(|&b| b as usize).sum();
+                    let values = rest.get(17..17 + total).ok_or_else(|| corrupt("truncated Huffman table"))?.to_vec();
+                    if id > 3 {
+                        return Err(corrupt("invalid Huffman table id"));
+                    }
+                    let decoder = Decoder::new(bits, values);
+                    if class == 0 {
+                        dc_tables[id] = Some(decoder);
+                    } else {
+                        ac_tables[id] = Some(decoder);
+                    }
+                    rest = &rest[17 + total..];
+                }
+            }
+            0xDB => {
+                let mut rest = body;
+                while !rest.is_empty() {
+                    let (precision, id) = (rest[0] >> 4, (rest[0] & 15) as usize);
+                    let size = if precision == 0 { 64 } else { 128 };
+                    let raw = rest.get(1..1 + size).ok_or_else(|| corrupt("truncated quantization table"))?;
+                    if id > 3 {
+                        return Err(corrupt("invalid quantization table id"));
+                    }
+                    let mut values = [0u16; 64];
+                    for (k, natural) in ZIGZAG.iter().enumerate() {
+                        values[*natural] = if precision == 0 {
+                            raw[k] as u16
+                        } else {
+                            u16::from_be_bytes([raw[k * 2], raw[k * 2 + 1]])
+                        };
+                    }
+                    jpeg.quant[id] = Some(QuantTable { precision, values });
+                    rest = &rest[1 + size..];
+                }
+            }
+            0xDD => restart_interval = read_u16(body, 0)?,
+            0xDA => {
+                decode_scan(&mut jpeg, body, &data[pos + length..], &dc_tables, &ac_tables, restart_interval)?;
+                return Ok(jpeg);
+            }
+            0xD9 => return Err(corrupt("no image data")),
+            0xE0..=0xEF | 0xFE => jpeg.segments.push(data[pos - 2..pos + length].to_vec()),
+            _ => {}
+        }
+        pos += length;
+    }
+}
+
+fn decode_scan(
+    jpeg: &mut Jpeg,
+    header: &[u8],
+    data: &[u8],
+    dc_tables: &[Option<Decoder>; 4],
+    ac_tables: &[Option<Decoder>; 4],
+    restart_interval: usize,
+) -> Result<(), AppError> {
+    if jpeg.components.is_empty() {
+        return Err(corrupt("scan before frame header"));
+    }
+    let count = *header.first().ok_or_else(|| corrupt("invalid scan header"))? as usize;
+    if count != jpeg.components.len() {
+        // 每个分量单独一次扫描的 JPEG 很少见，暂不支持
+        return Err(AppError::Unsupported("Lossless transforms of multi-scan JPEG".to_string()));
+    }
+    let mut tables = Vec::with_capacity(count);
+    for (index, c) in header.get(1..1 + count * 2).ok_or_else(|| corrupt("invalid scan header"))?.chunks_exact(2).enumerate() {
+        if jpeg.components[index].id != c[0] {
+            return Err(AppError::Unsupported("Scan components in a different order than the frame".to_string()));
+        }
+        let dc = dc_tables[(c[1] >> 4) as usize & 3].as_ref().ok_or_else(|| corrupt("missing Huffman table"))?;
+        let ac = ac_tables[(c[1] & 15) as usize & 3].as_ref().ok_or_else(|| corrupt("missing Huffman table"))?;
+        tables.push((dc, ac));
+    }
+
+    let (hmax, vmax) = jpeg.max_factors();
+    let (mcus_x, mcus_y) = (jpeg.width.div_ceil(8 * hmax) as usize, jpeg.height.div_ceil(8 * vmax) as usize);
+    // 解码时保留完整的 MCU，结束后再去掉图片外的块
+    let single = count == 1;
+    let counts: Vec<(usize, usize)> = jpeg.components.iter().map(|c| jpeg.block_counts(c)).collect();
+    for (component, &(blocks_w, blocks_h)) in jpeg.components.iter_mut().zip(&counts) {
+        if single {
+            component.blocks_w = blocks_w;
+            component.blocks_h = blocks_h;
+        } else {
+            component.blocks_w = mcus_x * component.h as usize;
+            component.blocks_h = mcus_y * component.v as usize;
+        }
+        component.blocks = vec![[0; 64]; component.blocks_w * component.blocks_h];
+    }
+
+    let mut reader = BitReader {
+        data,
+        pos: 0,
+        buffer: 0,
+        count: 0,
+        at_marker: false,
+    };
+    let mut predictors = vec![0i32; count];
+    let total = if single {
+        counts[0].0 * counts[0].1
+    } else {
+        mcus_x * mcus_y
+    };
+    for mcu in 0..total {
+        if restart_interval > 0 && mcu > 0 && mcu % restart_interval == 0 {
+            reader.restart();
+            predictors.fill(0);
+        }
+        if single {
+            let (dc, ac) = tables[0];
+            jpeg.components[0].blocks[mcu] = decode_block(&mut reader, dc, ac, &mut predictors[0])?;
+            continue;
+        }
+        let (mx, my) = (mcu % mcus_x, mcu / mcus_x);
+        for (index, component) in jpeg.components.iter_mut().enumerate() {
+            let (dc, ac) = tables[index];
+            let (h, v) = (component.h as usize, component.v as usize);
+            for by in 0..v {
+                for bx in 0..h {
+                    let block = decode_block(&mut reader, dc, ac, &mut predictors[index])?;
+                    component.blocks[(my * v + by) * component.blocks_w + mx * h + bx] = block;
+                }
+            }
+        }
+    }
+    jpeg.fit_blocks((0, 0));
+    Ok(())
+}
+
+// ---- 霍夫曼编码 ----
+
+fn category(value: i32) -> u8 {
+    (32 - value.unsigned_abs().leading_zeros()) as u8
+}
+
+fn value_bits(value: i32, size: u8) -> u32 {
+    let bits = if value < 0 { value - 1 } else { value };
+    bits as u32 & ((1u32 << size) - 1)
+}
+
+// 一个块的霍夫曼符号：(是否为 AC, 符号, 附加位, 附加位数)
+fn block_symbols<F: FnMut(bool, u8, u32, u8)>(block: &Block, predictor: &mut i32, mut emit: F) {
+    let diff = block[0] as i32 - *predictor;
+    *predictor = block[0] as i32;
+    let size = category(diff);
+    emit(false, size, value_bits(diff, size), size);
+
+    let mut run = 0;
+    for &natural in &ZIGZAG[1..] {
+        let value = block[natural] as i32;
+        if value == 0 {
+            run += 1;
+            continue;
+        }
+        while run > 15 {
+            emit(true, 0xF0, 0, 0);
+            run -= 16;
+        }
+        let size = category(value);
+        emit(true, ((run << 4) as u8) | size, value_bits(value, size), size);
+        run = 0;
+    }
+    if run > 0 {
+        emit(true, 0x00, 0, 0);
+    }
+}
+
+// 按符号频率生成最优霍夫曼表（JPEG 标准附录 K.2），码长不超过 16 位
+fn optimal_table(frequencies: &[u32; 256]) -> ([u8; 16], Vec<u8>) {
+    let mut freq: Vec<u64> = frequencies.iter().map(|&f| f as u64).collect();
+    if freq.iter().all(|&f| f == 0) {
+        freq[0] = 1;
+    }
+    // 保留一个频率为 1 的符号，保证不会出现全 1 的码字
+    freq.push(1);
+    let mut code_size = [0usize; 257];
+    let mut others = [usize::MAX; 257];
+
+    loop {
+        let (mut c1, mut c2): (Option<usize>, Option<usize>) = (None, None);
+        for (i, &f) in freq.iter().enumerate() {
+            if f == 0 {
+                continue;
+            }
+            if c1.map_or(true, |c| f <= freq[c]) {
+                c2 = c1;
+                c1 = Some(i);
+            } else if c2.map_or(true, |c| f <= freq[c]) {
+                c2 = Some(i);
+            }
+        }
+        let (Some(mut a), Some(mut b)) = (c1, c2) else {
+            break;
+        };
+        freq[a] += freq[b];
+        freq[b] = 0;
+        code_size[a] += 1;
+        while others[a] != usize::MAX {
+            a = others[a];
+            code_size[a] += 1;
+        }
+        others[a] = b;
+        code_size[b] += 1;
+        while others[b] != usize::MAX {
+            b = others[b];
+            code_size[b] += 1;
+        }
+    }
+
+    let mut bits = [0u32; 258];
+    for &size in &code_size {
+        if size > 0 {
+            bits[size] += 1;
+        }
+    }
+    // 把超过 16 位的码字移到较短的长度
+    for i in (17..258).rev() {
+        while bits[i] > 0 {
+            let mut j = i - 2;
+            while bits[j] == 0 {
+                j -= 1;
+            }
+            bits[i] -= 2;
+            bits[i - 1] += 1;
+            bits[j + 1] += 2;
+            bits[j] -= 1;
+        }
+    }
+    // 去掉保留的符号
+    let mut longest = 16;
+    while bits[longest] == 0 {
+        longest -= 1;
+    }
+    bits[longest] -= 1;
+
+    let mut counts = [0u8; 16];
+    for (length, count) in counts.iter_mut().enumerate() {
+        *count = bits[length + 1] as u8;
+    }
+    // 符号按原始码长排序，再按 counts 依次分配到各个长度
+    let mut symbols: Vec<(usize, u8)> = (0..256).filter(|&s| code_size[s] > 0).map(|s| (code_size[s], s as u8)).collect();
+    symbols.sort();
+    (counts, symbols.into_iter().map(|(_, s)| s).collect())
+}
+
+struct Encoder {
+    codes: [u16; 256],
+    sizes: [u8; 256],
+}
+
+impl Encoder {
+    fn new(bits: &[u8; 16], values: &[u8]) -> Self {
+        let mut encoder = Encoder {
+            codes: [0; 256],
+            sizes: [0; 256],
+        };
+        let (mut code, mut k) = (0u16, 0);
+        for (length, &count) in bits.iter().enumerate() {
+            for _ in 0..count {
+                encoder.codes[values[k] as usize] = code;
+                encoder.sizes[values[k] as usize] = length as u8 + 1;
+                code += 1;
+                k += 1;
+            }
+            code <<= 1;
+        }
+        encoder
+    }
+}
+
+#[derive(Default)]
+struct BitWriter {
+    data: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, bits: u32, size: u8) {
+        self.buffer = (self.buffer << size) | bits as u64;
+        self.count += size as u32;
+        while self.count >= 8 {
+            self.count -= 8;
+            let byte = (self.buffer >> self.count) as u8;
+            self.data.push(byte);
+            if byte == 0xFF {
+                self.data.push(0);
+            }
+        }
+    }
+
+    // 最后不足一个字节的部分用 1 填充
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            let pad = 8 - self.count as u8;
+            self.put((1 << pad) - 1, pad);
+        }
+        self.data
+    }
+}
+
+fn segment(out: &mut Vec<u8>, marker: u8, body: &[u8]) {
+    out.extend_from_slice(&[0xFF, marker]);
+    out.extend_from_slice(&((body.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(body);
+}
+
+fn encode(jpeg: &Jpeg) -> Vec<u8> {
+    // 第一个分量（亮度）使用 0 号表，其余分量共用 1 号表
+    let table_of = |index: usize| usize::from(index > 0);
+
+    // 第一遍统计符号频率，生成最优霍夫曼表
+    let mut frequencies = [[[0u32; 256]; 2]; 2];
+    let mut predictors = vec![0i32; jpeg.components.len()];
+    jpeg.for_each_block(|index, block| {
+        block_symbols(block, &mut predictors[index], |ac, symbol, _, _| {
+            frequencies[usize::from(ac)][table_of(index)][symbol as usize] += 1;
+        });
+    });
+    let tables_used = if jpeg.components.len() > 1 { 2 } else { 1 };
+    let mut specs = Vec::new();
+    let mut encoders: Vec<Vec<Encoder>> = vec![Vec::new(), Vec::new()];
+    for class in 0..2 {
+        for id in 0..tables_used {
+            let (bits, values) = optimal_table(&frequencies[class][id]);
+            encoders[class].push(Encoder::new(&bits, &values));
+            specs.push((((class as u8) << 4) | id as u8, bits, values));
+        }
+    }
+
+    // 第二遍编码
+    let mut writer = BitWriter::default();
+    predictors.fill(0);
+    jpeg.for_each_block(|index, block| {
+        block_symbols(block, &mut predictors[index], |ac, symbol, bits, size| {
+            let encoder = &encoders[usize::from(ac)][table_of(index)];
+            writer.put(encoder.codes[symbol as usize] as u32, encoder.sizes[symbol as usize]);
+            if size > 0 {
+                writer.put(bits, size);
+            }
+        });
+    });
+    let entropy = writer.finish();
+
+    let mut out = vec![0xFF, 0xD8];
+    for data in &jpeg.segments {
+        out.extend_from_slice(data);
+    }
+    // 像素已经按变换重排，EXIF 中的方向改为 1，否则查看器会再旋转一次
+    metadata::reset_jpeg_orientation(&mut out);
+    for (id, table) in jpeg.quant.iter().enumerate() {
+        let Some(table) = table else { continue };
+        let mut body = vec![(table.precision << 4) | id as u8];
+        for &natural in &ZIGZAG {
+            let value = table.values[natural];
+            if table.precision == 0 {
+                body.push(value as u8);
+            } else {
+                body.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        segment(&mut out, 0xDB, &body);
+    }
+
+    let mut frame = vec![8];
+    frame.extend_from_slice(&(jpeg.height as u16).to_be_bytes());
+    frame.extend_from_slice(&(jpeg.width as u16).to_be_bytes());
+    frame.push(jpeg.components.len() as u8);
+    for component in &jpeg.components {
+        frame.extend_from_slice(&[component.id, ((component.h << 4) | component.v) as u8, component.quant]);
+    }
+    // 基线 JPEG 不允许 16 位量化表，此时即使原图为 SOF0 也写为 SOF1
+    let extended = jpeg.frame_marker == 0xC1 || jpeg.quant.iter().flatten().any(|table| table.precision > 0);
+    segment(&mut out, if extended { 0xC1 } else { 0xC0 }, &frame);
+
+    for (class_id, bits, values) in &specs {
+        let mut body = vec![*class_id];
+        body.extend_from_slice(bits);
+        body.extend_from_slice(values);
+        segment(&mut out, 0xC4, &body);
+    }
+
+    let mut scan = vec![jpeg.components.len() as u8];
+    for (index, component) in jpeg.components.iter().enumerate() {
+        let id = table_of(index) as u8;
+        scan.extend_from_slice(&[component.id, (id << 4) | id]);
+    }
+    scan.extend_from_slice(&[0, 63, 0]);
+    segment(&mut out, 0xDA, &scan);
+    out.extend_from_slice(&entropy);
+    out.extend_from_slice(&[0xFF, 0xD9]);
+    out
+}
+
+// 对 JPEG 数据依次执行无损变换，返回新的 JPEG 数据、尺寸以及是否裁掉了边缘
+pub(crate) fn transform(data: &[u8], operations: &[LosslessOperation]) -> Result<(Vec<u8>, u32, u32, bool), AppError> {
+    let mut jpeg = parse(data)?;
+    for operation in operations {
+        jpeg.apply(*operation)?;
+    }
+    Ok((encode(&jpeg), jpeg.width, jpeg.height, jpeg.trimmed))
+}
+
+// 对 JPEG 文件执行无损变换，output 为空时覆盖原文件
+#[tauri::command]
+pub async fn transform_jpeg_lossless(
+    path: String,
+    operations: Vec<LosslessOperation>,
+    output: Option<String>,
+) -> Result<LosslessResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    if operations.is_empty() {
+        return Err(AppError::InvalidArgument("No operations".to_string()));
+    }
+
+    run_blocking("transform_jpeg_lossless", move || {
+        let path = Path::new(&path);
+        let data = fs::read(paths::extended(path)).map_err(|e| AppError::from(e).at(path))?;
+        let (result, width, height, trimmed) = transform(&data, &operations).map_err(|e| e.at(path))?;
+        let output = output.map(PathBuf::from).unwrap_or_else(|| path.to_path_buf());
+        write_bytes(&output, &result)?;
+        Ok(LosslessResult {
+            output: paths::display(&output),
+            width,
+            height,
+            trimmed,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+
+    use super::*;
+
+    // 32x48 的彩色图片，尺寸为 MCU 的整数倍，旋转和翻转时不会裁掉边缘
+    fn sample_jpeg() -> Vec<u8> {
+        let img = RgbImage::from_fn(32, 48, |x, y| Rgb([(x * 8) as u8, (y * 5) as u8, (x * y % 256) as u8]));
+        let mut data = Vec::new();
+        JpegEncoder::new_with_quality(&mut data, 90).encode_image(&img).unwrap();
+        data
+    }
+
+    fn decode(data: &[u8]) -> DynamicImage {
+        image::load_from_memory(data).unwrap()
+    }
+
+    fn max_difference(a: &DynamicImage, b: &DynamicImage) -> u8 {
+        assert_eq!(a.dimensions(), b.dimensions());
+        let (a, b) = (a.to_rgb8(), b.to_rgb8());
+        a.as_raw().iter().zip(b.as_raw()).map(|(x, y)| x.abs_diff(*y)).max().unwrap_or(0)
+    }
+
+    // 帧头（SOFn）标记的位置
+    fn frame_position(data: &[u8]) -> Option<usize> {
+        let mut pos = 2;
+        while pos + 4 <= data.len() && data[pos] == 0xFF {
+            match data[pos + 1] {
+                0xC0..=0xC3 => return Some(pos),
+                0xDA => return None,
+                _ => pos += 2 + read_u16(data, pos + 2).ok()?,
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn rotate90_four_times_is_identity() {
+        let data = sample_jpeg();
+        let (unchanged, ..) = transform(&data, &[]).unwrap();
+        let (rotated, width, height, trimmed) = transform(&data, &[LosslessOperation::Rotate90; 4]).unwrap();
+        assert_eq!((width, height, trimmed), (32, 48, false));
+        assert_eq!(rotated, unchanged);
+    }
+
+    #[test]
+    fn transforms_match_decoded_pixels() {
+        let data = sample_jpeg();
+        let original = decode(&data);
+        let cases = [
+            (LosslessOperation::Rotate90, original.rotate90()),
+            (LosslessOperation::Rotate180, original.rotate180()),
+            (LosslessOperation::Rotate270, original.rotate270()),
+            (LosslessOperation::FlipHorizontal, original.fliph()),
+            (LosslessOperation::FlipVertical, original.flipv()),
+        ];
+        for (operation, expected) in cases {
+            let (result, ..) = transform(&data, &[operation]).unwrap();
+            // 解码器的 IDCT 在行、列方向上的舍入不完全对称，转置后的块解码结果可能差一两个色阶
+            assert!(max_difference(&decode(&result), &expected) <= 4, "{:?}", operation);
+        }
+    }
+
+    #[test]
+    fn crop_rounds_down_to_mcu() {
+        let data = sample_jpeg();
+        let (mcu_w, mcu_h) = parse(&data).unwrap().mcu_size();
+        let crop = LosslessOperation::Crop {
+            x: mcu_w + 3,
+            y: mcu_h,
+            width: 10,
+            height: 12,
+        };
+        let (result, width, height, _) = transform(&data, &[crop]).unwrap();
+        assert_eq!((width, height), (13, 12));
+        let expected = decode(&data).crop_imm(mcu_w, mcu_h, 13, 12);
+        assert!(max_difference(&decode(&result), &expected) <= 4);
+    }
+
+    #[test]
+    fn keeps_extended_frame_marker() {
+        let mut data = sample_jpeg();
+        let pos = frame_position(&data).unwrap();
+        assert_eq!(data[pos + 1], 0xC0);
+        data[pos + 1] = 0xC1;
+        let (result, ..) = transform(&data, &[LosslessOperation::Rotate90]).unwrap();
+        assert_eq!(result[frame_position(&result).unwrap() + 1], 0xC1);
+    }
+
+    #[test]
+    fn sixteen_bit_quant_tables_use_extended_frame() {
+        let mut jpeg = parse(&sample_jpeg()).unwrap();
+        let table = jpeg.quant[0].as_mut().unwrap();
+        table.precision = 1;
+        table.values[0] = 300;
+        let result = encode(&jpeg);
+        assert_eq!(result[frame_position(&result).unwrap() + 1], 0xC1);
+        let parsed = parse(&result).unwrap();
+        assert_eq!(parsed.quant[0].unwrap().precision, 1);
+        assert_eq!(parsed.quant[0].unwrap().values[0], 300);
+    }
+
+    #[test]
+    fn resets_exif_orientation() {
+        let data = sample_jpeg();
+        // 只有方向标签（值为 6）的小端 TIFF
+        let tiff = [
+            b'I', b'I', 42, 0, 8, 0, 0, 0, 1, 0, 0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&tiff);
+        let mut with_exif = data[..2].to_vec();
+        segment(&mut with_exif, 0xE1, &app1);
+        with_exif.extend_from_slice(&data[2..]);
+
+        let (result, ..) = transform(&with_exif, &[LosslessOperation::Rotate90]).unwrap();
+        let exif = metadata::jpeg_exif_segment(&result).unwrap();
+        // 方向的值在 TIFF 头之后第 18 字节，TIFF 头在 "Exif\0\0" 之后
+        let value = exif.start + 10 + 18;
+        assert_eq!(&result[value..value + 2], &[1, 0]);
+    }
+}
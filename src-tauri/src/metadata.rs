@@ -0,0 +1,346 @@
+// EXIF 元数据：读取、按方向旋转，以及保存编辑结果时把原图的 EXIF 写回 JPEG/PNG/WebP
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::path::Path;
+
+use exif::{DateTime, Exif, In, Tag, Value};
+use image::{DynamicImage, ImageFormat};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::{geo, paths, run_blocking, scope, xmp};
+
+// 查找内嵌 XMP 时最多读取的字节数，XMP 通常位于文件开头
+const XMP_SCAN_BYTES: u64 = 4 * 1024 * 1024;
+
+pub(crate) fn read_exif(path: &Path) -> Option<Exif> {
+    let file = File::open(paths::extended(path)).ok()?;
+    exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()
+}
+
+// 拍摄时间：优先使用 DateTimeOriginal，没有时使用 DateTime
+pub(crate) fn date_taken(exif: &Exif) -> Option<DateTime> {
+    [Tag::DateTimeOriginal, Tag::DateTime].iter().find_map(|tag| {
+        match &exif.get_field(*tag, In::PRIMARY)?.value {
+            Value::Ascii(values) => DateTime::from_ascii(values.first()?).ok(),
+            _ => None,
+        }
+    })
+}
+
+// EXIF 方向（1~8），没有该标签时为 1
+pub(crate) fn orientation(exif: &Exif) -> u32 {
+    exif.get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .filter(|value| (1..=8).contains(value))
+        .unwrap_or(1)
+}
+
+// 按 EXIF 方向旋转、翻转，得到正常方向的图片
+pub(crate) fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate90().flipv(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+// 换算为 35mm 胶片的等效焦距，毫米
+pub(crate) fn focal_length_35mm(exif: &Exif) -> Option<f32> {
+    exif.get_field(Tag::FocalLengthIn35mmFilm, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .filter(|value| *value > 0)
+        .map(|value| value as f32)
+}
+
+// JPEG 中 EXIF 所在 APP1 段的范围（含标记和长度）
+pub(crate) fn jpeg_exif_segment(data: &[u8]) -> Option<Range<usize>> {
+    let mut pos = 2;
+    while pos + 4 <= data.len() && data[pos] == 0xFF {
+        let marker = data[pos + 1];
+        // 到达图像数据后不再有元数据段
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let end = pos + 2 + length;
+        if marker == 0xE1 && data.get(pos + 4..pos + 10) == Some(b"Exif\0\0".as_slice()) {
+            return (end <= data.len()).then_some(pos..end);
+        }
+        pos = end;
+    }
+    None
+}
+
+// 把 JPEG 数据中 IFD0 的方向标签改为 1（原地修改，不改变其他元数据），返回是否找到该标签
+pub(crate) fn reset_jpeg_orientation(data: &mut [u8]) -> bool {
+    let Some(segment) = jpeg_exif_segment(data) else {
+        return false;
+    };
+    // TIFF 头在 "Exif\0\0" 之后
+    reset_tiff_orientation(&mut data[segment.start + 10..segment.end])
+}
+
+// 把 TIFF 格式的 EXIF 数据中 IFD0 的方向标签改为 1
+fn reset_tiff_orientation(tiff: &mut [u8]) -> bool {
+    let big_endian = match tiff.get(0..2) {
+        Some(b"MM") => true,
+        Some(b"II") => false,
+        _ => return false,
+    };
+    let read_u16 = |bytes: &[u8], at: usize| -> Option<usize> {
+        let pair = [*bytes.get(at)?, *bytes.get(at + 1)?];
+        Some(if big_endian { u16::from_be_bytes(pair) } else { u16::from_le_bytes(pair) } as usize)
+    };
+    let read_u32 = |bytes: &[u8], at: usize| -> Option<usize> {
+        let quad = [*bytes.get(at)?, *bytes.get(at + 1)?, *bytes.get(at + 2)?, *bytes.get(at + 3)?];
+        Some(if big_endian { u32::from_be_bytes(quad) } else { u32::from_le_bytes(quad) } as usize)
+    };
+
+    let Some(ifd) = read_u32(tiff, 4) else {
+        return false;
+    };
+    let Some(count) = read_u16(tiff, ifd) else {
+        return false;
+    };
+    for index in 0..count {
+        let entry = ifd + 2 + index * 12;
+        // 方向标签为 SHORT 类型，值直接存放在条目中
+        if read_u16(tiff, entry) == Some(0x0112) && read_u16(tiff, entry + 2) == Some(3) && entry + 10 <= tiff.len() {
+            let one = if big_endian { 1u16.to_be_bytes() } else { 1u16.to_le_bytes() };
+            tiff[entry + 8..entry + 10].copy_from_slice(&one);
+            return true;
+        }
+    }
+    false
+}
+
+// 写入输出文件的 EXIF（TIFF 格式）：源文件的 EXIF，方向改为 1（保存的像素已经按方向旋转过）
+pub(crate) fn output_exif(source: &Path) -> Option<Vec<u8>> {
+    let exif = read_exif(source)?;
+    let mut tiff = exif.buf().to_vec();
+    reset_tiff_orientation(&mut tiff);
+    Some(tiff)
+}
+
+pub(crate) fn supports_exif(format: ImageFormat) -> bool {
+    matches!(format, ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)
+}
+
+// 把 EXIF 写入编码好的图片数据；格式不支持或数据无法识别时原样返回
+pub(crate) fn embed_exif(data: Vec<u8>, format: ImageFormat, tiff: &[u8]) -> Vec<u8> {
+    let embedded = match format {
+        ImageFormat::Jpeg => embed_jpeg(&data, tiff),
+        ImageFormat::Png => embed_png(&data, tiff),
+        ImageFormat::WebP => embed_webp(&data, tiff),
+        _ => None,
+    };
+    embedded.unwrap_or(data)
+}
+
+// JPEG：在 SOI 之后插入 APP1 段
+fn embed_jpeg(data: &[u8], tiff: &[u8]) -> Option<Vec<u8>> {
+    let length = u16::try_from(2 + 6 + tiff.len()).ok()?;
+    if data.get(0..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+    let mut output = Vec::with_capacity(data.len() + tiff.len() + 10);
+    output.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xE1]);
+    output.extend_from_slice(&length.to_be_bytes());
+    output.extend_from_slice(b"Exif\0\0");
+    output.extend_from_slice(tiff);
+    output.extend_from_slice(&data[2..]);
+    Some(output)
+}
+
+// PNG：在 IHDR 之后插入 eXIf 块
+fn embed_png(data: &[u8], tiff: &[u8]) -> Option<Vec<u8>> {
+    // 8 字节签名 + IHDR 块（长度 4 + 类型 4 + 数据 13 + CRC 4）
+    const IHDR_END: usize = 8 + 25;
+    if data.len() < IHDR_END || data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let mut chunk = Vec::with_capacity(tiff.len() + 12);
+    chunk.extend_from_slice(&u32::try_from(tiff.len()).ok()?.to_be_bytes());
+    chunk.extend_from_slice(b"eXIf");
+    chunk.extend_from_slice(tiff);
+    let crc = crc32fast::hash(&chunk[4..]);
+    chunk.extend_from_slice(&crc.to_be_bytes());
+
+    let mut output = Vec::with_capacity(data.len() + chunk.len());
+    output.extend_from_slice(&data[..IHDR_END]);
+    output.extend_from_slice(&chunk);
+    output.extend_from_slice(&data[IHDR_END..]);
+    Some(output)
+}
+
+// WebP：EXIF 块只能用于扩展格式，简单格式（只有 VP8/VP8L 块）需要先加上 VP8X 块
+fn embed_webp(data: &[u8], tiff: &[u8]) -> Option<Vec<u8>> {
+    const EXIF_FLAG: u8 = 0x08;
+    const ALPHA_FLAG: u8 = 0x10;
+    if data.get(0..4)? != b"RIFF" || data.get(8..12)? != b"WEBP" {
+        return None;
+    }
+    let mut output = data[..12].to_vec();
+    match data.get(12..16)? {
+        b"VP8X" => {
+            output.extend_from_slice(&data[12..]);
+            output[20] |= EXIF_FLAG;
+        }
+        fourcc => {
+            // 画布尺寸从图像数据的头部读取
+            let (width, height, alpha) = match fourcc {
+                b"VP8L" => {
+                    let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+                    ((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1, (bits >> 28) & 1 == 1)
+                }
+                b"VP8 " => {
+                    let width = u16::from_le_bytes(data.get(26..28)?.try_into().ok()?) & 0x3FFF;
+                    let height = u16::from_le_bytes(data.get(28..30)?.try_into().ok()?) & 0x3FFF;
+                    (width as u32, height as u32, false)
+                }
+                _ => return None,
+            };
+            output.extend_from_slice(b"VP8X");
+            output.extend_from_slice(&10u32.to_le_bytes());
+            output.push(EXIF_FLAG | if alpha { ALPHA_FLAG } else { 0 });
+            output.extend_from_slice(&[0; 3]);
+            output.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+            output.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+            output.extend_from_slice(&data[12..]);
+        }
+    }
+    output.extend_from_slice(b"EXIF");
+    output.extend_from_slice(&u32::try_from(tiff.len()).ok()?.to_le_bytes());
+    output.extend_from_slice(tiff);
+    // 块的长度为奇数时补一个字节
+    if tiff.len() % 2 == 1 {
+        output.push(0);
+    }
+    let riff_size = u32::try_from(output.len() - 8).ok()?;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(output)
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataField {
+    // primary（主图）或 thumbnail（缩略图）
+    pub ifd: String,
+    pub tag: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageMetadata {
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub lens: Option<String>,
+    // YYYY-MM-DD HH:MM:SS
+    pub date_taken: Option<String>,
+    pub orientation: u32,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    // 例如 1/125 s
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<u32>,
+    // 毫米
+    pub focal_length: Option<f64>,
+    // XMP 旁车文件中的关键词
+    pub keywords: Vec<String>,
+    // 文件内嵌的 XMP 数据包（XML）
+    pub xmp: Option<String>,
+    // 全部 EXIF 字段（不含厂商私有的 MakerNote）
+    pub fields: Vec<MetadataField>,
+}
+
+fn text_field(exif: &Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let text = field.display_value().to_string().trim_matches('"').trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn rational_field(exif: &Exif, tag: Tag) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) => values.first().map(|v| v.to_f64()).filter(|v| v.is_finite()),
+        _ => None,
+    }
+}
+
+// 文件中内嵌的 XMP 数据包
+fn embedded_xmp(path: &Path) -> Option<String> {
+    let mut data = Vec::new();
+    File::open(paths::extended(path)).ok()?.take(XMP_SCAN_BYTES).read_to_end(&mut data).ok()?;
+    let find = |needle: &[u8], from: usize| data[from..].windows(needle.len()).position(|w| w == needle).map(|i| i + from);
+    let start = find(b"<x:xmpmeta", 0)?;
+    let end = find(b"</x:xmpmeta>", start)? + b"</x:xmpmeta>".len();
+    Some(String::from_utf8_lossy(&data[start..end]).into_owned())
+}
+
+// 读取图片的 EXIF/XMP 元数据；没有元数据的图片返回空的各项
+#[tauri::command]
+pub async fn get_image_metadata(path: String) -> Result<ImageMetadata, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("get_image_metadata", move || {
+        let path = Path::new(&path);
+        if !path.is_file() {
+            return Err(AppError::NotFound(paths::display(path)));
+        }
+        let mut metadata = ImageMetadata {
+            orientation: 1,
+            keywords: xmp::read_keywords(path),
+            xmp: embedded_xmp(path),
+            ..Default::default()
+        };
+        let Some(exif) = read_exif(path) else {
+            return Ok(metadata);
+        };
+
+        let gps = geo::read_gps(path);
+        metadata = ImageMetadata {
+            make: text_field(&exif, Tag::Make),
+            model: text_field(&exif, Tag::Model),
+            lens: text_field(&exif, Tag::LensModel),
+            date_taken: date_taken(&exif).map(|d| {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    d.year, d.month, d.day, d.hour, d.minute, d.second
+                )
+            }),
+            orientation: orientation(&exif),
+            latitude: gps.map(|g| g.0),
+            longitude: gps.map(|g| g.1),
+            exposure_time: exif
+                .get_field(Tag::ExposureTime, In::PRIMARY)
+                .map(|field| field.display_value().with_unit(&exif).to_string()),
+            f_number: rational_field(&exif, Tag::FNumber),
+            iso: exif
+                .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0)),
+            focal_length: rational_field(&exif, Tag::FocalLength),
+            fields: exif
+                .fields()
+                .filter(|field| field.tag != Tag::MakerNote)
+                .map(|field| MetadataField {
+                    ifd: if field.ifd_num == In::PRIMARY { "primary" } else { "thumbnail" }.to_string(),
+                    tag: field.tag.to_string(),
+                    value: field.display_value().with_unit(&exif).to_string(),
+                })
+                .collect(),
+            ..metadata
+        };
+        Ok(metadata)
+    })
+    .await
+}
@@ -0,0 +1,71 @@
+// 本地 ONNX 模型推理的公共部分：模型定位、会话缓存和张量转换
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use image::RgbImage;
+use ndarray::Array4;
+use ort::session::builder::GraphOptimizationLevel;
+use ort::session::Session;
+use parking_lot::Mutex;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+// ImageNet 的归一化参数，大多数视觉模型都使用这一组
+pub const IMAGENET_MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+pub const IMAGENET_STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+lazy_static::lazy_static! {
+    // 已加载的模型会话，按模型文件路径缓存
+    static ref SESSIONS: Mutex<HashMap<PathBuf, Arc<Session>>> = Mutex::new(HashMap::new());
+}
+
+// 查找模型文件：优先使用应用数据目录下用户放置的模型，其次使用随应用打包的模型
+pub fn resolve_model(app: &AppHandle, file_name: &str) -> Result<PathBuf, AppError> {
+    let candidates = [
+        app.path()
+            .resolve(format!("models/{}", file_name), BaseDirectory::AppData),
+        app.path()
+            .resolve(format!("models/{}", file_name), BaseDirectory::Resource),
+    ];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .find(|p| p.is_file())
+        .ok_or_else(|| AppError::NotFound(format!("Model {}", file_name)))
+}
+
+// 加载（或复用已加载的）模型会话
+pub fn load_session(path: &PathBuf) -> Result<Arc<Session>, AppError> {
+    let mut sessions = SESSIONS.lock();
+    if let Some(session) = sessions.get(path) {
+        return Ok(session.clone());
+    }
+
+    let session = Session::builder()
+        .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+        .and_then(|b| b.commit_from_file(path))
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to load model: {}", e)))?;
+    let session = Arc::new(session);
+    sessions.insert(path.clone(), session.clone());
+    Ok(session)
+}
+
+// 将 RGB 图片转换为 NCHW 格式的张量，可选按均值/标准差归一化
+pub fn to_tensor(img: &RgbImage, normalize: Option<([f32; 3], [f32; 3])>) -> Array4<f32> {
+    let (width, height) = img.dimensions();
+    let mut tensor = Array4::<f32>::zeros((1, 3, height as usize, width as usize));
+    for (x, y, pixel) in img.enumerate_pixels() {
+        for c in 0..3 {
+            let mut value = pixel[c] as f32 / 255.0;
+            if let Some((mean, std)) = normalize {
+                value = (value - mean[c]) / std[c];
+            }
+            tensor[[0, c, y as usize, x as usize]] = value;
+        }
+    }
+    tensor
+}
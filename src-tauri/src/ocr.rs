@@ -0,0 +1,96 @@
+// OCR 文字识别（基于 tesseract，需要系统安装 tesseract 及语言数据）
+use serde::Serialize;
+use tesseract::Tesseract;
+
+use crate::barcode::BoundingBox;
+use crate::error::AppError;
+use crate::run_blocking;
+use crate::scope;
+
+// 未指定语言时同时识别英文和简体中文
+const DEFAULT_LANGUAGES: &str = "eng+chi_sim";
+
+#[derive(Serialize, Debug)]
+pub struct OcrWord {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: BoundingBox,
+    pub line: u32,
+}
+
+#[derive(Serialize, Debug)]
+pub struct OcrResult {
+    pub text: String,
+    pub words: Vec<OcrWord>,
+}
+
+// 解析 tesseract 的 TSV 输出，只保留单词级别的记录
+fn parse_tsv(tsv: &str) -> Vec<OcrWord> {
+    let mut words = Vec::new();
+    let mut line_index = 0u32;
+    let mut last_line_key = None;
+
+    for row in tsv.lines() {
+        let columns: Vec<&str> = row.split('\t').collect();
+        if columns.len() < 12 || columns[0] != "5" {
+            continue;
+        }
+        let text = columns[11].trim();
+        let confidence: f32 = columns[10].parse().unwrap_or(-1.0);
+        if text.is_empty() || confidence < 0.0 {
+            continue;
+        }
+
+        // 以 块/段落/行 编号区分不同的文本行
+        let line_key = (columns[2], columns[3], columns[4]);
+        if last_line_key.is_some() && last_line_key != Some(line_key) {
+            line_index += 1;
+        }
+        last_line_key = Some(line_key);
+
+        let number = |i: usize| columns[i].parse::<u32>().unwrap_or(0);
+        words.push(OcrWord {
+            text: text.to_string(),
+            confidence,
+            bounds: BoundingBox {
+                x: number(6),
+                y: number(7),
+                width: number(8),
+                height: number(9),
+            },
+            line: line_index,
+        });
+    }
+
+    words
+}
+
+// 识别图片中的文字，返回全文以及每个单词的位置和置信度
+#[tauri::command]
+pub async fn recognize_text(path: String, languages: Option<String>) -> Result<OcrResult, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("recognize_text", move || {
+        let languages = languages.unwrap_or_else(|| DEFAULT_LANGUAGES.to_string());
+
+        let mut engine = Tesseract::new(None, Some(&languages))
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to initialize OCR engine: {}", e)))?
+            .set_image(&path)
+            .map_err(|e| AppError::DecodeFailed(format!("Failed to open image: {}", e)))?
+            .recognize()
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to recognize text: {}", e)))?;
+
+        let text = engine
+            .get_text()
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to read recognized text: {}", e)))?;
+        let tsv = engine
+            .get_tsv_text(0)
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to read recognized text: {}", e)))?;
+
+        Ok(OcrResult {
+            text: text.trim().to_string(),
+            words: parse_tsv(&tsv),
+        })
+    })
+    .await
+}
@@ -0,0 +1,158 @@
+// 方向校正：把 EXIF 方向不为 1 的图片按方向实际旋转像素，并把方向标签改为 1，
+// 不读取 EXIF 方向的查看器（以及网页上传后去掉元数据的情况）也能正确显示
+// JPEG 优先使用无损变换；需要裁掉边缘时改为解码后旋转再重新编码，保留原有的 EXIF
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::ImageFormat;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::listing::{list_files, SymlinkPolicy};
+use crate::lossless::{self, LosslessOperation};
+use crate::storage::{save_image, write_bytes};
+use crate::{gif, metadata, open_image, paths, scope, settings};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrientationResult {
+    pub path: String,
+    // 校正前的 EXIF 方向
+    pub orientation: u32,
+    pub lossless: bool,
+    pub error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrientationProgress {
+    pub path: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 把图片转为正常方向所需的无损变换
+fn operations(orientation: u32) -> &'static [LosslessOperation] {
+    match orientation {
+        2 => &[LosslessOperation::FlipHorizontal],
+        3 => &[LosslessOperation::Rotate180],
+        4 => &[LosslessOperation::FlipVertical],
+        5 => &[LosslessOperation::Transpose],
+        6 => &[LosslessOperation::Rotate90],
+        7 => &[LosslessOperation::Rotate90, LosslessOperation::FlipVertical],
+        8 => &[LosslessOperation::Rotate270],
+        _ => &[],
+    }
+}
+
+fn is_jpeg(path: &Path) -> bool {
+    matches!(ImageFormat::from_path(path), Ok(ImageFormat::Jpeg))
+}
+
+// 重新编码 JPEG（open_image 已按方向旋转），并把原文件的 EXIF 段（方向已改为 1）放回新文件
+// JFIF 要求 APP0 紧跟在 SOI 之后，EXIF 段插在编码器写出的 APP0 之后
+fn reencode_jpeg(path: &Path, original: &[u8]) -> Result<(), AppError> {
+    let img = open_image(path)?;
+    let mut encoded = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut encoded, settings::current().export_quality).encode_image(&img.to_rgb8())?;
+    let encoded = encoded.into_inner();
+
+    let header_end = match encoded.get(2..6) {
+        Some([0xFF, 0xE0, high, low]) => 4 + u16::from_be_bytes([*high, *low]) as usize,
+        _ => 2,
+    };
+    let mut data = encoded[..header_end].to_vec();
+    if let Some(segment) = metadata::jpeg_exif_segment(original) {
+        data.extend_from_slice(&original[segment]);
+    }
+    data.extend_from_slice(&encoded[header_end..]);
+    write_bytes(path, &data)
+}
+
+// 校正一张图片，返回是否为无损处理
+fn normalize(path: &Path, orientation: u32) -> Result<bool, AppError> {
+    if gif::is_gif(path) {
+        // 逐帧旋转，保留动画
+        gif::edit_frames(path, path, |frame| metadata::apply_orientation(frame, orientation))?;
+        return Ok(false);
+    }
+    if !is_jpeg(path) {
+        // 其他格式重新保存时不写入 EXIF，方向标签随之去掉；open_image 已按方向旋转
+        save_image(&open_image(path)?, path)?;
+        return Ok(false);
+    }
+
+    let mut original = fs::read(paths::extended(path)).map_err(|e| AppError::from(e).at(path))?;
+    metadata::reset_jpeg_orientation(&mut original);
+    match lossless::transform(&original, operations(orientation)) {
+        Ok((data, _, _, false)) => {
+            write_bytes(path, &data)?;
+            Ok(true)
+        }
+        // 尺寸不是 MCU 的整数倍（无损变换会裁掉边缘）或无法无损处理的 JPEG
+        Ok(_) | Err(AppError::Unsupported(_)) => {
+            reencode_jpeg(path, &original)?;
+            Ok(false)
+        }
+        Err(e) => Err(e.at(path)),
+    }
+}
+
+// 校正 folder 中所有 EXIF 方向不为 1 的图片，只返回需要校正的图片
+#[tauri::command]
+pub async fn normalize_orientation(
+    app: AppHandle,
+    folder: String,
+    recursive: Option<bool>,
+) -> Result<Vec<OrientationResult>, AppError> {
+    let folder = scope::check(&folder)?;
+
+    // 耗时与图片数量成正比，不使用单个操作的超时
+    tauri::async_runtime::spawn_blocking(move || {
+        let files: Vec<PathBuf> = list_files(&folder, SymlinkPolicy::Skip, recursive.unwrap_or(false))?
+            .files
+            .into_iter()
+            .filter(|path| is_image(path))
+            .collect();
+        let total = files.len();
+        let mut results = Vec::new();
+
+        for (index, path) in files.iter().enumerate() {
+            let orientation = metadata::read_exif(path).map(|exif| metadata::orientation(&exif)).unwrap_or(1);
+            if orientation != 1 {
+                let (lossless, error) = match normalize(path, orientation) {
+                    Ok(lossless) => (lossless, None),
+                    Err(error) => (false, Some(error)),
+                };
+                results.push(OrientationResult {
+                    path: paths::display(path),
+                    orientation,
+                    lossless,
+                    error,
+                });
+            }
+            let _ = app.emit(
+                "orientation-progress",
+                OrientationProgress {
+                    path: paths::display(path),
+                    done: index + 1,
+                    total,
+                },
+            );
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Orientation task failed: {}", e)))?
+}
@@ -0,0 +1,374 @@
+// 调色板量化：转换为 GIF 或索引色 PNG 时把颜色减少到最多 256 种
+// 颜色超过上限时用 NeuQuant 生成调色板，可选的抖动算法减轻渐变处的色带
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_quant::NeuQuant;
+use image::codecs::gif::GifEncoder;
+use image::{DynamicImage, Frame, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::storage::{write_atomic, write_bytes};
+use crate::{open_image, paths, run_blocking, scope};
+
+// NeuQuant 的采样间隔，1 最慢质量最好，30 最快
+const NEUQUANT_SAMPLE: i32 = 10;
+
+// 8x8 Bayer 矩阵
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Dither {
+    None,
+    // 有序抖动（Bayer 矩阵），图案规则，适合动画（相邻帧不会闪烁）
+    Ordered,
+    // 误差扩散，渐变最平滑
+    #[default]
+    FloydSteinberg,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PaletteOptions {
+    // 调色板的颜色数上限（2~256）
+    pub colors: u16,
+    pub dither: Dither,
+}
+
+impl Default for PaletteOptions {
+    fn default() -> Self {
+        Self {
+            colors: 256,
+            dither: Dither::FloydSteinberg,
+        }
+    }
+}
+
+// 索引色图片：每个像素是调色板中的序号
+pub(crate) struct Indexed {
+    pub width: u32,
+    pub height: u32,
+    pub palette: Vec<[u8; 4]>,
+    pub indices: Vec<u8>,
+}
+
+impl Indexed {
+    pub(crate) fn to_rgba(&self) -> RgbaImage {
+        let data = self.indices.iter().flat_map(|&i| self.palette[i as usize]).collect();
+        RgbaImage::from_raw(self.width, self.height, data).expect("indices cover every pixel")
+    }
+}
+
+enum Palette {
+    // 图片本身的颜色不超过上限，直接使用原有颜色
+    Exact(HashMap<[u8; 4], u8>),
+    Neural(NeuQuant),
+}
+
+impl Palette {
+    fn nearest(&self, colors: &[[u8; 4]], pixel: [u8; 4]) -> u8 {
+        match self {
+            Palette::Exact(lookup) => lookup.get(&pixel).copied().unwrap_or_else(|| {
+                let distance = |c: &[u8; 4]| (0..4).map(|i| (c[i] as i32 - pixel[i] as i32).pow(2)).sum::<i32>();
+                (0..colors.len()).min_by_key(|&i| distance(&colors[i])).unwrap_or(0) as u8
+            }),
+            Palette::Neural(quant) => quant.index_of(&pixel) as u8,
+        }
+    }
+}
+
+// 图片中不同颜色的数量不超过 limit 时返回这些颜色
+fn exact_colors(rgba: &RgbaImage, limit: usize) -> Option<Vec<[u8; 4]>> {
+    let mut seen = HashSet::new();
+    for pixel in rgba.pixels() {
+        if !seen.contains(&pixel.0) {
+            if seen.len() == limit {
+                return None;
+            }
+            seen.insert(pixel.0);
+        }
+    }
+    Some(seen.into_iter().collect())
+}
+
+fn build_palette(rgba: &RgbaImage, colors: usize) -> (Palette, Vec<[u8; 4]>) {
+    if let Some(exact) = exact_colors(rgba, colors) {
+        let lookup = exact.iter().enumerate().map(|(i, c)| (*c, i as u8)).collect();
+        return (Palette::Exact(lookup), exact);
+    }
+    let quant = NeuQuant::new(NEUQUANT_SAMPLE, colors, rgba.as_raw());
+    let palette = quant
+        .color_map_rgba()
+        .chunks_exact(4)
+        .map(|c| [c[0], c[1], c[2], c[3]])
+        .collect();
+    (Palette::Neural(quant), palette)
+}
+
+fn clamp(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+// 把 weight 比例的误差累加到相邻像素
+fn diffuse(target: &mut [f32; 4], error: &[f32; 4], weight: f32) {
+    for (t, e) in target.iter_mut().zip(error) {
+        *t += e * weight;
+    }
+}
+
+// 把图片减少到最多 options.colors 种颜色
+pub(crate) fn quantize(img: &DynamicImage, options: &PaletteOptions) -> Result<Indexed, AppError> {
+    if !(2..=256).contains(&options.colors) {
+        return Err(AppError::InvalidArgument(format!(
+            "Palette size must be between 2 and 256: {}",
+            options.colors
+        )));
+    }
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let (palette, colors) = build_palette(&rgba, options.colors as usize);
+    let mut indices = Vec::with_capacity((width * height) as usize);
+
+    // 原有颜色已全部在调色板中，不需要抖动
+    let dither = match palette {
+        Palette::Exact(_) => Dither::None,
+        Palette::Neural(_) => options.dither,
+    };
+    match dither {
+        Dither::None => {
+            indices.extend(rgba.pixels().map(|p| palette.nearest(&colors, p.0)));
+        }
+        Dither::Ordered => {
+            // 抖动幅度约为调色板中相邻颜色的间距
+            let spread = 255.0 / (colors.len() as f32).cbrt();
+            for (x, y, pixel) in rgba.enumerate_pixels() {
+                let threshold = (BAYER[(y % 8) as usize][(x % 8) as usize] as f32 + 0.5) / 64.0 - 0.5;
+                let mut color = pixel.0;
+                for c in color.iter_mut().take(3) {
+                    *c = clamp(*c as f32 + threshold * spread);
+                }
+                indices.push(palette.nearest(&colors, color));
+            }
+        }
+        Dither::FloydSteinberg => {
+            // 只保留当前行和下一行的误差，两端各留一个位置省去边界判断
+            let row = width as usize + 2;
+            let mut current = vec![[0f32; 4]; row];
+            let mut next = vec![[0f32; 4]; row];
+            for y in 0..height {
+                for x in 0..width {
+                    let pixel = rgba.get_pixel(x, y).0;
+                    let i = x as usize + 1;
+                    let wanted: [f32; 4] = std::array::from_fn(|c| (pixel[c] as f32 + current[i][c]).clamp(0.0, 255.0));
+                    let index = palette.nearest(&colors, wanted.map(clamp));
+                    indices.push(index);
+                    let chosen = colors[index as usize];
+                    let error: [f32; 4] = std::array::from_fn(|c| wanted[c] - chosen[c] as f32);
+                    diffuse(&mut current[i + 1], &error, 7.0 / 16.0);
+                    diffuse(&mut next[i - 1], &error, 3.0 / 16.0);
+                    diffuse(&mut next[i], &error, 5.0 / 16.0);
+                    diffuse(&mut next[i + 1], &error, 1.0 / 16.0);
+                }
+                std::mem::swap(&mut current, &mut next);
+                next.fill([0.0; 4]);
+            }
+        }
+    }
+
+    Ok(Indexed {
+        width,
+        height,
+        palette: colors,
+        indices,
+    })
+}
+
+// 编码为索引色 PNG，颜色较少时使用 1/2/4 位深度
+pub(crate) fn encode_png(indexed: &Indexed) -> Result<Vec<u8>, AppError> {
+    let encode_error = |e: png::EncodingError| AppError::EncodeFailed(format!("Indexed PNG: {}", e));
+    let (depth, bits) = match indexed.palette.len() {
+        0..=2 => (png::BitDepth::One, 1),
+        3..=4 => (png::BitDepth::Two, 2),
+        5..=16 => (png::BitDepth::Four, 4),
+        _ => (png::BitDepth::Eight, 8),
+    };
+
+    // 每行按位深度打包，行末不足一个字节的部分补零
+    let width = indexed.width as usize;
+    let per_byte = 8 / bits;
+    let mut data = Vec::with_capacity(width.div_ceil(per_byte) * indexed.height as usize);
+    for row in indexed.indices.chunks_exact(width) {
+        for group in row.chunks(per_byte) {
+            let mut byte = 0u8;
+            for (i, &index) in group.iter().enumerate() {
+                byte |= index << (8 - bits * (i + 1));
+            }
+            data.push(byte);
+        }
+    }
+
+    let rgb: Vec<u8> = indexed.palette.iter().flat_map(|c| [c[0], c[1], c[2]]).collect();
+    let alpha: Vec<u8> = indexed.palette.iter().map(|c| c[3]).collect();
+    let mut buffer = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut buffer, indexed.width, indexed.height);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(depth);
+        encoder.set_palette(rgb);
+        if alpha.iter().any(|&a| a < 255) {
+            encoder.set_trns(alpha);
+        }
+        encoder.set_compression(png::Compression::Best);
+        let mut writer = encoder.write_header().map_err(encode_error)?;
+        writer.write_image_data(&data).map_err(encode_error)?;
+        writer.finish().map_err(encode_error)?;
+    }
+    Ok(buffer)
+}
+
+// 按扩展名保存为索引色 PNG 或 GIF
+pub(crate) fn save_indexed(indexed: &Indexed, path: &Path) -> Result<(), AppError> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => write_bytes(path, &encode_png(indexed)?),
+        // 颜色已不超过 256 种，GIF 编码器直接使用这些颜色，不会再次量化
+        "gif" => write_atomic(path, |writer| {
+            GifEncoder::new(writer).encode_frame(Frame::new(indexed.to_rgba()))?;
+            Ok(())
+        }),
+        _ => Err(AppError::Unsupported(format!("Indexed color output as {}", ext)).at(path)),
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuantizeOptions {
+    pub max_colors: u16,
+    // 最低质量（0~100，与 pngquant 的 --quality 下限含义相同），达不到时不写入文件
+    pub min_quality: u8,
+    pub dither: Dither,
+}
+
+impl Default for QuantizeOptions {
+    fn default() -> Self {
+        Self {
+            max_colors: 256,
+            min_quality: 0,
+            dither: Dither::FloydSteinberg,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QuantizeResult {
+    pub output: String,
+    pub colors: usize,
+    // 0~100，100 表示与原图完全相同
+    pub quality: u8,
+    pub original_size: u64,
+    pub size: u64,
+}
+
+// pngquant 中质量与均方误差的对应关系
+fn quality_to_mse(quality: u8) -> f64 {
+    if quality == 100 {
+        return 0.0;
+    }
+    let q = quality as f64;
+    let extra_low_quality_fudge = (0.016 / (0.001 + q) - 0.001).max(0.0);
+    extra_low_quality_fudge + 2.5 / (210.0 + q).powf(1.2) * (100.1 - q) / 100.0
+}
+
+// 量化前后的均方误差（通道值归一化到 0~1，RGB 预乘透明度）
+fn mean_squared_error(original: &RgbaImage, quantized: &RgbaImage) -> f64 {
+    let premultiplied = |p: &image::Rgba<u8>| {
+        let a = p[3] as f64 / 255.0;
+        [p[0] as f64 / 255.0 * a, p[1] as f64 / 255.0 * a, p[2] as f64 / 255.0 * a, a]
+    };
+    let total: f64 = original
+        .pixels()
+        .zip(quantized.pixels())
+        .map(|(a, b)| {
+            let (a, b) = (premultiplied(a), premultiplied(b));
+            (0..4).map(|c| (a[c] - b[c]).powi(2)).sum::<f64>()
+        })
+        .sum();
+    total / (original.width() as f64 * original.height() as f64).max(1.0)
+}
+
+// 误差不超过 quality_to_mse(q) 的最高质量 q
+fn quality_of(mse: f64) -> u8 {
+    (0..=100u8).rev().find(|&q| mse <= quality_to_mse(q)).unwrap_or(0)
+}
+
+// 把图片量化为索引色 PNG（类似 pngquant），output 为空时在原图旁边生成 name-fs8.png
+#[tauri::command]
+pub async fn quantize_image(
+    path: String,
+    output: Option<String>,
+    options: Option<QuantizeOptions>,
+) -> Result<QuantizeResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    let options = options.unwrap_or_default();
+    if options.min_quality > 100 {
+        return Err(AppError::InvalidArgument(format!("Quality must be between 0 and 100: {}", options.min_quality)));
+    }
+
+    run_blocking("quantize_image", move || {
+        let path = Path::new(&path);
+        let output = match output {
+            Some(output) => PathBuf::from(output),
+            None => path.with_file_name(format!("{}-fs8.png", paths::file_stem(path, "image"))),
+        };
+        let img = open_image(path)?;
+        let indexed = quantize(
+            &img,
+            &PaletteOptions {
+                colors: options.max_colors,
+                dither: options.dither,
+            },
+        )?;
+
+        let quality = quality_of(mean_squared_error(&img.to_rgba8(), &indexed.to_rgba()));
+        if quality < options.min_quality {
+            return Err(AppError::ProcessingFailed(format!(
+                "Quality {} is below the minimum {} with {} colors",
+                quality,
+                options.min_quality,
+                indexed.palette.len()
+            ))
+            .at(path));
+        }
+
+        let data = encode_png(&indexed)?;
+        write_bytes(&output, &data)?;
+        let original_size = fs::metadata(path).map_err(|e| AppError::from(e).at(path))?.len();
+        Ok(QuantizeResult {
+            output: paths::display(&output),
+            colors: indexed.palette.len(),
+            quality,
+            original_size,
+            size: data.len() as u64,
+        })
+    })
+    .await
+}
@@ -0,0 +1,477 @@
+// 全景拼接：把从左到右依次拍摄、相邻照片有重叠的多张照片拼成一张横向全景图
+//   1. 投影到圆柱面上（转动相机拍摄的照片在圆柱面上只相差平移）；焦距取自 EXIF 的 35mm 等效焦距
+//   2. 在缩小的图片上检测 Harris 角点，用归一化的邻域像素作为特征描述，按最近邻比值匹配
+//   3. RANSAC 估计相邻照片之间的平移
+//   4. 按到照片边缘的距离加权混合重叠区域，可选裁掉上下不完整的部分
+use std::path::{Path, PathBuf};
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, GrayImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::storage::save_image;
+use crate::{metadata, open_image, paths, run_blocking, scope};
+
+const MAX_IMAGES: usize = 20;
+// 特征检测时图片缩小到的高度
+const ANALYSIS_HEIGHT: u32 = 600;
+// 没有 EXIF 焦距时假定的 35mm 等效焦距（手机主摄约为 26~28mm）
+const DEFAULT_FOCAL_35MM: f32 = 28.0;
+const MAX_CORNERS: usize = 800;
+// 角点非极大值抑制的半径
+const CORNER_RADIUS: i32 = 3;
+// 描述子为 8x8 的采样网格，间隔 2 像素
+const DESCRIPTOR_GRID: i32 = 8;
+const DESCRIPTOR_STEP: i32 = 2;
+// 最近邻与次近邻距离之比小于该值才算匹配
+const MATCH_RATIO: f32 = 0.8;
+// RANSAC 的内点距离（缩小后的像素）和最少内点数
+const INLIER_DISTANCE: f32 = 3.0;
+const MIN_INLIERS: usize = 8;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Projection {
+    // 转动相机拍摄的风景
+    #[default]
+    Cylindrical,
+    // 平移相机拍摄的平面（扫描件、长截图、壁画等）
+    Planar,
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PanoramaOptions {
+    #[serde(default)]
+    pub projection: Projection,
+    // 35mm 等效焦距，为空时取自第一张照片的 EXIF
+    pub focal_length: Option<f32>,
+    // 裁掉上下边缘没有完全覆盖的部分；为 false 时保留完整画布，空白处透明
+    #[serde(default = "yes")]
+    pub crop: bool,
+    // 为空时在第一张照片旁边生成 name_panorama.ext
+    pub output: Option<String>,
+}
+
+impl Default for PanoramaOptions {
+    fn default() -> Self {
+        Self {
+            projection: Projection::Cylindrical,
+            focal_length: None,
+            crop: true,
+            output: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PanoramaResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    // 每张照片（投影后）在全景图中的位置
+    pub offsets: Vec<(i32, i32)>,
+}
+
+// 圆柱投影：焦距为空时不投影
+#[derive(Clone, Copy)]
+struct Warp {
+    width: u32,
+    height: u32,
+    focal: Option<f32>,
+}
+
+impl Warp {
+    fn output_size(&self) -> (u32, u32) {
+        match self.focal {
+            Some(f) => ((2.0 * f * (self.width as f32 / 2.0 / f).atan()).round() as u32, self.height),
+            None => (self.width, self.height),
+        }
+    }
+
+    // 投影后坐标 (u, v) 对应的原图坐标
+    fn source(&self, u: f32, v: f32) -> (f32, f32) {
+        let Some(f) = self.focal else {
+            return (u, v);
+        };
+        let (out_width, _) = self.output_size();
+        let (cx, cy) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+        let theta = (u - out_width as f32 / 2.0) / f;
+        let x = f * theta.tan() + cx;
+        let y = (v - cy) / theta.cos() + cy;
+        (x, y)
+    }
+
+    // 投影后每一列都有原图像素的行范围（圆柱投影后上下边缘为弧形）
+    fn valid_rows(&self) -> (f32, f32) {
+        match self.focal {
+            Some(f) => {
+                let (out_width, _) = self.output_size();
+                let cos = (out_width as f32 / 2.0 / f).cos();
+                let half = self.height as f32 / 2.0 * cos;
+                (self.height as f32 / 2.0 - half, self.height as f32 / 2.0 + half)
+            }
+            None => (0.0, self.height as f32),
+        }
+    }
+}
+
+fn bilinear_gray(img: &GrayImage, x: f32, y: f32) -> f32 {
+    let x = x.clamp(0.0, img.width() as f32 - 1.0);
+    let y = y.clamp(0.0, img.height() as f32 - 1.0);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(img.width() - 1), (y0 + 1).min(img.height() - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let at = |x: u32, y: u32| img.get_pixel(x, y)[0] as f32;
+    let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+    let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+fn bilinear_rgba(img: &RgbaImage, x: f32, y: f32) -> [f32; 4] {
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(img.width() - 1), (y0 + 1).min(img.height() - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let mut output = [0.0; 4];
+    for (c, value) in output.iter_mut().enumerate() {
+        let at = |x: u32, y: u32| img.get_pixel(x, y)[c] as f32;
+        let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+        let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+        *value = top * (1.0 - ty) + bottom * ty;
+    }
+    output
+}
+
+// 投影灰度图（用于特征检测），超出原图的部分取最近的边缘像素，避免产生假角点
+fn warp_gray(img: &GrayImage, warp: &Warp) -> GrayImage {
+    let (width, height) = warp.output_size();
+    GrayImage::from_fn(width, height, |u, v| {
+        let (x, y) = warp.source(u as f32 + 0.5, v as f32 + 0.5);
+        image::Luma([bilinear_gray(img, x - 0.5, y - 0.5).round() as u8])
+    })
+}
+
+struct Feature {
+    x: f32,
+    y: f32,
+    descriptor: [f32; (DESCRIPTOR_GRID * DESCRIPTOR_GRID) as usize],
+}
+
+// Harris 角点响应
+fn harris(gray: &GrayImage) -> Vec<f32> {
+    let (width, height) = (gray.width() as i32, gray.height() as i32);
+    let at = |x: i32, y: i32| gray.get_pixel(x.clamp(0, width - 1) as u32, y.clamp(0, height - 1) as u32)[0] as f32;
+    let size = (width * height) as usize;
+    let (mut xx, mut yy, mut xy) = (vec![0.0; size], vec![0.0; size], vec![0.0; size]);
+    for y in 0..height {
+        for x in 0..width {
+            // Sobel 梯度
+            let gx = (at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1))
+                - (at(x - 1, y - 1) + 2.0 * at(x - 1, y) + at(x - 1, y + 1));
+            let gy = (at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1))
+                - (at(x - 1, y - 1) + 2.0 * at(x, y - 1) + at(x + 1, y - 1));
+            let i = (y * width + x) as usize;
+            xx[i] = gx * gx;
+            yy[i] = gy * gy;
+            xy[i] = gx * gy;
+        }
+    }
+    // 5x5 窗口内求和
+    let window = |values: &[f32]| {
+        let mut output = vec![0.0; size];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for dy in -2..=2 {
+                    for dx in -2..=2 {
+                        let (sx, sy) = ((x + dx).clamp(0, width - 1), (y + dy).clamp(0, height - 1));
+                        sum += values[(sy * width + sx) as usize];
+                    }
+                }
+                output[(y * width + x) as usize] = sum;
+            }
+        }
+        output
+    };
+    let (xx, yy, xy) = (window(&xx), window(&yy), window(&xy));
+    (0..size)
+        .map(|i| xx[i] * yy[i] - xy[i] * xy[i] - 0.04 * (xx[i] + yy[i]).powi(2))
+        .collect()
+}
+
+fn features(gray: &GrayImage) -> Vec<Feature> {
+    let (width, height) = (gray.width() as i32, gray.height() as i32);
+    let response = harris(gray);
+    let max = response.iter().cloned().fold(0.0, f32::max);
+    let margin = DESCRIPTOR_GRID / 2 * DESCRIPTOR_STEP + 1;
+
+    let mut corners = Vec::new();
+    for y in margin..height - margin {
+        for x in margin..width - margin {
+            let value = response[(y * width + x) as usize];
+            if value <= max * 1e-4 {
+                continue;
+            }
+            let is_peak = (-CORNER_RADIUS..=CORNER_RADIUS).all(|dy| {
+                (-CORNER_RADIUS..=CORNER_RADIUS).all(|dx| {
+                    let (sx, sy) = ((x + dx).clamp(0, width - 1), (y + dy).clamp(0, height - 1));
+                    (dx == 0 && dy == 0) || response[(sy * width + sx) as usize] <= value
+                })
+            });
+            if is_peak {
+                corners.push((value, x, y));
+            }
+        }
+    }
+    corners.sort_by(|a, b| b.0.total_cmp(&a.0));
+    corners.truncate(MAX_CORNERS);
+
+    // 在模糊后的图片上采样，描述子对小的位置误差不敏感；减去均值、除以标准差，对亮度变化不敏感
+    let blurred = imageops::blur(gray, 2.0);
+    corners
+        .into_iter()
+        .filter_map(|(_, x, y)| {
+            let mut descriptor = [0.0; (DESCRIPTOR_GRID * DESCRIPTOR_GRID) as usize];
+            let start = -(DESCRIPTOR_GRID / 2) * DESCRIPTOR_STEP + DESCRIPTOR_STEP / 2;
+            for (i, value) in descriptor.iter_mut().enumerate() {
+                let sx = x + start + (i as i32 % DESCRIPTOR_GRID) * DESCRIPTOR_STEP;
+                let sy = y + start + (i as i32 / DESCRIPTOR_GRID) * DESCRIPTOR_STEP;
+                *value = blurred.get_pixel(sx as u32, sy as u32)[0] as f32;
+            }
+            let mean = descriptor.iter().sum::<f32>() / descriptor.len() as f32;
+            let deviation = (descriptor.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / descriptor.len() as f32).sqrt();
+            if deviation < 1.0 {
+                return None;
+            }
+            for value in descriptor.iter_mut() {
+                *value = (*value - mean) / deviation;
+            }
+            Some(Feature {
+                x: x as f32,
+                y: y as f32,
+                descriptor,
+            })
+        })
+        .collect()
+}
+
+fn distance(a: &Feature, b: &Feature) -> f32 {
+    a.descriptor.iter().zip(&b.descriptor).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+// 估计 right 相对 left 的平移（right 中的点加上平移得到 left 中的对应点）
+fn estimate_translation(left: &[Feature], right: &[Feature]) -> Option<(f32, f32)> {
+    let mut candidates = Vec::new();
+    for a in left {
+        let mut best = (f32::MAX, f32::MAX, 0);
+        for (j, b) in right.iter().enumerate() {
+            let d = distance(a, b);
+            if d < best.0 {
+                best = (d, best.0, j);
+            } else if d < best.1 {
+                best.1 = d;
+            }
+        }
+        // 距离为平方和，比值也取平方
+        if best.0 < best.1 * MATCH_RATIO * MATCH_RATIO {
+            let b = &right[best.2];
+            candidates.push((a.x - b.x, a.y - b.y));
+        }
+    }
+
+    // 每个匹配本身就是一个平移假设，取内点最多的，再用内点的平均值细化
+    let inliers = |t: (f32, f32)| {
+        candidates
+            .iter()
+            .filter(|c| (c.0 - t.0).hypot(c.1 - t.1) <= INLIER_DISTANCE)
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    let best = candidates.iter().copied().max_by_key(|t| inliers(*t).len())?;
+    let inliers = inliers(best);
+    if inliers.len() < MIN_INLIERS {
+        return None;
+    }
+    let n = inliers.len() as f32;
+    Some((inliers.iter().map(|t| t.0).sum::<f32>() / n, inliers.iter().map(|t| t.1).sum::<f32>() / n))
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "jpg".to_string());
+            path.with_file_name(format!("{}_panorama.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 按从左到右的顺序拼接照片
+#[tauri::command]
+pub async fn stitch_panorama(paths: Vec<String>, options: Option<PanoramaOptions>) -> Result<PanoramaResult, AppError> {
+    if !(2..=MAX_IMAGES).contains(&paths.len()) {
+        return Err(AppError::InvalidArgument(format!(
+            "Panorama needs 2 to {} images, got {}",
+            MAX_IMAGES,
+            paths.len()
+        )));
+    }
+    for path in &paths {
+        scope::check(path)?;
+    }
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    if let Some(focal) = options.focal_length {
+        if !(5.0..=1000.0).contains(&focal) {
+            return Err(AppError::InvalidArgument(format!(
+                "Focal length must be between 5 and 1000 mm: {}",
+                focal
+            )));
+        }
+    }
+
+    run_blocking("stitch_panorama", move || {
+        // 所有照片缩放到第一张的高度
+        let first = open_image(&paths[0])?;
+        let height = first.height();
+        let images = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let img = if i == 0 { first.clone() } else { open_image(path)? };
+                Ok(if img.height() == height {
+                    img.to_rgba8()
+                } else {
+                    let width = (img.width() as f64 * height as f64 / img.height() as f64).round() as u32;
+                    img.resize_exact(width.max(1), height, FilterType::Lanczos3).to_rgba8()
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        let focal_35mm = options.focal_length.unwrap_or_else(|| {
+            metadata::read_exif(Path::new(&paths[0]))
+                .and_then(|exif| metadata::focal_length_35mm(&exif))
+                .unwrap_or(DEFAULT_FOCAL_35MM)
+        });
+        // 35mm 胶片长边为 36mm
+        let warp_for = |img: &RgbaImage| Warp {
+            width: img.width(),
+            height: img.height(),
+            focal: (options.projection == Projection::Cylindrical)
+                .then(|| focal_35mm * img.width().max(img.height()) as f32 / 36.0),
+        };
+
+        // 在缩小的投影图片上估计相邻照片之间的平移
+        let scale = (ANALYSIS_HEIGHT as f32 / height as f32).min(1.0);
+        let mut features_list = Vec::with_capacity(images.len());
+        for img in &images {
+            let small_width = ((img.width() as f32 * scale).round() as u32).max(1);
+            let small_height = ((img.height() as f32 * scale).round() as u32).max(1);
+            let gray = DynamicImage::ImageRgba8(img.clone())
+                .resize_exact(small_width, small_height, FilterType::Triangle)
+                .to_luma8();
+            let warp = warp_for(img);
+            let small_warp = Warp {
+                width: small_width,
+                height: small_height,
+                focal: warp.focal.map(|f| f * scale),
+            };
+            features_list.push(features(&warp_gray(&gray, &small_warp)));
+        }
+        let mut positions = vec![(0.0f32, 0.0f32)];
+        for i in 1..images.len() {
+            let (dx, dy) = estimate_translation(&features_list[i - 1], &features_list[i]).ok_or_else(|| {
+                AppError::ProcessingFailed(format!("Could not find enough overlap between images {} and {}", i, i + 1))
+                    .at(&paths[i])
+            })?;
+            let previous = positions[i - 1];
+            positions.push((previous.0 + dx / scale, previous.1 + dy / scale));
+        }
+        let offsets: Vec<(i32, i32)> = positions.iter().map(|p| (p.0.round() as i32, p.1.round() as i32)).collect();
+
+        // 画布范围
+        let warps: Vec<Warp> = images.iter().map(warp_for).collect();
+        let left = offsets.iter().map(|o| o.0).min().unwrap_or(0);
+        let top = offsets.iter().map(|o| o.1).min().unwrap_or(0);
+        let right = offsets.iter().zip(&warps).map(|(o, w)| o.0 + w.output_size().0 as i32).max().unwrap_or(0);
+        let bottom = offsets.iter().zip(&warps).map(|(o, w)| o.1 + w.output_size().1 as i32).max().unwrap_or(0);
+        let (canvas_width, canvas_height) = ((right - left) as u32, (bottom - top) as u32);
+
+        // 按到原图边缘的距离加权累加，重叠处平滑过渡
+        let mut sum = vec![[0f32; 5]; canvas_width as usize * canvas_height as usize];
+        for ((img, warp), offset) in images.iter().zip(&warps).zip(&offsets) {
+            let (out_width, out_height) = warp.output_size();
+            let (w, h) = (img.width() as f32, img.height() as f32);
+            for v in 0..out_height {
+                for u in 0..out_width {
+                    let (x, y) = warp.source(u as f32 + 0.5, v as f32 + 0.5);
+                    let (x, y) = (x - 0.5, y - 0.5);
+                    if x < 0.0 || y < 0.0 || x > w - 1.0 || y > h - 1.0 {
+                        continue;
+                    }
+                    let weight = ((x.min(w - 1.0 - x) + 1.0) / w) * ((y.min(h - 1.0 - y) + 1.0) / h);
+                    let color = bilinear_rgba(img, x, y);
+                    let cx = (offset.0 - left) as usize + u as usize;
+                    let cy = (offset.1 - top) as usize + v as usize;
+                    let cell = &mut sum[cy * canvas_width as usize + cx];
+                    for (value, channel) in cell.iter_mut().zip(color) {
+                        *value += channel * weight;
+                    }
+                    cell[4] += weight;
+                }
+            }
+        }
+        let mut panorama = RgbaImage::from_fn(canvas_width, canvas_height, |x, y| {
+            let cell = sum[(y * canvas_width + x) as usize];
+            if cell[4] <= 0.0 {
+                return Rgba([0, 0, 0, 0]);
+            }
+            Rgba(std::array::from_fn(|c| (cell[c] / cell[4]).round().clamp(0.0, 255.0) as u8))
+        });
+
+        if options.crop {
+            // 各张照片都有像素的行范围的交集
+            let crop_top = offsets
+                .iter()
+                .zip(&warps)
+                .map(|(o, w)| o.1 - top + w.valid_rows().0.ceil() as i32)
+                .max()
+                .unwrap_or(0);
+            let crop_bottom = offsets
+                .iter()
+                .zip(&warps)
+                .map(|(o, w)| o.1 - top + w.valid_rows().1.floor() as i32)
+                .min()
+                .unwrap_or(canvas_height as i32);
+            if crop_bottom > crop_top {
+                panorama = imageops::crop_imm(&panorama, 0, crop_top as u32, canvas_width, (crop_bottom - crop_top) as u32)
+                    .to_image();
+            }
+        }
+
+        let output = output_path(Path::new(&paths[0]), options.output);
+        let (width, height) = panorama.dimensions();
+        let has_alpha = panorama.pixels().any(|p| p[3] < 255);
+        let result = if has_alpha {
+            DynamicImage::ImageRgba8(panorama)
+        } else {
+            DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(panorama).to_rgb8())
+        };
+        save_image(&result, &output)?;
+        Ok(PanoramaResult {
+            output: paths::display(&output),
+            width,
+            height,
+            offsets: offsets.iter().map(|o| (o.0 - left, o.1 - top)).collect(),
+        })
+    })
+    .await
+}
@@ -0,0 +1,174 @@
+// 路径辅助：Windows 长路径（\\?\ 扩展长度前缀）以及返回给前端的路径/文件名字符串
+// 文件名不一定是合法的 UTF-8，转换为字符串时一律使用有损转换，不能 unwrap
+use std::borrow::Cow;
+use std::path::Path;
+
+// Windows 传统 API 的路径长度上限（MAX_PATH，含结尾的 NUL）
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+
+// Windows 上超过 MAX_PATH 的绝对路径转为扩展长度形式（\\?\C:\... 或 \\?\UNC\server\share\...）
+// 扩展长度路径不会再被系统规范化，所以这里统一分隔符并去掉 . 组件；其他平台原样返回
+#[cfg(windows)]
+pub(crate) fn extended(path: &Path) -> Cow<'_, Path> {
+    use std::ffi::OsString;
+    use std::path::{Component, PathBuf, Prefix};
+
+    if path.as_os_str().len() < MAX_PATH || path.components().any(|c| c == Component::ParentDir) {
+        return Cow::Borrowed(path);
+    }
+    let mut components = path.components();
+    let mut root = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => {
+                let mut root = OsString::from(r"\\?\");
+                root.push(prefix.as_os_str());
+                root
+            }
+            Prefix::UNC(server, share) => {
+                let mut root = OsString::from(r"\\?\UNC\");
+                root.push(server);
+                root.push(r"\");
+                root.push(share);
+                root
+            }
+            // 已经是扩展长度路径或设备路径
+            _ => return Cow::Borrowed(path),
+        },
+        // 相对路径无法加前缀
+        _ => return Cow::Borrowed(path),
+    };
+    root.push(r"\");
+
+    let mut extended = PathBuf::from(root);
+    for component in components {
+        if let Component::Normal(name) = component {
+            extended.push(name);
+        }
+    }
+    Cow::Owned(extended)
+}
+
+#[cfg(not(windows))]
+pub(crate) fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+// 返回给前端的路径字符串，Windows 上去掉 \\?\ 前缀（规范化路径总是带有该前缀）
+pub(crate) fn display(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if cfg!(windows) {
+        if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+            return format!(r"\\{}", rest);
+        }
+        if let Some(rest) = path.strip_prefix(r"\\?\") {
+            return rest.to_string();
+        }
+    }
+    path.into_owned()
+}
+
+// 文件名（不含目录），没有文件名时返回空字符串
+pub(crate) fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// 不含扩展名的文件名，没有时返回 default
+pub(crate) fn file_stem(path: &Path, default: &str) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| default.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use image::{DynamicImage, GenericImageView, RgbImage};
+
+    use super::*;
+    use crate::{open_image, storage};
+
+    const NAMES: [&str; 4] = ["照片 2024.jpg", "夏休み・写真.png", "🐱 cat 🎉.png", "사진 ✨.gif"];
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("paths-test-{}-{}", std::process::id(), name));
+        fs::create_dir_all(extended(&dir)).unwrap();
+        dir
+    }
+
+    // 保存后再打开，尺寸不变
+    fn round_trip(path: &Path) {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(3, 2, image::Rgb([200, 100, 50])));
+        storage::save_image(&img, path).unwrap();
+        assert!(extended(path).is_file());
+        assert_eq!(open_image(path).unwrap().dimensions(), (3, 2));
+    }
+
+    #[test]
+    fn names_keep_cjk_and_emoji() {
+        let dir = Path::new("/photos/旅行");
+        for name in NAMES {
+            let path = dir.join(name);
+            assert_eq!(file_name(&path), name);
+            let stem = &name[..name.rfind('.').unwrap()];
+            assert_eq!(file_stem(&path, "image"), stem);
+            assert_eq!(display(&path), path.to_string_lossy());
+        }
+        assert_eq!(file_name(Path::new("/")), "");
+        assert_eq!(file_stem(Path::new("/"), "image"), "image");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn names_tolerate_invalid_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new("/photos").join(OsStr::from_bytes(b"bad\xff.jpg"));
+        assert_eq!(file_name(&path), "bad\u{fffd}.jpg");
+        assert_eq!(file_stem(&path, "image"), "bad\u{fffd}");
+    }
+
+    #[test]
+    fn short_paths_are_not_extended() {
+        let path = std::env::temp_dir().join("照片.jpg");
+        assert_eq!(extended(&path), path);
+    }
+
+    #[test]
+    fn cjk_and_emoji_files_round_trip() {
+        let dir = test_dir("names");
+        for name in NAMES {
+            round_trip(&dir.join(name));
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn display_strips_extended_prefix() {
+        assert_eq!(display(Path::new(r"\\?\C:\照片\a.jpg")), r"C:\照片\a.jpg");
+        assert_eq!(display(Path::new(r"\\?\UNC\server\share\a.jpg")), r"\\server\share\a.jpg");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_paths_round_trip() {
+        let mut dir = test_dir("long");
+        // 多层较长的目录名，总长度超过 MAX_PATH
+        for i in 0..6 {
+            dir.push(format!("{}-長いフォルダ名-{}", i, "x".repeat(40)));
+        }
+        let path = dir.join("🐱 照片.png");
+        assert!(path.as_os_str().len() > MAX_PATH);
+        assert!(extended(&path).to_string_lossy().starts_with(r"\\?\"));
+        assert_eq!(display(&extended(&path)), path.to_string_lossy());
+
+        fs::create_dir_all(extended(&dir)).unwrap();
+        round_trip(&path);
+        assert_eq!(file_name(&path), "🐱 照片.png");
+    }
+}
@@ -0,0 +1,180 @@
+// 性能统计：记录每次操作的解码、处理、编码耗时，供前端查看慢操作的原因
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+// 保留最近 100 次操作的明细
+const MAX_RECENT: usize = 100;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Stage {
+    Decode,
+    Encode,
+}
+
+// 一次操作中解码和编码（含写入文件）的耗时，其余时间计为处理
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct StageTimes {
+    decode: Duration,
+    encode: Duration,
+}
+
+thread_local! {
+    // 当前线程上正在统计的操作，不在 track 中时为 None
+    static CURRENT: Cell<Option<StageTimes>> = const { Cell::new(None) };
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PerfSample {
+    pub command: String,
+    pub ok: bool,
+    pub total_ms: f64,
+    pub decode_ms: f64,
+    pub process_ms: f64,
+    pub encode_ms: f64,
+    // Unix 毫秒
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPerf {
+    pub command: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub avg_decode_ms: f64,
+    pub avg_process_ms: f64,
+    pub avg_encode_ms: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PerfStats {
+    // 按平均耗时从高到低排列
+    pub commands: Vec<CommandPerf>,
+    // 最近的操作，从新到旧
+    pub recent: Vec<PerfSample>,
+}
+
+#[derive(Default)]
+struct Totals {
+    calls: u64,
+    failures: u64,
+    total: f64,
+    max: f64,
+    decode: f64,
+    process: f64,
+    encode: f64,
+}
+
+lazy_static::lazy_static! {
+    static ref TOTALS: Mutex<HashMap<String, Totals>> = Mutex::new(HashMap::new());
+    static ref RECENT: Mutex<VecDeque<PerfSample>> = Mutex::new(VecDeque::new());
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// 在当前线程上统计 task 中各阶段的耗时
+pub(crate) fn track<T>(task: impl FnOnce() -> T) -> (T, StageTimes) {
+    let previous = CURRENT.with(|current| current.replace(Some(StageTimes::default())));
+    let result = task();
+    let times = CURRENT.with(|current| current.replace(previous)).unwrap_or_default();
+    (result, times)
+}
+
+// 计时某个阶段，不在 track 中调用时只执行 task
+pub(crate) fn measure<T>(stage: Stage, task: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = task();
+    let elapsed = started.elapsed();
+    CURRENT.with(|current| {
+        if let Some(mut times) = current.get() {
+            match stage {
+                Stage::Decode => times.decode += elapsed,
+                Stage::Encode => times.encode += elapsed,
+            }
+            current.set(Some(times));
+        }
+    });
+    result
+}
+
+// 记录一次操作；total 为命令的总耗时（含排队等待）
+pub(crate) fn record(command: &str, total: Duration, times: StageTimes, ok: bool) {
+    let total_ms = ms(total);
+    let decode_ms = ms(times.decode);
+    let encode_ms = ms(times.encode);
+    let process_ms = (total_ms - decode_ms - encode_ms).max(0.0);
+
+    {
+        let mut totals = TOTALS.lock();
+        let entry = totals.entry(command.to_string()).or_default();
+        entry.calls += 1;
+        if !ok {
+            entry.failures += 1;
+        }
+        entry.total += total_ms;
+        entry.max = entry.max.max(total_ms);
+        entry.decode += decode_ms;
+        entry.process += process_ms;
+        entry.encode += encode_ms;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let mut recent = RECENT.lock();
+    recent.push_front(PerfSample {
+        command: command.to_string(),
+        ok,
+        total_ms,
+        decode_ms,
+        process_ms,
+        encode_ms,
+        timestamp,
+    });
+    recent.truncate(MAX_RECENT);
+}
+
+#[tauri::command]
+pub fn get_perf_stats() -> PerfStats {
+    let mut commands: Vec<CommandPerf> = TOTALS
+        .lock()
+        .iter()
+        .map(|(command, totals)| {
+            let calls = totals.calls.max(1) as f64;
+            CommandPerf {
+                command: command.clone(),
+                calls: totals.calls,
+                failures: totals.failures,
+                avg_ms: totals.total / calls,
+                max_ms: totals.max,
+                avg_decode_ms: totals.decode / calls,
+                avg_process_ms: totals.process / calls,
+                avg_encode_ms: totals.encode / calls,
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.avg_ms.total_cmp(&a.avg_ms));
+
+    PerfStats {
+        commands,
+        recent: RECENT.lock().iter().cloned().collect(),
+    }
+}
+
+#[tauri::command]
+pub fn reset_perf_stats() -> bool {
+    TOTALS.lock().clear();
+    RECENT.lock().clear();
+    true
+}
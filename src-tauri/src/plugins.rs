@@ -0,0 +1,268 @@
+// WASM 滤镜插件：启动时加载应用数据目录 plugins 下的 .wasm 模块，通过统一的 apply_plugin 命令调用
+//
+// 插件模块需要导出：
+//   memory                      线性内存
+//   alloc(len: i32) -> i32      分配 len 字节，返回指针
+//   apply(pixels, pixels_len, width, height, params, params_len) -> i32
+//                               原地处理 RGBA 像素，返回 0 表示成功
+// 可选导出：
+//   schema_ptr() -> i32 / schema_len() -> i32   参数描述（JSON 字符串）
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, RgbaImage};
+use parking_lot::RwLock;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use wasmtime::{Config, Engine, Instance, Module, Store};
+
+use crate::error::AppError;
+use crate::history::{self, EditOperation};
+use crate::open_image;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::save_image_from;
+
+// 每次调用允许消耗的指令燃料，防止插件死循环
+const FUEL_LIMIT: u64 = 20_000_000_000;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PluginInfo {
+    pub name: String,
+    pub path: String,
+    // 插件自描述的参数结构，未提供时为 null
+    pub schema: serde_json::Value,
+}
+
+struct LoadedPlugin {
+    info: PluginInfo,
+    module: Module,
+}
+
+pub struct PluginRegistry {
+    engine: Engine,
+    dir: PathBuf,
+    plugins: RwLock<HashMap<String, LoadedPlugin>>,
+}
+
+fn new_store(engine: &Engine) -> Result<Store<()>, AppError> {
+    let mut store = Store::new(engine, ());
+    store
+        .set_fuel(FUEL_LIMIT)
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to configure plugin store: {}", e)))?;
+    Ok(store)
+}
+
+// 读取插件导出的参数描述
+fn read_schema(engine: &Engine, module: &Module) -> Result<serde_json::Value, AppError> {
+    let mut store = new_store(engine)?;
+    let instance = Instance::new(&mut store, module, &[])
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to instantiate plugin: {}", e)))?;
+
+    let (ptr, len) = match (
+        instance.get_typed_func::<(), i32>(&mut store, "schema_ptr"),
+        instance.get_typed_func::<(), i32>(&mut store, "schema_len"),
+    ) {
+        (Ok(ptr), Ok(len)) => (
+            ptr.call(&mut store, ()).map_err(|e| AppError::ProcessingFailed(format!("Plugin schema failed: {}", e)))?,
+            len.call(&mut store, ()).map_err(|e| AppError::ProcessingFailed(format!("Plugin schema failed: {}", e)))?,
+        ),
+        _ => return Ok(serde_json::Value::Null),
+    };
+
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| AppError::Unsupported("Plugin does not export memory".to_string()))?;
+    let mut buffer = vec![0u8; len.max(0) as usize];
+    memory
+        .read(&store, ptr as usize, &mut buffer)
+        .map_err(|e| AppError::ProcessingFailed(format!("Failed to read plugin schema: {}", e)))?;
+
+    serde_json::from_slice(&buffer).map_err(|e| AppError::ProcessingFailed(format!("Invalid plugin schema: {}", e)))
+}
+
+impl PluginRegistry {
+    pub fn new(dir: PathBuf) -> Result<Self, AppError> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| AppError::Internal(format!("Failed to create WASM engine: {}", e)))?;
+
+        let registry = PluginRegistry {
+            engine,
+            dir,
+            plugins: RwLock::new(HashMap::new()),
+        };
+        registry.reload();
+        Ok(registry)
+    }
+
+    // 重新扫描插件目录，加载失败的插件会被跳过
+    pub fn reload(&self) -> Vec<String> {
+        let mut plugins = HashMap::new();
+        let mut errors = Vec::new();
+
+        if let Ok(entries) = fs::read_dir(&self.dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+                    continue;
+                }
+                match self.load(&path) {
+                    Ok(plugin) => {
+                        plugins.insert(plugin.info.name.clone(), plugin);
+                    }
+                    Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+                }
+            }
+        }
+
+        *self.plugins.write() = plugins;
+        errors
+    }
+
+    fn load(&self, path: &Path) -> Result<LoadedPlugin, AppError> {
+        let module = Module::from_file(&self.engine, path).map_err(|e| AppError::ProcessingFailed(format!("Failed to load plugin: {}", e)))?;
+        let schema = read_schema(&self.engine, &module)?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Ok(LoadedPlugin {
+            info: PluginInfo {
+                name,
+                path: path.to_string_lossy().to_string(),
+                schema,
+            },
+            module,
+        })
+    }
+
+    // 在插件中处理一张 RGBA 图片
+    pub fn run(&self, name: &str, img: &mut RgbaImage, params: &serde_json::Value) -> Result<(), AppError> {
+        let plugins = self.plugins.read();
+        let plugin = plugins.get(name).ok_or_else(|| AppError::NotFound(format!("Plugin {}", name)))?;
+
+        let mut store = new_store(&self.engine)?;
+        let instance = Instance::new(&mut store, &plugin.module, &[])
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to instantiate plugin: {}", e)))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| AppError::Unsupported("Plugin does not export memory".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| AppError::Unsupported(format!("Plugin does not export alloc: {}", e)))?;
+        let apply = instance
+            .get_typed_func::<(i32, i32, i32, i32, i32, i32), i32>(&mut store, "apply")
+            .map_err(|e| AppError::Unsupported(format!("Plugin does not export apply: {}", e)))?;
+
+        let params = serde_json::to_vec(params).map_err(|e| AppError::InvalidArgument(format!("Invalid plugin params: {}", e)))?;
+        let pixels_len = i32::try_from(img.as_raw().len()).map_err(|_| AppError::TooLarge("Image is too large for plugin".to_string()))?;
+        let params_len = params.len() as i32;
+
+        let pixels_ptr = alloc
+            .call(&mut store, pixels_len)
+            .map_err(|e| AppError::ProcessingFailed(format!("Plugin alloc failed: {}", e)))?;
+        memory
+            .write(&mut store, pixels_ptr as usize, img.as_raw())
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to pass image to plugin: {}", e)))?;
+        let params_ptr = alloc
+            .call(&mut store, params_len)
+            .map_err(|e| AppError::ProcessingFailed(format!("Plugin alloc failed: {}", e)))?;
+        memory
+            .write(&mut store, params_ptr as usize, &params)
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to pass params to plugin: {}", e)))?;
+
+        let status = apply
+            .call(
+                &mut store,
+                (
+                    pixels_ptr,
+                    pixels_len,
+                    img.width() as i32,
+                    img.height() as i32,
+                    params_ptr,
+                    params_len,
+                ),
+            )
+            .map_err(|e| AppError::ProcessingFailed(format!("Plugin execution failed: {}", e)))?;
+        if status != 0 {
+            return Err(AppError::ProcessingFailed(format!("Plugin {} returned error code {}", name, status)));
+        }
+
+        let buffer: &mut [u8] = img;
+        memory
+            .read(&store, pixels_ptr as usize, buffer)
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to read plugin output: {}", e)))?;
+        Ok(())
+    }
+
+    // 用插件处理图片；原图没有透明通道时按 RGB 返回，JPEG 等格式不支持透明
+    pub fn apply(&self, name: &str, img: &DynamicImage, params: &serde_json::Value) -> Result<DynamicImage, AppError> {
+        let mut rgba = img.to_rgba8();
+        self.run(name, &mut rgba, params)?;
+
+        Ok(if img.color().has_alpha() {
+            DynamicImage::ImageRgba8(rgba)
+        } else {
+            DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(rgba).to_rgb8())
+        })
+    }
+}
+
+// 启动时创建插件注册表并加入托管状态
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("plugins");
+    fs::create_dir_all(&dir).map_err(AppError::from)?;
+
+    let registry = PluginRegistry::new(dir)?;
+    app.manage(registry);
+    Ok(())
+}
+
+// 列出已加载的插件
+#[tauri::command]
+pub fn list_plugins(registry: State<'_, PluginRegistry>) -> Vec<PluginInfo> {
+    let mut plugins: Vec<PluginInfo> = registry.plugins.read().values().map(|p| p.info.clone()).collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+// 重新加载插件目录，返回加载失败的插件及原因
+#[tauri::command]
+pub fn reload_plugins(registry: State<'_, PluginRegistry>) -> Vec<String> {
+    registry.reload()
+}
+
+// 使用插件处理图片，output 为空时覆盖原图
+#[tauri::command]
+pub async fn apply_plugin(
+    app: AppHandle,
+    name: String,
+    path: String,
+    params: serde_json::Value,
+    output: Option<String>,
+) -> Result<bool, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("apply_plugin", move || {
+        let registry = app.state::<PluginRegistry>();
+        let img = open_image(&path)?;
+        let result = registry.apply(&name, &img, &params)?;
+
+        let output = output.unwrap_or_else(|| path.clone());
+        save_image_from(&result, Path::new(&path), Path::new(&output))?;
+        history::record(Path::new(&path), Path::new(&output), EditOperation::Plugin { name, params })?;
+
+        Ok(true)
+    })
+    .await
+}
@@ -0,0 +1,290 @@
+// 导出预设：社交媒体等常用尺寸，一次调用完成缩放/裁剪和编码
+// 内置预设不能修改；用户自定义的预设持久化到应用数据目录的 export_presets.json
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::storage::{write_app_file, write_bytes};
+use crate::text::parse_color;
+use crate::{open_image, paths, run_blocking, scope};
+
+const MAX_DIMENSION: u32 = 16384;
+
+lazy_static::lazy_static! {
+    static ref CUSTOM_PRESETS: RwLock<Vec<ExportPreset>> = RwLock::new(Vec::new());
+    // 预设文件，init 之前为 None（此时修改只保存在内存中）
+    static ref PRESETS_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+// 内置预设：(id, 名称, 宽, 高)，均为 JPEG、裁剪填满
+const BUILTIN_PRESETS: [(&str, &str, u32, u32); 12] = [
+    ("instagram-post", "Instagram Post", 1080, 1080),
+    ("instagram-portrait", "Instagram Portrait", 1080, 1350),
+    ("instagram-story", "Instagram Story / Reels", 1080, 1920),
+    ("youtube-thumbnail", "YouTube Thumbnail", 1280, 720),
+    ("youtube-banner", "YouTube Channel Banner", 2560, 1440),
+    ("twitter-post", "X / Twitter Post", 1600, 900),
+    ("twitter-header", "X / Twitter Header", 1500, 500),
+    ("facebook-cover", "Facebook Cover", 851, 315),
+    ("linkedin-banner", "LinkedIn Banner", 1584, 396),
+    ("pinterest-pin", "Pinterest Pin", 1000, 1500),
+    ("wechat-cover", "微信公众号封面", 900, 383),
+    ("xiaohongshu-post", "小红书笔记", 1080, 1440),
+];
+
+// 尺寸与目标不一致时的处理方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FitMode {
+    // 等比缩放后裁掉超出的部分
+    #[default]
+    Cover,
+    // 等比缩放后完整放入，空白处填充背景色
+    Contain,
+    // 拉伸到目标尺寸
+    Stretch,
+}
+
+fn default_quality() -> u8 {
+    90
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+    // 为空时保存时自动生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub fit: FitMode,
+    // jpg 或 png
+    pub format: String,
+    // JPEG 编码质量
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+    // Contain 模式的填充颜色，为空时 PNG 保持透明、JPEG 使用白色
+    #[serde(default)]
+    pub background: Option<String>,
+    // 内置预设，由后端设置
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetExportResult {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    pub size: u64,
+}
+
+impl ExportPreset {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::InvalidArgument("Preset name must not be empty".to_string()));
+        }
+        if !(1..=MAX_DIMENSION).contains(&self.width) || !(1..=MAX_DIMENSION).contains(&self.height) {
+            return Err(AppError::InvalidArgument(format!(
+                "Preset dimensions must be between 1 and {}: {}x{}",
+                MAX_DIMENSION, self.width, self.height
+            )));
+        }
+        if !(1..=100).contains(&self.quality) {
+            return Err(AppError::InvalidArgument(format!(
+                "Quality must be between 1 and 100: {}",
+                self.quality
+            )));
+        }
+        self.image_format()?;
+        if let Some(background) = &self.background {
+            parse_color(background)?;
+        }
+        Ok(())
+    }
+
+    fn image_format(&self) -> Result<ImageFormat, AppError> {
+        match self.format.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Ok(ImageFormat::Jpeg),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(AppError::Unsupported(format!("Preset format {}", self.format))),
+        }
+    }
+}
+
+fn builtin_presets() -> Vec<ExportPreset> {
+    BUILTIN_PRESETS
+        .iter()
+        .map(|(id, name, width, height)| ExportPreset {
+            id: id.to_string(),
+            name: name.to_string(),
+            width: *width,
+            height: *height,
+            fit: FitMode::Cover,
+            format: "jpg".to_string(),
+            quality: default_quality(),
+            background: None,
+            builtin: true,
+        })
+        .collect()
+}
+
+fn find(id: &str) -> Result<ExportPreset, AppError> {
+    builtin_presets()
+        .into_iter()
+        .chain(CUSTOM_PRESETS.read().iter().cloned())
+        .find(|preset| preset.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Export preset {}", id)))
+}
+
+fn save_custom(presets: &[ExportPreset]) -> Result<(), AppError> {
+    if let Some(file) = PRESETS_FILE.read().as_ref() {
+        write_app_file(file, &serde_json::to_vec_pretty(presets)?)?;
+    }
+    Ok(())
+}
+
+// 启动时读取自定义预设；无效的预设被忽略
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let file = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("export_presets.json");
+
+    let saved: Vec<ExportPreset> = fs::read_to_string(&file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    *CUSTOM_PRESETS.write() = saved
+        .into_iter()
+        .filter(|preset| match preset.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(preset = %preset.id, error = %e, "ignoring invalid export preset");
+                false
+            }
+        })
+        .map(|preset| ExportPreset { builtin: false, ..preset })
+        .collect();
+    *PRESETS_FILE.write() = Some(file);
+    Ok(())
+}
+
+// 按预设缩放/裁剪
+fn fit(img: &DynamicImage, preset: &ExportPreset, background: Option<Rgba<u8>>) -> DynamicImage {
+    let (width, height) = (preset.width, preset.height);
+    match preset.fit {
+        FitMode::Cover => img.resize_to_fill(width, height, FilterType::Lanczos3),
+        FitMode::Stretch => img.resize_exact(width, height, FilterType::Lanczos3),
+        FitMode::Contain => {
+            let scaled = img.resize(width, height, FilterType::Lanczos3).to_rgba8();
+            let mut canvas = RgbaImage::from_pixel(width, height, background.unwrap_or(Rgba([0, 0, 0, 0])));
+            let x = (width - scaled.width()) / 2;
+            let y = (height - scaled.height()) / 2;
+            imageops::overlay(&mut canvas, &scaled, x as i64, y as i64);
+            DynamicImage::ImageRgba8(canvas)
+        }
+    }
+}
+
+// 内置预设和自定义预设
+#[tauri::command]
+pub fn list_export_presets() -> Vec<ExportPreset> {
+    let mut presets = builtin_presets();
+    presets.extend(CUSTOM_PRESETS.read().iter().cloned());
+    presets
+}
+
+// 新增或替换（id 相同时）自定义预设，返回保存后的预设
+#[tauri::command]
+pub fn save_export_preset(mut preset: ExportPreset) -> Result<ExportPreset, AppError> {
+    preset.validate()?;
+    if BUILTIN_PRESETS.iter().any(|(id, ..)| *id == preset.id) {
+        return Err(AppError::InvalidArgument(format!("Built-in preset cannot be changed: {}", preset.id)));
+    }
+    if preset.id.is_empty() {
+        preset.id = format!("custom-{:08x}", rand::random::<u32>());
+    }
+    preset.builtin = false;
+
+    let mut presets = CUSTOM_PRESETS.write();
+    let mut updated = presets.clone();
+    match updated.iter_mut().find(|existing| existing.id == preset.id) {
+        Some(existing) => *existing = preset.clone(),
+        None => updated.push(preset.clone()),
+    }
+    save_custom(&updated)?;
+    *presets = updated;
+    Ok(preset)
+}
+
+#[tauri::command]
+pub fn delete_export_preset(id: &str) -> Result<bool, AppError> {
+    let mut presets = CUSTOM_PRESETS.write();
+    let updated: Vec<ExportPreset> = presets.iter().filter(|preset| preset.id != id).cloned().collect();
+    if updated.len() == presets.len() {
+        return Ok(false);
+    }
+    save_custom(&updated)?;
+    *presets = updated;
+    Ok(true)
+}
+
+// 按预设导出图片，output 为空时在原图旁边生成 name_<预设 id>.<格式>
+#[tauri::command]
+pub async fn export_with_preset(
+    path: String,
+    preset_id: String,
+    output: Option<String>,
+) -> Result<PresetExportResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    let preset = find(&preset_id)?;
+    let format = preset.image_format()?;
+    let background = preset.background.as_deref().map(parse_color).transpose()?;
+
+    run_blocking("export_with_preset", move || {
+        let path = Path::new(&path);
+        let img = fit(&open_image(path)?, &preset, background);
+
+        let mut data = Cursor::new(Vec::new());
+        match format {
+            ImageFormat::Jpeg => {
+                // JPEG 不支持透明，先合成到背景色上
+                let mut canvas = RgbaImage::from_pixel(img.width(), img.height(), background.unwrap_or(Rgba([255; 4])));
+                imageops::overlay(&mut canvas, &img.to_rgba8(), 0, 0);
+                let rgb = DynamicImage::ImageRgba8(canvas).to_rgb8();
+                JpegEncoder::new_with_quality(&mut data, preset.quality).encode_image(&rgb)?;
+            }
+            _ => img.write_to(&mut data, format)?,
+        }
+        let data = data.into_inner();
+
+        let output = output.map(PathBuf::from).unwrap_or_else(|| {
+            let ext = if format == ImageFormat::Jpeg { "jpg" } else { "png" };
+            path.with_file_name(format!("{}_{}.{}", paths::file_stem(path, "image"), preset.id, ext))
+        });
+        write_bytes(&output, &data)?;
+        Ok(PresetExportResult {
+            output: paths::display(&output),
+            width: img.width(),
+            height: img.height(),
+            size: data.len() as u64,
+        })
+    })
+    .await
+}
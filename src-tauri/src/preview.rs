@@ -0,0 +1,169 @@
+// 渐进式预览：打开大图时先尽快发送一张低分辨率预览，再逐级发送更清晰的版本
+// JPEG 可以在解码时直接按 1/2~1/8 缩小（DCT 缩放），第一帧通常在 100 ms 内就能显示
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::codecs::jpeg::{JpegDecoder, JpegEncoder};
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageDecoder, ImageFormat};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::{open_image, paths, run_blocking, scope};
+
+// 第一帧（粗略预览）的最长边
+const COARSE_SIZE: u32 = 256;
+// 中间帧的最长边，小于最终尺寸的级别才会发送
+const REFINE_SIZES: [u32; 2] = [512, 1024];
+// 最终预览默认的最长边
+const DEFAULT_MAX_SIZE: u32 = 2048;
+const PREVIEW_JPEG_QUALITY: u8 = 80;
+
+// 每次开始新的预览时递增；较旧的预览发现自己已过期后停止发送
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewFrame {
+    pub path: String,
+    // 从 0 开始的级别，越大越清晰
+    pub level: u32,
+    // 是否为最后一帧
+    pub last: bool,
+    pub width: u32,
+    pub height: u32,
+    // 原图尺寸
+    pub source_width: u32,
+    pub source_height: u32,
+    // data:image/jpeg 或 data:image/png（有透明通道时）
+    pub data_url: String,
+}
+
+fn is_current(generation: u64) -> bool {
+    GENERATION.load(Ordering::SeqCst) == generation
+}
+
+fn data_url(img: &DynamicImage) -> Result<String, AppError> {
+    let mut buffer = Cursor::new(Vec::new());
+    let mime = if img.color().has_alpha() {
+        img.write_to(&mut buffer, ImageFormat::Png)?;
+        "image/png"
+    } else {
+        JpegEncoder::new_with_quality(&mut buffer, PREVIEW_JPEG_QUALITY).encode_image(&img.to_rgb8())?;
+        "image/jpeg"
+    };
+    Ok(format!("data:{};base64,{}", mime, STANDARD.encode(buffer.into_inner())))
+}
+
+// JPEG 按 DCT 缩放解码，只需要解码一小部分数据；返回缩小后的图片和原图尺寸
+fn decode_jpeg_scaled(path: &Path, size: u32) -> Result<(DynamicImage, (u32, u32)), AppError> {
+    let file = File::open(paths::extended(path)).map_err(|e| AppError::from(e).at(path))?;
+    let mut decoder = JpegDecoder::new(BufReader::new(file)).map_err(|e| AppError::from(e).at(path))?;
+    let source = decoder.dimensions();
+    let size = size.min(u16::MAX as u32) as u16;
+    decoder.scale(size, size).map_err(|e| AppError::from(e).at(path))?;
+    let img = DynamicImage::from_decoder(decoder).map_err(|e| AppError::from(e).at(path))?;
+    Ok((img, source))
+}
+
+fn is_jpeg(path: &Path) -> bool {
+    matches!(ImageFormat::from_path(path), Ok(ImageFormat::Jpeg))
+}
+
+// 按顺序生成并发送各级预览；被更新的预览请求取代时返回 false
+fn stream_frames(app: &AppHandle, path: &Path, max_size: u32, generation: u64) -> Result<bool, AppError> {
+    let display = paths::display(path);
+    let mut level = 0;
+    let emit = |img: &DynamicImage, level: u32, last: bool, source: (u32, u32)| -> Result<bool, AppError> {
+        if !is_current(generation) {
+            return Ok(false);
+        }
+        let (width, height) = img.dimensions();
+        let _ = app.emit(
+            "preview-frame",
+            PreviewFrame {
+                path: display.clone(),
+                level,
+                last,
+                width,
+                height,
+                source_width: source.0,
+                source_height: source.1,
+                data_url: data_url(img)?,
+            },
+        );
+        Ok(true)
+    };
+
+    // JPEG 先发送 DCT 缩放得到的粗略预览，再解码整张图片；失败时直接走完整解码
+    let mut coarse_sent = false;
+    if is_jpeg(path) {
+        match decode_jpeg_scaled(path, COARSE_SIZE) {
+            Ok((img, source)) => {
+                let last = source.0.max(source.1) <= COARSE_SIZE;
+                let img = img.thumbnail(COARSE_SIZE, COARSE_SIZE);
+                if !emit(&img, level, last, source)? {
+                    return Ok(false);
+                }
+                if last {
+                    return Ok(true);
+                }
+                level += 1;
+                coarse_sent = true;
+            }
+            Err(e) => tracing::debug!(path = %display, error = %e, "scaled JPEG decode failed"),
+        }
+    }
+
+    let img = open_image(path)?;
+    let source = img.dimensions();
+    let longest = source.0.max(source.1);
+    let final_size = max_size.min(longest);
+
+    let mut sizes = Vec::new();
+    if !coarse_sent && COARSE_SIZE < final_size {
+        sizes.push(COARSE_SIZE);
+    }
+    sizes.extend(REFINE_SIZES.iter().copied().filter(|size| *size < final_size));
+
+    // 中间帧使用较快的缩放，最终帧使用高质量缩放
+    for size in sizes {
+        if !emit(&img.thumbnail(size, size), level, false, source)? {
+            return Ok(false);
+        }
+        level += 1;
+    }
+    if final_size < longest {
+        emit(&img.resize(final_size, final_size, FilterType::Lanczos3), level, true, source)
+    } else {
+        emit(&img, level, true, source)
+    }
+}
+
+// 开始渐进式预览：通过 preview-frame 事件依次发送从粗到细的预览，最后一帧的 last 为 true
+// 新的预览请求会让之前未完成的请求停止；返回 false 表示本次请求已被取代
+#[tauri::command]
+pub async fn stream_preview(app: AppHandle, path: String, max_size: Option<u32>) -> Result<bool, AppError> {
+    scope::check(&path)?;
+    let max_size = max_size.unwrap_or(DEFAULT_MAX_SIZE);
+    if max_size == 0 {
+        return Err(AppError::InvalidArgument("Preview size must be positive".to_string()));
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    run_blocking("stream_preview", move || {
+        stream_frames(&app, Path::new(&path), max_size, generation)
+    })
+    .await
+}
+
+// 停止正在进行的渐进式预览
+#[tauri::command]
+pub fn cancel_preview() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
@@ -0,0 +1,175 @@
+// 打印输出：将一张或多张图片按纸张尺寸排版，生成 PDF 并交给系统打开打印
+use std::path::Path;
+use std::sync::Arc;
+
+use image::GenericImageView;
+use printpdf::{Image, ImageTransform, Mm, PdfDocument};
+use serde::Deserialize;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::AppError;
+use crate::open_image;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::write_atomic;
+
+// 嵌入 PDF 时使用的分辨率
+const PRINT_DPI: f32 = 300.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PaperSize {
+    #[default]
+    A4,
+    Letter,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum FitMode {
+    // 完整显示图片，可能留白
+    Fit,
+    // 裁剪图片以填满格子
+    Fill,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintLayout {
+    pub paper: PaperSize,
+    #[serde(default)]
+    pub landscape: bool,
+    // 页边距（毫米）
+    pub margin: f32,
+    // 格子之间的间距（毫米）
+    #[serde(default)]
+    pub spacing: f32,
+    pub columns: u32,
+    pub rows: u32,
+    pub fit: FitMode,
+}
+
+impl PaperSize {
+    // 纸张尺寸（毫米，纵向）
+    pub(crate) fn dimensions(self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+// 按格子的宽高比从中心裁剪图片
+fn crop_to_aspect(img: image::DynamicImage, aspect: f32) -> image::DynamicImage {
+    let (width, height) = img.dimensions();
+    let current = width as f32 / height as f32;
+    if current > aspect {
+        let new_width = (height as f32 * aspect).round() as u32;
+        img.crop_imm((width - new_width) / 2, 0, new_width, height)
+    } else {
+        let new_height = (width as f32 / aspect).round() as u32;
+        img.crop_imm(0, (height - new_height) / 2, width, new_height)
+    }
+}
+
+// 生成打印用的 PDF
+#[tauri::command]
+pub async fn generate_print_pdf(
+    app: AppHandle,
+    paths: Vec<String>,
+    layout: PrintLayout,
+    output: String,
+    open_print: bool,
+) -> Result<bool, AppError> {
+    for path in &paths {
+        scope::check(path)?;
+    }
+    scope::check(&output)?;
+
+    run_blocking("generate_print_pdf", move || {
+        if paths.is_empty() {
+            return Err(AppError::InvalidArgument("No images to print".to_string()));
+        }
+        if layout.columns == 0 || layout.rows == 0 {
+            return Err(AppError::InvalidArgument("Columns and rows must be at least 1".to_string()));
+        }
+
+        let (mut page_width, mut page_height) = layout.paper.dimensions();
+        if layout.landscape {
+            std::mem::swap(&mut page_width, &mut page_height);
+        }
+
+        // 计算每个格子的尺寸
+        let usable_width = page_width - layout.margin * 2.0 - layout.spacing * (layout.columns - 1) as f32;
+        let usable_height = page_height - layout.margin * 2.0 - layout.spacing * (layout.rows - 1) as f32;
+        if usable_width <= 0.0 || usable_height <= 0.0 {
+            return Err(AppError::InvalidArgument("Margins are too large for the selected paper".to_string()));
+        }
+        let cell_width = usable_width / layout.columns as f32;
+        let cell_height = usable_height / layout.rows as f32;
+
+        let per_page = (layout.columns * layout.rows) as usize;
+        let (doc, first_page, first_layer) =
+            PdfDocument::new("Print", Mm(page_width), Mm(page_height), "Layer 1");
+        let mut layer = doc.get_page(first_page).get_layer(first_layer);
+
+        for (index, path) in paths.iter().enumerate() {
+            let slot = index % per_page;
+            if index > 0 && slot == 0 {
+                let (page, page_layer) = doc.add_page(Mm(page_width), Mm(page_height), "Layer 1");
+                layer = doc.get_page(page).get_layer(page_layer);
+            }
+
+            let img = Arc::unwrap_or_clone(open_image(path)?);
+
+            let img = match layout.fit {
+                FitMode::Fill => crop_to_aspect(img, cell_width / cell_height),
+                FitMode::Fit => img,
+            };
+
+            // 图片在 PRINT_DPI 下的原始尺寸（毫米）
+            let (width, height) = img.dimensions();
+            let native_width = width as f32 / PRINT_DPI * 25.4;
+            let native_height = height as f32 / PRINT_DPI * 25.4;
+            let scale = (cell_width / native_width).min(cell_height / native_height);
+            let draw_width = native_width * scale;
+            let draw_height = native_height * scale;
+
+            // PDF 坐标原点在左下角，格子从左上角开始排列
+            let column = (slot as u32 % layout.columns) as f32;
+            let row = (slot as u32 / layout.columns) as f32;
+            let cell_left = layout.margin + column * (cell_width + layout.spacing);
+            let cell_top = page_height - layout.margin - row * (cell_height + layout.spacing);
+            let x = cell_left + (cell_width - draw_width) / 2.0;
+            let y = cell_top - cell_height + (cell_height - draw_height) / 2.0;
+
+            Image::from_dynamic_image(&img).add_to_layer(
+                layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(x)),
+                    translate_y: Some(Mm(y)),
+                    scale_x: Some(scale),
+                    scale_y: Some(scale),
+                    dpi: Some(PRINT_DPI),
+                    ..Default::default()
+                },
+            );
+        }
+
+        write_atomic(Path::new(&output), |writer| {
+            doc.save(writer)
+                .map_err(|e| AppError::EncodeFailed(format!("Failed to save PDF: {}", e)))
+        })?;
+
+        // 交给系统默认的 PDF 程序，由其弹出打印对话框
+        if open_print {
+            app.opener()
+                .open_path(output, None::<&str>)
+                .map_err(|e| AppError::Internal(format!("Failed to open PDF: {}", e)))?;
+        }
+
+        Ok(true)
+    })
+    .await
+}
@@ -0,0 +1,177 @@
+// 隐私打码：批量检测人脸（以及可选的车牌）并进行马赛克或模糊处理
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::face::{detect_with_model, find_faces, FaceBox, DEFAULT_THRESHOLD};
+use crate::open_image;
+use crate::paths;
+use crate::run_blocking;
+use crate::scope;
+use crate::settings;
+use crate::storage::save_image;
+
+const PLATE_MODEL_FILE: &str = "plate-detector.onnx";
+// 检测框向外扩展的比例，保证完整覆盖
+const BOX_EXPAND: f32 = 0.1;
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactMode {
+    Pixelate,
+    Blur,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactOptions {
+    pub mode: RedactMode,
+    // 马赛克块大小（像素）或模糊半径
+    pub strength: f32,
+    #[serde(default)]
+    pub include_plates: bool,
+    pub threshold: Option<f32>,
+    // 输出目录，为空时输出到原图旁边的 name_redacted.ext
+    pub output_dir: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RedactResult {
+    pub path: String,
+    pub output: Option<String>,
+    pub regions: usize,
+    pub error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RedactProgress {
+    pub path: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+// 对图片中的一个区域打码
+fn redact_region(canvas: &mut RgbaImage, region: &FaceBox, mode: RedactMode, strength: f32) {
+    let (width, height) = canvas.dimensions();
+    let expand_x = region.width * BOX_EXPAND;
+    let expand_y = region.height * BOX_EXPAND;
+    let left = ((region.x - expand_x).max(0.0) * width as f32) as u32;
+    let top = ((region.y - expand_y).max(0.0) * height as f32) as u32;
+    let right = (((region.x + region.width + expand_x).min(1.0)) * width as f32) as u32;
+    let bottom = (((region.y + region.height + expand_y).min(1.0)) * height as f32) as u32;
+    if right <= left || bottom <= top {
+        return;
+    }
+
+    let area = canvas.view(left, top, right - left, bottom - top).to_image();
+    let processed = match mode {
+        RedactMode::Pixelate => {
+            let block = strength.max(2.0);
+            let small_width = ((area.width() as f32 / block).ceil() as u32).max(1);
+            let small_height = ((area.height() as f32 / block).ceil() as u32).max(1);
+            let small = image::imageops::resize(&area, small_width, small_height, FilterType::Triangle);
+            image::imageops::resize(&small, area.width(), area.height(), FilterType::Nearest)
+        }
+        RedactMode::Blur => image::imageops::blur(&area, strength.max(1.0)),
+    };
+
+    image::imageops::replace(canvas, &processed, left as i64, top as i64);
+}
+
+fn output_path(path: &Path, output_dir: Option<&str>) -> PathBuf {
+    match output_dir {
+        Some(dir) => Path::new(dir).join(path.file_name().unwrap_or_default()),
+        None => {
+            let stem = paths::file_stem(path, "image");
+            let ext = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| settings::current().export_format);
+            path.with_file_name(format!("{}_redacted.{}", stem, ext))
+        }
+    }
+}
+
+fn redact_file(app: &AppHandle, path: &str, options: &RedactOptions) -> Result<(String, usize), AppError> {
+    let img = open_image(path)?;
+
+    let threshold = options.threshold.unwrap_or(DEFAULT_THRESHOLD);
+    let mut regions = find_faces(app, &img, threshold)?;
+    if options.include_plates {
+        regions.extend(detect_with_model(app, PLATE_MODEL_FILE, &img, threshold)?);
+    }
+
+    let mut canvas = img.to_rgba8();
+    for region in &regions {
+        redact_region(&mut canvas, region, options.mode, options.strength);
+    }
+
+    let output = output_path(Path::new(path), options.output_dir.as_deref());
+    let result = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(canvas)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(canvas).to_rgb8())
+    };
+    save_image(&result, &output)?;
+
+    Ok((output.to_string_lossy().to_string(), regions.len()))
+}
+
+// 批量打码，单个文件失败不会中断整个批次
+#[tauri::command]
+pub async fn redact_images(
+    app: AppHandle,
+    paths: Vec<String>,
+    options: RedactOptions,
+) -> Result<Vec<RedactResult>, AppError> {
+    for path in &paths {
+        scope::check(path)?;
+    }
+    if let Some(dir) = &options.output_dir {
+        scope::check(dir)?;
+        fs::create_dir_all(dir).map_err(AppError::from)?;
+    }
+
+    // 每个文件单独在阻塞线程中处理，超时只影响当前文件，批次的总耗时不受限制
+    let total = paths.len();
+    let mut results = Vec::with_capacity(total);
+    for (index, path) in paths.into_iter().enumerate() {
+        let redacted = run_blocking("redact_images", {
+            let app = app.clone();
+            let path = path.clone();
+            let options = options.clone();
+            move || redact_file(&app, &path, &options)
+        })
+        .await;
+        let result = match redacted {
+            Ok((output, regions)) => RedactResult {
+                path: path.clone(),
+                output: Some(output),
+                regions,
+                error: None,
+            },
+            Err(error) => RedactResult {
+                path: path.clone(),
+                output: None,
+                regions: 0,
+                error: Some(error),
+            },
+        };
+        results.push(result);
+
+        let _ = app.emit(
+            "redact-progress",
+            RedactProgress {
+                path,
+                done: index + 1,
+                total,
+            },
+        );
+    }
+    Ok(results)
+}
@@ -0,0 +1,262 @@
+// 预览会话：拖动滑块调整参数时在缩小的工作副本上处理，不必每次都解码、处理、编码整张原图
+// 会话保留每个操作之后的中间结果，修改第 i 个操作时只从第 i 个操作开始重新计算
+// 确定参数后，前端再把同一组操作应用到原图（apply_session_operation 等）
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use image::{DynamicImage, GenericImageView};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::AppHandle;
+
+use crate::adjust::Adjustment;
+use crate::color::source_image;
+use crate::error::AppError;
+use crate::filters::Filter;
+use crate::history::{self, EditOperation};
+use crate::run_blocking;
+use crate::tiles::{encode_tile, TileFormat};
+
+// 工作副本默认的最长边
+const DEFAULT_PROXY_SIZE: u32 = 1024;
+const MAX_PROXY_SIZE: u32 = 4096;
+// 同时保留的会话数，超过时关闭最久未使用的会话
+const MAX_SESSIONS: usize = 8;
+
+struct PreviewSession {
+    // 工作副本相对原图的比例
+    scale: f32,
+    source_size: (u32, u32),
+    base: Arc<DynamicImage>,
+    operations: Vec<EditOperation>,
+    // stages[i] 为应用前 i + 1 个操作后的结果；修改操作时删除其后已过期的结果
+    stages: Vec<Arc<DynamicImage>>,
+    // 最近一次使用的序号，越小越久未使用
+    used: u64,
+    // 最新一次请求的序号，排队中的旧请求据此跳过
+    latest: Arc<AtomicU64>,
+}
+
+impl PreviewSession {
+    fn current(&self) -> Arc<DynamicImage> {
+        self.stages.last().unwrap_or(&self.base).clone()
+    }
+
+    // 从第 from 个操作开始重新计算
+    fn render(&mut self, app: &AppHandle, from: usize) -> Result<Arc<DynamicImage>, AppError> {
+        let from = from.min(self.stages.len());
+        self.stages.truncate(from);
+        for operation in &self.operations[from..] {
+            let input = self.stages.last().unwrap_or(&self.base);
+            let output = history::apply(app, (**input).clone(), &scale_operation(operation, self.scale))?;
+            self.stages.push(Arc::new(output));
+        }
+        Ok(self.current())
+    }
+}
+
+lazy_static::lazy_static! {
+    // 打开的预览会话，按会话 id 索引
+    static ref SESSIONS: Mutex<HashMap<String, Arc<Mutex<PreviewSession>>>> = Mutex::new(HashMap::new());
+}
+
+static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+fn tick() -> u64 {
+    CLOCK.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewSessionInfo {
+    pub id: String,
+    // 工作副本尺寸
+    pub width: u32,
+    pub height: u32,
+    pub source_width: u32,
+    pub source_height: u32,
+    pub scale: f32,
+}
+
+fn get(id: &str) -> Result<Arc<Mutex<PreviewSession>>, AppError> {
+    SESSIONS
+        .lock()
+        .get(id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Preview session {}", id)))
+}
+
+// 以像素为单位的参数按工作副本的比例换算，使预览与原图上的效果一致
+fn scale_operation(operation: &EditOperation, scale: f32) -> EditOperation {
+    let px = |value: u32| ((value as f32 * scale).round() as u32).max(1);
+    match operation {
+        EditOperation::Resize { width, height } => EditOperation::Resize {
+            width: px(*width),
+            height: px(*height),
+        },
+        EditOperation::Filter {
+            filter: Filter::Resize { width, height },
+        } => EditOperation::Filter {
+            filter: Filter::Resize {
+                width: px(*width),
+                height: px(*height),
+            },
+        },
+        EditOperation::Filter {
+            filter: Filter::Blur { sigma },
+        } => EditOperation::Filter {
+            filter: Filter::Blur { sigma: sigma * scale },
+        },
+        EditOperation::Adjust {
+            adjustment: Adjustment::Blur { sigma },
+        } => EditOperation::Adjust {
+            adjustment: Adjustment::Blur { sigma: sigma * scale },
+        },
+        EditOperation::Adjust {
+            adjustment: Adjustment::Sharpen { sigma, threshold },
+        } => EditOperation::Adjust {
+            adjustment: Adjustment::Sharpen {
+                sigma: sigma * scale,
+                threshold: *threshold,
+            },
+        },
+        other => other.clone(),
+    }
+}
+
+// 在阻塞线程中修改会话并返回编码后的预览；排队期间有更新的请求到达时跳过处理，返回空的响应体
+async fn update<F>(app: AppHandle, id: String, command: &str, change: F) -> Result<Response, AppError>
+where
+    F: FnOnce(&mut PreviewSession) -> Result<usize, AppError> + Send + 'static,
+{
+    let session = get(&id)?;
+    let latest = session.lock().latest.clone();
+    let request = latest.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let data = run_blocking(command, move || {
+        let mut session = session.lock();
+        session.used = tick();
+        let from = change(&mut session)?;
+        if latest.load(Ordering::SeqCst) != request {
+            return Ok(Vec::new());
+        }
+        let image = session.render(&app, from)?;
+        encode_tile(&image, TileFormat::Auto)
+    })
+    .await?;
+    Ok(Response::new(data))
+}
+
+// 为 source（文件路径或编辑会话 id）创建预览会话，工作副本的最长边为 max_size（默认 1024）
+#[tauri::command]
+pub async fn open_preview_session(
+    app: AppHandle,
+    source: String,
+    max_size: Option<u32>,
+) -> Result<PreviewSessionInfo, AppError> {
+    let max_size = max_size.unwrap_or(DEFAULT_PROXY_SIZE);
+    if max_size == 0 || max_size > MAX_PROXY_SIZE {
+        return Err(AppError::InvalidArgument(format!(
+            "Preview size must be between 1 and {}",
+            MAX_PROXY_SIZE
+        )));
+    }
+
+    let session = run_blocking("open_preview_session", move || {
+        let img = source_image(&app, &source)?;
+        let (source_width, source_height) = img.dimensions();
+        let base = if source_width.max(source_height) > max_size {
+            Arc::new(img.thumbnail(max_size, max_size))
+        } else {
+            img
+        };
+        let scale = base.width() as f32 / source_width as f32;
+        Ok(PreviewSession {
+            scale,
+            source_size: (source_width, source_height),
+            base,
+            operations: Vec::new(),
+            stages: Vec::new(),
+            used: tick(),
+            latest: Arc::new(AtomicU64::new(0)),
+        })
+    })
+    .await?;
+
+    let id = format!("{:016x}", rand::random::<u64>());
+    let (width, height) = session.base.dimensions();
+    let info = PreviewSessionInfo {
+        id: id.clone(),
+        width,
+        height,
+        source_width: session.source_size.0,
+        source_height: session.source_size.1,
+        scale: session.scale,
+    };
+
+    let mut sessions = SESSIONS.lock();
+    while sessions.len() >= MAX_SESSIONS {
+        let Some(oldest) = sessions
+            .iter()
+            .min_by_key(|(_, session)| session.lock().used)
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        sessions.remove(&oldest);
+    }
+    sessions.insert(id, Arc::new(Mutex::new(session)));
+    Ok(info)
+}
+
+// 设置第 index 个操作（index 等于操作数时追加），返回新的预览图片字节
+// 返回空的响应体表示该请求已被同一会话中更新的请求取代
+#[tauri::command]
+pub async fn set_preview_operation(
+    app: AppHandle,
+    id: String,
+    index: usize,
+    operation: EditOperation,
+) -> Result<Response, AppError> {
+    update(app, id, "set_preview_operation", move |session| {
+        let count = session.operations.len();
+        if index > count {
+            return Err(AppError::InvalidArgument(format!(
+                "Operation index {} is out of range (0-{})",
+                index, count
+            )));
+        }
+        if index == count {
+            session.operations.push(operation);
+        } else {
+            session.operations[index] = operation;
+        }
+        session.stages.truncate(index);
+        Ok(index)
+    })
+    .await
+}
+
+// 删除第 from 个及之后的操作，返回新的预览图片字节
+#[tauri::command]
+pub async fn truncate_preview_operations(app: AppHandle, id: String, from: usize) -> Result<Response, AppError> {
+    update(app, id, "truncate_preview_operations", move |session| {
+        session.operations.truncate(from);
+        session.stages.truncate(from);
+        Ok(from)
+    })
+    .await
+}
+
+// 会话中的操作列表（以原图像素为单位），用于把确定的参数应用到原图
+#[tauri::command]
+pub fn get_preview_operations(id: &str) -> Result<Vec<EditOperation>, AppError> {
+    Ok(get(id)?.lock().operations.clone())
+}
+
+#[tauri::command]
+pub fn close_preview_session(id: &str) -> bool {
+    SESSIONS.lock().remove(id).is_some()
+}
@@ -0,0 +1,207 @@
+// 红眼修复：在指定区域（或自动检测到的人脸的眼睛附近）找出偏红的瞳孔像素，去饱和并压暗
+// 按红色程度渐变处理，瞳孔边缘过渡自然
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::storage::save_image;
+use crate::{open_image, paths, run_blocking, scope};
+
+// 红色分量与绿、蓝均值之比超过该值的像素视为红眼
+const DEFAULT_SENSITIVITY: f32 = 1.8;
+// 比值超过阈值该值以上时完全修正，之间渐变
+const FEATHER: f32 = 0.6;
+// 太暗的像素不处理
+const MIN_RED: u8 = 60;
+// 修正后的亮度比例
+const DARKEN: f32 = 0.8;
+
+// 像素坐标的矩形区域
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct EyeRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RedEyeResult {
+    pub output: String,
+    // 处理的区域（自动检测时为推算的眼睛区域）
+    pub regions: Vec<EyeRegion>,
+    // 修正的像素数
+    pub pixels: u32,
+}
+
+// 像素的修正强度（0~1）
+fn redness(pixel: &[u8], sensitivity: f32) -> f32 {
+    let [r, g, b] = [pixel[0], pixel[1], pixel[2]];
+    if r < MIN_RED {
+        return 0.0;
+    }
+    let ratio = r as f32 / ((g as f32 + b as f32) / 2.0).max(1.0);
+    ((ratio - sensitivity) / FEATHER).clamp(0.0, 1.0)
+}
+
+// 修正区域内的红色像素；only_largest 时只处理最大的红色连通区域（自动检测时避开皮肤等误检）
+fn correct_region(img: &mut RgbaImage, region: &EyeRegion, sensitivity: f32, only_largest: bool) -> u32 {
+    let (width, height) = img.dimensions();
+    let x1 = (region.x + region.width).min(width);
+    let y1 = (region.y + region.height).min(height);
+    if region.x >= x1 || region.y >= y1 {
+        return 0;
+    }
+    let (w, h) = (x1 - region.x, y1 - region.y);
+    let weights: Vec<f32> = (0..w * h)
+        .map(|i| redness(&img.get_pixel(region.x + i % w, region.y + i / w).0, sensitivity))
+        .collect();
+
+    // 4 邻域连通区域
+    let mut label = vec![usize::MAX; weights.len()];
+    let mut components: Vec<Vec<u32>> = Vec::new();
+    for start in 0..weights.len() {
+        if weights[start] <= 0.0 || label[start] != usize::MAX {
+            continue;
+        }
+        let id = components.len();
+        let mut members = Vec::new();
+        let mut queue = VecDeque::from([start as u32]);
+        label[start] = id;
+        while let Some(index) = queue.pop_front() {
+            members.push(index);
+            let (x, y) = (index % w, index / w);
+            let neighbors = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < w).then(|| index + 1),
+                (y > 0).then(|| index - w),
+                (y + 1 < h).then(|| index + w),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                if weights[neighbor as usize] > 0.0 && label[neighbor as usize] == usize::MAX {
+                    label[neighbor as usize] = id;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        components.push(members);
+    }
+    if only_largest {
+        if let Some(largest) = components.iter().map(|c| c.len()).max() {
+            components.retain(|c| c.len() == largest);
+            components.truncate(1);
+        }
+    }
+
+    let mut corrected = 0;
+    for index in components.iter().flatten() {
+        let weight = weights[*index as usize];
+        let pixel = img.get_pixel_mut(region.x + index % w, region.y + index / w);
+        // 红色用绿、蓝的均值代替，三个通道一起压暗
+        let neutral = (pixel[1] as f32 + pixel[2] as f32) / 2.0 * DARKEN;
+        for c in 0..3 {
+            let value = pixel[c] as f32;
+            pixel[c] = (value + (neutral - value) * weight).round().clamp(0.0, 255.0) as u8;
+        }
+        corrected += 1;
+    }
+    corrected
+}
+
+// 按人脸框推算左右眼所在的区域
+#[cfg(feature = "ml")]
+fn eye_regions(app: &AppHandle, img: &DynamicImage) -> Result<Vec<EyeRegion>, AppError> {
+    use crate::face::{find_faces, DEFAULT_THRESHOLD};
+
+    let (width, height) = img.dimensions();
+    let mut regions = Vec::new();
+    for face in find_faces(app, img, DEFAULT_THRESHOLD)? {
+        // 眼睛大致位于人脸框上部 20%~55% 的高度，左右各占一半
+        let top = ((face.y + face.height * 0.2) * height as f32).max(0.0) as u32;
+        let eye_height = (face.height * 0.35 * height as f32) as u32;
+        for (from, to) in [(0.1, 0.5), (0.5, 0.9)] {
+            let left = ((face.x + face.width * from) * width as f32).max(0.0) as u32;
+            let eye_width = (face.width * (to - from) * width as f32) as u32;
+            regions.push(EyeRegion {
+                x: left,
+                y: top,
+                width: eye_width,
+                height: eye_height,
+            });
+        }
+    }
+    Ok(regions)
+}
+
+#[cfg(not(feature = "ml"))]
+fn eye_regions(_app: &AppHandle, _img: &DynamicImage) -> Result<Vec<EyeRegion>, AppError> {
+    Err(AppError::Unsupported(
+        "Automatic red-eye detection needs face detection (ml feature); please select the eyes".to_string(),
+    ))
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "png".to_string());
+            path.with_file_name(format!("{}_redeye.{}", paths::file_stem(path, "image"), ext))
+        }
+    }
+}
+
+// 修复红眼：regions 为空时在检测到的人脸中自动查找；sensitivity 越小，判为红眼的像素越多
+#[tauri::command]
+pub async fn remove_red_eye(
+    app: AppHandle,
+    path: String,
+    regions: Option<Vec<EyeRegion>>,
+    sensitivity: Option<f32>,
+    output: Option<String>,
+) -> Result<RedEyeResult, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    let sensitivity = sensitivity.unwrap_or(DEFAULT_SENSITIVITY);
+    if !(1.0..=5.0).contains(&sensitivity) {
+        return Err(AppError::InvalidArgument(format!(
+            "Sensitivity must be between 1 and 5: {}",
+            sensitivity
+        )));
+    }
+
+    run_blocking("remove_red_eye", move || {
+        let path = Path::new(&path);
+        let img = open_image(path)?;
+        let (automatic, regions) = match regions {
+            Some(regions) => (false, regions),
+            None => (true, eye_regions(&app, &img)?),
+        };
+
+        let mut canvas = img.to_rgba8();
+        let pixels = regions
+            .iter()
+            .map(|region| correct_region(&mut canvas, region, sensitivity, automatic))
+            .sum();
+        let result = if img.color().has_alpha() {
+            DynamicImage::ImageRgba8(canvas)
+        } else {
+            DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(canvas).to_rgb8())
+        };
+
+        let output = output_path(path, output);
+        save_image(&result, &output)?;
+        Ok(RedEyeResult {
+            output: paths::display(&output),
+            regions,
+            pixels,
+        })
+    })
+    .await
+}
@@ -0,0 +1,65 @@
+// 资源占用：进程内存、解码图片缓存和缩略图缓存的大小，用于排查低内存机器上的问题
+use std::path::Path;
+
+use serde::Serialize;
+use sysinfo::{MemoryRefreshKind, Pid, ProcessRefreshKind, RefreshKind, System};
+use tauri::{AppHandle, Manager};
+use walkdir::WalkDir;
+
+use crate::cache::{self, CacheUsage};
+use crate::error::AppError;
+use crate::run_blocking;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+    // 进程常驻内存，字节
+    pub process_memory: u64,
+    // 进程虚拟内存，字节
+    pub virtual_memory: u64,
+    pub system_total_memory: u64,
+    pub system_available_memory: u64,
+    pub image_cache: CacheUsage,
+    // 缓存目录下 thumbnails 文件夹的总大小，字节
+    pub thumbnail_cache_bytes: u64,
+}
+
+// 目录下所有文件的总大小，目录不存在时为 0
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[tauri::command]
+pub async fn get_resource_usage(app: AppHandle) -> Result<ResourceUsage, AppError> {
+    let thumbnails = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve cache directory: {}", e)))?
+        .join("thumbnails");
+
+    run_blocking("get_resource_usage", move || {
+        let mut system = System::new_with_specifics(RefreshKind::new().with_memory(MemoryRefreshKind::everything()));
+        let pid = Pid::from_u32(std::process::id());
+        system.refresh_process_specifics(pid, ProcessRefreshKind::new().with_memory());
+        let (process_memory, virtual_memory) = system
+            .process(pid)
+            .map(|process| (process.memory(), process.virtual_memory()))
+            .unwrap_or_default();
+
+        Ok(ResourceUsage {
+            process_memory,
+            virtual_memory,
+            system_total_memory: system.total_memory(),
+            system_available_memory: system.available_memory(),
+            image_cache: cache::usage(),
+            thumbnail_cache_bytes: dir_size(&thumbnails),
+        })
+    })
+    .await
+}
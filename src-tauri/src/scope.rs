@@ -0,0 +1,275 @@
+// 路径访问范围：命令只能读写用户通过对话框、拖放或文件关联授权过的路径
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use parking_lot::RwLock;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::error::AppError;
+use crate::paths;
+
+lazy_static::lazy_static! {
+    // 已授权的根路径（规范化后），可以是目录或单个文件
+    static ref ROOTS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+    // 持久化授权列表的文件，init 之前为 None
+    static ref SCOPE_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+    // 应用自己的数据和缓存目录（授权列表、设置、插件、模型等），即使位于已授权的目录下也不允许访问
+    static ref PROTECTED: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+    // 应用目录下允许前端读写的输出目录
+    static ref OUTPUT_DIRS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+}
+
+// 截图和摄像头拍照的默认保存目录
+const CAPTURES_DIR: &str = "captures";
+
+// 规范化路径；目标不存在时规范化最近的已存在上级目录，再拼接剩余部分
+pub(crate) fn resolve(path: &Path) -> Result<PathBuf, AppError> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return Err(AppError::NotFound(path.display().to_string())),
+        }
+    }
+
+    let mut resolved = fs::canonicalize(existing).map_err(|e| AppError::from(e).at(existing))?;
+    for name in rest.into_iter().rev() {
+        resolved.push(name);
+    }
+    Ok(resolved)
+}
+
+fn save(roots: &[PathBuf]) {
+    if let Some(file) = SCOPE_FILE.read().as_ref() {
+        let roots: Vec<String> = roots.iter().map(|r| r.to_string_lossy().to_string()).collect();
+        if let Ok(content) = serde_json::to_string_pretty(&roots) {
+            let _ = fs::write(file, content);
+        }
+    }
+}
+
+// 校验命令收到的路径：必须是绝对路径、不含 ..，且位于已授权的范围内
+pub(crate) fn check(path: impl AsRef<Path>) -> Result<PathBuf, AppError> {
+    let path = path.as_ref();
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(AppError::PermissionDenied(format!(
+            "Path must be absolute without '..': {}",
+            path.display()
+        )));
+    }
+
+    let resolved = resolve(path)?;
+    if OUTPUT_DIRS.read().iter().any(|dir| resolved.starts_with(dir)) {
+        return Ok(resolved);
+    }
+    if is_protected(&resolved) {
+        return Err(AppError::PermissionDenied(format!(
+            "Path is inside the application data folder: {}",
+            path.display()
+        )));
+    }
+    if ROOTS.read().iter().any(|root| resolved.starts_with(root)) {
+        Ok(resolved)
+    } else {
+        Err(AppError::PermissionDenied(format!(
+            "Path is outside the allowed folders: {}",
+            path.display()
+        )))
+    }
+}
+
+fn is_protected(resolved: &Path) -> bool {
+    PROTECTED.read().iter().any(|dir| resolved.starts_with(dir))
+}
+
+// 授权访问路径（目录时包含其下所有文件）；应用目录下的路径不授权
+pub(crate) fn allow(path: impl AsRef<Path>) {
+    let Ok(resolved) = resolve(path.as_ref()) else {
+        return;
+    };
+    if is_protected(&resolved) {
+        return;
+    }
+    let mut roots = ROOTS.write();
+    if roots.iter().any(|root| resolved.starts_with(root)) {
+        return;
+    }
+    // 新的根包含了已有的根时，去掉被包含的部分
+    roots.retain(|root| !root.starts_with(&resolved));
+    roots.push(resolved);
+    save(&roots);
+}
+
+// 截图等输出文件的目录，位于应用缓存目录下，前端可以读写
+pub(crate) fn captures_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve cache directory: {}", e)))?
+        .join(CAPTURES_DIR);
+    fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+    Ok(dir)
+}
+
+// 启动时加载已保存的授权；应用的数据和缓存目录不在授权范围内，只开放其中的输出目录
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?;
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve cache directory: {}", e)))?;
+    fs::create_dir_all(&data_dir).map_err(AppError::from)?;
+    fs::create_dir_all(&cache_dir).map_err(AppError::from)?;
+    *PROTECTED.write() = vec![resolve(&data_dir)?, resolve(&cache_dir)?];
+    *OUTPUT_DIRS.write() = vec![resolve(&captures_dir(app)?)?];
+
+    let file = data_dir.join("scope.json");
+    let saved: Vec<String> = fs::read_to_string(&file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    *SCOPE_FILE.write() = Some(file);
+
+    // 旧版本授权过应用目录，加载时去掉
+    for root in saved {
+        allow(root);
+    }
+    save(&ROOTS.read());
+    Ok(())
+}
+
+// 通过系统对话框选择图片，选中的文件自动授权
+#[tauri::command]
+pub async fn pick_image_file(app: AppHandle, title: Option<String>) -> Option<String> {
+    let mut dialog = app.dialog().file().add_filter("Images", &["jpg", "jpeg", "png", "gif", "bmp"]);
+    if let Some(title) = title {
+        dialog = dialog.set_title(title);
+    }
+    let path = dialog.blocking_pick_file()?.as_path()?.to_path_buf();
+    allow(&path);
+    Some(path.to_string_lossy().to_string())
+}
+
+// 通过系统对话框选择文件夹，选中的文件夹自动授权
+#[tauri::command]
+pub async fn pick_directory(app: AppHandle, title: Option<String>) -> Option<String> {
+    let mut dialog = app.dialog().file();
+    if let Some(title) = title {
+        dialog = dialog.set_title(title);
+    }
+    let path = dialog.blocking_pick_folder()?.as_path()?.to_path_buf();
+    allow(&path);
+    Some(path.to_string_lossy().to_string())
+}
+
+// 通过系统保存对话框选择输出路径，选中的路径自动授权
+#[tauri::command]
+pub async fn pick_save_path(app: AppHandle, default_name: Option<String>, title: Option<String>) -> Option<String> {
+    let mut dialog = app
+        .dialog()
+        .file()
+        .add_filter("PNG", &["png"])
+        .add_filter("JPEG", &["jpg", "jpeg"])
+        .add_filter("ICO", &["ico"])
+        .add_filter("All Images", &["jpg", "jpeg", "png", "gif", "bmp", "ico"]);
+    if let Some(name) = default_name {
+        dialog = dialog.set_file_name(name);
+    }
+    if let Some(title) = title {
+        dialog = dialog.set_title(title);
+    }
+    let path = dialog.blocking_save_file()?.as_path()?.to_path_buf();
+    allow(&path);
+    Some(path.to_string_lossy().to_string())
+}
+
+// 列出已授权的路径
+#[tauri::command]
+pub fn list_allowed_paths() -> Vec<String> {
+    ROOTS.read().iter().map(|r| paths::display(r)).collect()
+}
+
+// 撤销对某个路径的授权
+#[tauri::command]
+pub fn revoke_path(path: &str) -> bool {
+    let Ok(resolved) = resolve(Path::new(path)) else {
+        return false;
+    };
+    let mut roots = ROOTS.write();
+    let before = roots.len();
+    roots.retain(|root| *root != resolved);
+    let removed = roots.len() != before;
+    if removed {
+        save(&roots);
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 授权列表是全局的，每个测试使用各自的目录，互不影响
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("scope-test-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        resolve(&dir).unwrap()
+    }
+
+    #[test]
+    fn check_rejects_relative_and_parent_paths() {
+        let dir = test_dir("relative");
+        allow(&dir);
+        assert!(matches!(check("photo.jpg"), Err(AppError::PermissionDenied(_))));
+        assert!(matches!(check(dir.join("..").join("photo.jpg")), Err(AppError::PermissionDenied(_))));
+        assert!(matches!(check(dir.join("sub/../photo.jpg")), Err(AppError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn check_allows_paths_under_root() {
+        let dir = test_dir("allowed");
+        fs::write(dir.join("photo.jpg"), b"").unwrap();
+        allow(&dir);
+        assert_eq!(check(dir.join("photo.jpg")).unwrap(), dir.join("photo.jpg"));
+        // 尚不存在的输出文件按上级目录判断
+        assert_eq!(check(dir.join("new/out.png")).unwrap(), dir.join("new/out.png"));
+    }
+
+    #[test]
+    fn check_denies_paths_outside_roots() {
+        let allowed = test_dir("inside");
+        let other = test_dir("outside");
+        fs::write(allowed.join("photo.jpg"), b"").unwrap();
+        allow(allowed.join("photo.jpg"));
+        assert!(matches!(check(other.join("photo.jpg")), Err(AppError::PermissionDenied(_))));
+        // 授权单个文件不包括同目录的其他文件
+        assert!(check(allowed.join("photo.jpg")).is_ok());
+        assert!(matches!(check(allowed.join("other.jpg")), Err(AppError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn check_denies_protected_except_output_dirs() {
+        let dir = test_dir("protected");
+        let data = dir.join("data");
+        let output = data.join("captures");
+        fs::create_dir_all(&output).unwrap();
+        PROTECTED.write().push(data.clone());
+        OUTPUT_DIRS.write().push(output.clone());
+        allow(&dir);
+
+        assert!(check(dir.join("photo.jpg")).is_ok());
+        assert!(matches!(check(data.join("scope.json")), Err(AppError::PermissionDenied(_))));
+        assert!(check(output.join("shot.png")).is_ok());
+        // 应用目录本身不能被授权
+        allow(&data);
+        assert!(matches!(check(data.join("settings.json")), Err(AppError::PermissionDenied(_))));
+    }
+}
@@ -0,0 +1,248 @@
+// 脚本化处理流程：使用 Rhai 脚本对文件夹中的每张图片执行带条件的多步操作
+//
+// 脚本中可用的变量：img（当前图片）、path（原文件路径）、name（文件名）
+// 例如：
+//   if img.width > 4000 { img.resize(4000, 4000); }
+//   img.grayscale();
+//   img.export("webp");
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use rhai::{Engine, EvalAltResult, Scope, AST};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::listing::{list_files, report_symlinks, SymlinkPolicy};
+use crate::paths;
+use crate::open_image;
+use crate::prepare_for_format;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::save_image_with_format;
+
+// 单张图片允许执行的最大脚本操作数，防止死循环
+const MAX_OPERATIONS: u64 = 1_000_000;
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+
+// 脚本中的图片对象
+#[derive(Clone)]
+struct ScriptImage {
+    image: DynamicImage,
+    stem: String,
+    output_dir: PathBuf,
+    outputs: Rc<RefCell<Vec<String>>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ScriptFileResult {
+    pub path: String,
+    pub outputs: Vec<String>,
+    pub error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ScriptProgress {
+    pub path: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+fn to_u32(value: i64, name: &str) -> Result<u32, Box<EvalAltResult>> {
+    u32::try_from(value).map_err(|_| format!("Invalid {}: {}", name, value).into())
+}
+
+// 把图片保存为指定格式，输出到 output_dir/stem.ext
+fn export(img: &mut ScriptImage, ext: &str) -> Result<String, Box<EvalAltResult>> {
+    let ext = ext.trim_start_matches('.').to_lowercase();
+    let format = ImageFormat::from_extension(&ext).ok_or_else(|| format!("Unsupported output format: {}", ext))?;
+    let output = img.output_dir.join(format!("{}.{}", img.stem, ext));
+
+    let mut processed = prepare_for_format(img.image.clone(), &ext);
+    // JPEG 不支持透明通道
+    if format == ImageFormat::Jpeg {
+        processed = DynamicImage::ImageRgb8(processed.to_rgb8());
+    }
+    save_image_with_format(&processed, &output, format)
+        .map_err(|e| format!("Failed to save image: {}", e))?;
+
+    let output = output.to_string_lossy().to_string();
+    img.outputs.borrow_mut().push(output.clone());
+    Ok(output)
+}
+
+fn build_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    engine
+        .register_type_with_name::<ScriptImage>("Image")
+        .register_get("width", |img: &mut ScriptImage| img.image.width() as i64)
+        .register_get("height", |img: &mut ScriptImage| img.image.height() as i64)
+        .register_get("has_alpha", |img: &mut ScriptImage| img.image.color().has_alpha())
+        .register_get("name", |img: &mut ScriptImage| img.stem.clone())
+        // 保持宽高比缩放到不超过给定尺寸
+        .register_fn("resize", |img: &mut ScriptImage, width: i64, height: i64| {
+            img.image = img
+                .image
+                .resize(to_u32(width, "width")?, to_u32(height, "height")?, FilterType::Triangle);
+            Ok::<(), Box<EvalAltResult>>(())
+        })
+        .register_fn("resize_exact", |img: &mut ScriptImage, width: i64, height: i64| {
+            img.image = img
+                .image
+                .resize_exact(to_u32(width, "width")?, to_u32(height, "height")?, FilterType::Triangle);
+            Ok::<(), Box<EvalAltResult>>(())
+        })
+        .register_fn("crop", |img: &mut ScriptImage, x: i64, y: i64, width: i64, height: i64| {
+            img.image = img.image.crop_imm(
+                to_u32(x, "x")?,
+                to_u32(y, "y")?,
+                to_u32(width, "width")?,
+                to_u32(height, "height")?,
+            );
+            Ok::<(), Box<EvalAltResult>>(())
+        })
+        .register_fn("grayscale", |img: &mut ScriptImage| img.image = img.image.grayscale())
+        .register_fn("invert", |img: &mut ScriptImage| img.image.invert())
+        .register_fn("blur", |img: &mut ScriptImage, sigma: f64| img.image = img.image.blur(sigma as f32))
+        .register_fn("rotate90", |img: &mut ScriptImage| img.image = img.image.rotate90())
+        .register_fn("rotate180", |img: &mut ScriptImage| img.image = img.image.rotate180())
+        .register_fn("rotate270", |img: &mut ScriptImage| img.image = img.image.rotate270())
+        .register_fn("flip_horizontal", |img: &mut ScriptImage| img.image = img.image.fliph())
+        .register_fn("flip_vertical", |img: &mut ScriptImage| img.image = img.image.flipv())
+        .register_fn("brighten", |img: &mut ScriptImage, value: i64| {
+            img.image = img.image.brighten(value.clamp(-255, 255) as i32)
+        })
+        .register_fn("contrast", |img: &mut ScriptImage, value: f64| {
+            img.image = img.image.adjust_contrast(value as f32)
+        })
+        .register_fn("export", |img: &mut ScriptImage, ext: &str| export(img, ext));
+
+    engine
+}
+
+fn compile(engine: &Engine, script: &str) -> Result<AST, AppError> {
+    engine
+        .compile(script)
+        .map_err(|e| AppError::ProcessingFailed(format!("Script error: {}", e)))
+}
+
+// 处理单个文件；Engine 和 AST 不能跨线程共享，每个文件在自己的阻塞线程中重新编译脚本
+fn run_file(script: &str, path: &Path, output_dir: &Path) -> Result<Vec<String>, AppError> {
+    let engine = build_engine();
+    let ast = compile(&engine, script)?;
+    let image = Arc::unwrap_or_clone(open_image(path)?);
+
+    let outputs = Rc::new(RefCell::new(Vec::new()));
+    let script_image = ScriptImage {
+        image,
+        stem: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        output_dir: output_dir.to_path_buf(),
+        outputs: outputs.clone(),
+    };
+
+    let mut scope = Scope::new();
+    scope.push("img", script_image);
+    scope.push_constant("path", path.to_string_lossy().to_string());
+    scope.push_constant(
+        "name",
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+
+    engine
+        .run_ast_with_scope(&mut scope, &ast)
+        .map_err(|e| AppError::ProcessingFailed(format!("Script error: {}", e)))?;
+
+    let outputs = outputs.borrow().clone();
+    Ok(outputs)
+}
+
+// 检查脚本语法，返回错误信息
+#[tauri::command]
+pub fn validate_script(script: &str) -> Result<bool, AppError> {
+    compile(&build_engine(), script)?;
+    Ok(true)
+}
+
+// 对文件夹中的所有图片运行脚本，单个文件失败不会中断整个流程
+#[tauri::command]
+pub async fn run_script(
+    app: AppHandle,
+    script: String,
+    path: String,
+    output_dir: String,
+    symlinks: Option<SymlinkPolicy>,
+) -> Result<Vec<ScriptFileResult>, AppError> {
+    scope::check(&path)?;
+    scope::check(&output_dir)?;
+
+    fs::create_dir_all(&output_dir).map_err(AppError::from)?;
+
+    // 脚本有语法错误时不处理任何文件
+    validate_script(&script)?;
+
+    let files = run_blocking("run_script", {
+        let app = app.clone();
+        move || {
+            let listing = list_files(Path::new(&path), symlinks.unwrap_or_default(), false)?;
+            report_symlinks(&app, Path::new(&path), &listing);
+            Ok(listing
+                .files
+                .into_iter()
+                .filter(|p| {
+                    p.extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                        .unwrap_or(false)
+                })
+                .collect::<Vec<PathBuf>>())
+        }
+    })
+    .await?;
+
+    // 每个文件单独在阻塞线程中处理，超时只影响当前文件，批次的总耗时不受限制
+    let total = files.len();
+    let output_dir = PathBuf::from(&output_dir);
+    let mut results = Vec::with_capacity(total);
+    for (index, file) in files.into_iter().enumerate() {
+        let outcome = run_blocking("run_script", {
+            let script = script.clone();
+            let file = file.clone();
+            let output_dir = output_dir.clone();
+            move || run_file(&script, &file, &output_dir)
+        })
+        .await;
+        let (outputs, error) = match outcome {
+            Ok(outputs) => (outputs, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        let file = paths::display(&file);
+        results.push(ScriptFileResult {
+            path: file.clone(),
+            outputs,
+            error,
+        });
+
+        let _ = app.emit(
+            "script-progress",
+            ScriptProgress {
+                path: file,
+                done: index + 1,
+                total,
+            },
+        );
+    }
+
+    Ok(results)
+}
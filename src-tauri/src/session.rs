@@ -0,0 +1,382 @@
+// 共享编辑会话：同一文件在多个窗口（例如浏览窗口和编辑窗口、双显示器）中打开时共用一份已解码的图片
+// 任一窗口修改会话后通过 session-changed 事件通知其他窗口，其他窗口不需要重新解码文件
+// 操作只修改内存中的图片，保存时才写入文件；保存前可以撤销、重做
+use std::collections::{BTreeSet, HashMap};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use crate::error::AppError;
+use crate::history::{self, EditOperation};
+use crate::storage::save_image_from;
+use crate::{autosave, open_image, paths, run_blocking, scope};
+
+// 每个会话为撤销保留的中间结果的总大小，超出时丢弃最早的，撤销到那里时从头重新计算
+const UNDO_MEMORY: usize = 512 * 1024 * 1024;
+
+struct Session {
+    path: PathBuf,
+    // 打开（或上次保存）时的图片
+    base: Arc<DynamicImage>,
+    image: Arc<DynamicImage>,
+    // 打开以来应用的操作，保存时写入编辑历史
+    operations: Vec<EditOperation>,
+    // stages[i] 为应用前 i + 1 个操作后的结果，None 表示已丢弃
+    stages: Vec<Option<Arc<DynamicImage>>>,
+    // 撤销的操作，最近撤销的在最后；应用新操作时清空
+    undone: Vec<EditOperation>,
+    // 每次修改加一，窗口据此判断自己的副本是否过期
+    version: u64,
+    // 引用该会话的窗口标签
+    windows: BTreeSet<String>,
+}
+
+impl Session {
+    fn info(&self, id: &str) -> SessionInfo {
+        let (width, height) = self.image.dimensions();
+        SessionInfo {
+            id: id.to_string(),
+            path: paths::display(&self.path),
+            width,
+            height,
+            version: self.version,
+            operations: self.operations.clone(),
+            undone: self.undone.iter().rev().cloned().collect(),
+            windows: self.windows.iter().cloned().collect(),
+        }
+    }
+
+    // 应用操作后记录结果，超出内存限制时丢弃最早的中间结果（当前结果始终保留）
+    fn push(&mut self, operation: EditOperation, image: DynamicImage) {
+        let image = Arc::new(image);
+        self.image = image.clone();
+        self.operations.push(operation);
+        self.stages.push(Some(image));
+        let mut total: usize = self.stages.iter().flatten().map(|s| s.as_bytes().len()).sum();
+        let keep = self.stages.len() - 1;
+        for stage in &mut self.stages[..keep] {
+            if total <= UNDO_MEMORY {
+                break;
+            }
+            if let Some(image) = stage.take() {
+                total -= image.as_bytes().len();
+            }
+        }
+        self.version += 1;
+    }
+
+    fn undo(&mut self, app: &AppHandle) -> Result<(), AppError> {
+        let Some(last) = self.operations.len().checked_sub(1) else {
+            return Err(AppError::InvalidArgument("Nothing to undo".to_string()));
+        };
+        // 从最近一个保留的中间结果重新计算被丢弃的部分，失败时会话保持不变
+        let start = self.stages[..last].iter().rposition(|s| s.is_some()).map(|i| i + 1).unwrap_or(0);
+        let mut image = match start {
+            0 => self.base.clone(),
+            i => self.stages[i - 1].clone().unwrap_or_else(|| self.base.clone()),
+        };
+        let mut recomputed = Vec::with_capacity(last - start);
+        for operation in &self.operations[start..last] {
+            image = Arc::new(history::apply(app, (*image).clone(), operation)?);
+            recomputed.push(image.clone());
+        }
+        for (stage, image) in self.stages[start..last].iter_mut().zip(recomputed) {
+            *stage = Some(image);
+        }
+        self.stages.truncate(last);
+        self.undone.extend(self.operations.pop());
+        self.image = image;
+        self.version += 1;
+        Ok(())
+    }
+
+    fn redo(&mut self, app: &AppHandle) -> Result<(), AppError> {
+        let Some(operation) = self.undone.pop() else {
+            return Err(AppError::InvalidArgument("Nothing to redo".to_string()));
+        };
+        match history::apply(app, (*self.image).clone(), &operation) {
+            Ok(image) => {
+                self.push(operation, image);
+                Ok(())
+            }
+            Err(e) => {
+                self.undone.push(operation);
+                Err(e)
+            }
+        }
+    }
+}
+
+// 所有窗口共用的会话，按会话 id 索引；每个会话单独加锁，不同会话的处理可以并行
+#[derive(Default)]
+pub struct SessionState {
+    sessions: Mutex<HashMap<String, Arc<Mutex<Session>>>>,
+}
+
+impl SessionState {
+    fn get(&self, id: &str) -> Result<Arc<Mutex<Session>>, AppError> {
+        self.sessions
+            .lock()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Session {}", id)))
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub id: String,
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub version: u64,
+    pub operations: Vec<EditOperation>,
+    // 可以重做的操作，下一个重做的在最前
+    pub undone: Vec<EditOperation>,
+    pub windows: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionChangeKind {
+    Joined,
+    Released,
+    Changed,
+    Saved,
+}
+
+// 会话变化时向所有窗口发送 session-changed 事件，window 为引起变化的窗口
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEvent {
+    pub kind: SessionChangeKind,
+    pub window: String,
+    pub session: SessionInfo,
+}
+
+// 修改和保存后同时更新自动保存的快照；窗口离开会话不影响未保存的修改
+fn notify(app: &AppHandle, kind: SessionChangeKind, window: &str, session: SessionInfo) {
+    if let SessionChangeKind::Changed | SessionChangeKind::Saved = kind {
+        autosave::snapshot(&session.id, &session.path, &session.operations);
+    }
+    let _ = app.emit(
+        "session-changed",
+        SessionEvent {
+            kind,
+            window: window.to_string(),
+            session,
+        },
+    );
+}
+
+// 用规范化路径作为会话 id，同一文件不论以何种写法传入都打开同一个会话
+fn session_id(path: &Path) -> String {
+    paths::display(path)
+}
+
+// 打开文件的会话；其他窗口已经打开时直接加入，不再解码
+#[tauri::command]
+pub async fn open_session(app: AppHandle, window: Window, path: String) -> Result<SessionInfo, AppError> {
+    let resolved = scope::check(&path)?;
+    let id = session_id(&resolved);
+    let label = window.label().to_string();
+
+    let existing = app.state::<SessionState>().sessions.lock().get(&id).cloned();
+    let session = match existing {
+        Some(session) => session,
+        None => {
+            let image = run_blocking("open_session", move || open_image(&path)).await?;
+            let state = app.state::<SessionState>();
+            let mut sessions = state.sessions.lock();
+            // 解码期间其他窗口可能已经打开了同一文件
+            let session = sessions
+                .entry(id.clone())
+                .or_insert_with(|| {
+                    Arc::new(Mutex::new(Session {
+                        path: resolved,
+                        base: image.clone(),
+                        image,
+                        operations: Vec::new(),
+                        stages: Vec::new(),
+                        undone: Vec::new(),
+                        version: 0,
+                        windows: BTreeSet::new(),
+                    }))
+                })
+                .clone();
+            session
+        }
+    };
+
+    let info = {
+        let mut session = session.lock();
+        session.windows.insert(label.clone());
+        session.info(&id)
+    };
+    notify(&app, SessionChangeKind::Joined, &label, info.clone());
+    Ok(info)
+}
+
+// 窗口离开会话；没有窗口引用时释放会话
+fn release(app: &AppHandle, label: &str, id: &str) -> bool {
+    let state = app.state::<SessionState>();
+    let mut sessions = state.sessions.lock();
+    let Some(session) = sessions.get(id).cloned() else {
+        return false;
+    };
+    let info = {
+        let mut session = session.lock();
+        if !session.windows.remove(label) {
+            return false;
+        }
+        session.info(id)
+    };
+    if info.windows.is_empty() {
+        sessions.remove(id);
+    }
+    drop(sessions);
+    notify(app, SessionChangeKind::Released, label, info);
+    true
+}
+
+// 窗口关闭时调用，离开该窗口引用的所有会话
+pub(crate) fn release_window(app: &AppHandle, label: &str) {
+    let ids: Vec<String> = app.state::<SessionState>().sessions.lock().keys().cloned().collect();
+    for id in ids {
+        release(app, label, &id);
+    }
+}
+
+#[tauri::command]
+pub fn release_session(app: AppHandle, window: Window, id: &str) -> bool {
+    release(&app, window.label(), id)
+}
+
+#[tauri::command]
+pub fn list_sessions(state: State<'_, SessionState>) -> Vec<SessionInfo> {
+    let sessions: Vec<(String, Arc<Mutex<Session>>)> = state
+        .sessions
+        .lock()
+        .iter()
+        .map(|(id, session)| (id.clone(), session.clone()))
+        .collect();
+    sessions.iter().map(|(id, session)| session.lock().info(id)).collect()
+}
+
+// 对会话中的图片应用操作（只修改内存中的图片），并通知其他窗口
+#[tauri::command]
+pub async fn apply_session_operation(
+    app: AppHandle,
+    window: Window,
+    id: String,
+    operation: EditOperation,
+) -> Result<SessionInfo, AppError> {
+    let session = app.state::<SessionState>().get(&id)?;
+    let label = window.label().to_string();
+
+    let handle = app.clone();
+    let info = run_blocking("apply_session_operation", move || {
+        // 持有会话锁直到处理完成，多个窗口同时修改时按顺序执行
+        let mut session = session.lock();
+        let image = history::apply(&handle, (*session.image).clone(), &operation)?;
+        session.push(operation, image);
+        session.undone.clear();
+        Ok(session.info(&id))
+    })
+    .await?;
+    notify(&app, SessionChangeKind::Changed, &label, info.clone());
+    Ok(info)
+}
+
+// 撤销会话中最近一个操作，并通知其他窗口
+#[tauri::command]
+pub async fn undo_session(app: AppHandle, window: Window, id: String) -> Result<SessionInfo, AppError> {
+    let session = app.state::<SessionState>().get(&id)?;
+    let label = window.label().to_string();
+
+    let handle = app.clone();
+    let info = run_blocking("undo_session", move || {
+        let mut session = session.lock();
+        session.undo(&handle)?;
+        Ok(session.info(&id))
+    })
+    .await?;
+    notify(&app, SessionChangeKind::Changed, &label, info.clone());
+    Ok(info)
+}
+
+// 重做最近撤销的操作，并通知其他窗口
+#[tauri::command]
+pub async fn redo_session(app: AppHandle, window: Window, id: String) -> Result<SessionInfo, AppError> {
+    let session = app.state::<SessionState>().get(&id)?;
+    let label = window.label().to_string();
+
+    let handle = app.clone();
+    let info = run_blocking("redo_session", move || {
+        let mut session = session.lock();
+        session.redo(&handle)?;
+        Ok(session.info(&id))
+    })
+    .await?;
+    notify(&app, SessionChangeKind::Changed, &label, info.clone());
+    Ok(info)
+}
+
+// 以 PNG 原始字节返回会话中的当前图片，供其他窗口显示
+#[tauri::command]
+pub async fn get_session_image(app: AppHandle, id: String) -> Result<Response, AppError> {
+    let session = app.state::<SessionState>().get(&id)?;
+    let image = session.lock().image.clone();
+
+    let png = run_blocking("get_session_image", move || {
+        let mut buffer = Cursor::new(Vec::new());
+        image.write_to(&mut buffer, ImageFormat::Png)?;
+        Ok(buffer.into_inner())
+    })
+    .await?;
+    Ok(Response::new(png))
+}
+
+// 把会话中的图片写入 output（为空时覆盖原文件），并记录编辑历史；保存后不能再撤销之前的操作
+#[tauri::command]
+pub async fn save_session(
+    app: AppHandle,
+    window: Window,
+    id: String,
+    output: Option<String>,
+) -> Result<SessionInfo, AppError> {
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    let session = app.state::<SessionState>().get(&id)?;
+    let label = window.label().to_string();
+
+    let info = run_blocking("save_session", move || {
+        let mut session = session.lock();
+        let output = output.map(PathBuf::from).unwrap_or_else(|| session.path.clone());
+        save_image_from(&session.image, &session.path, &output)?;
+        history::record_all(&session.path, &output, session.operations.clone())?;
+        session.base = session.image.clone();
+        session.operations.clear();
+        session.stages.clear();
+        session.undone.clear();
+        Ok(session.info(&id))
+    })
+    .await?;
+    notify(&app, SessionChangeKind::Saved, &label, info.clone());
+    Ok(info)
+}
+
+// 会话中的当前图片，id 不是打开的会话时为 None
+pub(crate) fn current_image(app: &AppHandle, id: &str) -> Option<Arc<DynamicImage>> {
+    let session = app.state::<SessionState>().get(id).ok()?;
+    let image = session.lock().image.clone();
+    Some(image)
+}
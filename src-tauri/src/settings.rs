@@ -0,0 +1,170 @@
+// 设置：持久化到应用数据目录的 settings.json，代替各模块中写死的默认值
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::ImageFormat;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::i18n;
+use crate::scope;
+use crate::storage::write_app_file;
+
+lazy_static::lazy_static! {
+    static ref SETTINGS: RwLock<Settings> = RwLock::new(Settings::default());
+    // 设置文件，init 之前为 None（此时修改只保存在内存中）
+    static ref SETTINGS_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    // 没有指定输出格式时使用的格式（扩展名）
+    pub export_format: String,
+    // JPEG 编码质量，1-100
+    pub export_quality: u8,
+    // 缩略图的最长边，像素
+    pub thumbnail_size: u32,
+    // 原图备份的保留天数
+    pub backup_retention_days: u64,
+    // 原图备份的总大小上限，MB
+    pub backup_max_mb: u64,
+    // 解码图片缓存的内存上限，MB
+    pub cache_max_mb: u64,
+    // 图库根目录
+    pub library_roots: Vec<String>,
+    // 界面语言，决定错误说明使用的语言
+    pub language: String,
+    // ffmpeg 可执行文件，为空时在 PATH 中查找；只能通过 pick_ffmpeg 的系统对话框设置
+    pub ffmpeg_path: Option<String>,
+    // 保存编辑结果（JPEG/PNG/WebP）时写入原图的 EXIF；关闭时去掉元数据
+    pub keep_metadata: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            export_format: "png".to_string(),
+            export_quality: 90,
+            thumbnail_size: 256,
+            backup_retention_days: 30,
+            backup_max_mb: 2048,
+            cache_max_mb: 512,
+            library_roots: Vec::new(),
+            language: "en".to_string(),
+            ffmpeg_path: None,
+            keep_metadata: true,
+        }
+    }
+}
+
+impl Settings {
+    // 检查各项取值；library_roots 必须是已授权的目录
+    fn validate(&self) -> Result<(), AppError> {
+        let format = ImageFormat::from_extension(&self.export_format)
+            .ok_or_else(|| AppError::InvalidArgument(format!("Unsupported export format: {}", self.export_format)))?;
+        if !format.writing_enabled() {
+            return Err(AppError::InvalidArgument(format!(
+                "Export format is read-only: {}",
+                self.export_format
+            )));
+        }
+        if !(1..=100).contains(&self.export_quality) {
+            return Err(AppError::InvalidArgument(format!(
+                "Export quality must be between 1 and 100: {}",
+                self.export_quality
+            )));
+        }
+        if !(32..=4096).contains(&self.thumbnail_size) {
+            return Err(AppError::InvalidArgument(format!(
+                "Thumbnail size must be between 32 and 4096: {}",
+                self.thumbnail_size
+            )));
+        }
+        if self.backup_retention_days == 0 {
+            return Err(AppError::InvalidArgument("Backup retention must be at least one day".to_string()));
+        }
+        if i18n::normalize(&self.language).is_none() {
+            return Err(AppError::InvalidArgument(format!(
+                "Unsupported language: {} (supported: {})",
+                self.language,
+                i18n::SUPPORTED_LOCALES.join(", ")
+            )));
+        }
+        if let Some(ffmpeg) = &self.ffmpeg_path {
+            if !Path::new(ffmpeg).is_file() {
+                return Err(AppError::InvalidArgument(format!("ffmpeg not found: {}", ffmpeg)));
+            }
+        }
+        for root in &self.library_roots {
+            scope::check(root)?;
+            if !Path::new(root).is_dir() {
+                return Err(AppError::InvalidArgument(format!("Library root is not a directory: {}", root)));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 当前设置的副本
+pub(crate) fn current() -> Settings {
+    SETTINGS.read().clone()
+}
+
+// 启动时读取设置文件；文件损坏或取值无效时使用默认设置
+pub fn init(app: &AppHandle) -> Result<(), AppError> {
+    let file = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve data directory: {}", e)))?
+        .join("settings.json");
+
+    let saved: Option<Settings> = fs::read_to_string(&file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    if let Some(saved) = saved {
+        match saved.validate() {
+            Ok(()) => *SETTINGS.write() = saved,
+            Err(e) => tracing::warn!(error = %e, "ignoring invalid settings"),
+        }
+    }
+    *SETTINGS_FILE.write() = Some(file);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings() -> Settings {
+    current()
+}
+
+fn store(settings: Settings) -> Result<Settings, AppError> {
+    settings.validate()?;
+
+    if let Some(file) = SETTINGS_FILE.read().as_ref() {
+        write_app_file(file, &serde_json::to_vec_pretty(&settings)?)?;
+    }
+    *SETTINGS.write() = settings.clone();
+    Ok(settings)
+}
+
+// 保存用户在对话框中确认过的 ffmpeg
+pub(crate) fn set_ffmpeg_path(path: Option<String>) -> Result<Settings, AppError> {
+    store(Settings {
+        ffmpeg_path: path,
+        ..current()
+    })
+}
+
+// 校验并保存设置，返回保存后的设置
+// ffmpeg_path 会被执行，前端只能清除，不能改为其他路径
+#[tauri::command]
+pub fn set_settings(settings: Settings) -> Result<Settings, AppError> {
+    if settings.ffmpeg_path.is_some() && settings.ffmpeg_path != current().ffmpeg_path {
+        return Err(AppError::PermissionDenied(
+            "The ffmpeg path can only be chosen through pick_ffmpeg".to_string(),
+        ));
+    }
+    store(settings)
+}
@@ -0,0 +1,101 @@
+// 分享：在 iOS/Android 上调用系统分享面板，桌面端退化为在文件管理器中显示
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::{AppHandle, Runtime};
+
+#[cfg(mobile)]
+use tauri::plugin::PluginHandle;
+#[cfg(mobile)]
+use tauri::Manager;
+#[cfg(desktop)]
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::AppError;
+use crate::scope;
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_share);
+
+// 原生分享插件的句柄
+#[cfg(mobile)]
+pub struct ShareHandle<R: Runtime>(PluginHandle<R>);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareRequest {
+    path: String,
+    mime_type: String,
+    title: Option<String>,
+}
+
+// 注册原生分享插件（Kotlin/Swift 实现位于 gen 目录下）
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("share")
+        .setup(|_app, _api| {
+            #[cfg(target_os = "android")]
+            let handle = _api.register_android_plugin("com.zhanggf.file_flower", "SharePlugin")?;
+            #[cfg(target_os = "ios")]
+            let handle = _api.register_ios_plugin(init_plugin_share)?;
+            #[cfg(mobile)]
+            _app.manage(ShareHandle(handle));
+            Ok(())
+        })
+        .build()
+}
+
+// 根据扩展名推断 MIME 类型
+fn mime_type(path: &Path) -> String {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        _ => "image/*",
+    }
+    .to_string()
+}
+
+// 将导出的图片交给系统分享面板
+#[tauri::command]
+pub fn share_image<R: Runtime>(app: AppHandle<R>, path: &str, title: Option<String>) -> Result<bool, AppError> {
+    scope::check(path)?;
+
+    let file = Path::new(path);
+    if !file.is_file() {
+        return Err(AppError::NotFound(path.to_string()));
+    }
+
+    let request = ShareRequest {
+        path: path.to_string(),
+        mime_type: mime_type(file),
+        title,
+    };
+
+    #[cfg(mobile)]
+    {
+        app.state::<ShareHandle<R>>()
+            .0
+            .run_mobile_plugin::<()>("shareFile", request)
+            .map_err(|e| AppError::Internal(format!("Failed to share image: {}", e)))?;
+    }
+
+    // 桌面端没有统一的分享面板，在文件管理器中定位文件
+    #[cfg(desktop)]
+    {
+        let _ = request;
+        app.opener()
+            .reveal_item_in_dir(path)
+            .map_err(|e| AppError::Internal(format!("Failed to reveal file: {}", e)))?;
+    }
+
+    Ok(true)
+}
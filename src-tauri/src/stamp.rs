@@ -0,0 +1,263 @@
+// 日期/说明水印：按模板在每张照片的指定角落印上 EXIF 拍摄日期或说明文字（类似相机的“打印日期”功能）
+//
+// 模板中可用的标记：
+//   {date} 2024-05-01   {time} 14:30   {datetime} 2024-05-01 14:30
+//   {year} {month} {day}   {filename} 带扩展名的文件名   {name} 不带扩展名的文件名
+//   {camera} 相机型号
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use exif::{In, Tag};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::storage::save_image;
+use crate::text::{draw_text, load_font, measure, parse_color};
+use crate::{metadata, open_image, paths, scope, settings};
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StampOptions {
+    pub template: String,
+    pub corner: Corner,
+    // 字号，占图片短边的百分比
+    pub font_size: f32,
+    // 与图片边缘的距离，占图片短边的百分比
+    pub margin: f32,
+    pub color: String,
+    // 在文字下方绘制半透明阴影，浅色背景上也能看清
+    pub shadow: bool,
+    pub font: Option<String>,
+    // 没有 EXIF 拍摄时间时使用文件修改时间，否则跳过该图片
+    pub fallback_to_file_time: bool,
+    // 为空时在原图旁边生成 name_stamped.ext
+    pub output_dir: Option<String>,
+}
+
+impl Default for StampOptions {
+    fn default() -> Self {
+        Self {
+            template: "{date}".to_string(),
+            corner: Corner::BottomRight,
+            font_size: 3.0,
+            margin: 2.0,
+            color: "#ff9a1f".to_string(),
+            shadow: true,
+            font: None,
+            fallback_to_file_time: true,
+            output_dir: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StampResult {
+    pub path: String,
+    pub output: Option<String>,
+    pub text: Option<String>,
+    pub error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StampProgress {
+    pub path: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+// 模板中日期相关的部分
+struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+}
+
+// 文件修改时间（UTC）换算为日期
+fn file_time(path: &Path) -> Option<Timestamp> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let seconds = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?.as_secs() as i64;
+    // 由 Unix 天数计算公历日期（Howard Hinnant 的 civil_from_days 算法）
+    let days = seconds.div_euclid(86400);
+    let secs_of_day = seconds.rem_euclid(86400);
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    Some(Timestamp {
+        year: year as u16,
+        month: month as u8,
+        day: day as u8,
+        hour: (secs_of_day / 3600) as u8,
+        minute: (secs_of_day % 3600 / 60) as u8,
+    })
+}
+
+fn render_template(path: &Path, options: &StampOptions) -> Result<String, AppError> {
+    let exif = metadata::read_exif(path);
+    let taken = exif
+        .as_ref()
+        .and_then(metadata::date_taken)
+        .map(|d| Timestamp {
+            year: d.year,
+            month: d.month,
+            day: d.day,
+            hour: d.hour,
+            minute: d.minute,
+        })
+        .or_else(|| options.fallback_to_file_time.then(|| file_time(path)).flatten());
+
+    let uses_date = ["{date}", "{time}", "{datetime}", "{year}", "{month}", "{day}"]
+        .iter()
+        .any(|token| options.template.contains(token));
+    let mut text = options.template.clone();
+    if uses_date {
+        let t = taken.ok_or_else(|| AppError::NotFound("Capture date".to_string()).at(path))?;
+        let date = format!("{:04}-{:02}-{:02}", t.year, t.month, t.day);
+        let time = format!("{:02}:{:02}", t.hour, t.minute);
+        text = text
+            .replace("{datetime}", &format!("{} {}", date, time))
+            .replace("{date}", &date)
+            .replace("{time}", &time)
+            .replace("{year}", &format!("{:04}", t.year))
+            .replace("{month}", &format!("{:02}", t.month))
+            .replace("{day}", &format!("{:02}", t.day));
+    }
+    let camera = exif
+        .as_ref()
+        .and_then(|exif| exif.get_field(Tag::Model, In::PRIMARY))
+        .map(|field| field.display_value().to_string().trim_matches('"').trim().to_string())
+        .unwrap_or_default();
+    Ok(text
+        .replace("{filename}", &paths::file_name(path))
+        .replace("{name}", &paths::file_stem(path, "image"))
+        .replace("{camera}", &camera))
+}
+
+fn output_path(path: &Path, output_dir: Option<&str>) -> PathBuf {
+    match output_dir {
+        Some(dir) => Path::new(dir).join(path.file_name().unwrap_or_default()),
+        None => {
+            let stem = paths::file_stem(path, "image");
+            let ext = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| settings::current().export_format);
+            path.with_file_name(format!("{}_stamped.{}", stem, ext))
+        }
+    }
+}
+
+fn stamp_file(path: &str, options: &StampOptions) -> Result<(String, String), AppError> {
+    let path = Path::new(path);
+    let text = render_template(path, options)?;
+    let img = open_image(path)?;
+    let font = load_font(options.font.as_deref())?;
+    let color = parse_color(&options.color)?;
+
+    let mut canvas = img.to_rgba8();
+    let (width, height) = canvas.dimensions();
+    let short_side = width.min(height) as f32;
+    let size = (short_side * options.font_size / 100.0).max(8.0);
+    let margin = (short_side * options.margin / 100.0) as i32;
+    let (text_width, text_height) = measure(&font, size, &text);
+
+    let x = match options.corner {
+        Corner::TopLeft | Corner::BottomLeft => margin,
+        Corner::TopRight | Corner::BottomRight => width as i32 - margin - text_width as i32,
+    };
+    let y = match options.corner {
+        Corner::TopLeft | Corner::TopRight => margin,
+        Corner::BottomLeft | Corner::BottomRight => height as i32 - margin - text_height as i32,
+    };
+    if options.shadow {
+        let offset = ((size / 16.0).round() as i32).max(1);
+        let shadow = image::Rgba([0, 0, 0, 160]);
+        draw_text(&mut canvas, &font, size, x + offset, y + offset, &text, shadow);
+    }
+    draw_text(&mut canvas, &font, size, x, y, &text, color);
+
+    let result = if img.color().has_alpha() {
+        DynamicImage::ImageRgba8(canvas)
+    } else {
+        DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(canvas).to_rgb8())
+    };
+    let output = output_path(path, options.output_dir.as_deref());
+    save_image(&result, &output)?;
+    Ok((paths::display(&output), text))
+}
+
+// 批量印日期/说明，单个文件失败（例如没有拍摄时间）不会中断整个批次
+#[tauri::command]
+pub async fn stamp_images(app: AppHandle, paths: Vec<String>, options: StampOptions) -> Result<Vec<StampResult>, AppError> {
+    for path in &paths {
+        scope::check(path)?;
+    }
+    if let Some(dir) = &options.output_dir {
+        scope::check(dir)?;
+        fs::create_dir_all(dir).map_err(|e| AppError::from(e).at(dir))?;
+    }
+    if let Some(font) = &options.font {
+        scope::check(font)?;
+    }
+    if !(options.font_size > 0.0 && options.font_size <= 50.0) {
+        return Err(AppError::InvalidArgument(format!("Font size must be between 0 and 50%: {}", options.font_size)));
+    }
+    parse_color(&options.color)?;
+
+    // 批次耗时与图片数量成正比，不使用单个操作的超时
+    tauri::async_runtime::spawn_blocking(move || {
+        let total = paths.len();
+        let mut results = Vec::with_capacity(total);
+        for (index, path) in paths.into_iter().enumerate() {
+            let result = match stamp_file(&path, &options) {
+                Ok((output, text)) => StampResult {
+                    path: path.clone(),
+                    output: Some(output),
+                    text: Some(text),
+                    error: None,
+                },
+                Err(error) => StampResult {
+                    path: path.clone(),
+                    output: None,
+                    text: None,
+                    error: Some(error),
+                },
+            };
+            results.push(result);
+
+            let _ = app.emit(
+                "stamp-progress",
+                StampProgress {
+                    path,
+                    done: index + 1,
+                    total,
+                },
+            );
+        }
+        results
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Stamp task failed: {}", e)))
+}
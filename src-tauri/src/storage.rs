@@ -0,0 +1,283 @@
+// 文件保存：先写入同目录下的临时文件，成功后再重命名覆盖目标，避免写入中途失败损坏原图
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Cursor, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilter, PngEncoder};
+use image::codecs::webp::{WebPEncoder, WebPQuality};
+use image::{DynamicImage, GenericImageView, ImageEncoder, ImageFormat};
+use serde::Deserialize;
+
+use crate::backup::backup_original;
+use crate::cache;
+use crate::error::AppError;
+use crate::metadata;
+use crate::paths;
+use crate::perf;
+use crate::scope;
+use crate::settings;
+use crate::timeouts;
+
+// AVIF 编码速度（1 最慢、压缩率最高，10 最快）
+const AVIF_SPEED: u8 = 6;
+
+// 编码选项，为空的项使用默认值（质量默认为设置中的导出质量）
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EncodeOptions {
+    // JPEG、WebP、AVIF 的质量，1-100
+    pub quality: Option<u8>,
+    // PNG 压缩级别，0（最快）~9（最小）
+    pub compression: Option<u8>,
+    // WebP 使用无损压缩（忽略 quality）；AVIF 不支持无损，使用质量 100
+    pub lossless: bool,
+}
+
+impl EncodeOptions {
+    pub(crate) fn validate(&self) -> Result<(), AppError> {
+        if let Some(quality) = self.quality {
+            if !(1..=100).contains(&quality) {
+                return Err(AppError::InvalidArgument(format!("Quality must be between 1 and 100: {}", quality)));
+            }
+        }
+        if let Some(compression) = self.compression {
+            if compression > 9 {
+                return Err(AppError::InvalidArgument(format!(
+                    "PNG compression must be between 0 and 9: {}",
+                    compression
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn quality(&self) -> u8 {
+        self.quality.unwrap_or_else(|| settings::current().export_quality)
+    }
+}
+
+// 扩展名对应的可写格式
+pub(crate) fn output_format(ext: &str) -> Result<ImageFormat, AppError> {
+    let format = ImageFormat::from_extension(ext).ok_or_else(|| AppError::Unsupported(format!("Output format {}", ext)))?;
+    if !format.writing_enabled() {
+        return Err(AppError::Unsupported(format!("Writing {} images", ext)));
+    }
+    Ok(format)
+}
+
+lazy_static::lazy_static! {
+    // 每个文件一把写锁，以规范化路径为键；只保存弱引用，没有人持有时自动失效
+    static ref FILE_LOCKS: parking_lot::Mutex<HashMap<PathBuf, Weak<tokio::sync::Mutex<()>>>> =
+        parking_lot::Mutex::new(HashMap::new());
+}
+
+// 获取文件的写锁，同一文件（不论以何种写法传入路径）共用一把锁
+// 异步代码中使用 lock().await，阻塞线程中使用 blocking_lock()
+pub(crate) fn file_lock(path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+    let key = scope::resolve(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut locks = FILE_LOCKS.lock();
+    locks.retain(|_, lock| lock.strong_count() > 0);
+    if let Some(lock) = locks.get(&key).and_then(Weak::upgrade) {
+        return lock;
+    }
+    let lock = Arc::new(tokio::sync::Mutex::new(()));
+    locks.insert(key, Arc::downgrade(&lock));
+    lock
+}
+
+// 临时文件与目标文件放在同一目录，保证重命名是原子操作
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+// 为已存在的文件生成不冲突的文件名：name (1).ext、name (2).ext……
+pub(crate) fn unique_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string());
+
+    (1..)
+        .map(|i| {
+            let name = match &ext {
+                Some(ext) => format!("{} ({}).{}", stem, i, ext),
+                None => format!("{} ({})", stem, i),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+// 通过 write 回调写入临时文件，全部成功后重命名为目标文件；backup 为 true 且目标已存在时先备份原图
+// 整个过程持有该文件的写锁，批处理和手动编辑同时写入同一文件时依次进行
+// 必须在阻塞线程中调用
+fn replace_file<F>(path: &Path, backup: bool, write: F) -> Result<(), AppError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), AppError>,
+{
+    let lock = file_lock(path);
+    let _guard = lock.blocking_lock();
+    perf::measure(perf::Stage::Encode, || write_locked(path, backup, write))
+}
+
+fn write_locked<F>(path: &Path, backup: bool, write: F) -> Result<(), AppError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), AppError>,
+{
+    let target = paths::extended(path);
+    let temp = temp_path(&target);
+    let result = File::create(&temp)
+        .map_err(AppError::from)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write(&mut writer)?;
+            let file = writer.into_inner().map_err(|e| AppError::from(e.into_error()))?;
+            file.sync_all().map_err(AppError::from)
+        })
+        .and_then(|_| {
+            // 编码完成后才开始替换目标文件；操作已超时时不再备份和覆盖
+            let _write = timeouts::begin_write()?;
+            if backup && target.is_file() {
+                backup_original(path)?;
+            }
+            fs::rename(&temp, &target).map_err(AppError::from)
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    cache::invalidate(path);
+    result.map_err(|e| e.at(path))
+}
+
+// 原子地写入用户文件，覆盖前备份原图
+pub(crate) fn write_atomic<F>(path: &Path, write: F) -> Result<(), AppError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), AppError>,
+{
+    replace_file(path, true, write)
+}
+
+// 原子地写入应用自己的状态文件（自动保存、设置等），不做备份
+pub(crate) fn write_app_file(path: &Path, data: &[u8]) -> Result<(), AppError> {
+    replace_file(path, false, |writer| writer.write_all(data).map_err(AppError::from))
+}
+
+// 原子地写入字节数据
+pub(crate) fn write_bytes(path: &Path, data: &[u8]) -> Result<(), AppError> {
+    write_atomic(path, |writer| writer.write_all(data).map_err(AppError::from))
+}
+
+// 按扩展名推断格式，原子地保存图片
+pub(crate) fn save_image(img: &DynamicImage, path: &Path) -> Result<(), AppError> {
+    let format = ImageFormat::from_path(path).map_err(|e| AppError::from(e).at(path))?;
+    save_image_with_format(img, path, format)
+}
+
+// 按选项编码；JPEG、WebP、AVIF 只支持 8 位 RGB/RGBA，其他颜色类型先转换（JPEG 去掉透明通道）
+pub(crate) fn encode<W: Write + Seek>(
+    img: &DynamicImage,
+    writer: &mut W,
+    format: ImageFormat,
+    options: &EncodeOptions,
+) -> Result<(), AppError> {
+    let (width, height) = img.dimensions();
+    let eight_bit = || {
+        if img.color().has_alpha() {
+            (img.to_rgba8().into_raw(), image::ColorType::Rgba8)
+        } else {
+            (img.to_rgb8().into_raw(), image::ColorType::Rgb8)
+        }
+    };
+    match format {
+        ImageFormat::Jpeg => JpegEncoder::new_with_quality(writer, options.quality())
+            .encode_image(&img.to_rgb8())
+            .map_err(AppError::from),
+        ImageFormat::Png => match options.compression {
+            Some(level) => {
+                let compression = match level {
+                    0..=3 => CompressionType::Fast,
+                    4..=6 => CompressionType::Default,
+                    _ => CompressionType::Best,
+                };
+                PngEncoder::new_with_quality(writer, compression, PngFilter::Adaptive)
+                    .write_image(img.as_bytes(), width, height, img.color())
+                    .map_err(AppError::from)
+            }
+            None => img.write_to(writer, format).map_err(AppError::from),
+        },
+        ImageFormat::WebP => {
+            let quality = if options.lossless {
+                WebPQuality::lossless()
+            } else {
+                WebPQuality::lossy(options.quality())
+            };
+            let (data, color) = eight_bit();
+            WebPEncoder::new_with_quality(writer, quality)
+                .encode(&data, width, height, color)
+                .map_err(AppError::from)
+        }
+        ImageFormat::Avif => {
+            let quality = if options.lossless { 100 } else { options.quality() };
+            let (data, color) = eight_bit();
+            AvifEncoder::new_with_speed_quality(writer, AVIF_SPEED, quality)
+                .write_image(&data, width, height, color)
+                .map_err(AppError::from)
+        }
+        _ => img.write_to(writer, format).map_err(AppError::from),
+    }
+}
+
+pub(crate) fn encode_to_vec(img: &DynamicImage, format: ImageFormat, options: &EncodeOptions) -> Result<Vec<u8>, AppError> {
+    let mut buffer = Cursor::new(Vec::new());
+    encode(img, &mut buffer, format, options)?;
+    Ok(buffer.into_inner())
+}
+
+pub(crate) fn save_image_with_format(img: &DynamicImage, path: &Path, format: ImageFormat) -> Result<(), AppError> {
+    write_atomic(path, |writer| encode(img, writer, format, &EncodeOptions::default()))
+}
+
+// 保存由 source 编辑得到的图片，并写入 source 的 EXIF（方向改为 1）
+// 设置中关闭了保留元数据、source 没有 EXIF 或格式不支持时与 save_image 相同
+pub(crate) fn save_image_from(img: &DynamicImage, source: &Path, path: &Path) -> Result<(), AppError> {
+    save_image_as(img, source, path, &EncodeOptions::default())
+}
+
+// 与 save_image_from 相同，按 options 编码
+pub(crate) fn save_image_as(img: &DynamicImage, source: &Path, path: &Path, options: &EncodeOptions) -> Result<(), AppError> {
+    let format = ImageFormat::from_path(path).map_err(|e| AppError::from(e).at(path))?;
+    let Some(exif) = source_exif(source, format) else {
+        return write_atomic(path, |writer| encode(img, writer, format, options));
+    };
+
+    let data = metadata::embed_exif(encode_to_vec(img, format, options)?, format, &exif);
+    write_atomic(path, |writer| writer.write_all(&data).map_err(AppError::from))
+}
+
+// 编码为字节并写入 source 的 EXIF，用于不写入本地文件的输出（例如导出到服务器）
+pub(crate) fn encode_from(img: &DynamicImage, source: &Path, format: ImageFormat, options: &EncodeOptions) -> Result<Vec<u8>, AppError> {
+    let data = encode_to_vec(img, format, options)?;
+    Ok(match source_exif(source, format) {
+        Some(exif) => metadata::embed_exif(data, format, &exif),
+        None => data,
+    })
+}
+
+fn source_exif(source: &Path, format: ImageFormat) -> Option<Vec<u8>> {
+    if settings::current().keep_metadata && metadata::supports_exif(format) {
+        metadata::output_exif(source)
+    } else {
+        None
+    }
+}
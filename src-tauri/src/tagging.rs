@@ -0,0 +1,101 @@
+// 自动标签：使用 ImageNet 分类模型生成标签，并写入 XMP 关键词
+use std::fs;
+use std::path::Path;
+
+use image::imageops::FilterType;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::ml::{load_session, resolve_model, to_tensor, IMAGENET_MEAN, IMAGENET_STD};
+use crate::open_image;
+use crate::run_blocking;
+use crate::scope;
+use crate::xmp::{read_keywords, write_keywords};
+
+const MODEL_FILE: &str = "mobilenetv2.onnx";
+const LABELS_FILE: &str = "imagenet-labels.txt";
+const MODEL_SIZE: u32 = 224;
+const DEFAULT_TOP_K: usize = 5;
+const DEFAULT_MIN_CONFIDENCE: f32 = 0.1;
+
+#[derive(Serialize, Debug)]
+pub struct ImageTag {
+    pub label: String,
+    pub confidence: f32,
+}
+
+fn load_labels(app: &AppHandle) -> Result<Vec<String>, AppError> {
+    let content = fs::read_to_string(resolve_model(app, LABELS_FILE)?)
+        .map_err(AppError::from)?;
+    // ImageNet 标签常见形如 "golden retriever, retriever"，只取第一个名称
+    Ok(content
+        .lines()
+        .map(|line| line.split(',').next().unwrap_or("").trim().to_string())
+        .collect())
+}
+
+// 对图片分类并返回置信度最高的标签，save 为 true 时合并写入 XMP 旁车文件
+#[tauri::command]
+pub async fn classify_image(
+    app: AppHandle,
+    path: String,
+    top_k: Option<usize>,
+    min_confidence: Option<f32>,
+    save: bool,
+) -> Result<Vec<ImageTag>, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("classify_image", move || {
+        let img = open_image(&path)?;
+
+        let session = load_session(&resolve_model(&app, MODEL_FILE)?)?;
+        let labels = load_labels(&app)?;
+
+        let input = img
+            .resize_exact(MODEL_SIZE, MODEL_SIZE, FilterType::Triangle)
+            .to_rgb8();
+        let inputs = ort::inputs![to_tensor(&input, Some((IMAGENET_MEAN, IMAGENET_STD)))]
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to prepare model input: {}", e)))?;
+        let outputs = session
+            .run(inputs)
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to run model: {}", e)))?;
+        let logits = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| AppError::ProcessingFailed(format!("Failed to read model output: {}", e)))?;
+
+        // softmax 将输出转换为概率
+        let max = logits.iter().cloned().fold(f32::MIN, f32::max);
+        let exp: Vec<f32> = logits.iter().map(|v| (v - max).exp()).collect();
+        let sum: f32 = exp.iter().sum();
+        let mut ranked: Vec<(usize, f32)> = exp.iter().map(|v| v / sum).enumerate().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let min_confidence = min_confidence.unwrap_or(DEFAULT_MIN_CONFIDENCE);
+        let tags: Vec<ImageTag> = ranked
+            .into_iter()
+            .take(top_k.unwrap_or(DEFAULT_TOP_K))
+            .filter(|(_, p)| *p >= min_confidence)
+            .filter_map(|(index, confidence)| {
+                labels.get(index).map(|label| ImageTag {
+                    label: label.clone(),
+                    confidence,
+                })
+            })
+            .collect();
+
+        if save && !tags.is_empty() {
+            let image_path = Path::new(&path);
+            let mut keywords = read_keywords(image_path);
+            for tag in &tags {
+                if !keywords.iter().any(|k| k.eq_ignore_ascii_case(&tag.label)) {
+                    keywords.push(tag.label.clone());
+                }
+            }
+            write_keywords(image_path, &keywords)?;
+        }
+
+        Ok(tags)
+    })
+    .await
+}
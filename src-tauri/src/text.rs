@@ -0,0 +1,162 @@
+// 文字绘制：联系表标签、日期水印等在图片上绘制文字时共用
+// 没有内置字体，默认使用系统字体（优先选择支持中文的字体），也可以指定字体文件
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::{Rgba, RgbaImage};
+use parking_lot::Mutex;
+
+use crate::error::AppError;
+
+lazy_static::lazy_static! {
+    // 已加载的字体，按文件路径索引
+    static ref FONTS: Mutex<HashMap<PathBuf, Arc<FontVec>>> = Mutex::new(HashMap::new());
+}
+
+// 按顺序查找的系统字体
+fn system_fonts() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if cfg!(target_os = "windows") {
+        let windir = std::env::var("WINDIR").unwrap_or_else(|_| "C:\\Windows".to_string());
+        let fonts = Path::new(&windir).join("Fonts");
+        for name in ["msyh.ttc", "simhei.ttf", "segoeui.ttf", "arial.ttf"] {
+            candidates.push(fonts.join(name));
+        }
+    } else if cfg!(target_os = "macos") {
+        for path in [
+            "/System/Library/Fonts/PingFang.ttc",
+            "/System/Library/Fonts/STHeiti Medium.ttc",
+            "/Library/Fonts/Arial Unicode.ttf",
+            "/System/Library/Fonts/Helvetica.ttc",
+        ] {
+            candidates.push(PathBuf::from(path));
+        }
+    } else {
+        for path in [
+            "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+            "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+            "/usr/share/fonts/TTF/DejaVuSans.ttf",
+            "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+        ] {
+            candidates.push(PathBuf::from(path));
+        }
+    }
+    candidates
+}
+
+fn load_file(path: &Path) -> Result<Arc<FontVec>, AppError> {
+    if let Some(font) = FONTS.lock().get(path) {
+        return Ok(font.clone());
+    }
+    let data = fs::read(path).map_err(|e| AppError::from(e).at(path))?;
+    // 字体集合（.ttc）使用其中的第一个字体
+    let font = FontVec::try_from_vec_and_index(data, 0)
+        .map_err(|e| AppError::Unsupported(format!("Invalid font file: {}", e)).at(path))?;
+    let font = Arc::new(font);
+    FONTS.lock().insert(path.to_path_buf(), font.clone());
+    Ok(font)
+}
+
+// 加载字体：指定了 path 时使用该文件，否则使用第一个可用的系统字体
+pub(crate) fn load_font(path: Option<&str>) -> Result<Arc<FontVec>, AppError> {
+    if let Some(path) = path {
+        return load_file(Path::new(path));
+    }
+    system_fonts()
+        .iter()
+        .filter(|candidate| candidate.is_file())
+        .find_map(|candidate| load_file(candidate).ok())
+        .ok_or_else(|| AppError::NotFound("No usable system font; please choose a font file".to_string()))
+}
+
+// 文字在 size 像素字号下的宽度和行高
+pub(crate) fn measure(font: &FontVec, size: f32, text: &str) -> (u32, u32) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    (width.ceil() as u32, scaled.height().ceil() as u32)
+}
+
+// 行高（含行间距）
+pub(crate) fn line_height(font: &FontVec, size: f32) -> u32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    (scaled.height() + scaled.line_gap()).ceil().max(size * 1.2) as u32
+}
+
+// 在 (x, y)（文字框左上角）处绘制单行文字，按覆盖率与原像素做 alpha 混合
+pub(crate) fn draw_text(img: &mut RgbaImage, font: &FontVec, size: f32, x: i32, y: i32, text: &str, color: Rgba<u8>) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let baseline = y as f32 + scaled.ascent();
+    let mut caret = x as f32;
+    let mut previous = None;
+
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(scaled.scale(), point(caret, baseline));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i32 + gx as i32;
+            let py = bounds.min.y as i32 + gy as i32;
+            if px < 0 || py < 0 || px >= img.width() as i32 || py >= img.height() as i32 {
+                return;
+            }
+            blend(img.get_pixel_mut(px as u32, py as u32), color, coverage);
+        });
+    }
+}
+
+// 以 color 的透明度乘以 coverage 为不透明度，把 color 叠加到 pixel 上
+pub(crate) fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>, coverage: f32) {
+    let alpha = coverage.clamp(0.0, 1.0) * color[3] as f32 / 255.0;
+    if alpha <= 0.0 {
+        return;
+    }
+    let base_alpha = pixel[3] as f32 / 255.0;
+    let out_alpha = alpha + base_alpha * (1.0 - alpha);
+    for c in 0..3 {
+        let value = (color[c] as f32 * alpha + pixel[c] as f32 * base_alpha * (1.0 - alpha)) / out_alpha;
+        pixel[c] = value.round().clamp(0.0, 255.0) as u8;
+    }
+    pixel[3] = (out_alpha * 255.0).round() as u8;
+}
+
+// 解析 #rgb、#rrggbb 或 #rrggbbaa 形式的颜色
+pub(crate) fn parse_color(value: &str) -> Result<Rgba<u8>, AppError> {
+    let hex = value.trim().trim_start_matches('#');
+    let digits: Vec<u8> = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect::<String>().into_bytes(),
+        6 | 8 => hex.as_bytes().to_vec(),
+        _ => return Err(AppError::InvalidArgument(format!("Invalid color: {}", value))),
+    };
+    let channel = |i: usize| {
+        std::str::from_utf8(&digits[i * 2..i * 2 + 2])
+            .ok()
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            .ok_or_else(|| AppError::InvalidArgument(format!("Invalid color: {}", value)))
+    };
+    let alpha = if digits.len() == 8 { channel(3)? } else { 255 };
+    Ok(Rgba([channel(0)?, channel(1)?, channel(2)?, alpha]))
+}
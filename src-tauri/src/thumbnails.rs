@@ -0,0 +1,124 @@
+// 缩略图缓存：列出文件夹后在后台为每个文件生成缩略图（PNG），保存在应用缓存目录下
+// 以路径、修改时间、大小和设置中的缩略图尺寸为键，文件改变或尺寸设置改变后重新生成
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+use image::ImageFormat;
+use parking_lot::Mutex;
+use rayon::prelude::*;
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::storage::write_app_file;
+use crate::{paths, run_blocking, scope, settings, video};
+
+// 每次开始后台生成时加一；正在进行的生成发现序号已变（例如切换了文件夹）就停止
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    // 本次运行中每个文件最近使用的缩略图文件，文件改变时据此删除旧的缩略图
+    static ref THUMBNAILS: Mutex<HashMap<PathBuf, PathBuf>> = Mutex::new(HashMap::new());
+}
+
+// 每生成（或从缓存中找到）一张缩略图发送一次 thumbnail-ready 事件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailEvent {
+    pub path: String,
+    // 缓存中的缩略图文件
+    pub thumbnail: Option<String>,
+    pub error: Option<AppError>,
+}
+
+fn thumbnail_file(app: &AppHandle, path: &Path) -> Result<PathBuf, AppError> {
+    let metadata = fs::metadata(path).map_err(|e| AppError::from(e).at(path))?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    settings::current().thumbnail_size.hash(&mut hasher);
+
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve cache directory: {}", e)))?
+        .join("thumbnails");
+    fs::create_dir_all(&dir).map_err(|e| AppError::from(e).at(&dir))?;
+    Ok(dir.join(format!("{:016x}.png", hasher.finish())))
+}
+
+// 文件的缩略图，缓存中没有时生成；视频使用封面帧
+pub(crate) fn thumbnail(app: &AppHandle, path: &Path) -> Result<PathBuf, AppError> {
+    let file = thumbnail_file(app, path)?;
+    THUMBNAILS.lock().insert(path.to_path_buf(), file.clone());
+    if file.is_file() {
+        return Ok(file);
+    }
+
+    // 不经过解码缓存：浏览大量图片时会把正在编辑的图片挤出缓存
+    let source = if video::is_video(path) {
+        video::poster(app, path)?.0
+    } else {
+        path.to_path_buf()
+    };
+    let img = image::open(&source).map_err(|e| AppError::from(e).at(path))?;
+    let size = settings::current().thumbnail_size;
+    let mut buffer = Cursor::new(Vec::new());
+    img.thumbnail(size, size).write_to(&mut buffer, ImageFormat::Png)?;
+    write_app_file(&file, &buffer.into_inner())?;
+    Ok(file)
+}
+
+// 删除文件的旧缩略图（文件已修改或删除）
+pub(crate) fn invalidate(path: &Path) {
+    if let Some(file) = THUMBNAILS.lock().remove(path) {
+        let _ = fs::remove_file(file);
+    }
+}
+
+// 在后台并行生成缩略图，并停止上一次尚未完成的生成
+pub(crate) fn generate_in_background(app: AppHandle, files: Vec<PathBuf>) {
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    thread::spawn(move || {
+        files.par_iter().for_each(|path| {
+            if GENERATION.load(Ordering::Relaxed) != generation {
+                return;
+            }
+            let (thumbnail, error) = match thumbnail(&app, path) {
+                Ok(file) => (Some(paths::display(&file)), None),
+                Err(error) => {
+                    tracing::debug!(path = %path.display(), error = %error, "thumbnail generation failed");
+                    (None, Some(error))
+                }
+            };
+            let _ = app.emit(
+                "thumbnail-ready",
+                ThumbnailEvent {
+                    path: paths::display(path),
+                    thumbnail,
+                    error,
+                },
+            );
+        });
+    });
+}
+
+// 以 PNG 原始字节返回缩略图，缓存中没有时立即生成
+#[tauri::command]
+pub async fn get_thumbnail(app: AppHandle, path: String) -> Result<Response, AppError> {
+    let path = scope::check(&path)?;
+    let png = run_blocking("get_thumbnail", move || {
+        let file = thumbnail(&app, &path)?;
+        fs::read(&file).map_err(|e| AppError::from(e).at(&file))
+    })
+    .await?;
+    Ok(Response::new(png))
+}
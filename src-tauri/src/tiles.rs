@@ -0,0 +1,171 @@
+// 区域/瓦片读取：查看器平移缩放超大图片（例如 100 MP 全景图）时只取当前视口需要的部分
+// 解码后的原图由 cache 保留在后端，webview 中不需要加载整张位图
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use serde::Deserialize;
+use tauri::ipc::Response;
+use tauri::AppHandle;
+
+use crate::color::source_image;
+use crate::error::AppError;
+use crate::run_blocking;
+
+// 单次返回的区域最大边长（缩放后），防止一次请求整张原图
+const MAX_REGION_SIZE: u32 = 4096;
+const DEFAULT_TILE_SIZE: u32 = 256;
+const TILE_JPEG_QUALITY: u8 = 85;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TileFormat {
+    // 有透明通道时为 PNG，否则为 JPEG
+    #[default]
+    Auto,
+    Jpeg,
+    Png,
+}
+
+// 原图中的矩形区域，像素坐标
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// 金字塔的级数：最高一级为原图，每低一级宽高减半，最低一级为 1x1（与 DZI 的约定一致）
+pub(crate) fn max_level(width: u32, height: u32) -> u32 {
+    let longest = width.max(height).max(1);
+    32 - (longest - 1).leading_zeros()
+}
+
+// 第 level 级的图片尺寸
+pub(crate) fn level_size(width: u32, height: u32, level: u32) -> (u32, u32) {
+    let shift = max_level(width, height).saturating_sub(level);
+    (
+        width.div_ceil(1 << shift).max(1),
+        height.div_ceil(1 << shift).max(1),
+    )
+}
+
+// 取出原图中的区域并缩放到 out_width x out_height
+pub(crate) fn render_region(img: &DynamicImage, region: Region, out_width: u32, out_height: u32) -> DynamicImage {
+    let Region { x, y, width, height } = region;
+    let region = img.crop_imm(x, y, width, height);
+    if (out_width, out_height) == (width, height) {
+        region
+    } else if out_width * 2 <= width {
+        // 缩小较多时使用更快的缩略图算法
+        region.thumbnail_exact(out_width, out_height)
+    } else {
+        region.resize_exact(out_width, out_height, FilterType::Triangle)
+    }
+}
+
+pub(crate) fn encode_tile(img: &DynamicImage, format: TileFormat) -> Result<Vec<u8>, AppError> {
+    let png = match format {
+        TileFormat::Auto => img.color().has_alpha(),
+        TileFormat::Jpeg => false,
+        TileFormat::Png => true,
+    };
+    let mut buffer = Cursor::new(Vec::new());
+    if png {
+        img.write_to(&mut buffer, ImageFormat::Png)?;
+    } else {
+        JpegEncoder::new_with_quality(&mut buffer, TILE_JPEG_QUALITY).encode_image(&img.to_rgb8())?;
+    }
+    Ok(buffer.into_inner())
+}
+
+// 返回原图中 region 区域按 scale（0~1]缩放后的图片字节
+// source 可以是文件路径或打开的编辑会话 id
+#[tauri::command]
+pub async fn get_image_region(
+    app: AppHandle,
+    source: String,
+    region: Region,
+    scale: Option<f32>,
+    format: Option<TileFormat>,
+) -> Result<Response, AppError> {
+    let scale = scale.unwrap_or(1.0);
+    if !(scale > 0.0 && scale <= 1.0) {
+        return Err(AppError::InvalidArgument(format!("Scale must be between 0 and 1: {}", scale)));
+    }
+
+    let data = run_blocking("get_image_region", move || {
+        let img = source_image(&app, &source)?;
+        let (img_width, img_height) = img.dimensions();
+        let Region { x, y, width, height } = region;
+        if width == 0 || height == 0 || x >= img_width || y >= img_height {
+            return Err(AppError::InvalidArgument(format!(
+                "Region ({}, {}, {}x{}) is outside the {}x{} image",
+                x, y, width, height, img_width, img_height
+            )));
+        }
+        // 超出图片的部分裁掉
+        let width = width.min(img_width - x);
+        let height = height.min(img_height - y);
+        let out_width = ((width as f32 * scale).round() as u32).max(1);
+        let out_height = ((height as f32 * scale).round() as u32).max(1);
+        if out_width > MAX_REGION_SIZE || out_height > MAX_REGION_SIZE {
+            return Err(AppError::TooLarge(format!(
+                "Region {}x{} exceeds {} pixels per side",
+                out_width, out_height, MAX_REGION_SIZE
+            )));
+        }
+
+        let region = render_region(&img, Region { x, y, width, height }, out_width, out_height);
+        encode_tile(&region, format.unwrap_or_default())
+    })
+    .await?;
+    Ok(Response::new(data))
+}
+
+// 按金字塔级别返回一个瓦片：第 level 级中第 col 列、第 row 行，边长 tile_size（默认 256）
+#[tauri::command]
+pub async fn get_image_tile(
+    app: AppHandle,
+    source: String,
+    level: u32,
+    col: u32,
+    row: u32,
+    tile_size: Option<u32>,
+    format: Option<TileFormat>,
+) -> Result<Response, AppError> {
+    let tile_size = tile_size.unwrap_or(DEFAULT_TILE_SIZE);
+    if tile_size == 0 || tile_size > MAX_REGION_SIZE {
+        return Err(AppError::InvalidArgument(format!("Tile size must be between 1 and {}", MAX_REGION_SIZE)));
+    }
+
+    let data = run_blocking("get_image_tile", move || {
+        let img = source_image(&app, &source)?;
+        let (img_width, img_height) = img.dimensions();
+        if level > max_level(img_width, img_height) {
+            return Err(AppError::InvalidArgument(format!("Level {} does not exist", level)));
+        }
+        let (level_width, level_height) = level_size(img_width, img_height, level);
+        let (left, top) = (col.saturating_mul(tile_size), row.saturating_mul(tile_size));
+        if left >= level_width || top >= level_height {
+            return Err(AppError::InvalidArgument(format!("Tile {}_{} is outside level {}", col, row, level)));
+        }
+        let out_width = tile_size.min(level_width - left);
+        let out_height = tile_size.min(level_height - top);
+
+        // 把瓦片在本级的范围换算回原图坐标
+        let scale_x = img_width as f64 / level_width as f64;
+        let scale_y = img_height as f64 / level_height as f64;
+        let x = (left as f64 * scale_x) as u32;
+        let y = (top as f64 * scale_y) as u32;
+        let width = (((left + out_width) as f64 * scale_x).ceil() as u32).min(img_width) - x;
+        let height = (((top + out_height) as f64 * scale_y).ceil() as u32).min(img_height) - y;
+
+        let tile = render_region(&img, Region { x, y, width, height }, out_width, out_height);
+        encode_tile(&tile, format.unwrap_or_default())
+    })
+    .await?;
+    Ok(Response::new(data))
+}
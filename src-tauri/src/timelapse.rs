@@ -0,0 +1,292 @@
+// 延时动画：把文件夹中的连拍帧按拍摄时间排序，合成为 GIF、WebP 动画或 MP4 视频
+// GIF 由本程序直接编码；WebP 动画和 MP4 需要 ffmpeg
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, DynamicImage, Frame, GenericImageView, ImageFormat};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::listing::{list_files, SymlinkPolicy};
+use crate::storage::{write_atomic, write_bytes};
+use crate::{ffmpeg, metadata, open_image, paths, scope};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "bmp"];
+const MAX_FPS: f32 = 60.0;
+const MAX_OUTPUT_SIZE: u32 = 7680;
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimelapseOptions {
+    pub fps: f32,
+    // 输出尺寸；只指定一边时按第一帧的宽高比计算另一边，都不指定时使用第一帧的尺寸
+    // 宽高比与输出不同的帧居中裁剪填满画面
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    // MP4 的 CRF（0~51，越小质量越高）
+    pub quality: u8,
+}
+
+impl Default for TimelapseOptions {
+    fn default() -> Self {
+        Self {
+            fps: 10.0,
+            width: None,
+            height: None,
+            quality: 23,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelapseResult {
+    pub output: String,
+    pub frames: usize,
+    pub width: u32,
+    pub height: u32,
+    // 动画时长（秒）
+    pub duration: f32,
+}
+
+// 每处理完一帧发送一次 timelapse-progress 事件
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelapseProgress {
+    pub path: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputKind {
+    Gif,
+    WebP,
+    Mp4,
+}
+
+fn output_kind(output: &Path) -> Result<OutputKind, AppError> {
+    let ext = output
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "gif" => Ok(OutputKind::Gif),
+        "webp" => Ok(OutputKind::WebP),
+        "mp4" => Ok(OutputKind::Mp4),
+        _ => Err(AppError::Unsupported(format!("Time-lapse output format {}", ext))),
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 由公历日期计算 Unix 天数（Howard Hinnant 的 days_from_civil 算法）
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// 帧的时间（秒）：优先使用 EXIF 拍摄时间，其次使用文件修改时间
+fn capture_time(path: &Path) -> Option<i64> {
+    let taken = metadata::read_exif(path).and_then(|exif| metadata::date_taken(&exif));
+    if let Some(d) = taken {
+        let days = days_from_civil(d.year as i64, d.month as i64, d.day as i64);
+        return Some(days * 86400 + d.hour as i64 * 3600 + d.minute as i64 * 60 + d.second as i64);
+    }
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+// 按时间排序，时间相同（例如连拍同一秒内的多张）时按文件名排序
+fn sorted_frames(folder: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let listing = list_files(folder, SymlinkPolicy::Skip, false)?;
+    let mut frames: Vec<(i64, PathBuf)> = listing
+        .files
+        .into_iter()
+        .filter(|path| is_image(path))
+        .map(|path| (capture_time(&path).unwrap_or(i64::MAX), path))
+        .collect();
+    frames.sort();
+    Ok(frames.into_iter().map(|(_, path)| path).collect())
+}
+
+// 输出尺寸；MP4（yuv420p）要求宽高为偶数
+fn output_size(first: &DynamicImage, options: &TimelapseOptions, kind: OutputKind) -> (u32, u32) {
+    let (w, h) = first.dimensions();
+    let (width, height) = match (options.width, options.height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, (h as f64 * width as f64 / w as f64).round() as u32),
+        (None, Some(height)) => ((w as f64 * height as f64 / h as f64).round() as u32, height),
+        (None, None) => (w, h),
+    };
+    let (width, height) = (width.clamp(2, MAX_OUTPUT_SIZE), height.clamp(2, MAX_OUTPUT_SIZE));
+    if kind == OutputKind::Mp4 {
+        (width & !1, height & !1)
+    } else {
+        (width, height)
+    }
+}
+
+fn fit_frame(img: DynamicImage, width: u32, height: u32) -> DynamicImage {
+    if img.dimensions() == (width, height) {
+        img
+    } else {
+        img.resize_to_fill(width, height, FilterType::Triangle)
+    }
+}
+
+// 依次解码、缩放每一帧并交给 visit
+fn for_each_frame<F>(
+    app: &AppHandle,
+    frames: &[PathBuf],
+    width: u32,
+    height: u32,
+    mut visit: F,
+) -> Result<(), AppError>
+where
+    F: FnMut(usize, DynamicImage) -> Result<(), AppError>,
+{
+    let total = frames.len();
+    for (index, path) in frames.iter().enumerate() {
+        let frame = fit_frame(Arc::unwrap_or_clone(open_image(path)?), width, height);
+        visit(index, frame)?;
+        let _ = app.emit(
+            "timelapse-progress",
+            TimelapseProgress {
+                path: paths::display(path),
+                done: index + 1,
+                total,
+            },
+        );
+    }
+    Ok(())
+}
+
+fn encode_gif(
+    app: &AppHandle,
+    frames: &[PathBuf],
+    output: &Path,
+    (width, height): (u32, u32),
+    fps: f32,
+) -> Result<(), AppError> {
+    let delay = Delay::from_numer_denom_ms((1000.0 / fps).round() as u32, 1);
+    write_atomic(output, |writer| {
+        let mut encoder = GifEncoder::new_with_speed(writer, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        for_each_frame(app, frames, width, height, |_, frame| {
+            encoder.encode_frame(Frame::from_parts(frame.to_rgba8(), 0, 0, delay))?;
+            Ok(())
+        })
+    })
+}
+
+// 帧先以 PNG 写入临时文件夹，再由 ffmpeg 编码
+fn encode_with_ffmpeg(
+    app: &AppHandle,
+    frames: &[PathBuf],
+    output: &Path,
+    kind: OutputKind,
+    (width, height): (u32, u32),
+    options: &TimelapseOptions,
+) -> Result<(), AppError> {
+    let work_dir = ffmpeg::WorkDir::new("timelapse")?;
+    for_each_frame(app, frames, width, height, |index, frame| {
+        let file = work_dir.path().join(format!("frame_{:05}.png", index));
+        DynamicImage::ImageRgb8(frame.to_rgb8())
+            .save_with_format(&file, ImageFormat::Png)
+            .map_err(|e| AppError::from(e).at(&file))
+    })?;
+
+    let encoded = work_dir.path().join(if kind == OutputKind::Mp4 { "out.mp4" } else { "out.webp" });
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-framerate".into(),
+        options.fps.to_string(),
+        "-i".into(),
+        paths::display(&work_dir.path().join("frame_%05d.png")),
+    ];
+    match kind {
+        OutputKind::Mp4 => args.extend([
+            "-c:v".into(),
+            "libx264".into(),
+            "-pix_fmt".into(),
+            "yuv420p".into(),
+            "-crf".into(),
+            options.quality.min(51).to_string(),
+            "-movflags".into(),
+            "+faststart".into(),
+        ]),
+        _ => args.extend([
+            "-c:v".into(),
+            "libwebp".into(),
+            "-lossless".into(),
+            "0".into(),
+            "-loop".into(),
+            "0".into(),
+        ]),
+    }
+    args.push(paths::display(&encoded));
+    ffmpeg::run(&args)?;
+
+    let data = fs::read(&encoded).map_err(|e| AppError::from(e).at(&encoded))?;
+    write_bytes(output, &data)
+}
+
+// 把 folder 中的图片合成为延时动画，输出格式由 output 的扩展名（gif、webp、mp4）决定
+#[tauri::command]
+pub async fn create_timelapse(
+    app: AppHandle,
+    folder: String,
+    output: String,
+    options: Option<TimelapseOptions>,
+) -> Result<TimelapseResult, AppError> {
+    let folder = scope::check(&folder)?;
+    let output = scope::check(&output)?;
+    let options = options.unwrap_or_default();
+    let kind = output_kind(&output)?;
+    if !(options.fps > 0.0 && options.fps <= MAX_FPS) {
+        return Err(AppError::InvalidArgument(format!("Frame rate must be between 0 and {}: {}", MAX_FPS, options.fps)));
+    }
+    if kind != OutputKind::Gif {
+        // 在解码所有帧之前确认 ffmpeg 可用
+        ffmpeg::binary()?;
+    }
+
+    // 耗时与帧数成正比，不使用单个操作的超时
+    tauri::async_runtime::spawn_blocking(move || {
+        let frames = sorted_frames(&folder)?;
+        let first = frames
+            .first()
+            .ok_or_else(|| AppError::NotFound("Images in folder".to_string()).at(&folder))?;
+        let (width, height) = output_size(&open_image(first)?, &options, kind);
+
+        match kind {
+            OutputKind::Gif => encode_gif(&app, &frames, &output, (width, height), options.fps)?,
+            _ => encode_with_ffmpeg(&app, &frames, &output, kind, (width, height), &options)?,
+        }
+
+        Ok(TimelapseResult {
+            output: paths::display(&output),
+            frames: frames.len(),
+            width,
+            height,
+            duration: frames.len() as f32 / options.fps,
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Time-lapse task failed: {}", e)))?
+}
@@ -0,0 +1,129 @@
+// 操作超时：每个命令可以单独设置超时时间，避免损坏的图片让操作一直挂起
+// 超时后阻塞线程无法强制终止，只标记为已取消，之后写入用户文件时直接失败；已经开始写入的操作不再取消
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::error::AppError;
+
+// 未单独设置时使用的默认超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+// 操作的状态
+const RUNNING: u8 = 0;
+const WRITING: u8 = 1;
+// 已写入过文件，结果必须如实返回
+const WRITTEN: u8 = 2;
+const CANCELLED: u8 = 3;
+
+lazy_static::lazy_static! {
+    static ref TIMEOUTS: RwLock<TimeoutConfig> = RwLock::new(TimeoutConfig::default());
+}
+
+thread_local! {
+    // 当前线程上正在执行的操作，不在 run_blocking 中时为 None
+    static CURRENT: RefCell<Option<Cancel>> = const { RefCell::new(None) };
+}
+
+// run_blocking 中一次操作的取消标志
+#[derive(Clone, Default)]
+pub(crate) struct Cancel(Arc<AtomicU8>);
+
+impl Cancel {
+    // 超时时调用；操作已经开始写入文件时不能取消，返回 false，调用方应等待操作完成
+    pub(crate) fn cancel(&self) -> bool {
+        self.0
+            .compare_exchange(RUNNING, CANCELLED, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+}
+
+// 在 task 执行期间把 cancel 设为当前线程的取消标志
+pub(crate) fn with_cancel<T>(cancel: Cancel, task: impl FnOnce() -> T) -> T {
+    let previous = CURRENT.with(|current| current.replace(Some(cancel)));
+    let result = task();
+    CURRENT.with(|current| current.replace(previous));
+    result
+}
+
+// 写入文件期间持有，结束后当前操作不能再被取消
+pub(crate) struct WriteGuard(Option<Cancel>);
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        if let Some(cancel) = &self.0 {
+            cancel.0.store(WRITTEN, Ordering::SeqCst);
+        }
+    }
+}
+
+// 替换文件之前调用；当前操作已超时时返回超时错误，不再写入
+// 嵌套写入（例如覆盖前备份原图）和不在 run_blocking 中的写入不受影响
+pub(crate) fn begin_write() -> Result<WriteGuard, AppError> {
+    let Some(cancel) = CURRENT.with(|current| current.borrow().clone()) else {
+        return Ok(WriteGuard(None));
+    };
+    let previous = cancel.0.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |state| match state {
+        WRITING | CANCELLED => None,
+        _ => Some(WRITING),
+    });
+    match previous {
+        Ok(_) => Ok(WriteGuard(Some(cancel))),
+        Err(WRITING) => Ok(WriteGuard(None)),
+        Err(_) => Err(AppError::Timeout(
+            "Operation timed out before the result was written".to_string(),
+        )),
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeoutConfig {
+    // 毫秒，0 表示不限制
+    pub default_ms: u64,
+    pub commands: HashMap<String, u64>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        TimeoutConfig {
+            default_ms: DEFAULT_TIMEOUT.as_millis() as u64,
+            commands: HashMap::new(),
+        }
+    }
+}
+
+// 查询命令的超时时间，None 表示不限制
+pub(crate) fn timeout_for(command: &str) -> Option<Duration> {
+    let config = TIMEOUTS.read();
+    let ms = config.commands.get(command).copied().unwrap_or(config.default_ms);
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+// 设置超时时间：command 为空时修改默认值，timeout_ms 为空时恢复该命令的默认值
+#[tauri::command]
+pub fn set_operation_timeout(command: Option<String>, timeout_ms: Option<u64>) -> TimeoutConfig {
+    let mut config = TIMEOUTS.write();
+    match (command, timeout_ms) {
+        (Some(command), Some(ms)) => {
+            config.commands.insert(command, ms);
+        }
+        (Some(command), None) => {
+            config.commands.remove(&command);
+        }
+        (None, ms) => {
+            config.default_ms = ms.unwrap_or(DEFAULT_TIMEOUT.as_millis() as u64);
+        }
+    }
+    config.clone()
+}
+
+#[tauri::command]
+pub fn get_operation_timeouts() -> TimeoutConfig {
+    TIMEOUTS.read().clone()
+}
@@ -0,0 +1,127 @@
+// 二进制数据传输：图片字节通过原始请求体/响应体传递，避免 JSON 数组序列化
+// 较大的数据可以分块上传：begin_transfer 创建缓冲区，append_transfer_chunk 逐块追加，
+// 再在命令请求头 transfer-id 中引用；下载方向用 read_file_chunk 按偏移分块读取
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use parking_lot::Mutex;
+use image::ImageFormat;
+use serde::de::DeserializeOwned;
+use tauri::ipc::{InvokeBody, Request, Response};
+
+use crate::error::AppError;
+use crate::storage::{self, EncodeOptions};
+use crate::{paths, run_blocking, scope};
+
+// 单次传输的大小上限
+const MAX_TRANSFER_BYTES: usize = 1024 * 1024 * 1024;
+// 单块读取的大小上限
+const MAX_CHUNK_BYTES: u64 = 64 * 1024 * 1024;
+const TRANSFER_HEADER: &str = "transfer-id";
+
+lazy_static::lazy_static! {
+    // 上传中的数据，按传输 id 索引
+    static ref TRANSFERS: Mutex<HashMap<String, Vec<u8>>> = Mutex::new(HashMap::new());
+}
+
+fn header<'a>(request: &'a Request<'_>, name: &str) -> Option<&'a str> {
+    request.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+// 读取数字类型的请求头
+pub(crate) fn header_u32(request: &Request<'_>, name: &str) -> Result<u32, AppError> {
+    header(request, name)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| AppError::InvalidArgument(format!("Missing or invalid header: {}", name)))
+}
+
+// 读取 JSON 格式的请求头
+pub(crate) fn header_json<T: DeserializeOwned>(request: &Request<'_>, name: &str) -> Result<T, AppError> {
+    let value = header(request, name).ok_or_else(|| AppError::InvalidArgument(format!("Missing header: {}", name)))?;
+    serde_json::from_str(value).map_err(|e| AppError::InvalidArgument(format!("Invalid header {}: {}", name, e)))
+}
+
+// 返回数据的格式和编码选项：请求头 format 为扩展名（默认 png），options 为 JSON 格式的编码选项
+pub(crate) fn output_encoding(request: &Request<'_>) -> Result<(ImageFormat, EncodeOptions), AppError> {
+    let format = storage::output_format(header(request, "format").unwrap_or("png"))?;
+    let options: EncodeOptions = match header(request, "options") {
+        Some(_) => header_json(request, "options")?,
+        None => EncodeOptions::default(),
+    };
+    options.validate()?;
+    Ok((format, options))
+}
+
+// 取出请求携带的数据：请求头中有 transfer-id 时使用分块上传的数据，否则使用原始请求体
+pub(crate) fn request_data(request: &Request<'_>) -> Result<Vec<u8>, AppError> {
+    if let Some(id) = header(request, TRANSFER_HEADER) {
+        return TRANSFERS
+            .lock()
+            .remove(id)
+            .ok_or_else(|| AppError::NotFound(format!("Transfer {}", id)));
+    }
+    match request.body() {
+        InvokeBody::Raw(data) => Ok(data.clone()),
+        InvokeBody::Json(_) => Err(AppError::InvalidArgument(
+            "Expected binary request body (Uint8Array or ArrayBuffer)".to_string(),
+        )),
+    }
+}
+
+// 开始分块上传，返回传输 id
+#[tauri::command]
+pub fn begin_transfer() -> String {
+    let id = format!("{:016x}", rand::random::<u64>());
+    TRANSFERS.lock().insert(id.clone(), Vec::new());
+    id
+}
+
+// 追加一块数据（原始请求体），请求头 transfer-id 指定传输；返回已接收的总字节数
+#[tauri::command]
+pub fn append_transfer_chunk(request: Request<'_>) -> Result<u64, AppError> {
+    let id = header(&request, TRANSFER_HEADER)
+        .ok_or_else(|| AppError::InvalidArgument(format!("Missing header: {}", TRANSFER_HEADER)))?;
+    let InvokeBody::Raw(chunk) = request.body() else {
+        return Err(AppError::InvalidArgument("Expected binary request body".to_string()));
+    };
+
+    let mut transfers = TRANSFERS.lock();
+    let buffer = transfers
+        .get_mut(id)
+        .ok_or_else(|| AppError::NotFound(format!("Transfer {}", id)))?;
+    if buffer.len() + chunk.len() > MAX_TRANSFER_BYTES {
+        transfers.remove(id);
+        return Err(AppError::TooLarge(format!("Transfer exceeds {} bytes", MAX_TRANSFER_BYTES)));
+    }
+    buffer.extend_from_slice(chunk);
+    Ok(buffer.len() as u64)
+}
+
+// 放弃未使用的上传
+#[tauri::command]
+pub fn cancel_transfer(id: &str) -> bool {
+    TRANSFERS.lock().remove(id).is_some()
+}
+
+// 从文件 offset 处读取最多 length 字节，以原始响应体返回；到达文件末尾时返回的数据少于 length
+#[tauri::command]
+pub async fn read_file_chunk(path: String, offset: u64, length: u64) -> Result<Response, AppError> {
+    scope::check(&path)?;
+    if length > MAX_CHUNK_BYTES {
+        return Err(AppError::TooLarge(format!("Chunk exceeds {} bytes", MAX_CHUNK_BYTES)));
+    }
+
+    let data = run_blocking("read_file_chunk", move || {
+        let mut file = File::open(paths::extended(Path::new(&path))).map_err(|e| AppError::from(e).at(&path))?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| AppError::from(e).at(&path))?;
+        let mut data = Vec::new();
+        file.take(length)
+            .read_to_end(&mut data)
+            .map_err(|e| AppError::from(e).at(&path))?;
+        Ok(data)
+    })
+    .await?;
+    Ok(Response::new(data))
+}
@@ -0,0 +1,188 @@
+// 上传子系统：将导出的图片上传到 S3 兼容存储（AWS S3 / MinIO / Cloudflare R2）
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use parking_lot::Mutex;
+use s3::creds::Credentials;
+use s3::{Bucket, Region};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::error::AppError;
+use crate::scope;
+
+// 上传配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadConfig {
+    pub endpoint: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    // MinIO 等自建服务通常需要路径风格的地址
+    #[serde(default)]
+    pub path_style: bool,
+}
+
+// 上传进度事件
+#[derive(Serialize, Clone, Debug)]
+pub struct UploadProgress {
+    pub path: String,
+    pub bucket: String,
+    pub key: String,
+    pub uploaded: u64,
+    pub total: u64,
+}
+
+// 上传子系统的托管状态，未配置时为 None
+#[derive(Default)]
+pub struct UploadState {
+    config: Mutex<Option<UploadConfig>>,
+}
+
+// 包装一个异步读取器，在读取过程中回调已读字节数
+struct ProgressReader<R> {
+    inner: R,
+    read: u64,
+    on_progress: Box<dyn FnMut(u64) + Send>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = &poll {
+            let n = (buf.filled().len() - before) as u64;
+            if n > 0 {
+                self.read += n;
+                let read = self.read;
+                (self.on_progress)(read);
+            }
+        }
+        poll
+    }
+}
+
+fn open_bucket(config: &UploadConfig, bucket: &str) -> Result<Bucket, AppError> {
+    let region = Region::Custom {
+        region: config.region.clone(),
+        endpoint: config.endpoint.clone(),
+    };
+    let credentials = Credentials::new(
+        Some(&config.access_key),
+        Some(&config.secret_key),
+        None,
+        None,
+        None,
+    )
+    .map_err(|e| AppError::InvalidArgument(format!("Invalid credentials: {}", e)))?;
+
+    let bucket = Bucket::new(bucket, region, credentials)
+        .map_err(|e| AppError::Network(format!("Failed to open bucket: {}", e)))?;
+
+    Ok(if config.path_style {
+        bucket.with_path_style()
+    } else {
+        bucket
+    })
+}
+
+// 设置上传配置
+#[tauri::command]
+pub fn configure_upload(state: State<'_, UploadState>, config: UploadConfig) -> Result<bool, AppError> {
+    if config.endpoint.trim().is_empty() {
+        return Err(AppError::InvalidArgument("Upload endpoint must not be empty".to_string()));
+    }
+    *state.config.lock() = Some(config);
+    Ok(true)
+}
+
+// 清除上传配置
+#[tauri::command]
+pub fn clear_upload_config(state: State<'_, UploadState>) -> bool {
+    state.config.lock().take().is_some()
+}
+
+// 上传图片，过程中发送 upload-progress 事件
+#[tauri::command]
+pub async fn upload_image(
+    app: AppHandle,
+    state: State<'_, UploadState>,
+    path: String,
+    bucket: String,
+    key: String,
+) -> Result<bool, AppError> {
+    scope::check(&path)?;
+
+    let config = state
+        .config
+        .lock()
+        .clone()
+        .ok_or_else(|| AppError::InvalidArgument("Upload is not configured".to_string()))?;
+
+    if !Path::new(&path).is_file() {
+        return Err(AppError::NotFound(path.to_string()));
+    }
+
+    let target = open_bucket(&config, &bucket)?;
+
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(AppError::from)?;
+    let total = file
+        .metadata()
+        .await
+        .map_err(AppError::from)?
+        .len();
+
+    // 每增加 1% 才发送一次进度事件，避免事件过多
+    let step = (total / 100).max(1);
+    let mut last_reported = 0u64;
+    let progress = UploadProgress {
+        path: path.clone(),
+        bucket: bucket.clone(),
+        key: key.clone(),
+        uploaded: 0,
+        total,
+    };
+    let emitter = app.clone();
+    let mut event = progress.clone();
+    let mut reader = ProgressReader {
+        inner: file,
+        read: 0,
+        on_progress: Box::new(move |read| {
+            if read - last_reported >= step || read >= total {
+                last_reported = read;
+                event.uploaded = read;
+                let _ = emitter.emit("upload-progress", event.clone());
+            }
+        }),
+    };
+
+    let _ = app.emit("upload-progress", progress.clone());
+
+    let status = target
+        .put_object_stream(&mut reader, &key)
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to upload image: {}", e)))?;
+
+    if !(200..300).contains(&status) {
+        return Err(AppError::Network(format!("Upload failed with status {}", status)));
+    }
+
+    let _ = app.emit(
+        "upload-progress",
+        UploadProgress {
+            uploaded: total,
+            ..progress
+        },
+    );
+
+    Ok(true)
+}
@@ -0,0 +1,156 @@
+// 超分辨率放大：使用 Real-ESRGAN 类模型分块推理，避免大图占用过多显存/内存
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::ml::{load_session, resolve_model, to_tensor};
+use crate::{open_image, run_blocking};
+use crate::paths;
+use crate::scope;
+use crate::storage::save_image;
+
+const MODEL_FILE: &str = "realesrgan-x4.onnx";
+// 模型固定的放大倍数，其他倍数由 4 倍结果缩小得到
+const MODEL_SCALE: u32 = 4;
+// 分块大小以及每块四周额外读取的像素，用于消除拼接缝
+const TILE_SIZE: u32 = 128;
+const TILE_PAD: u32 = 10;
+// 4 倍结果最多的像素数（RGB 约 768 MB），更大的图片在分配结果之前拒绝
+const MAX_OUTPUT_PIXELS: u64 = 256 * 1024 * 1024;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UpscaleProgress {
+    pub path: String,
+    pub done: u32,
+    pub total: u32,
+}
+
+// 对整张 RGB 图片分块运行模型，返回 4 倍尺寸的结果
+fn upscale_tiles(app: &AppHandle, path: &str, img: &RgbImage) -> Result<RgbImage, AppError> {
+    let (width, height) = img.dimensions();
+    let output_pixels = width as u64 * height as u64 * (MODEL_SCALE * MODEL_SCALE) as u64;
+    if output_pixels > MAX_OUTPUT_PIXELS {
+        return Err(AppError::TooLarge(format!(
+            "Image is too large to upscale ({}x{}, at most {} pixels after {}x upscaling)",
+            width, height, MAX_OUTPUT_PIXELS, MODEL_SCALE
+        )));
+    }
+    let session = load_session(&resolve_model(app, MODEL_FILE)?)?;
+    let mut result = RgbImage::new(width * MODEL_SCALE, height * MODEL_SCALE);
+
+    let tiles_x = width.div_ceil(TILE_SIZE);
+    let tiles_y = height.div_ceil(TILE_SIZE);
+    let total = tiles_x * tiles_y;
+    let mut done = 0;
+
+    for tile_y in 0..tiles_y {
+        for tile_x in 0..tiles_x {
+            // 本块负责输出的区域
+            let x = tile_x * TILE_SIZE;
+            let y = tile_y * TILE_SIZE;
+            let x_end = (x + TILE_SIZE).min(width);
+            let y_end = (y + TILE_SIZE).min(height);
+
+            // 实际送入模型的区域（带边缘）
+            let in_x = x.saturating_sub(TILE_PAD);
+            let in_y = y.saturating_sub(TILE_PAD);
+            let in_x_end = (x_end + TILE_PAD).min(width);
+            let in_y_end = (y_end + TILE_PAD).min(height);
+
+            let tile = img.view(in_x, in_y, in_x_end - in_x, in_y_end - in_y).to_image();
+            let inputs = ort::inputs![to_tensor(&tile, None)]
+                .map_err(|e| AppError::ProcessingFailed(format!("Failed to prepare model input: {}", e)))?;
+            let outputs = session
+                .run(inputs)
+                .map_err(|e| AppError::ProcessingFailed(format!("Failed to run model: {}", e)))?;
+            let output = outputs[0]
+                .try_extract_tensor::<f32>()
+                .map_err(|e| AppError::ProcessingFailed(format!("Failed to read model output: {}", e)))?;
+
+            // 只拷贝本块负责的部分，丢弃边缘
+            for oy in (y - in_y) * MODEL_SCALE..(y_end - in_y) * MODEL_SCALE {
+                for ox in (x - in_x) * MODEL_SCALE..(x_end - in_x) * MODEL_SCALE {
+                    let channel = |c: usize| {
+                        (output[[0, c, oy as usize, ox as usize]].clamp(0.0, 1.0) * 255.0).round() as u8
+                    };
+                    result.put_pixel(
+                        in_x * MODEL_SCALE + ox,
+                        in_y * MODEL_SCALE + oy,
+                        Rgb([channel(0), channel(1), channel(2)]),
+                    );
+                }
+            }
+
+            done += 1;
+            let _ = app.emit(
+                "upscale-progress",
+                UpscaleProgress {
+                    path: path.to_string(),
+                    done,
+                    total,
+                },
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+// 放大图片（2~4 倍），返回输出文件路径
+#[tauri::command]
+pub async fn upscale_image(
+    app: AppHandle,
+    path: String,
+    factor: u32,
+    output: Option<String>,
+) -> Result<String, AppError> {
+    scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    if !(2..=MODEL_SCALE).contains(&factor) {
+        return Err(AppError::InvalidArgument(format!("Upscale factor must be between 2 and {}", MODEL_SCALE)));
+    }
+
+    // 推理耗时较长，超时时间可通过 set_operation_timeout 单独调整
+    run_blocking("upscale_image", move || {
+        let img = open_image(&path)?;
+        let (width, height) = img.dimensions();
+        let target_width = width * factor;
+        let target_height = height * factor;
+
+        let mut upscaled = DynamicImage::ImageRgb8(upscale_tiles(&app, &path, &img.to_rgb8())?);
+        if factor != MODEL_SCALE {
+            upscaled = upscaled.resize_exact(target_width, target_height, FilterType::Lanczos3);
+        }
+
+        // 透明通道不经过模型，直接插值放大
+        if img.color().has_alpha() {
+            let alpha = image::imageops::resize(&img.to_rgba8(), target_width, target_height, FilterType::Lanczos3);
+            let mut rgba = upscaled.to_rgba8();
+            for (pixel, source) in rgba.pixels_mut().zip(alpha.pixels()) {
+                pixel[3] = source[3];
+            }
+            upscaled = DynamicImage::ImageRgba8(rgba);
+        }
+
+        let output_path = match output {
+            Some(output) => PathBuf::from(output),
+            None => {
+                let source = Path::new(&path);
+                let stem = paths::file_stem(source, "image");
+                source.with_file_name(format!("{}_x{}.png", stem, factor))
+            }
+        };
+
+        save_image(&upscaled, &output_path)?;
+
+        Ok(output_path.to_string_lossy().to_string())
+    })
+    .await
+}
@@ -0,0 +1,435 @@
+// 位图转 SVG：描出像素区域的边界，化简为折线后平滑成曲线（思路与 potrace 类似）
+//   黑白：按阈值（默认用 Otsu 法自动选择）二值化后描出深色区域，适合 logo、线稿
+//   彩色：先减少到少量颜色，按面积从大到小逐层描出，每层包含其上方所有层的区域，层间没有缝隙
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::palette::{self, Dither, PaletteOptions};
+use crate::storage::write_bytes;
+use crate::text::parse_color;
+use crate::{open_image, paths, run_blocking, scope};
+
+// 沿边界前进的方向（y 轴向下）：右、下、左、上
+const STEPS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum VectorizeMode {
+    #[default]
+    Bilevel,
+    Color,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VectorizeOptions {
+    pub mode: VectorizeMode,
+    // 黑白模式的亮度阈值，低于该值的像素为前景；为空时自动选择
+    pub threshold: Option<u8>,
+    // 黑白模式下描出浅色区域
+    pub invert: bool,
+    // 黑白模式的填充颜色
+    pub fill: String,
+    // 彩色模式的颜色数
+    pub colors: u16,
+    // 面积小于该值（像素）的斑点和孔洞被忽略
+    pub turd_size: u32,
+    // 折线化简的最大偏差，像素
+    pub tolerance: f32,
+    // 把折线平滑为曲线
+    pub smooth: bool,
+    // 平滑时转角不小于该角度（度）的顶点保留为尖角
+    pub corner_angle: f32,
+    // 为空时在原图旁边生成同名 .svg
+    pub output: Option<String>,
+}
+
+impl Default for VectorizeOptions {
+    fn default() -> Self {
+        Self {
+            mode: VectorizeMode::Bilevel,
+            threshold: None,
+            invert: false,
+            fill: "#000000".to_string(),
+            colors: 8,
+            turd_size: 2,
+            tolerance: 1.0,
+            smooth: true,
+            corner_angle: 80.0,
+            output: None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorizeResult {
+    pub output: String,
+    // 生成的 path 元素数（每种颜色一个）
+    pub layers: usize,
+    // 闭合轮廓总数
+    pub contours: usize,
+    pub size: u64,
+}
+
+type Point = (f32, f32);
+
+struct Bitmap {
+    width: u32,
+    height: u32,
+    bits: Vec<bool>,
+}
+
+impl Bitmap {
+    fn get(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return false;
+        }
+        self.bits[(y as u32 * self.width + x as u32) as usize]
+    }
+}
+
+// Otsu 法：使前景和背景类间方差最大的阈值
+fn otsu(histogram: &[u64; 256]) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram.iter().enumerate().map(|(i, n)| i as f64 * *n as f64).sum();
+    let (mut weight, mut partial, mut best, mut best_variance) = (0u64, 0.0, 0u8, 0.0);
+    for (i, n) in histogram.iter().enumerate() {
+        weight += n;
+        if weight == 0 || weight == total {
+            continue;
+        }
+        partial += i as f64 * *n as f64;
+        let mean_low = partial / weight as f64;
+        let mean_high = (sum - partial) / (total - weight) as f64;
+        let variance = weight as f64 * (total - weight) as f64 * (mean_low - mean_high).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = i as u8;
+        }
+    }
+    best.saturating_add(1)
+}
+
+// 透明部分按白色背景计算亮度
+fn luma_image(img: &DynamicImage) -> (u32, u32, Vec<u8>) {
+    let rgba = img.to_rgba8();
+    let luma = rgba
+        .pixels()
+        .map(|p| {
+            let alpha = p[3] as f32 / 255.0;
+            let y = 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32;
+            (y * alpha + 255.0 * (1.0 - alpha)).round() as u8
+        })
+        .collect();
+    (rgba.width(), rgba.height(), luma)
+}
+
+// 描出前景区域的所有边界，返回由像素角点组成的闭合折线（已去掉共线的点）
+// 边界按顺时针方向行走（前景在右侧）；两个前景像素只有对角相接时向右转，把它们分为两个区域
+fn trace(bitmap: &Bitmap, turd_size: u32) -> Vec<Vec<Point>> {
+    let (width, height) = (bitmap.width as i32, bitmap.height as i32);
+    let stride = (width + 1) as usize;
+    let index = |x: i32, y: i32, dir: usize| ((y as usize * stride + x as usize) * 4) + dir;
+    let mut edges = vec![false; stride * (height as usize + 1) * 4];
+    for y in 0..height {
+        for x in 0..width {
+            if !bitmap.get(x, y) {
+                continue;
+            }
+            if !bitmap.get(x, y - 1) {
+                edges[index(x, y, 0)] = true;
+            }
+            if !bitmap.get(x + 1, y) {
+                edges[index(x + 1, y, 1)] = true;
+            }
+            if !bitmap.get(x, y + 1) {
+                edges[index(x + 1, y + 1, 2)] = true;
+            }
+            if !bitmap.get(x - 1, y) {
+                edges[index(x, y + 1, 3)] = true;
+            }
+        }
+    }
+
+    let mut visited = vec![false; edges.len()];
+    let mut loops = Vec::new();
+    for start in 0..edges.len() {
+        if !edges[start] || visited[start] {
+            continue;
+        }
+        let vertex = start / 4;
+        let (mut x, mut y, mut dir) = ((vertex % stride) as i32, (vertex / stride) as i32, start % 4);
+        let mut points = Vec::new();
+        loop {
+            visited[index(x, y, dir)] = true;
+            x += STEPS[dir].0;
+            y += STEPS[dir].1;
+            let Some(next) = [(dir + 1) % 4, dir, (dir + 3) % 4].into_iter().find(|d| edges[index(x, y, *d)]) else {
+                break;
+            };
+            if next != dir {
+                points.push((x as f32, y as f32));
+            }
+            if visited[index(x, y, next)] {
+                break;
+            }
+            dir = next;
+        }
+        if points.len() >= 3 && area(&points).abs() >= turd_size as f32 {
+            loops.push(points);
+        }
+    }
+    loops
+}
+
+fn area(points: &[Point]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt();
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0);
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+// Douglas-Peucker 化简开放折线，保留首尾点；用栈代替递归，长轮廓也不会栈溢出
+fn simplify_open(points: &[Point], tolerance: f32) -> Vec<Point> {
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| (i, distance_to_segment(points[i], points[start], points[end])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                keep[i] = true;
+                stack.push((start, i));
+                stack.push((i, end));
+            }
+        }
+    }
+    points.iter().zip(keep).filter(|(_, keep)| *keep).map(|(p, _)| *p).collect()
+}
+
+// 化简闭合折线：从第一个点和离它最远的点处分成两段分别化简
+fn simplify(points: &[Point], tolerance: f32) -> Vec<Point> {
+    let first = points[0];
+    let far = (1..points.len())
+        .max_by(|a, b| {
+            let da = (points[*a].0 - first.0).powi(2) + (points[*a].1 - first.1).powi(2);
+            let db = (points[*b].0 - first.0).powi(2) + (points[*b].1 - first.1).powi(2);
+            da.total_cmp(&db)
+        })
+        .unwrap_or(0);
+    let mut closed = points.to_vec();
+    closed.push(first);
+    // 两段的末点分别是下一段的起点，去掉以免重复
+    let mut output = simplify_open(&closed[..=far], tolerance);
+    output.pop();
+    let mut second = simplify_open(&closed[far..], tolerance);
+    second.pop();
+    output.extend(second);
+    output
+}
+
+fn number(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn coordinate(p: Point) -> String {
+    format!("{} {}", number(p.0), number(p.1))
+}
+
+// 闭合折线的 SVG 路径；平滑时以相邻线段的中点为端点、顶点为控制点画二次曲线，转角较大的顶点保留尖角
+fn path_data(points: &[Point], options: &VectorizeOptions) -> String {
+    let n = points.len();
+    if !options.smooth {
+        let segments: Vec<String> = points.iter().map(|p| coordinate(*p)).collect();
+        return format!("M{}Z", segments.join("L"));
+    }
+    let mid = |i: usize| {
+        let (a, b) = (points[i % n], points[(i + 1) % n]);
+        ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+    };
+    let corner_cos = options.corner_angle.to_radians().cos();
+    let mut data = format!("M{}", coordinate(mid(n - 1)));
+    for i in 0..n {
+        let (previous, current, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        let incoming = (current.0 - previous.0, current.1 - previous.1);
+        let outgoing = (next.0 - current.0, next.1 - current.1);
+        let length = (incoming.0.hypot(incoming.1) * outgoing.0.hypot(outgoing.1)).max(f32::EPSILON);
+        // 转角越大，两个方向的夹角余弦越小
+        let cos = (incoming.0 * outgoing.0 + incoming.1 * outgoing.1) / length;
+        if cos <= corner_cos {
+            data.push_str(&format!("L{}L{}", coordinate(current), coordinate(mid(i))));
+        } else {
+            data.push_str(&format!("Q{} {}", coordinate(current), coordinate(mid(i))));
+        }
+    }
+    data.push('Z');
+    data
+}
+
+// 描出一层并生成 path 元素，没有轮廓时返回 None
+fn layer(bitmap: &Bitmap, color: [u8; 4], options: &VectorizeOptions) -> Option<(String, usize)> {
+    let contours: Vec<Vec<Point>> = trace(bitmap, options.turd_size)
+        .into_iter()
+        .map(|points| simplify(&points, options.tolerance))
+        .filter(|points| points.len() >= 3)
+        .collect();
+    if contours.is_empty() {
+        return None;
+    }
+    let data: String = contours.iter().map(|points| path_data(points, options)).collect();
+    let opacity = if color[3] < 255 {
+        format!(" fill-opacity=\"{}\"", number(color[3] as f32 / 255.0))
+    } else {
+        String::new()
+    };
+    let element = format!(
+        "<path fill=\"#{:02x}{:02x}{:02x}\"{} fill-rule=\"evenodd\" d=\"{}\"/>",
+        color[0], color[1], color[2], opacity, data
+    );
+    Some((element, contours.len()))
+}
+
+fn bilevel_layers(img: &DynamicImage, options: &VectorizeOptions) -> Result<Vec<(String, usize)>, AppError> {
+    let fill = parse_color(&options.fill)?;
+    let (width, height, luma) = luma_image(img);
+    let threshold = options.threshold.unwrap_or_else(|| {
+        let mut histogram = [0u64; 256];
+        for value in &luma {
+            histogram[*value as usize] += 1;
+        }
+        otsu(&histogram)
+    });
+    let bits = luma.iter().map(|value| (*value < threshold) != options.invert).collect();
+    let bitmap = Bitmap { width, height, bits };
+    Ok(layer(&bitmap, fill.0, options).into_iter().collect())
+}
+
+fn color_layers(img: &DynamicImage, options: &VectorizeOptions) -> Result<Vec<(String, usize)>, AppError> {
+    let indexed = palette::quantize(
+        img,
+        &PaletteOptions {
+            colors: options.colors,
+            dither: Dither::None,
+        },
+    )?;
+    let mut counts = vec![0usize; indexed.palette.len()];
+    for index in &indexed.indices {
+        counts[*index as usize] += 1;
+    }
+    // 面积大的颜色在下层；几乎透明的颜色不描出
+    let mut order: Vec<usize> = (0..indexed.palette.len())
+        .filter(|i| counts[*i] > 0 && indexed.palette[*i][3] >= 128)
+        .collect();
+    order.sort_by_key(|i| std::cmp::Reverse(counts[*i]));
+    let mut rank = vec![usize::MAX; indexed.palette.len()];
+    for (position, i) in order.iter().enumerate() {
+        rank[*i] = position;
+    }
+
+    let mut layers = Vec::new();
+    for (position, i) in order.iter().enumerate() {
+        // 每层包含其上方所有层的像素，上层覆盖下层，曲线平滑后层间不会露出缝隙
+        let bits = indexed
+            .indices
+            .iter()
+            .map(|index| rank[*index as usize] != usize::MAX && rank[*index as usize] >= position)
+            .collect();
+        let bitmap = Bitmap {
+            width: indexed.width,
+            height: indexed.height,
+            bits,
+        };
+        layers.extend(layer(&bitmap, indexed.palette[*i], options));
+    }
+    Ok(layers)
+}
+
+fn output_path(path: &Path, output: Option<String>) -> PathBuf {
+    output.map(PathBuf::from).unwrap_or_else(|| path.with_extension("svg"))
+}
+
+// 把位图描成 SVG
+#[tauri::command]
+pub async fn vectorize_image(path: String, options: Option<VectorizeOptions>) -> Result<VectorizeResult, AppError> {
+    scope::check(&path)?;
+    let options = options.unwrap_or_default();
+    if let Some(output) = &options.output {
+        scope::check(output)?;
+    }
+    if options.mode == VectorizeMode::Color && !(2..=64).contains(&options.colors) {
+        return Err(AppError::InvalidArgument(format!(
+            "Colors must be between 2 and 64: {}",
+            options.colors
+        )));
+    }
+    if !(options.tolerance >= 0.0 && options.tolerance <= 10.0) {
+        return Err(AppError::InvalidArgument(format!(
+            "Tolerance must be between 0 and 10: {}",
+            options.tolerance
+        )));
+    }
+    if !(0.0..=180.0).contains(&options.corner_angle) {
+        return Err(AppError::InvalidArgument(format!(
+            "Corner angle must be between 0 and 180: {}",
+            options.corner_angle
+        )));
+    }
+
+    run_blocking("vectorize_image", move || {
+        let path = Path::new(&path);
+        let img = open_image(path)?;
+        let (width, height) = img.dimensions();
+        let layers = match options.mode {
+            VectorizeMode::Bilevel => bilevel_layers(&img, &options)?,
+            VectorizeMode::Color => color_layers(&img, &options)?,
+        };
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = width,
+            h = height
+        );
+        for (element, _) in &layers {
+            svg.push_str(element);
+            svg.push('\n');
+        }
+        svg.push_str("</svg>\n");
+
+        let output = output_path(path, options.output);
+        write_bytes(&output, svg.as_bytes())?;
+        Ok(VectorizeResult {
+            output: paths::display(&output),
+            layers: layers.len(),
+            contours: layers.iter().map(|(_, count)| count).sum(),
+            size: svg.len() as u64,
+        })
+    })
+    .await
+}
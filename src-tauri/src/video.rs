@@ -0,0 +1,123 @@
+// 视频帧提取（需要 ffmpeg）：文件夹中的 MP4/MOV 以封面帧显示，也可以截取任意时间的画面作为图片编辑
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::storage::{save_image, unique_path, write_app_file};
+use crate::{ffmpeg, paths, run_blocking, scope, ImageInfo};
+
+const VIDEO_EXTENSIONS: [&str; 4] = ["mp4", "mov", "m4v", "3gp"];
+// 封面帧的时间（秒），跳过视频开头常见的黑场；短于该时间的视频使用第一帧
+const POSTER_TIME: f64 = 1.0;
+const POSTER_QUALITY: u8 = 85;
+
+pub(crate) fn is_video(path: &Path) -> bool {
+    path.extension()
+        .map(|e| VIDEO_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 截取 time 秒处的一帧；time 超出视频长度时 ffmpeg 不输出任何内容
+fn grab_frame(path: &Path, time: f64) -> Result<Option<DynamicImage>, AppError> {
+    let data = ffmpeg::run([
+        "-ss".to_string(),
+        format!("{:.3}", time),
+        "-i".to_string(),
+        paths::display(path),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-f".to_string(),
+        "image2pipe".to_string(),
+        "-c:v".to_string(),
+        "png".to_string(),
+        "-".to_string(),
+    ])
+    .map_err(|e| e.at(path))?;
+    if data.is_empty() {
+        return Ok(None);
+    }
+    let img = image::load_from_memory_with_format(&data, ImageFormat::Png).map_err(|e| AppError::from(e).at(path))?;
+    Ok(Some(img))
+}
+
+// 封面帧缓存在应用缓存目录下，以视频路径、修改时间和大小为键，视频改变后重新提取
+fn poster_file(app: &AppHandle, path: &Path) -> Result<PathBuf, AppError> {
+    let metadata = fs::metadata(path).map_err(|e| AppError::from(e).at(path))?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve cache directory: {}", e)))?
+        .join("video-posters");
+    fs::create_dir_all(&dir).map_err(AppError::from)?;
+    Ok(dir.join(format!("{:016x}.jpg", hasher.finish())))
+}
+
+// 视频的封面帧（JPEG 文件）及视频画面尺寸
+pub(crate) fn poster(app: &AppHandle, path: &Path) -> Result<(PathBuf, u32, u32), AppError> {
+    let file = poster_file(app, path)?;
+    if let Ok((width, height)) = image::image_dimensions(&file) {
+        return Ok((file, width, height));
+    }
+
+    let frame = match grab_frame(path, POSTER_TIME)? {
+        Some(frame) => frame,
+        None => grab_frame(path, 0.0)?.ok_or_else(|| AppError::DecodeFailed("Video has no frames".to_string()).at(path))?,
+    };
+    let mut buffer = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buffer, POSTER_QUALITY).encode_image(&frame.to_rgb8())?;
+    write_app_file(&file, &buffer.into_inner())?;
+
+    let (width, height) = frame.dimensions();
+    Ok((file, width, height))
+}
+
+// 截取视频 time 秒处的画面并保存为 PNG，返回图片信息供编辑器打开
+// output 为空时保存到视频旁边：name_12.50s.png
+#[tauri::command]
+pub async fn extract_video_frame(path: String, time: f64, output: Option<String>) -> Result<ImageInfo, AppError> {
+    let path = scope::check(&path)?;
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+    if !(time.is_finite() && time >= 0.0) {
+        return Err(AppError::InvalidArgument(format!("Invalid frame time: {}", time)));
+    }
+
+    run_blocking("extract_video_frame", move || {
+        if !is_video(&path) {
+            return Err(AppError::Unsupported("Not a video file".to_string()).at(&path));
+        }
+        let frame = grab_frame(&path, time)?
+            .ok_or_else(|| AppError::InvalidArgument(format!("Time {:.2}s is past the end of the video", time)).at(&path))?;
+
+        let output_path = match output {
+            Some(output) => PathBuf::from(output),
+            None => unique_path(&path.with_file_name(format!("{}_{:.2}s.png", paths::file_stem(&path, "video"), time))),
+        };
+        save_image(&frame, &output_path)?;
+
+        let (width, height) = frame.dimensions();
+        let size = fs::metadata(&output_path).map_err(AppError::from)?.len();
+        Ok(ImageInfo {
+            path: paths::display(&output_path),
+            name: paths::file_name(&output_path),
+            width,
+            height,
+            size,
+            poster: None,
+        })
+    })
+    .await
+}
@@ -0,0 +1,120 @@
+// 监视目录：在应用外添加、修改、删除图片后发送 image-added、image-modified、image-removed 事件，列表不必手动刷新
+// 文件系统事件合并 DEBOUNCE 时间后再处理，复制大文件时不会连续发送多次；变化的文件同时清除解码缓存和缩略图
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::error::AppError;
+use crate::listing::{list_files, SymlinkPolicy};
+use crate::{cache, image_info, paths, run_blocking, scope, thumbnails, ImageInfo};
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+// image-added、image-modified 和 image-removed 事件的内容；删除时为删除前的信息
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryChange {
+    pub dir: String,
+    pub image: ImageInfo,
+}
+
+// 正在监视的目录，移除后停止监视
+#[derive(Default)]
+pub struct WatchState {
+    watchers: Mutex<HashMap<PathBuf, Debouncer<RecommendedWatcher>>>,
+}
+
+// 对比已知的文件判断是添加、修改还是删除；仍存在但暂时读取不了（例如还在写入）的文件等下一次变化再处理
+fn handle_change(app: &AppHandle, dir: &str, known: &mut HashMap<PathBuf, ImageInfo>, path: &Path) {
+    cache::invalidate(path);
+    thumbnails::invalidate(path);
+
+    let info = if path.is_file() {
+        image_info(app, path).unwrap_or_else(|error| {
+            tracing::debug!(path = %path.display(), error = %error, "failed to read changed file");
+            None
+        })
+    } else {
+        None
+    };
+    if info.is_none() && path.exists() {
+        return;
+    }
+
+    let (event, image) = match (info, known.remove(path)) {
+        (Some(info), None) => ("image-added", info),
+        (Some(info), Some(_)) => ("image-modified", info),
+        (None, Some(previous)) => ("image-removed", previous),
+        (None, None) => return,
+    };
+    if event != "image-removed" {
+        known.insert(path.to_path_buf(), image.clone());
+    }
+    let _ = app.emit(
+        event,
+        DirectoryChange {
+            dir: dir.to_string(),
+            image,
+        },
+    );
+}
+
+// 开始监视目录（不含子目录），已在监视时重新开始
+#[tauri::command]
+pub async fn watch_directory(app: AppHandle, state: State<'_, WatchState>, path: String) -> Result<bool, AppError> {
+    let dir = scope::check(&path)?;
+    if !dir.is_dir() {
+        return Err(AppError::NotFound("Folder".to_string()).at(&dir));
+    }
+
+    // 记录开始监视时已有的文件，之后据此区分添加和修改
+    let debouncer = run_blocking("watch_directory", {
+        let dir = dir.clone();
+        move || {
+            let mut known: HashMap<PathBuf, ImageInfo> = HashMap::new();
+            for file in list_files(&dir, SymlinkPolicy::Skip, false)?.files {
+                if let Ok(Some(info)) = image_info(&app, &file) {
+                    known.insert(file, info);
+                }
+            }
+
+            let display = paths::display(&dir);
+            let watched = dir.clone();
+            let mut debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| match result {
+                Ok(events) => {
+                    let changed: HashSet<PathBuf> = events
+                        .into_iter()
+                        .map(|event| event.path)
+                        .filter(|path| *path != watched)
+                        .collect();
+                    for path in changed {
+                        handle_change(&app, &display, &mut known, &path);
+                    }
+                }
+                Err(error) => tracing::warn!(dir = %watched.display(), error = %error, "directory watcher error"),
+            })
+            .map_err(|e| AppError::Internal(format!("Failed to create directory watcher: {}", e)))?;
+            debouncer
+                .watcher()
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|e| AppError::Io(format!("Failed to watch directory: {}", e)).at(&dir))?;
+            Ok(debouncer)
+        }
+    })
+    .await?;
+
+    state.watchers.lock().insert(dir, debouncer);
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn unwatch_directory(state: State<'_, WatchState>, path: String) -> Result<bool, AppError> {
+    let dir = scope::check(&path)?;
+    Ok(state.watchers.lock().remove(&dir).is_some())
+}
@@ -0,0 +1,106 @@
+// 摄像头拍照：枚举摄像头并抓取一帧保存为图片
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::{DynamicImage, RgbImage};
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{ApiBackend, CameraIndex, RequestedFormat, RequestedFormatType};
+use nokhwa::{query, Camera};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::ImageInfo;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::save_image;
+
+// 部分摄像头刚打开时曝光未稳定，先丢弃几帧
+const WARMUP_FRAMES: usize = 5;
+
+#[derive(Serialize, Debug)]
+pub struct CameraInfo {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+}
+
+// 列出可用的摄像头
+#[tauri::command]
+pub fn list_cameras() -> Result<Vec<CameraInfo>, AppError> {
+    let cameras = query(ApiBackend::Auto).map_err(|e| AppError::Device(format!("Failed to query cameras: {}", e)))?;
+
+    Ok(cameras
+        .iter()
+        .filter_map(|c| {
+            c.index().as_index().ok().map(|index| CameraInfo {
+                index,
+                name: c.human_name(),
+                description: c.description().to_string(),
+            })
+        })
+        .collect())
+}
+
+// 从指定摄像头抓取一帧并保存
+#[tauri::command]
+pub async fn capture_webcam(app: AppHandle, index: u32, output: Option<String>) -> Result<ImageInfo, AppError> {
+    if let Some(output) = &output {
+        scope::check(output)?;
+    }
+
+    run_blocking("capture_webcam", move || {
+        let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestResolution);
+        let mut camera = Camera::new(CameraIndex::Index(index), format)
+            .map_err(|e| AppError::Device(format!("Failed to open camera: {}", e)))?;
+        camera
+            .open_stream()
+            .map_err(|e| AppError::Device(format!("Failed to start camera stream: {}", e)))?;
+
+        let mut frame = camera.frame();
+        for _ in 0..WARMUP_FRAMES {
+            frame = camera.frame();
+        }
+        let _ = camera.stop_stream();
+
+        let decoded = frame
+            .map_err(|e| AppError::Device(format!("Failed to capture frame: {}", e)))?
+            .decode_image::<RgbFormat>()
+            .map_err(|e| AppError::Device(format!("Failed to decode frame: {}", e)))?;
+        let (width, height) = (decoded.width(), decoded.height());
+        let captured = RgbImage::from_raw(width, height, decoded.into_raw())
+            .ok_or_else(|| AppError::Device("Invalid camera buffer".to_string()))?;
+
+        let output_path = match output {
+            Some(output) => PathBuf::from(output),
+            None => {
+                let dir = scope::captures_dir(&app)?;
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis())
+                    .unwrap_or(0);
+                dir.join(format!("webcam-{}.png", timestamp))
+            }
+        };
+
+        save_image(&DynamicImage::ImageRgb8(captured), &output_path)?;
+
+        let size = fs::metadata(&output_path)
+            .map_err(AppError::from)?
+            .len();
+
+        Ok(ImageInfo {
+            path: output_path.to_string_lossy().to_string(),
+            name: output_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            width,
+            height,
+            size,
+            poster: None,
+        })
+    })
+    .await
+}
@@ -0,0 +1,163 @@
+// XMP 旁车文件（sidecar）：读写 dc:subject 关键词，并按关键词搜索图片
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::listing::{list_files, report_symlinks, SymlinkPolicy};
+use crate::paths;
+use crate::ImageInfo;
+use crate::run_blocking;
+use crate::scope;
+use crate::storage::write_bytes;
+
+const SUBJECT_START: &str = "<dc:subject>";
+const SUBJECT_END: &str = "</dc:subject>";
+
+// 图片对应的旁车文件：photo.jpg -> photo.xmp
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    path.with_extension("xmp")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+fn subject_block(keywords: &[String]) -> String {
+    let mut block = String::from("<dc:subject>\n    <rdf:Bag>\n");
+    for keyword in keywords {
+        block.push_str(&format!("     <rdf:li>{}</rdf:li>\n", escape(keyword)));
+    }
+    block.push_str("    </rdf:Bag>\n   </dc:subject>");
+    block
+}
+
+// 读取旁车文件中的关键词，文件不存在时返回空列表
+pub fn read_keywords(path: &Path) -> Vec<String> {
+    let content = match fs::read_to_string(sidecar_path(path)) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let (start, end) = match (content.find(SUBJECT_START), content.find(SUBJECT_END)) {
+        (Some(start), Some(end)) if start < end => (start, end),
+        _ => return Vec::new(),
+    };
+
+    content[start..end]
+        .split("<rdf:li>")
+        .skip(1)
+        .filter_map(|item| item.split("</rdf:li>").next())
+        .map(|item| unescape(item.trim()))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// 写入关键词：保留旁车文件中的其他内容，只替换 dc:subject
+pub fn write_keywords(path: &Path, keywords: &[String]) -> Result<(), AppError> {
+    let sidecar = sidecar_path(path);
+    let block = subject_block(keywords);
+
+    let content = match fs::read_to_string(&sidecar) {
+        Ok(existing) => match (existing.find(SUBJECT_START), existing.find(SUBJECT_END)) {
+            (Some(start), Some(end)) if start < end => format!(
+                "{}{}{}",
+                &existing[..start],
+                block,
+                &existing[end + SUBJECT_END.len()..]
+            ),
+            _ => match existing.find("</rdf:Description>") {
+                Some(index) => format!("{}   {}\n  {}", &existing[..index], block, &existing[index..]),
+                None => return Err(AppError::DecodeFailed(format!("Unrecognized XMP sidecar: {}", sidecar.display()))),
+            },
+        },
+        Err(_) => [
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">",
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+            "  <rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">",
+            format!("   {}", block).as_str(),
+            "  </rdf:Description>",
+            " </rdf:RDF>",
+            "</x:xmpmeta>",
+            "<?xpacket end=\"w\"?>\n",
+        ]
+        .join("\n"),
+    };
+
+    write_bytes(&sidecar, content.as_bytes())
+}
+
+// 获取图片的关键词
+#[tauri::command]
+pub fn get_image_tags(path: &str) -> Vec<String> {
+    if scope::check(path).is_err() {
+        return Vec::new();
+    }
+
+    read_keywords(Path::new(path))
+}
+
+// 设置图片的关键词
+#[tauri::command]
+pub fn set_image_tags(path: &str, tags: Vec<String>) -> Result<bool, AppError> {
+    scope::check(path)?;
+
+    write_keywords(Path::new(path), &tags)?;
+    Ok(true)
+}
+
+// 在目录中搜索关键词匹配的图片（不区分大小写，部分匹配）
+#[tauri::command]
+pub async fn search_images_by_tag(
+    app: AppHandle,
+    path: String,
+    query: String,
+    symlinks: Option<SymlinkPolicy>,
+) -> Result<Vec<ImageInfo>, AppError> {
+    scope::check(&path)?;
+
+    run_blocking("search_images_by_tag", move || {
+        let query = query.trim().to_lowercase();
+        let listing = list_files(Path::new(&path), symlinks.unwrap_or_default(), false)?;
+        report_symlinks(&app, Path::new(&path), &listing);
+
+        let mut images = Vec::new();
+        for file in listing.files {
+            if file.extension().and_then(|e| e.to_str()) == Some("xmp") {
+                continue;
+            }
+
+            let keywords = read_keywords(&file);
+            if !keywords.iter().any(|k| k.to_lowercase().contains(&query)) {
+                continue;
+            }
+
+            // 只读取文件头获取尺寸
+            if let Ok((width, height)) = image::image_dimensions(&file) {
+                let size = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+                images.push(ImageInfo {
+                    path: paths::display(&file),
+                    name: paths::file_name(&file),
+                    width,
+                    height,
+                    size,
+                    poster: None,
+                });
+            }
+        }
+
+        Ok(images)
+    })
+    .await
+}
@@ -1,7 +1,5 @@
 import { useState, useRef, useEffect } from "react";
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
-import { open, save } from "@tauri-apps/plugin-dialog";
-import { readFile } from "@tauri-apps/plugin-fs";
 import "./App.css";
 import Toolbar from "./components/Toolbar";
 import ResizeDialog from "./components/ResizeDialog";
@@ -18,6 +16,7 @@ interface ImageInfo {
   width: number;
   height: number;
   size: number;
+  poster?: string;
 }
 
 interface CropArea {
@@ -74,7 +73,7 @@ function App() {
       setNewHeight(result.height);
       
       // 读取图片文件并转换为DataURL
-      const buffer = await readFile(path);
+      const buffer = await invoke<ArrayBuffer>("read_image_file", { path });
       const blob = new Blob([buffer], { type: getImageMimeType(path) });
       const url = URL.createObjectURL(blob);
       setPreviewUrl(url);
@@ -135,16 +134,12 @@ function App() {
   // 选择单张图片（通过对话框）
   const handleSelectImage = async () => {
     try {
-      const selected = await open({
-        directory: false,
-        multiple: false,
-        filters: [
-          { name: "Images", extensions: ["jpg", "jpeg", "png", "gif", "bmp"] }
-        ],
+      // 由后端弹出对话框，选中的文件会被加入允许访问的路径
+      const selected = await invoke<string | null>("pick_image_file", {
         title: t('app.selectImage')
       });
       
-      if (selected && typeof selected === "string") {
+      if (selected) {
         await loadImageFromPath(selected);
       }
     } catch (error) {
@@ -248,7 +243,7 @@ function App() {
       
       if (result) {
         // 重新加载图片预览
-        const buffer = await readFile(selectedImage.path);
+        const buffer = await invoke<ArrayBuffer>("read_image_file", { path: selectedImage.path });
         const blob = new Blob([buffer], { type: getImageMimeType(selectedImage.path) });
         const url = URL.createObjectURL(blob);
         setPreviewUrl(url);
@@ -286,7 +281,7 @@ function App() {
         
         if (result) {
           // 重新加载图片预览
-          const buffer = await readFile(selectedImage.path);
+          const buffer = await invoke<ArrayBuffer>("read_image_file", { path: selectedImage.path });
           const blob = new Blob([buffer], { type: getImageMimeType(selectedImage.path) });
           const url = URL.createObjectURL(blob);
           setPreviewUrl(url);
@@ -305,11 +300,9 @@ function App() {
         const arrayBuffer = await blob.arrayBuffer();
         const buffer = new Uint8Array(arrayBuffer);
         
-        // 调用后端命令调整图片大小
-        const resizedData = await invoke<Uint8Array>("resize_image_from_data", {
-          data: Array.from(buffer),
-          width: width,
-          height: height
+        // 调用后端命令调整图片大小，图片数据以二进制请求体传递，宽高放在请求头中
+        const resizedData = await invoke<ArrayBuffer>("resize_image_from_data", buffer, {
+          headers: { width: String(width), height: String(height) }
         });
         
         // 创建新的预览URL
@@ -340,24 +333,20 @@ function App() {
     
     try {
       const defaultName = selectedImage.name.replace(/\.[^/.]+$/, ".png");
-      const savedPath = await save({
-        filters: [
-          { name: "PNG", extensions: ["png"] },
-          { name: "JPEG", extensions: ["jpg", "jpeg"] },
-          { name: "ICO", extensions: ["ico"] },
-          { name: "All Images", extensions: ["jpg", "jpeg", "png", "gif", "bmp", "ico"] }
-        ],
-        title: t('app.saveAs'),
-        defaultPath: defaultName
+      const savedPath = await invoke<string | null>("pick_save_path", {
+        defaultName,
+        title: t('app.saveAs')
       });
       
       if (savedPath) {
         console.log("savedPath:" + savedPath);
         setLoading(true);
         // 使用后端保存图片为不同格式
-        await invoke<boolean>("save_as", {
+        // 系统保存对话框已确认过覆盖
+        await invoke<string>("save_as", {
           path: selectedImage.path,
-          output: savedPath
+          output: savedPath,
+          overwrite: true
         });
         setLoading(false);
       }
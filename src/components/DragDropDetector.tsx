@@ -1,5 +1,5 @@
 import React, { useState, useRef, useEffect } from 'react';
-import { open } from '@tauri-apps/plugin-dialog';
+import { invoke } from '@tauri-apps/api/core';
 import { listen, TauriEvent, UnlistenFn } from '@tauri-apps/api/event';
 import { useI18n } from '../contexts/I18nContext';
 
@@ -91,16 +91,12 @@ const DragDropDetector: React.FC<DragDropDetectorProps> = ({ onImageDrop, onDrag
     const handleClick = async () => {
       console.log("DragDropDetector: handleClick triggered");
       try {
-        const selected = await open({
-          directory: false,
-          multiple: false,
-          filters: [
-            { name: "Images", extensions: ["jpg", "jpeg", "png", "gif", "bmp"] }
-          ],
+        // 由后端弹出对话框，选中的文件会被加入允许访问的路径
+        const selected = await invoke<string | null>("pick_image_file", {
           title: t('app.selectImage')
         });
         
-        if (selected && typeof selected === "string") {
+        if (selected) {
           // 直接将路径传递给父组件处理
           onImageDrop(selected);
         }